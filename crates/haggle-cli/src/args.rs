@@ -0,0 +1,299 @@
+use std::path::PathBuf;
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use haggle_client::{NegotiationStatus, TerminationReason};
+use solana_sdk::pubkey::Pubkey;
+
+#[derive(Parser)]
+#[command(
+    name = "haggle",
+    version,
+    about = "Operate Haggle Protocol negotiations"
+)]
+pub struct Cli {
+    /// RPC URL or moniker: localhost, devnet, testnet, mainnet-beta
+    #[arg(
+        short,
+        long,
+        global = true,
+        env = "HAGGLE_URL",
+        default_value = "localhost"
+    )]
+    pub url: String,
+
+    /// Keypair file that signs and pays for transactions
+    /// [default: ~/.config/solana/id.json]
+    #[arg(short, long, global = true, env = "HAGGLE_KEYPAIR")]
+    pub keypair: Option<PathBuf>,
+
+    /// Print a single JSON document instead of text
+    #[arg(long, global = true)]
+    pub json: bool,
+
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+impl Cli {
+    pub fn rpc_url(&self) -> String {
+        match self.url.as_str() {
+            "localhost" | "l" => "http://127.0.0.1:8899",
+            "devnet" | "d" => "https://api.devnet.solana.com",
+            "testnet" | "t" => "https://api.testnet.solana.com",
+            "mainnet-beta" | "m" => "https://api.mainnet-beta.solana.com",
+            url => url,
+        }
+        .to_string()
+    }
+
+    pub fn keypair_path(&self) -> PathBuf {
+        self.keypair.clone().unwrap_or_else(|| {
+            let home = std::env::var_os("HOME").unwrap_or_default();
+            PathBuf::from(home).join(".config/solana/id.json")
+        })
+    }
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Manage the protocol config
+    #[command(subcommand)]
+    Config(ConfigCommand),
+
+    /// Create and drive negotiations
+    #[command(subcommand)]
+    Negotiate(NegotiateCommand),
+
+    /// Show a negotiation with its decay schedule
+    Show { negotiation: Pubkey },
+
+    /// List negotiations, newest first
+    List {
+        #[arg(long)]
+        buyer: Option<Pubkey>,
+        #[arg(long)]
+        seller: Option<Pubkey>,
+        #[arg(long, value_enum)]
+        status: Option<StatusArg>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ConfigCommand {
+    /// Create the config with the keypair as authority
+    Init {
+        /// Fee recipient [default: the keypair]
+        #[arg(long)]
+        treasury: Option<Pubkey>,
+        #[arg(long, default_value_t = 200)]
+        decay_rate_bps: u16,
+        #[arg(long, default_value_t = 300)]
+        response_window: i64,
+        #[arg(long, default_value_t = 50)]
+        protocol_fee_bps: u16,
+        #[arg(long, default_value_t = 10)]
+        max_rounds: u8,
+    },
+
+    /// Show the config
+    Show,
+
+    /// Change config fields; unset flags keep their current value
+    Update {
+        /// Hand the config over to a new authority
+        #[arg(long)]
+        authority: Option<Pubkey>,
+        #[arg(long)]
+        treasury: Option<Pubkey>,
+        #[arg(long)]
+        decay_rate_bps: Option<u16>,
+        #[arg(long)]
+        response_window: Option<i64>,
+        #[arg(long)]
+        protocol_fee_bps: Option<u16>,
+        #[arg(long)]
+        max_rounds: Option<u8>,
+        /// Lamports each new negotiation prepays for whoever cranks its cleanup
+        #[arg(long)]
+        crank_reward_lamports: Option<u64>,
+        /// Share of the refund paid to whoever expires a negotiation
+        #[arg(long)]
+        crank_reward_bps: Option<u16>,
+        /// Stop new negotiations from being created
+        #[arg(long, conflicts_with = "unpause")]
+        pause: bool,
+        #[arg(long)]
+        unpause: bool,
+    },
+
+    /// Rewrite the config in the current account layout
+    Migrate,
+}
+
+#[derive(Subcommand)]
+pub enum NegotiateCommand {
+    /// Open a negotiation as buyer and escrow funds
+    Create(Box<CreateArgs>),
+
+    /// Join a negotiation as seller
+    AcceptInvitation {
+        negotiation: Pubkey,
+        /// Counter-offer in the same transaction, in base units of the mint (or quote units)
+        #[arg(long)]
+        offer: Option<u64>,
+        /// Free text attached to the counter-offer (up to 64 bytes)
+        #[arg(long, default_value = "", requires = "offer")]
+        metadata: String,
+    },
+
+    /// Submit an offer, in base units of the escrow mint (or quote units)
+    Offer {
+        negotiation: Pubkey,
+        amount: u64,
+        /// Free text attached to the offer (up to 64 bytes)
+        #[arg(long, default_value = "")]
+        metadata: String,
+        /// Make this the last word: the counterparty can only accept
+        #[arg(long = "final")]
+        is_final: bool,
+    },
+
+    /// Accept the counterparty's current offer and settle
+    Accept { negotiation: Pubkey },
+
+    /// Reject and refund the buyer
+    Reject {
+        negotiation: Pubkey,
+        /// Why the negotiation is being rejected
+        #[arg(long, value_enum)]
+        reason: Option<ReasonArg>,
+    },
+
+    /// Refund the buyer after the global deadline
+    Expire { negotiation: Pubkey },
+
+    /// Close a finished negotiation and reclaim rent
+    Close { negotiation: Pubkey },
+
+    /// Withdraw an invitation the seller has not accepted, refunding escrow and rent
+    Cancel { negotiation: Pubkey },
+
+    /// Rewrite a negotiation in the current account layout, paying any extra rent
+    Migrate { negotiation: Pubkey },
+}
+
+#[derive(Args)]
+pub struct CreateArgs {
+    #[arg(long)]
+    pub seller: Pubkey,
+    /// Escrow token mint
+    #[arg(long)]
+    pub mint: Pubkey,
+    /// Escrow amount, in base units of the mint
+    #[arg(long)]
+    pub escrow: u64,
+    /// Service description, hashed into the negotiation (up to 32 bytes)
+    #[arg(long, default_value = "")]
+    pub service: String,
+    /// [default: current unix time]
+    #[arg(long)]
+    pub session_id: Option<u64>,
+    /// Seconds until the negotiation can be expired
+    #[arg(long, default_value_t = 86_400)]
+    pub deadline: i64,
+    /// Seconds the seller has to accept before the negotiation can be expired
+    #[arg(long)]
+    pub invitation_ttl: Option<i64>,
+    #[arg(long, default_value_t = 1000)]
+    pub min_offer_bps: u16,
+    /// [default: from config]
+    #[arg(long)]
+    pub max_rounds: Option<u8>,
+    /// [default: from config]
+    #[arg(long)]
+    pub decay_rate_bps: Option<u16>,
+    /// [default: from config]
+    #[arg(long)]
+    pub response_window: Option<i64>,
+    /// [default: from config]
+    #[arg(long)]
+    pub protocol_fee_bps: Option<u16>,
+    /// Pyth feed id (hex) to denominate offers in its quote currency, in
+    /// millionths
+    #[arg(long, value_parser = parse_feed_id)]
+    pub price_feed: Option<[u8; 32]>,
+    /// Mint to pay the seller in, swapped from the escrow at settlement
+    #[arg(long, requires = "swap_adapter")]
+    pub payout_mint: Option<Pubkey>,
+    /// Swap adapter program for --payout-mint
+    #[arg(long, requires = "payout_mint")]
+    pub swap_adapter: Option<Pubkey>,
+    /// Most the payout swap may fall short of the spot price
+    #[arg(long, default_value_t = 100, requires = "payout_mint")]
+    pub max_slippage_bps: u16,
+    /// Opening offer in the same transaction, in base units of the mint (or quote units)
+    #[arg(long)]
+    pub offer: Option<u64>,
+    /// Free text attached to the opening offer (up to 64 bytes)
+    #[arg(long, default_value = "", requires = "offer")]
+    pub metadata: String,
+}
+
+fn parse_feed_id(s: &str) -> Result<[u8; 32], String> {
+    let digits = s.strip_prefix("0x").unwrap_or(s);
+    if digits.len() != 64 || !digits.is_ascii() {
+        return Err("expected 32 bytes of hex".to_string());
+    }
+    let mut feed_id = [0u8; 32];
+    for (byte, pair) in feed_id.iter_mut().zip(digits.as_bytes().chunks(2)) {
+        let pair = std::str::from_utf8(pair).map_err(|e| e.to_string())?;
+        *byte = u8::from_str_radix(pair, 16).map_err(|e| e.to_string())?;
+    }
+    Ok(feed_id)
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum StatusArg {
+    Created,
+    Proposed,
+    Countered,
+    Accepted,
+    Settled,
+    Expired,
+    Rejected,
+}
+
+impl From<StatusArg> for NegotiationStatus {
+    fn from(status: StatusArg) -> Self {
+        match status {
+            StatusArg::Created => NegotiationStatus::Created,
+            StatusArg::Proposed => NegotiationStatus::Proposed,
+            StatusArg::Countered => NegotiationStatus::Countered,
+            StatusArg::Accepted => NegotiationStatus::Accepted,
+            StatusArg::Settled => NegotiationStatus::Settled,
+            StatusArg::Expired => NegotiationStatus::Expired,
+            StatusArg::Rejected => NegotiationStatus::Rejected,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum ReasonArg {
+    PriceGap,
+    ZopaFailed,
+    CounterpartyUnresponsive,
+    PolicyViolation,
+    Cancelled,
+}
+
+impl From<ReasonArg> for TerminationReason {
+    fn from(reason: ReasonArg) -> Self {
+        match reason {
+            ReasonArg::PriceGap => TerminationReason::PriceGap,
+            ReasonArg::ZopaFailed => TerminationReason::ZopaFailed,
+            ReasonArg::CounterpartyUnresponsive => TerminationReason::CounterpartyUnresponsive,
+            ReasonArg::PolicyViolation => TerminationReason::PolicyViolation,
+            ReasonArg::Cancelled => TerminationReason::Cancelled,
+        }
+    }
+}
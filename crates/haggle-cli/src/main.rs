@@ -0,0 +1,231 @@
+//! `haggle` command-line tool.
+//!
+//! Thin wrapper over `haggle-client` for operators and CI: every command
+//! prints either a human-readable summary or, with `--json`, a single JSON
+//! document on stdout.
+
+mod args;
+mod output;
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{anyhow, Context, Result};
+use clap::Parser;
+use haggle_client::{
+    create_service_hash, find_config_pda, instructions, ConfigUpdateParams, NegotiationFilter,
+    NegotiationParams, Negotiator, PayoutTerms,
+};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    pubkey::Pubkey,
+    signature::{read_keypair_file, Keypair},
+    signer::null_signer::NullSigner,
+};
+
+use args::{Cli, Command, ConfigCommand, NegotiateCommand};
+use output::{ConfigView, NegotiationSummary, NegotiationView, Output, TxView};
+
+fn main() {
+    let cli = Cli::parse();
+    if let Err(err) = run(&cli) {
+        eprintln!("error: {err:#}");
+        std::process::exit(1);
+    }
+}
+
+fn run(cli: &Cli) -> Result<()> {
+    let out = Output { json: cli.json };
+    match &cli.command {
+        Command::Config(cmd) => config(cli, out, cmd),
+        Command::Negotiate(cmd) => negotiate(cli, out, cmd),
+        Command::Show { negotiation } => {
+            let state = reader(cli).fetch_negotiation(negotiation)?;
+            out.print(&NegotiationView::new(negotiation, &state, now())?)
+        }
+        Command::List {
+            buyer,
+            seller,
+            status,
+        } => {
+            let filter = NegotiationFilter {
+                buyer: *buyer,
+                seller: *seller,
+                status: status.map(Into::into),
+            };
+            let mut negotiations = reader(cli).fetch_negotiations(&filter)?;
+            negotiations.sort_by_key(|(_, state)| std::cmp::Reverse(state.created_at));
+            let rows = negotiations
+                .iter()
+                .map(|(address, state)| NegotiationSummary::new(address, state))
+                .collect::<Result<Vec<_>>>()?;
+            out.print(&rows)
+        }
+    }
+}
+
+fn config(cli: &Cli, out: Output, cmd: &ConfigCommand) -> Result<()> {
+    match cmd {
+        ConfigCommand::Init {
+            treasury,
+            decay_rate_bps,
+            response_window,
+            protocol_fee_bps,
+            max_rounds,
+        } => {
+            let negotiator = signer(cli)?;
+            let treasury = treasury.unwrap_or_else(|| negotiator.pubkey());
+            let ix = instructions::initialize_config(
+                &negotiator.pubkey(),
+                &treasury,
+                *decay_rate_bps,
+                *response_window,
+                *protocol_fee_bps,
+                *max_rounds,
+            );
+            let signature = negotiator.send(&[ix])?;
+            out.print(&TxView::new(signature, Some(find_config_pda().0)))
+        }
+        ConfigCommand::Show => {
+            let config = reader(cli).fetch_config()?;
+            out.print(&ConfigView::new(&find_config_pda().0, &config))
+        }
+        ConfigCommand::Update {
+            authority,
+            treasury,
+            decay_rate_bps,
+            response_window,
+            protocol_fee_bps,
+            max_rounds,
+            crank_reward_lamports,
+            crank_reward_bps,
+            pause,
+            unpause,
+        } => {
+            let params = ConfigUpdateParams {
+                authority: *authority,
+                treasury: *treasury,
+                default_decay_rate_bps: *decay_rate_bps,
+                default_response_window: *response_window,
+                default_protocol_fee_bps: *protocol_fee_bps,
+                default_max_rounds: *max_rounds,
+                is_paused: match (pause, unpause) {
+                    (true, _) => Some(true),
+                    (_, true) => Some(false),
+                    _ => None,
+                },
+                crank_reward_lamports: *crank_reward_lamports,
+                crank_reward_bps: *crank_reward_bps,
+            };
+            let negotiator = signer(cli)?;
+            let ix = instructions::update_config(&negotiator.pubkey(), params);
+            let signature = negotiator.send(&[ix])?;
+            out.print(&TxView::new(signature, Some(find_config_pda().0)))
+        }
+        ConfigCommand::Migrate => {
+            let negotiator = signer(cli)?;
+            let ix = instructions::migrate_config(&negotiator.pubkey());
+            let signature = negotiator.send(&[ix])?;
+            out.print(&TxView::new(signature, Some(find_config_pda().0)))
+        }
+    }
+}
+
+fn negotiate(cli: &Cli, out: Output, cmd: &NegotiateCommand) -> Result<()> {
+    let negotiator = signer(cli)?;
+    let (signature, negotiation) = match cmd {
+        NegotiateCommand::Create(args) => {
+            // Unset parameters fall back to the protocol defaults.
+            let config = negotiator.fetch_config()?;
+            let params = NegotiationParams {
+                escrow_amount: args.escrow,
+                service_hash: create_service_hash(&args.service),
+                max_rounds: args.max_rounds.unwrap_or(config.default_max_rounds),
+                decay_rate_bps: args.decay_rate_bps.unwrap_or(config.default_decay_rate_bps),
+                response_window: args
+                    .response_window
+                    .unwrap_or(config.default_response_window),
+                global_deadline_offset: args.deadline,
+                min_offer_bps: args.min_offer_bps,
+                protocol_fee_bps: args
+                    .protocol_fee_bps
+                    .unwrap_or(config.default_protocol_fee_bps),
+                zopa_enabled: false,
+                price_feed: args.price_feed,
+                payout: args
+                    .payout_mint
+                    .zip(args.swap_adapter)
+                    .map(|(mint, swap_adapter)| PayoutTerms {
+                        mint,
+                        swap_adapter,
+                        max_slippage_bps: args.max_slippage_bps,
+                    }),
+                invitation_ttl: args.invitation_ttl,
+            };
+            let session_id = args.session_id.unwrap_or_else(|| now() as u64);
+            let opening_offer = args.offer.map(|amount| (amount, args.metadata.as_str()));
+            negotiator.create(&args.seller, session_id, &args.mint, params, opening_offer)?
+        }
+        NegotiateCommand::AcceptInvitation {
+            negotiation,
+            offer,
+            metadata,
+        } => {
+            let opening_offer = offer.map(|amount| (amount, metadata.as_str()));
+            (
+                negotiator.accept_invitation(negotiation, opening_offer)?,
+                *negotiation,
+            )
+        }
+        NegotiateCommand::Offer {
+            negotiation,
+            amount,
+            metadata,
+            is_final,
+        } => (
+            negotiator.offer(negotiation, *amount, metadata, *is_final)?,
+            *negotiation,
+        ),
+        NegotiateCommand::Accept { negotiation } => (negotiator.accept(negotiation)?, *negotiation),
+        NegotiateCommand::Reject {
+            negotiation,
+            reason,
+        } => (
+            negotiator.reject(negotiation, reason.map(Into::into))?,
+            *negotiation,
+        ),
+        NegotiateCommand::Expire { negotiation } => (negotiator.expire(negotiation)?, *negotiation),
+        NegotiateCommand::Close { negotiation } => (negotiator.close(negotiation)?, *negotiation),
+        NegotiateCommand::Cancel { negotiation } => {
+            (negotiator.cancel_invitation(negotiation)?, *negotiation)
+        }
+        NegotiateCommand::Migrate { negotiation } => {
+            (negotiator.migrate(negotiation)?, *negotiation)
+        }
+    };
+    out.print(&TxView::new(signature, Some(negotiation)))
+}
+
+fn rpc(cli: &Cli) -> RpcClient {
+    RpcClient::new_with_commitment(cli.rpc_url(), CommitmentConfig::confirmed())
+}
+
+/// Negotiator for commands that send transactions.
+fn signer(cli: &Cli) -> Result<Negotiator<Keypair>> {
+    let path = cli.keypair_path();
+    let keypair = read_keypair_file(&path)
+        .map_err(|err| anyhow!("{err}"))
+        .with_context(|| format!("reading keypair {}", path.display()))?;
+    Ok(Negotiator::new(rpc(cli), keypair))
+}
+
+/// Negotiator for read-only commands, which need no keypair on disk.
+fn reader(cli: &Cli) -> Negotiator<NullSigner> {
+    Negotiator::new(rpc(cli), NullSigner::new(&Pubkey::default()))
+}
+
+fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs() as i64)
+}
@@ -0,0 +1,471 @@
+//! Command output, as text or JSON.
+//!
+//! Every view serializes with pubkeys as base58 strings and statuses in the
+//! lowercase form the TypeScript SDK uses.
+
+use std::fmt::{self, Display, Write};
+
+use anyhow::Result;
+use haggle_client::{
+    decay_schedule, decode_metadata, is_terminal, NegotiationState, NegotiationStatus, OfferSide,
+    ProtocolConfig, ScheduledRound, TerminationReason,
+};
+use serde::Serialize;
+use solana_sdk::{pubkey::Pubkey, signature::Signature};
+
+#[derive(Clone, Copy)]
+pub struct Output {
+    pub json: bool,
+}
+
+impl Output {
+    pub fn print<T: Serialize + Render + ?Sized>(&self, view: &T) -> Result<()> {
+        if self.json {
+            println!("{}", serde_json::to_string_pretty(view)?);
+        } else {
+            print!("{}", view.render());
+        }
+        Ok(())
+    }
+}
+
+pub trait Render {
+    fn render(&self) -> String;
+}
+
+fn status_name(status: NegotiationStatus) -> String {
+    format!("{status:?}").to_lowercase()
+}
+
+fn reason_name(reason: TerminationReason) -> &'static str {
+    match reason {
+        TerminationReason::Unspecified => "unspecified",
+        TerminationReason::PriceGap => "priceGap",
+        TerminationReason::Timeout => "timeout",
+        TerminationReason::ZopaFailed => "zopaFailed",
+        TerminationReason::CounterpartyUnresponsive => "counterpartyUnresponsive",
+        TerminationReason::PolicyViolation => "policyViolation",
+        TerminationReason::Cancelled => "cancelled",
+    }
+}
+
+/// Formats `seconds` from now as "in 1h 5m" or "3m ago".
+fn relative(seconds: i64) -> String {
+    let abs = seconds.unsigned_abs();
+    let span = match abs {
+        0..=59 => format!("{abs}s"),
+        60..=3_599 => format!("{}m {}s", abs / 60, abs % 60),
+        3_600..=86_399 => format!("{}h {}m", abs / 3_600, abs % 3_600 / 60),
+        _ => format!("{}d {}h", abs / 86_400, abs % 86_400 / 3_600),
+    };
+    if seconds >= 0 {
+        format!("in {span}")
+    } else {
+        format!("{span} ago")
+    }
+}
+
+/// Notes an account still in an older layout and the command upgrading it.
+fn outdated(version: u8, command: &str) -> String {
+    format!("version {version}, run `haggle {command}` to upgrade")
+}
+
+struct Fields(String);
+
+impl Fields {
+    fn new() -> Self {
+        Self(String::new())
+    }
+
+    fn field(mut self, name: &str, value: impl Display) -> Self {
+        let _ = writeln!(self.0, "{:<18} {value}", format!("{name}:"));
+        self
+    }
+}
+
+impl fmt::Display for Fields {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+// ===== Transactions =====
+
+#[derive(Serialize)]
+pub struct TxView {
+    pub signature: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+}
+
+impl TxView {
+    pub fn new(signature: Signature, address: Option<Pubkey>) -> Self {
+        Self {
+            signature: signature.to_string(),
+            address: address.map(|a| a.to_string()),
+        }
+    }
+}
+
+impl Render for TxView {
+    fn render(&self) -> String {
+        let mut fields = Fields::new().field("Signature", &self.signature);
+        if let Some(address) = &self.address {
+            fields = fields.field("Address", address);
+        }
+        fields.to_string()
+    }
+}
+
+// ===== Config =====
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigView {
+    pub address: String,
+    pub authority: String,
+    pub treasury: String,
+    pub default_decay_rate_bps: u16,
+    pub default_response_window: i64,
+    pub default_protocol_fee_bps: u16,
+    pub default_max_rounds: u8,
+    pub total_negotiations: u64,
+    pub total_settled_volume: u64,
+    pub total_fees_collected: u64,
+    pub crank_reward_lamports: u64,
+    pub crank_reward_bps: u16,
+    pub total_cranks: u64,
+    pub total_crank_rewards_lamports: u64,
+    pub total_crank_rewards_tokens: u64,
+    pub is_paused: bool,
+    pub version: u8,
+}
+
+impl ConfigView {
+    pub fn new(address: &Pubkey, config: &ProtocolConfig) -> Self {
+        Self {
+            address: address.to_string(),
+            authority: config.authority.to_string(),
+            treasury: config.treasury.to_string(),
+            default_decay_rate_bps: config.default_decay_rate_bps,
+            default_response_window: config.default_response_window,
+            default_protocol_fee_bps: config.default_protocol_fee_bps,
+            default_max_rounds: config.default_max_rounds,
+            total_negotiations: config.total_negotiations,
+            total_settled_volume: config.total_settled_volume,
+            total_fees_collected: config.total_fees_collected,
+            crank_reward_lamports: config.crank_reward_lamports,
+            crank_reward_bps: config.crank_reward_bps,
+            total_cranks: config.total_cranks,
+            total_crank_rewards_lamports: config.total_crank_rewards_lamports,
+            total_crank_rewards_tokens: config.total_crank_rewards_tokens,
+            is_paused: config.is_paused,
+            version: config.version,
+        }
+    }
+}
+
+impl Render for ConfigView {
+    fn render(&self) -> String {
+        let mut fields = Fields::new()
+            .field("Config", &self.address)
+            .field("Authority", &self.authority)
+            .field("Treasury", &self.treasury)
+            .field("Paused", self.is_paused);
+        if self.version < ProtocolConfig::VERSION {
+            fields = fields.field("Layout", outdated(self.version, "config migrate"));
+        }
+        fields
+            .field("Decay rate", format!("{} bps", self.default_decay_rate_bps))
+            .field(
+                "Response window",
+                format!("{}s", self.default_response_window),
+            )
+            .field(
+                "Protocol fee",
+                format!("{} bps", self.default_protocol_fee_bps),
+            )
+            .field("Max rounds", self.default_max_rounds)
+            .field("Negotiations", self.total_negotiations)
+            .field("Settled volume", self.total_settled_volume)
+            .field("Fees collected", self.total_fees_collected)
+            .field(
+                "Crank reward",
+                format!(
+                    "{} lamports + {} bps",
+                    self.crank_reward_lamports, self.crank_reward_bps
+                ),
+            )
+            .field("Cranks", self.total_cranks)
+            .field(
+                "Crank rewards",
+                format!(
+                    "{} lamports + {} tokens",
+                    self.total_crank_rewards_lamports, self.total_crank_rewards_tokens
+                ),
+            )
+            .to_string()
+    }
+}
+
+// ===== Negotiations =====
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduleRow {
+    pub round: u8,
+    pub effective_escrow: u64,
+    pub min_offer: u64,
+}
+
+impl From<ScheduledRound> for ScheduleRow {
+    fn from(row: ScheduledRound) -> Self {
+        Self {
+            round: row.round,
+            effective_escrow: row.effective_escrow,
+            min_offer: row.min_offer,
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NegotiationView {
+    pub address: String,
+    pub buyer: String,
+    pub seller: String,
+    pub session_id: u64,
+    pub status: String,
+    pub terminal: bool,
+    pub current_round: u8,
+    pub max_rounds: u8,
+    pub current_offer_amount: u64,
+    pub current_offer_by: Option<String>,
+    pub offer_side: Option<String>,
+    pub final_offer: bool,
+    pub service: String,
+    pub token_mint: String,
+    pub escrow_amount: u64,
+    pub effective_escrow: u64,
+    pub decay_rate_bps: u16,
+    pub min_offer_bps: u16,
+    pub protocol_fee_bps: u16,
+    pub response_window: i64,
+    pub created_at: i64,
+    pub last_offer_at: i64,
+    pub global_deadline: i64,
+    pub invitation_deadline: Option<i64>,
+    pub termination_reason: Option<String>,
+    pub settled_at: i64,
+    pub settled_amount: u64,
+    pub metadata: String,
+    pub version: u8,
+    pub schedule: Vec<ScheduleRow>,
+    #[serde(skip)]
+    now: i64,
+}
+
+impl NegotiationView {
+    pub fn new(address: &Pubkey, state: &NegotiationState, now: i64) -> Result<Self> {
+        let has_offer = state.current_round > 0;
+        let status = state.status()?;
+        let offer_side = state.offer_side()?;
+        let termination_reason = state.termination_reason()?;
+        Ok(Self {
+            address: address.to_string(),
+            buyer: state.buyer.to_string(),
+            seller: state.seller.to_string(),
+            session_id: state.session_id,
+            status: status_name(status),
+            terminal: is_terminal(status),
+            current_round: state.current_round,
+            max_rounds: state.max_rounds,
+            current_offer_amount: state.current_offer_amount,
+            current_offer_by: has_offer.then(|| state.current_offer_by.to_string()),
+            offer_side: has_offer.then(|| {
+                match offer_side {
+                    OfferSide::Buyer => "buyer",
+                    OfferSide::Seller => "seller",
+                }
+                .to_string()
+            }),
+            final_offer: state.is_final_offer(),
+            service: decode_metadata(&state.service_hash),
+            token_mint: state.token_mint.to_string(),
+            escrow_amount: state.escrow_amount,
+            effective_escrow: state.effective_escrow,
+            decay_rate_bps: state.decay_rate_bps,
+            min_offer_bps: state.min_offer_bps,
+            protocol_fee_bps: state.protocol_fee_bps,
+            response_window: state.response_window,
+            created_at: state.created_at,
+            last_offer_at: state.last_offer_at,
+            global_deadline: state.global_deadline,
+            invitation_deadline: (state.invitation_deadline != 0)
+                .then_some(state.invitation_deadline),
+            termination_reason: matches!(
+                status,
+                NegotiationStatus::Expired | NegotiationStatus::Rejected
+            )
+            .then(|| reason_name(termination_reason).to_string()),
+            settled_at: state.settled_at,
+            settled_amount: state.settled_amount,
+            metadata: decode_metadata(&state.metadata),
+            version: state.version,
+            schedule: decay_schedule(state).into_iter().map(Into::into).collect(),
+            now,
+        })
+    }
+}
+
+impl Render for NegotiationView {
+    fn render(&self) -> String {
+        let mut fields = Fields::new()
+            .field("Negotiation", &self.address)
+            .field("Status", &self.status)
+            .field("Buyer", &self.buyer)
+            .field("Seller", &self.seller)
+            .field("Session", self.session_id)
+            .field("Mint", &self.token_mint)
+            .field("Service", &self.service)
+            .field(
+                "Round",
+                format!("{} / {}", self.current_round, self.max_rounds),
+            );
+        if self.version < NegotiationState::VERSION {
+            fields = fields.field("Layout", outdated(self.version, "negotiate migrate"));
+        }
+        if let (Some(side), Some(by)) = (&self.offer_side, &self.current_offer_by) {
+            fields = fields.field(
+                "Current offer",
+                format!(
+                    "{} by {side} ({by}){}",
+                    self.current_offer_amount,
+                    if self.final_offer { ", final" } else { "" }
+                ),
+            );
+            if !self.metadata.is_empty() {
+                fields = fields.field("Metadata", &self.metadata);
+            }
+        }
+        fields = fields
+            .field(
+                "Escrow",
+                format!(
+                    "{} (effective {})",
+                    self.escrow_amount, self.effective_escrow
+                ),
+            )
+            .field(
+                "Terms",
+                format!(
+                    "decay {} bps, min offer {} bps, fee {} bps",
+                    self.decay_rate_bps, self.min_offer_bps, self.protocol_fee_bps
+                ),
+            );
+        if self.status == "settled" {
+            fields = fields.field(
+                "Settled",
+                format!(
+                    "{} {}",
+                    self.settled_amount,
+                    relative(self.settled_at - self.now)
+                ),
+            );
+        } else if !self.terminal {
+            if self.current_round > 0 {
+                fields = fields.field(
+                    "Response due",
+                    relative(self.last_offer_at + self.response_window - self.now),
+                );
+            }
+            if let Some(deadline) = self
+                .invitation_deadline
+                .filter(|_| self.status == "created")
+            {
+                fields = fields.field("Invitation lapses", relative(deadline - self.now));
+            }
+            fields = fields.field("Deadline", relative(self.global_deadline - self.now));
+        } else if let Some(reason) = &self.termination_reason {
+            fields = fields.field("Reason", reason);
+        }
+
+        let mut out = fields.to_string();
+        let _ = writeln!(out, "\nDecay schedule:");
+        let _ = writeln!(
+            out,
+            "  {:>5}  {:>20}  {:>20}",
+            "round", "effective escrow", "min offer"
+        );
+        for row in &self.schedule {
+            let marker = if row.round == self.current_round {
+                "*"
+            } else {
+                " "
+            };
+            let _ = writeln!(
+                out,
+                "{marker} {:>5}  {:>20}  {:>20}",
+                row.round, row.effective_escrow, row.min_offer
+            );
+        }
+        out
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NegotiationSummary {
+    pub address: String,
+    pub buyer: String,
+    pub seller: String,
+    pub status: String,
+    pub current_round: u8,
+    pub max_rounds: u8,
+    pub current_offer_amount: u64,
+    pub effective_escrow: u64,
+    pub global_deadline: i64,
+    pub terminal: bool,
+}
+
+impl NegotiationSummary {
+    pub fn new(address: &Pubkey, state: &NegotiationState) -> Result<Self> {
+        let status = state.status()?;
+        Ok(Self {
+            address: address.to_string(),
+            buyer: state.buyer.to_string(),
+            seller: state.seller.to_string(),
+            status: status_name(status),
+            current_round: state.current_round,
+            max_rounds: state.max_rounds,
+            current_offer_amount: state.current_offer_amount,
+            effective_escrow: state.effective_escrow,
+            global_deadline: state.global_deadline,
+            terminal: is_terminal(status),
+        })
+    }
+}
+
+impl Render for Vec<NegotiationSummary> {
+    fn render(&self) -> String {
+        if self.is_empty() {
+            return "No negotiations found\n".to_string();
+        }
+        let mut out = format!(
+            "{:<44}  {:<9}  {:>5}  {:>14}  {:>14}\n",
+            "NEGOTIATION", "STATUS", "ROUND", "OFFER", "EFFECTIVE"
+        );
+        for row in self {
+            let _ = writeln!(
+                out,
+                "{:<44}  {:<9}  {:>5}  {:>14}  {:>14}",
+                row.address,
+                row.status,
+                format!("{}/{}", row.current_round, row.max_rounds),
+                row.current_offer_amount,
+                row.effective_escrow
+            );
+        }
+        out
+    }
+}
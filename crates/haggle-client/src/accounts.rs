@@ -0,0 +1,175 @@
+use anchor_lang::error::ErrorCode;
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::{AccountDeserialize, AnchorDeserialize, Discriminator};
+use haggle::migration::{NegotiationStateV0, NegotiationStateV1, ProtocolConfigV0};
+use haggle::state::{
+    AcceptancePolicy, Auction, BuyerCoalition, NegotiationState, NegotiationStatus,
+    NegotiationTemplate, ProtocolConfig, ReverseAuction, SealedBid, SellerBond,
+};
+use solana_client::rpc_client::RpcClient;
+use solana_client::rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig};
+use solana_client::rpc_filter::{Memcmp, RpcFilterType};
+use solana_sdk::account::Account;
+
+use crate::error::{ClientError, Result};
+
+// Byte offsets into a NegotiationState account, discriminator included. They
+// are the same in every layout version.
+const BUYER_OFFSET: usize = 8;
+const SELLER_OFFSET: usize = BUYER_OFFSET + 32;
+const STATUS_OFFSET: usize = SELLER_OFFSET + 32 + 8;
+
+/// Decodes a `NegotiationState` account, checking the Anchor discriminator.
+///
+/// Accounts in an older layout are upgraded as `migrate_negotiation` would,
+/// but keep their own `version` so callers can tell they still need it.
+/// Accounts whose enum bytes are out of range are refused, so the status,
+/// offer side, ZOPA phase and termination reason of a decoded state can be
+/// read without failing.
+pub fn decode_negotiation(data: &[u8]) -> Result<NegotiationState> {
+    if !data.starts_with(&NegotiationState::DISCRIMINATOR) {
+        return Err(ClientError::AccountDecode(
+            ErrorCode::AccountDiscriminatorMismatch.into(),
+        ));
+    }
+    let (mut state, version) = match data.len() {
+        NegotiationStateV0::LEN => (
+            decode_legacy::<NegotiationStateV0>(data)?
+                .upgrade()
+                .upgrade(),
+            0,
+        ),
+        NegotiationStateV1::LEN => (decode_legacy::<NegotiationStateV1>(data)?.upgrade(), 1),
+        len if len >= NegotiationState::LEN => {
+            let state: NegotiationState =
+                bytemuck::pod_read_unaligned(&data[8..NegotiationState::LEN]);
+            let version = state.version;
+            (state, version)
+        }
+        _ => {
+            return Err(ClientError::AccountDecode(
+                ErrorCode::AccountDidNotDeserialize.into(),
+            ))
+        }
+    };
+    state.version = version;
+    state.status()?;
+    state.offer_side()?;
+    state.zopa_phase()?;
+    state.termination_reason()?;
+    Ok(state)
+}
+
+/// Decodes the `ProtocolConfig` account, checking the Anchor discriminator.
+/// Older layouts are upgraded as for [`decode_negotiation`].
+pub fn decode_config(data: &[u8]) -> Result<ProtocolConfig> {
+    if data.len() == ProtocolConfigV0::LEN && data.starts_with(&ProtocolConfig::DISCRIMINATOR) {
+        let mut config = decode_legacy::<ProtocolConfigV0>(data)?.upgrade();
+        config.version = 0;
+        return Ok(config);
+    }
+    let mut data = data;
+    Ok(ProtocolConfig::try_deserialize(&mut data)?)
+}
+
+fn decode_legacy<T: AnchorDeserialize>(data: &[u8]) -> Result<T> {
+    T::deserialize(&mut &data[8..])
+        .map_err(|_| ClientError::AccountDecode(ErrorCode::AccountDidNotDeserialize.into()))
+}
+
+/// Decodes an `Auction` account, checking the Anchor discriminator.
+pub fn decode_auction(data: &[u8]) -> Result<Auction> {
+    let mut data = data;
+    Ok(Auction::try_deserialize(&mut data)?)
+}
+
+/// Decodes a `SealedBid` account, checking the Anchor discriminator.
+pub fn decode_sealed_bid(data: &[u8]) -> Result<SealedBid> {
+    let mut data = data;
+    Ok(SealedBid::try_deserialize(&mut data)?)
+}
+
+/// Decodes a `ReverseAuction` account, checking the Anchor discriminator.
+pub fn decode_reverse_auction(data: &[u8]) -> Result<ReverseAuction> {
+    let mut data = data;
+    Ok(ReverseAuction::try_deserialize(&mut data)?)
+}
+
+/// Decodes a `SellerBond` account, checking the Anchor discriminator.
+pub fn decode_seller_bond(data: &[u8]) -> Result<SellerBond> {
+    let mut data = data;
+    Ok(SellerBond::try_deserialize(&mut data)?)
+}
+
+/// Decodes a `NegotiationTemplate` account, checking the Anchor discriminator.
+pub fn decode_template(data: &[u8]) -> Result<NegotiationTemplate> {
+    let mut data = data;
+    Ok(NegotiationTemplate::try_deserialize(&mut data)?)
+}
+
+/// Decodes an `AcceptancePolicy` account, checking the Anchor discriminator.
+pub fn decode_policy(data: &[u8]) -> Result<AcceptancePolicy> {
+    let mut data = data;
+    Ok(AcceptancePolicy::try_deserialize(&mut data)?)
+}
+
+/// Decodes a `BuyerCoalition` account, checking the Anchor discriminator.
+pub fn decode_coalition(data: &[u8]) -> Result<BuyerCoalition> {
+    let mut data = data;
+    Ok(BuyerCoalition::try_deserialize(&mut data)?)
+}
+
+/// Server-side filter for `getProgramAccounts` over negotiation accounts.
+/// Unset fields match everything.
+#[derive(Clone, Debug, Default)]
+pub struct NegotiationFilter {
+    pub buyer: Option<Pubkey>,
+    pub seller: Option<Pubkey>,
+    pub status: Option<NegotiationStatus>,
+}
+
+impl NegotiationFilter {
+    pub fn to_rpc_filters(&self) -> Vec<RpcFilterType> {
+        let mut filters = vec![RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+            0,
+            &NegotiationState::DISCRIMINATOR,
+        ))];
+        if let Some(buyer) = self.buyer {
+            filters.push(memcmp(BUYER_OFFSET, buyer.as_ref()));
+        }
+        if let Some(seller) = self.seller {
+            filters.push(memcmp(SELLER_OFFSET, seller.as_ref()));
+        }
+        if let Some(status) = self.status {
+            filters.push(memcmp(STATUS_OFFSET, &[status as u8]));
+        }
+        filters
+    }
+}
+
+fn memcmp(offset: usize, bytes: &[u8]) -> RpcFilterType {
+    RpcFilterType::Memcmp(Memcmp::new_base58_encoded(offset, bytes))
+}
+
+/// Fetches every negotiation matching `filter`, skipping accounts that fail
+/// to decode.
+pub fn fetch_negotiations(
+    rpc: &RpcClient,
+    filter: &NegotiationFilter,
+) -> Result<Vec<(Pubkey, NegotiationState)>> {
+    let config = RpcProgramAccountsConfig {
+        filters: Some(filter.to_rpc_filters()),
+        account_config: RpcAccountInfoConfig {
+            encoding: Some(solana_account_decoder::UiAccountEncoding::Base64),
+            commitment: Some(rpc.commitment()),
+            ..RpcAccountInfoConfig::default()
+        },
+        ..RpcProgramAccountsConfig::default()
+    };
+    let accounts: Vec<(Pubkey, Account)> =
+        rpc.get_program_accounts_with_config(&haggle::ID, config)?;
+    Ok(accounts
+        .into_iter()
+        .filter_map(|(key, account)| Some((key, decode_negotiation(&account.data).ok()?)))
+        .collect())
+}
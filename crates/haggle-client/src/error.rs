@@ -0,0 +1,19 @@
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum ClientError {
+    #[error("rpc error: {0}")]
+    Rpc(Box<solana_client::client_error::ClientError>),
+    #[error("failed to decode account: {0}")]
+    AccountDecode(#[from] anchor_lang::error::Error),
+    #[error("account not found: {0}")]
+    AccountNotFound(solana_sdk::pubkey::Pubkey),
+}
+
+impl From<solana_client::client_error::ClientError> for ClientError {
+    fn from(err: solana_client::client_error::ClientError) -> Self {
+        ClientError::Rpc(Box::new(err))
+    }
+}
+
+pub type Result<T> = std::result::Result<T, ClientError>;
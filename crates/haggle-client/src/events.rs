@@ -0,0 +1,336 @@
+use std::str::FromStr;
+
+use anchor_lang::event::EVENT_IX_TAG_LE;
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::{AnchorDeserialize, Discriminator, Event};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use haggle::events::*;
+use solana_sdk::bs58;
+use solana_transaction_status::option_serializer::OptionSerializer;
+use solana_transaction_status::{
+    EncodedTransaction, EncodedTransactionWithStatusMeta, UiInstruction, UiMessage,
+    UiParsedInstruction,
+};
+
+const PROGRAM_DATA: &str = "Program data: ";
+
+/// Every event the program emits.
+pub enum HaggleEvent {
+    ConfigInitialized(ConfigInitialized),
+    TemplateRegistered(TemplateRegistered),
+    AcceptancePolicySet(AcceptancePolicySet),
+    AcceptancePolicyClosed(AcceptancePolicyClosed),
+    NegotiationCreated(NegotiationCreated),
+    InvitationAccepted(InvitationAccepted),
+    OfferSubmitted(OfferSubmitted),
+    NegotiationSettled(NegotiationSettled),
+    Payout(Payout),
+    NegotiationExpired(NegotiationExpired),
+    NegotiationRejected(NegotiationRejected),
+    NegotiationClosed(NegotiationClosed),
+    NegotiationCancelled(NegotiationCancelled),
+    AuctionCreated(AuctionCreated),
+    BidPlaced(BidPlaced),
+    AuctionUnsold(AuctionUnsold),
+    AuctionSettled(AuctionSettled),
+    AuctionClosed(AuctionClosed),
+    BidCommitted(BidCommitted),
+    BidRevealed(BidRevealed),
+    BidWithdrawn(BidWithdrawn),
+    ReverseAuctionCreated(ReverseAuctionCreated),
+    BondPosted(BondPosted),
+    ReverseBidPlaced(ReverseBidPlaced),
+    ReverseAuctionSettled(ReverseAuctionSettled),
+    ReverseAuctionClosed(ReverseAuctionClosed),
+    CoalitionCreated(CoalitionCreated),
+    CoalitionJoined(CoalitionJoined),
+    CoalitionExited(CoalitionExited),
+}
+
+impl HaggleEvent {
+    pub fn name(&self) -> &'static str {
+        match self {
+            HaggleEvent::ConfigInitialized(_) => "ConfigInitialized",
+            HaggleEvent::TemplateRegistered(_) => "TemplateRegistered",
+            HaggleEvent::AcceptancePolicySet(_) => "AcceptancePolicySet",
+            HaggleEvent::AcceptancePolicyClosed(_) => "AcceptancePolicyClosed",
+            HaggleEvent::NegotiationCreated(_) => "NegotiationCreated",
+            HaggleEvent::InvitationAccepted(_) => "InvitationAccepted",
+            HaggleEvent::OfferSubmitted(_) => "OfferSubmitted",
+            HaggleEvent::NegotiationSettled(_) => "NegotiationSettled",
+            HaggleEvent::Payout(_) => "Payout",
+            HaggleEvent::NegotiationExpired(_) => "NegotiationExpired",
+            HaggleEvent::NegotiationRejected(_) => "NegotiationRejected",
+            HaggleEvent::NegotiationClosed(_) => "NegotiationClosed",
+            HaggleEvent::NegotiationCancelled(_) => "NegotiationCancelled",
+            HaggleEvent::AuctionCreated(_) => "AuctionCreated",
+            HaggleEvent::BidPlaced(_) => "BidPlaced",
+            HaggleEvent::AuctionUnsold(_) => "AuctionUnsold",
+            HaggleEvent::AuctionSettled(_) => "AuctionSettled",
+            HaggleEvent::AuctionClosed(_) => "AuctionClosed",
+            HaggleEvent::BidCommitted(_) => "BidCommitted",
+            HaggleEvent::BidRevealed(_) => "BidRevealed",
+            HaggleEvent::BidWithdrawn(_) => "BidWithdrawn",
+            HaggleEvent::ReverseAuctionCreated(_) => "ReverseAuctionCreated",
+            HaggleEvent::BondPosted(_) => "BondPosted",
+            HaggleEvent::ReverseBidPlaced(_) => "ReverseBidPlaced",
+            HaggleEvent::ReverseAuctionSettled(_) => "ReverseAuctionSettled",
+            HaggleEvent::ReverseAuctionClosed(_) => "ReverseAuctionClosed",
+            HaggleEvent::CoalitionCreated(_) => "CoalitionCreated",
+            HaggleEvent::CoalitionJoined(_) => "CoalitionJoined",
+            HaggleEvent::CoalitionExited(_) => "CoalitionExited",
+        }
+    }
+
+    /// The negotiation the event belongs to; `None` for protocol-level events.
+    /// Auction events carry the auction account here instead.
+    pub fn negotiation_id(&self) -> Option<Pubkey> {
+        match self {
+            HaggleEvent::ConfigInitialized(_)
+            | HaggleEvent::TemplateRegistered(_)
+            | HaggleEvent::AcceptancePolicySet(_)
+            | HaggleEvent::AcceptancePolicyClosed(_) => None,
+            HaggleEvent::NegotiationCreated(e) => Some(e.negotiation_id),
+            HaggleEvent::InvitationAccepted(e) => Some(e.negotiation_id),
+            HaggleEvent::OfferSubmitted(e) => Some(e.negotiation_id),
+            HaggleEvent::NegotiationSettled(e) => Some(e.negotiation_id),
+            HaggleEvent::Payout(e) => Some(e.negotiation_id),
+            HaggleEvent::NegotiationExpired(e) => Some(e.negotiation_id),
+            HaggleEvent::NegotiationRejected(e) => Some(e.negotiation_id),
+            HaggleEvent::NegotiationClosed(e) => Some(e.negotiation_id),
+            HaggleEvent::NegotiationCancelled(e) => Some(e.negotiation_id),
+            HaggleEvent::AuctionCreated(e) => Some(e.auction),
+            HaggleEvent::BidPlaced(e) => Some(e.auction),
+            HaggleEvent::AuctionUnsold(e) => Some(e.auction),
+            HaggleEvent::AuctionSettled(e) => Some(e.auction),
+            HaggleEvent::AuctionClosed(e) => Some(e.auction),
+            HaggleEvent::BidCommitted(e) => Some(e.auction),
+            HaggleEvent::BidRevealed(e) => Some(e.auction),
+            HaggleEvent::BidWithdrawn(e) => Some(e.auction),
+            HaggleEvent::ReverseAuctionCreated(e) => Some(e.auction),
+            HaggleEvent::BondPosted(e) => Some(e.auction),
+            HaggleEvent::ReverseBidPlaced(e) => Some(e.auction),
+            HaggleEvent::ReverseAuctionSettled(e) => Some(e.auction),
+            HaggleEvent::ReverseAuctionClosed(e) => Some(e.auction),
+            HaggleEvent::CoalitionCreated(e) => Some(e.negotiation),
+            HaggleEvent::CoalitionJoined(e) => Some(e.negotiation),
+            HaggleEvent::CoalitionExited(e) => Some(e.negotiation),
+        }
+    }
+
+    /// Re-encodes the event as emitted: discriminator followed by borsh data.
+    /// `decode_event` is its inverse.
+    pub fn data(&self) -> Vec<u8> {
+        match self {
+            HaggleEvent::ConfigInitialized(e) => e.data(),
+            HaggleEvent::TemplateRegistered(e) => e.data(),
+            HaggleEvent::AcceptancePolicySet(e) => e.data(),
+            HaggleEvent::AcceptancePolicyClosed(e) => e.data(),
+            HaggleEvent::NegotiationCreated(e) => e.data(),
+            HaggleEvent::InvitationAccepted(e) => e.data(),
+            HaggleEvent::OfferSubmitted(e) => e.data(),
+            HaggleEvent::NegotiationSettled(e) => e.data(),
+            HaggleEvent::Payout(e) => e.data(),
+            HaggleEvent::NegotiationExpired(e) => e.data(),
+            HaggleEvent::NegotiationRejected(e) => e.data(),
+            HaggleEvent::NegotiationClosed(e) => e.data(),
+            HaggleEvent::NegotiationCancelled(e) => e.data(),
+            HaggleEvent::AuctionCreated(e) => e.data(),
+            HaggleEvent::BidPlaced(e) => e.data(),
+            HaggleEvent::AuctionUnsold(e) => e.data(),
+            HaggleEvent::AuctionSettled(e) => e.data(),
+            HaggleEvent::AuctionClosed(e) => e.data(),
+            HaggleEvent::BidCommitted(e) => e.data(),
+            HaggleEvent::BidRevealed(e) => e.data(),
+            HaggleEvent::BidWithdrawn(e) => e.data(),
+            HaggleEvent::ReverseAuctionCreated(e) => e.data(),
+            HaggleEvent::BondPosted(e) => e.data(),
+            HaggleEvent::ReverseBidPlaced(e) => e.data(),
+            HaggleEvent::ReverseAuctionSettled(e) => e.data(),
+            HaggleEvent::ReverseAuctionClosed(e) => e.data(),
+            HaggleEvent::CoalitionCreated(e) => e.data(),
+            HaggleEvent::CoalitionJoined(e) => e.data(),
+            HaggleEvent::CoalitionExited(e) => e.data(),
+        }
+    }
+}
+
+/// Decodes a raw event payload (8-byte discriminator followed by borsh data).
+/// Returns `None` for payloads that are not Haggle events.
+pub fn decode_event(data: &[u8]) -> Option<HaggleEvent> {
+    if data.len() < 8 {
+        return None;
+    }
+    let (disc, mut body) = data.split_at(8);
+
+    macro_rules! try_decode {
+        ($($ty:ident),*) => {
+            $(
+                if disc == $ty::DISCRIMINATOR {
+                    return $ty::deserialize(&mut body).ok().map(HaggleEvent::$ty);
+                }
+            )*
+        };
+    }
+    try_decode!(
+        ConfigInitialized,
+        TemplateRegistered,
+        AcceptancePolicySet,
+        AcceptancePolicyClosed,
+        NegotiationCreated,
+        InvitationAccepted,
+        OfferSubmitted,
+        NegotiationSettled,
+        Payout,
+        NegotiationExpired,
+        NegotiationRejected,
+        NegotiationClosed,
+        NegotiationCancelled,
+        AuctionCreated,
+        BidPlaced,
+        AuctionUnsold,
+        AuctionSettled,
+        AuctionClosed,
+        BidCommitted,
+        BidRevealed,
+        BidWithdrawn,
+        ReverseAuctionCreated,
+        BondPosted,
+        ReverseBidPlaced,
+        ReverseAuctionSettled,
+        ReverseAuctionClosed,
+        CoalitionCreated,
+        CoalitionJoined,
+        CoalitionExited
+    );
+    None
+}
+
+/// Decodes the data of an event self-CPI: the event instruction tag followed
+/// by the event payload. Returns `None` for any other instruction.
+pub fn decode_cpi_event(data: &[u8]) -> Option<HaggleEvent> {
+    data.strip_prefix(&EVENT_IX_TAG_LE[..])
+        .and_then(decode_event)
+}
+
+/// Extracts Haggle events from a transaction's inner instructions, given as
+/// `(program id, data)` pairs in execution order.
+///
+/// Only instructions invoking the Haggle program are considered. The program
+/// rejects event instructions not signed by its event authority PDA, so other
+/// programs cannot forge them.
+pub fn parse_inner_instructions(instructions: &[(Pubkey, Vec<u8>)]) -> Vec<HaggleEvent> {
+    instructions
+        .iter()
+        .filter(|(program_id, _)| *program_id == haggle::ID)
+        .filter_map(|(_, data)| decode_cpi_event(data))
+        .collect()
+}
+
+/// Flattens the inner instructions of a transaction fetched with
+/// `getTransaction` into `(program id, data)` pairs, resolving program
+/// indices against the message keys and any addresses loaded from lookup
+/// tables. Instructions the node fully parsed carry no raw data and are
+/// skipped.
+pub fn inner_instructions(tx: &EncodedTransactionWithStatusMeta) -> Vec<(Pubkey, Vec<u8>)> {
+    let Some(OptionSerializer::Some(inner)) = tx.meta.as_ref().map(|m| &m.inner_instructions)
+    else {
+        return Vec::new();
+    };
+    let keys = account_keys(tx);
+    inner
+        .iter()
+        .flat_map(|set| &set.instructions)
+        .filter_map(|ix| {
+            let (program_id, data) = match ix {
+                UiInstruction::Compiled(ix) => (*keys.get(ix.program_id_index as usize)?, &ix.data),
+                UiInstruction::Parsed(UiParsedInstruction::PartiallyDecoded(ix)) => {
+                    (Pubkey::from_str(&ix.program_id).ok()?, &ix.data)
+                }
+                UiInstruction::Parsed(UiParsedInstruction::Parsed(_)) => return None,
+            };
+            Some((program_id, bs58::decode(data).into_vec().ok()?))
+        })
+        .collect()
+}
+
+fn account_keys(tx: &EncodedTransactionWithStatusMeta) -> Vec<Pubkey> {
+    let parse = |key: &String| Pubkey::from_str(key).unwrap_or_default();
+    let mut keys: Vec<Pubkey> = match &tx.transaction {
+        // `jsonParsed` already lists the loaded addresses.
+        EncodedTransaction::Json(ui) => match &ui.message {
+            UiMessage::Parsed(message) => {
+                return message
+                    .account_keys
+                    .iter()
+                    .map(|k| parse(&k.pubkey))
+                    .collect()
+            }
+            UiMessage::Raw(message) => message.account_keys.iter().map(parse).collect(),
+        },
+        encoded => encoded
+            .decode()
+            .map(|tx| tx.message.static_account_keys().to_vec())
+            .unwrap_or_default(),
+    };
+    if let Some(OptionSerializer::Some(loaded)) = tx.meta.as_ref().map(|m| &m.loaded_addresses) {
+        keys.extend(loaded.writable.iter().chain(&loaded.readonly).map(parse));
+    }
+    keys
+}
+
+/// Extracts Haggle events from a transaction fetched with `getTransaction`.
+///
+/// Events are read from the program's self-CPIs, which survive log
+/// truncation. Transactions from before the program switched to self-CPI
+/// events carry them in the logs instead, which is used as a fallback.
+pub fn parse_transaction(tx: &EncodedTransactionWithStatusMeta) -> Vec<HaggleEvent> {
+    let events = parse_inner_instructions(&inner_instructions(tx));
+    if !events.is_empty() {
+        return events;
+    }
+    match tx.meta.as_ref().map(|m| &m.log_messages) {
+        Some(OptionSerializer::Some(logs)) => parse_logs(logs),
+        _ => Vec::new(),
+    }
+}
+
+/// Extracts Haggle events from a transaction's log messages, where the
+/// program wrote them before it switched to self-CPI events. Logs can be
+/// truncated, so prefer [`parse_transaction`] for current transactions.
+///
+/// Only `Program data:` lines written while the Haggle program is the
+/// innermost executing program are considered, so events from other
+/// programs with colliding discriminators are ignored.
+pub fn parse_logs<S: AsRef<str>>(logs: &[S]) -> Vec<HaggleEvent> {
+    let program_id = haggle::ID.to_string();
+    let mut stack: Vec<String> = Vec::new();
+    let mut events = Vec::new();
+
+    for line in logs {
+        let line = line.as_ref();
+        if let Some((id, tail)) = line
+            .strip_prefix("Program ")
+            .and_then(|rest| rest.split_once(' '))
+            .filter(|(id, _)| !id.ends_with(':'))
+        {
+            if tail.starts_with("invoke [") {
+                stack.push(id.to_string());
+                continue;
+            }
+            if tail == "success" || tail.starts_with("failed") {
+                stack.pop();
+                continue;
+            }
+        }
+        if stack.last() != Some(&program_id) {
+            continue;
+        }
+        if let Some(encoded) = line.strip_prefix(PROGRAM_DATA) {
+            if let Some(event) = STANDARD.decode(encoded).ok().and_then(|d| decode_event(&d)) {
+                events.push(event);
+            }
+        }
+    }
+    events
+}
@@ -0,0 +1,801 @@
+//! Instruction builders, one per program entrypoint.
+//!
+//! PDAs are derived from the arguments; token accounts are taken as-is so
+//! callers can use ATAs or any other account owned by the right party.
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::{system_program, InstructionData, ToAccountMetas};
+use anchor_spl::token;
+use haggle::state::{
+    AuctionParams, ConfigUpdateParams, NegotiationParams, OpeningOffer, PolicyParams,
+    ReverseAuctionParams, TemplateParams, TerminationReason,
+};
+use solana_sdk::instruction::{AccountMeta, Instruction};
+
+use crate::pda::{
+    find_auction_pda, find_bond_pda, find_coalition_pda, find_config_pda, find_event_authority_pda,
+    find_negotiation_pda, find_policy_pda, find_reverse_auction_pda, find_sealed_bid_pda,
+    find_template_pda, find_vault_pda,
+};
+
+fn instruction(accounts: impl ToAccountMetas, data: impl InstructionData) -> Instruction {
+    Instruction {
+        program_id: haggle::ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+pub fn initialize_config(
+    authority: &Pubkey,
+    treasury: &Pubkey,
+    default_decay_rate_bps: u16,
+    default_response_window: i64,
+    default_protocol_fee_bps: u16,
+    default_max_rounds: u8,
+) -> Instruction {
+    instruction(
+        haggle::accounts::InitializeConfig {
+            authority: *authority,
+            config: find_config_pda().0,
+            system_program: system_program::ID,
+            event_authority: find_event_authority_pda().0,
+            program: haggle::ID,
+        },
+        haggle::instruction::InitializeConfig {
+            treasury: *treasury,
+            default_decay_rate_bps,
+            default_response_window,
+            default_protocol_fee_bps,
+            default_max_rounds,
+        },
+    )
+}
+
+pub fn update_config(authority: &Pubkey, params: ConfigUpdateParams) -> Instruction {
+    instruction(
+        haggle::accounts::UpdateConfig {
+            authority: *authority,
+            config: find_config_pda().0,
+        },
+        haggle::instruction::UpdateConfig { params },
+    )
+}
+
+/// `price_update` is only needed for an opening offer in a quote-denominated
+/// negotiation.
+#[allow(clippy::too_many_arguments)]
+pub fn create_negotiation(
+    buyer: &Pubkey,
+    seller: &Pubkey,
+    token_mint: &Pubkey,
+    buyer_token_account: &Pubkey,
+    session_id: u64,
+    params: NegotiationParams,
+    opening_offer: Option<OpeningOffer>,
+    price_update: Option<&Pubkey>,
+) -> Instruction {
+    let negotiation = find_negotiation_pda(buyer, seller, session_id).0;
+    instruction(
+        haggle::accounts::CreateNegotiation {
+            buyer: *buyer,
+            seller: *seller,
+            policy: find_policy_pda(seller).0,
+            negotiation,
+            escrow_vault: find_vault_pda(&negotiation).0,
+            buyer_token_account: *buyer_token_account,
+            token_mint: *token_mint,
+            price_update: price_update.copied(),
+            config: find_config_pda().0,
+            token_program: token::ID,
+            system_program: system_program::ID,
+            event_authority: find_event_authority_pda().0,
+            program: haggle::ID,
+        },
+        haggle::instruction::CreateNegotiation {
+            session_id,
+            params,
+            opening_offer,
+        },
+    )
+}
+
+pub fn register_template(owner: &Pubkey, template_id: u64, params: TemplateParams) -> Instruction {
+    instruction(
+        haggle::accounts::RegisterTemplate {
+            owner: *owner,
+            template: find_template_pda(owner, template_id).0,
+            system_program: system_program::ID,
+            event_authority: find_event_authority_pda().0,
+            program: haggle::ID,
+        },
+        haggle::instruction::RegisterTemplate {
+            template_id,
+            params,
+        },
+    )
+}
+
+/// The template's terms apply as registered; the buyer still picks the
+/// escrow, the service and the `invitation_ttl`.
+#[allow(clippy::too_many_arguments)]
+pub fn create_negotiation_from_template(
+    buyer: &Pubkey,
+    seller: &Pubkey,
+    template: &Pubkey,
+    token_mint: &Pubkey,
+    buyer_token_account: &Pubkey,
+    session_id: u64,
+    escrow_amount: u64,
+    service_hash: [u8; 32],
+    opening_offer: Option<OpeningOffer>,
+    invitation_ttl: Option<i64>,
+) -> Instruction {
+    let negotiation = find_negotiation_pda(buyer, seller, session_id).0;
+    instruction(
+        haggle::accounts::CreateNegotiationFromTemplate {
+            buyer: *buyer,
+            seller: *seller,
+            policy: find_policy_pda(seller).0,
+            template: *template,
+            negotiation,
+            escrow_vault: find_vault_pda(&negotiation).0,
+            buyer_token_account: *buyer_token_account,
+            token_mint: *token_mint,
+            config: find_config_pda().0,
+            token_program: token::ID,
+            system_program: system_program::ID,
+            event_authority: find_event_authority_pda().0,
+            program: haggle::ID,
+        },
+        haggle::instruction::CreateNegotiationFromTemplate {
+            session_id,
+            escrow_amount,
+            service_hash,
+            opening_offer,
+            invitation_ttl,
+        },
+    )
+}
+
+pub fn create_acceptance_policy(seller: &Pubkey, params: PolicyParams) -> Instruction {
+    instruction(
+        haggle::accounts::CreateAcceptancePolicy {
+            seller: *seller,
+            policy: find_policy_pda(seller).0,
+            system_program: system_program::ID,
+            event_authority: find_event_authority_pda().0,
+            program: haggle::ID,
+        },
+        haggle::instruction::CreateAcceptancePolicy { params },
+    )
+}
+
+pub fn update_acceptance_policy(seller: &Pubkey, params: PolicyParams) -> Instruction {
+    instruction(
+        haggle::accounts::UpdateAcceptancePolicy {
+            seller: *seller,
+            policy: find_policy_pda(seller).0,
+            event_authority: find_event_authority_pda().0,
+            program: haggle::ID,
+        },
+        haggle::instruction::UpdateAcceptancePolicy { params },
+    )
+}
+
+pub fn close_acceptance_policy(seller: &Pubkey) -> Instruction {
+    instruction(
+        haggle::accounts::CloseAcceptancePolicy {
+            seller: *seller,
+            policy: find_policy_pda(seller).0,
+            event_authority: find_event_authority_pda().0,
+            program: haggle::ID,
+        },
+        haggle::instruction::CloseAcceptancePolicy {},
+    )
+}
+
+pub fn accept_invitation(
+    seller: &Pubkey,
+    negotiation: &Pubkey,
+    opening_offer: Option<OpeningOffer>,
+    price_update: Option<&Pubkey>,
+) -> Instruction {
+    instruction(
+        haggle::accounts::AcceptInvitation {
+            seller: *seller,
+            negotiation: *negotiation,
+            price_update: price_update.copied(),
+            event_authority: find_event_authority_pda().0,
+            program: haggle::ID,
+        },
+        haggle::instruction::AcceptInvitation { opening_offer },
+    )
+}
+
+/// `price_update` is the Pyth price account of a quote-denominated
+/// negotiation's feed, and `None` otherwise. A final offer can only be
+/// accepted or left to end.
+pub fn submit_offer(
+    offerer: &Pubkey,
+    negotiation: &Pubkey,
+    amount: u64,
+    metadata: [u8; 64],
+    is_final: bool,
+    price_update: Option<&Pubkey>,
+) -> Instruction {
+    instruction(
+        haggle::accounts::SubmitOffer {
+            offerer: *offerer,
+            negotiation: *negotiation,
+            price_update: price_update.copied(),
+            event_authority: find_event_authority_pda().0,
+            program: haggle::ID,
+        },
+        haggle::instruction::SubmitOffer {
+            amount,
+            metadata,
+            is_final,
+        },
+    )
+}
+
+/// Accounts to pay a cross-mint negotiation's seller through its swap
+/// adapter.
+#[derive(Clone, Debug)]
+pub struct SwapRoute {
+    pub swap_adapter: Pubkey,
+    /// Seller's token account for the payout mint.
+    pub seller_payout_account: Pubkey,
+    /// The adapter's own accounts, in the order its `quote` and `swap` take
+    /// them.
+    pub adapter_accounts: Vec<AccountMeta>,
+}
+
+/// `seller` is the negotiation's seller, whose acceptance policy gets the
+/// negotiation's slot back. Quote-denominated negotiations settle at the
+/// price in `price_update`. Cross-mint negotiations need `swap_route` unless
+/// the seller accepts, who may leave it out to be paid in the escrow mint.
+#[allow(clippy::too_many_arguments)]
+pub fn accept_offer(
+    acceptor: &Pubkey,
+    negotiation: &Pubkey,
+    seller: &Pubkey,
+    seller_token_account: &Pubkey,
+    treasury_token_account: &Pubkey,
+    buyer_token_account: &Pubkey,
+    price_update: Option<&Pubkey>,
+    swap_route: Option<&SwapRoute>,
+) -> Instruction {
+    let mut ix = instruction(
+        haggle::accounts::AcceptOffer {
+            acceptor: *acceptor,
+            negotiation: *negotiation,
+            escrow_vault: find_vault_pda(negotiation).0,
+            seller_token_account: *seller_token_account,
+            treasury_token_account: *treasury_token_account,
+            buyer_token_account: *buyer_token_account,
+            config: find_config_pda().0,
+            policy: find_policy_pda(seller).0,
+            price_update: price_update.copied(),
+            seller_payout_account: swap_route.map(|route| route.seller_payout_account),
+            swap_adapter: swap_route.map(|route| route.swap_adapter),
+            token_program: token::ID,
+            event_authority: find_event_authority_pda().0,
+            program: haggle::ID,
+        },
+        haggle::instruction::AcceptOffer {},
+    );
+    if let Some(route) = swap_route {
+        ix.accounts.extend(route.adapter_accounts.iter().cloned());
+    }
+    ix
+}
+
+/// The escrow lost to decay goes to `treasury_token_account`, the rest back
+/// to the buyer.
+/// `reason` is recorded on the negotiation and in `NegotiationRejected`;
+/// `Timeout` is refused with `InvalidParams`.
+pub fn reject_negotiation(
+    rejector: &Pubkey,
+    negotiation: &Pubkey,
+    seller: &Pubkey,
+    buyer_token_account: &Pubkey,
+    treasury_token_account: &Pubkey,
+    reason: Option<TerminationReason>,
+) -> Instruction {
+    instruction(
+        haggle::accounts::RejectNegotiation {
+            rejector: *rejector,
+            negotiation: *negotiation,
+            escrow_vault: find_vault_pda(negotiation).0,
+            buyer_token_account: *buyer_token_account,
+            treasury_token_account: *treasury_token_account,
+            config: find_config_pda().0,
+            policy: find_policy_pda(seller).0,
+            token_program: token::ID,
+            event_authority: find_event_authority_pda().0,
+            program: haggle::ID,
+        },
+        haggle::instruction::RejectNegotiation { reason },
+    )
+}
+
+/// `cranker_token_account` receives the cranker's share of the refund;
+/// without it the share stays with the buyer.
+pub fn expire_negotiation(
+    cranker: &Pubkey,
+    negotiation: &Pubkey,
+    seller: &Pubkey,
+    buyer_token_account: &Pubkey,
+    treasury_token_account: &Pubkey,
+    cranker_token_account: Option<&Pubkey>,
+) -> Instruction {
+    instruction(
+        haggle::accounts::ExpireNegotiation {
+            cranker: *cranker,
+            negotiation: *negotiation,
+            escrow_vault: find_vault_pda(negotiation).0,
+            buyer_token_account: *buyer_token_account,
+            treasury_token_account: *treasury_token_account,
+            cranker_token_account: cranker_token_account.copied(),
+            config: find_config_pda().0,
+            policy: find_policy_pda(seller).0,
+            token_program: token::ID,
+            event_authority: find_event_authority_pda().0,
+            program: haggle::ID,
+        },
+        haggle::instruction::ExpireNegotiation {},
+    )
+}
+
+/// Anything left in the vault goes to `buyer_token_account`. A pooled
+/// negotiation whose coalition still has members needs their token accounts
+/// in `member_token_accounts`, in the coalition's member order, to pay out
+/// what they have not claimed.
+pub fn close_negotiation(
+    closer: &Pubkey,
+    negotiation: &Pubkey,
+    buyer: &Pubkey,
+    buyer_token_account: &Pubkey,
+    member_token_accounts: &[Pubkey],
+) -> Instruction {
+    let mut ix = instruction(
+        haggle::accounts::CloseNegotiation {
+            closer: *closer,
+            buyer: *buyer,
+            negotiation: *negotiation,
+            escrow_vault: find_vault_pda(negotiation).0,
+            buyer_token_account: *buyer_token_account,
+            coalition: find_coalition_pda(negotiation).0,
+            config: find_config_pda().0,
+            token_program: token::ID,
+            event_authority: find_event_authority_pda().0,
+            program: haggle::ID,
+        },
+        haggle::instruction::CloseNegotiation {},
+    );
+    ix.accounts.extend(
+        member_token_accounts
+            .iter()
+            .map(|account| AccountMeta::new(*account, false)),
+    );
+    ix
+}
+
+/// The escrow lost to decay, if the buyer already made an offer, goes to
+/// `treasury_token_account`; the rest and both accounts' rent go back to the
+/// buyer.
+pub fn cancel_invitation(
+    buyer: &Pubkey,
+    negotiation: &Pubkey,
+    seller: &Pubkey,
+    buyer_token_account: &Pubkey,
+    treasury_token_account: &Pubkey,
+) -> Instruction {
+    instruction(
+        haggle::accounts::CancelInvitation {
+            buyer: *buyer,
+            negotiation: *negotiation,
+            escrow_vault: find_vault_pda(negotiation).0,
+            buyer_token_account: *buyer_token_account,
+            treasury_token_account: *treasury_token_account,
+            config: find_config_pda().0,
+            policy: find_policy_pda(seller).0,
+            token_program: token::ID,
+            event_authority: find_event_authority_pda().0,
+            program: haggle::ID,
+        },
+        haggle::instruction::CancelInvitation {},
+    )
+}
+
+pub fn create_coalition(lead: &Pubkey, negotiation: &Pubkey) -> Instruction {
+    instruction(
+        haggle::accounts::CreateCoalition {
+            lead: *lead,
+            negotiation: *negotiation,
+            coalition: find_coalition_pda(negotiation).0,
+            system_program: system_program::ID,
+            event_authority: find_event_authority_pda().0,
+            program: haggle::ID,
+        },
+        haggle::instruction::CreateCoalition {},
+    )
+}
+
+pub fn join_coalition(
+    member: &Pubkey,
+    negotiation: &Pubkey,
+    member_token_account: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    instruction(
+        haggle::accounts::JoinCoalition {
+            member: *member,
+            negotiation: *negotiation,
+            coalition: find_coalition_pda(negotiation).0,
+            escrow_vault: find_vault_pda(negotiation).0,
+            member_token_account: *member_token_account,
+            token_program: token::ID,
+            event_authority: find_event_authority_pda().0,
+            program: haggle::ID,
+        },
+        haggle::instruction::JoinCoalition { amount },
+    )
+}
+
+/// `lead` receives the coalition's rent if `member` is the last to exit.
+pub fn exit_coalition(
+    member: &Pubkey,
+    lead: &Pubkey,
+    negotiation: &Pubkey,
+    member_token_account: &Pubkey,
+) -> Instruction {
+    instruction(
+        haggle::accounts::ExitCoalition {
+            member: *member,
+            lead: *lead,
+            negotiation: *negotiation,
+            coalition: find_coalition_pda(negotiation).0,
+            escrow_vault: find_vault_pda(negotiation).0,
+            member_token_account: *member_token_account,
+            token_program: token::ID,
+            event_authority: find_event_authority_pda().0,
+            program: haggle::ID,
+        },
+        haggle::instruction::ExitCoalition {},
+    )
+}
+
+pub fn create_auction(
+    seller: &Pubkey,
+    token_mint: &Pubkey,
+    auction_id: u64,
+    params: AuctionParams,
+) -> Instruction {
+    let auction = find_auction_pda(seller, auction_id).0;
+    instruction(
+        haggle::accounts::CreateAuction {
+            seller: *seller,
+            auction,
+            escrow_vault: find_vault_pda(&auction).0,
+            token_mint: *token_mint,
+            config: find_config_pda().0,
+            token_program: token::ID,
+            system_program: system_program::ID,
+            event_authority: find_event_authority_pda().0,
+            program: haggle::ID,
+        },
+        haggle::instruction::CreateAuction { auction_id, params },
+    )
+}
+
+/// `outbid_token_account` receives the current highest bidder's refund and
+/// must be `None` only for the first bid.
+pub fn place_bid(
+    bidder: &Pubkey,
+    auction: &Pubkey,
+    bidder_token_account: &Pubkey,
+    outbid_token_account: Option<&Pubkey>,
+    amount: u64,
+) -> Instruction {
+    instruction(
+        haggle::accounts::PlaceBid {
+            bidder: *bidder,
+            auction: *auction,
+            escrow_vault: find_vault_pda(auction).0,
+            bidder_token_account: *bidder_token_account,
+            outbid_token_account: outbid_token_account.copied(),
+            token_program: token::ID,
+            event_authority: find_event_authority_pda().0,
+            program: haggle::ID,
+        },
+        haggle::instruction::PlaceBid { amount },
+    )
+}
+
+pub fn buy_auction(
+    buyer: &Pubkey,
+    auction: &Pubkey,
+    buyer_token_account: &Pubkey,
+    seller_token_account: &Pubkey,
+    treasury_token_account: &Pubkey,
+    max_price: u64,
+) -> Instruction {
+    instruction(
+        haggle::accounts::BuyAuction {
+            buyer: *buyer,
+            auction: *auction,
+            escrow_vault: find_vault_pda(auction).0,
+            buyer_token_account: *buyer_token_account,
+            seller_token_account: *seller_token_account,
+            treasury_token_account: *treasury_token_account,
+            config: find_config_pda().0,
+            token_program: token::ID,
+            event_authority: find_event_authority_pda().0,
+            program: haggle::ID,
+        },
+        haggle::instruction::BuyAuction { max_price },
+    )
+}
+
+pub fn settle_auction(
+    cranker: &Pubkey,
+    auction: &Pubkey,
+    seller_token_account: &Pubkey,
+    treasury_token_account: &Pubkey,
+) -> Instruction {
+    instruction(
+        haggle::accounts::SettleAuction {
+            cranker: *cranker,
+            auction: *auction,
+            escrow_vault: find_vault_pda(auction).0,
+            seller_token_account: *seller_token_account,
+            treasury_token_account: *treasury_token_account,
+            config: find_config_pda().0,
+            token_program: token::ID,
+            event_authority: find_event_authority_pda().0,
+            program: haggle::ID,
+        },
+        haggle::instruction::SettleAuction {},
+    )
+}
+
+/// Anything left in the vault goes to `seller_token_account`.
+pub fn close_auction(
+    seller: &Pubkey,
+    auction: &Pubkey,
+    seller_token_account: &Pubkey,
+) -> Instruction {
+    instruction(
+        haggle::accounts::CloseAuction {
+            seller: *seller,
+            auction: *auction,
+            escrow_vault: find_vault_pda(auction).0,
+            seller_token_account: *seller_token_account,
+            token_program: token::ID,
+            event_authority: find_event_authority_pda().0,
+            program: haggle::ID,
+        },
+        haggle::instruction::CloseAuction {},
+    )
+}
+
+/// `commitment` is `SealedBid::commitment(amount, &salt)`; keep the salt to
+/// reveal with.
+pub fn commit_bid(
+    bidder: &Pubkey,
+    auction: &Pubkey,
+    bidder_token_account: &Pubkey,
+    commitment: [u8; 32],
+    deposit: u64,
+) -> Instruction {
+    instruction(
+        haggle::accounts::CommitBid {
+            bidder: *bidder,
+            auction: *auction,
+            sealed_bid: find_sealed_bid_pda(auction, bidder).0,
+            escrow_vault: find_vault_pda(auction).0,
+            bidder_token_account: *bidder_token_account,
+            token_program: token::ID,
+            system_program: system_program::ID,
+            event_authority: find_event_authority_pda().0,
+            program: haggle::ID,
+        },
+        haggle::instruction::CommitBid {
+            commitment,
+            deposit,
+        },
+    )
+}
+
+pub fn reveal_bid(bidder: &Pubkey, auction: &Pubkey, amount: u64, salt: [u8; 32]) -> Instruction {
+    instruction(
+        haggle::accounts::RevealBid {
+            bidder: *bidder,
+            auction: *auction,
+            sealed_bid: find_sealed_bid_pda(auction, bidder).0,
+            event_authority: find_event_authority_pda().0,
+            program: haggle::ID,
+        },
+        haggle::instruction::RevealBid { amount, salt },
+    )
+}
+
+pub fn withdraw_bid(
+    cranker: &Pubkey,
+    auction: &Pubkey,
+    bidder: &Pubkey,
+    bidder_token_account: &Pubkey,
+    seller_token_account: &Pubkey,
+) -> Instruction {
+    instruction(
+        haggle::accounts::WithdrawBid {
+            cranker: *cranker,
+            bidder: *bidder,
+            auction: *auction,
+            sealed_bid: find_sealed_bid_pda(auction, bidder).0,
+            escrow_vault: find_vault_pda(auction).0,
+            bidder_token_account: *bidder_token_account,
+            seller_token_account: *seller_token_account,
+            token_program: token::ID,
+            event_authority: find_event_authority_pda().0,
+            program: haggle::ID,
+        },
+        haggle::instruction::WithdrawBid {},
+    )
+}
+
+pub fn create_reverse_auction(
+    buyer: &Pubkey,
+    token_mint: &Pubkey,
+    buyer_token_account: &Pubkey,
+    auction_id: u64,
+    params: ReverseAuctionParams,
+) -> Instruction {
+    let auction = find_reverse_auction_pda(buyer, auction_id).0;
+    instruction(
+        haggle::accounts::CreateReverseAuction {
+            buyer: *buyer,
+            auction,
+            escrow_vault: find_vault_pda(&auction).0,
+            buyer_token_account: *buyer_token_account,
+            token_mint: *token_mint,
+            config: find_config_pda().0,
+            token_program: token::ID,
+            system_program: system_program::ID,
+            event_authority: find_event_authority_pda().0,
+            program: haggle::ID,
+        },
+        haggle::instruction::CreateReverseAuction { auction_id, params },
+    )
+}
+
+pub fn post_bond(seller: &Pubkey, auction: &Pubkey, seller_token_account: &Pubkey) -> Instruction {
+    instruction(
+        haggle::accounts::PostBond {
+            seller: *seller,
+            auction: *auction,
+            bond: find_bond_pda(auction, seller).0,
+            escrow_vault: find_vault_pda(auction).0,
+            seller_token_account: *seller_token_account,
+            token_program: token::ID,
+            system_program: system_program::ID,
+            event_authority: find_event_authority_pda().0,
+            program: haggle::ID,
+        },
+        haggle::instruction::PostBond {},
+    )
+}
+
+/// Pass `bonded` when the auction requires seller bonds.
+pub fn place_reverse_bid(
+    seller: &Pubkey,
+    auction: &Pubkey,
+    bonded: bool,
+    amount: u64,
+) -> Instruction {
+    instruction(
+        haggle::accounts::PlaceReverseBid {
+            seller: *seller,
+            auction: *auction,
+            bond: bonded.then(|| find_bond_pda(auction, seller).0),
+            event_authority: find_event_authority_pda().0,
+            program: haggle::ID,
+        },
+        haggle::instruction::PlaceReverseBid { amount },
+    )
+}
+
+/// `seller_token_account` belongs to the lowest bidder and is `None` only
+/// when nobody bid.
+pub fn settle_reverse_auction(
+    cranker: &Pubkey,
+    auction: &Pubkey,
+    buyer_token_account: &Pubkey,
+    seller_token_account: Option<&Pubkey>,
+    treasury_token_account: &Pubkey,
+) -> Instruction {
+    instruction(
+        haggle::accounts::SettleReverseAuction {
+            cranker: *cranker,
+            auction: *auction,
+            escrow_vault: find_vault_pda(auction).0,
+            buyer_token_account: *buyer_token_account,
+            seller_token_account: seller_token_account.copied(),
+            treasury_token_account: *treasury_token_account,
+            config: find_config_pda().0,
+            token_program: token::ID,
+            event_authority: find_event_authority_pda().0,
+            program: haggle::ID,
+        },
+        haggle::instruction::SettleReverseAuction {},
+    )
+}
+
+pub fn withdraw_bond(
+    cranker: &Pubkey,
+    auction: &Pubkey,
+    seller: &Pubkey,
+    seller_token_account: &Pubkey,
+) -> Instruction {
+    instruction(
+        haggle::accounts::WithdrawBond {
+            cranker: *cranker,
+            seller: *seller,
+            auction: *auction,
+            bond: find_bond_pda(auction, seller).0,
+            escrow_vault: find_vault_pda(auction).0,
+            seller_token_account: *seller_token_account,
+            token_program: token::ID,
+            event_authority: find_event_authority_pda().0,
+            program: haggle::ID,
+        },
+        haggle::instruction::WithdrawBond {},
+    )
+}
+
+/// Anything left in the vault goes to `buyer_token_account`.
+pub fn close_reverse_auction(
+    buyer: &Pubkey,
+    auction: &Pubkey,
+    buyer_token_account: &Pubkey,
+) -> Instruction {
+    instruction(
+        haggle::accounts::CloseReverseAuction {
+            buyer: *buyer,
+            auction: *auction,
+            escrow_vault: find_vault_pda(auction).0,
+            buyer_token_account: *buyer_token_account,
+            token_program: token::ID,
+            event_authority: find_event_authority_pda().0,
+            program: haggle::ID,
+        },
+        haggle::instruction::CloseReverseAuction {},
+    )
+}
+
+/// Rewrites a negotiation from an older account layout in the current one.
+/// Anyone can migrate; `payer` covers the extra rent.
+pub fn migrate_negotiation(payer: &Pubkey, negotiation: &Pubkey) -> Instruction {
+    instruction(
+        haggle::accounts::MigrateNegotiation {
+            payer: *payer,
+            negotiation: *negotiation,
+            system_program: system_program::ID,
+        },
+        haggle::instruction::MigrateNegotiation {},
+    )
+}
+
+pub fn migrate_config(authority: &Pubkey) -> Instruction {
+    instruction(
+        haggle::accounts::MigrateConfig {
+            authority: *authority,
+            config: find_config_pda().0,
+            system_program: system_program::ID,
+        },
+        haggle::instruction::MigrateConfig {},
+    )
+}
@@ -0,0 +1,31 @@
+//! Rust client for the Haggle Protocol program.
+//!
+//! Mirrors the TypeScript SDK in `sdk/`: PDA helpers, instruction builders,
+//! typed account and event decoding, and a high-level [`Negotiator`].
+//! On-chain types are re-exported from the program crate so the two cannot
+//! drift apart.
+
+pub mod accounts;
+pub mod error;
+pub mod events;
+pub mod instructions;
+pub mod negotiator;
+pub mod pda;
+pub mod utils;
+
+pub use accounts::*;
+pub use error::{ClientError, Result};
+pub use events::*;
+pub use instructions::SwapRoute;
+pub use negotiator::Negotiator;
+pub use pda::*;
+pub use utils::*;
+
+pub use haggle::state::{
+    AcceptancePolicy, Auction, AuctionKind, AuctionParams, AuctionStatus, BuyerCoalition,
+    BuyerFilter, CoalitionShare, ConfigUpdateParams, NegotiationParams, NegotiationState,
+    NegotiationStatus, NegotiationTemplate, OfferSide, OpeningOffer, PayoutTerms, PolicyParams,
+    ProtocolConfig, ReverseAuction, ReverseAuctionParams, SealedBid, SellerBond, TemplateParams,
+    TerminationReason, ZopaPhase,
+};
+pub use haggle::ID as PROGRAM_ID;
@@ -0,0 +1,280 @@
+use anchor_lang::prelude::Pubkey;
+use anchor_spl::associated_token::get_associated_token_address;
+use haggle::state::{
+    Auction, NegotiationParams, NegotiationState, OpeningOffer, ProtocolConfig, TerminationReason,
+};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    instruction::Instruction, signature::Signature, signer::Signer, transaction::Transaction,
+};
+
+use crate::accounts::{
+    decode_auction, decode_coalition, decode_config, decode_negotiation, fetch_negotiations,
+    NegotiationFilter,
+};
+use crate::error::{ClientError, Result};
+use crate::instructions::{self, SwapRoute};
+use crate::pda::{find_coalition_pda, find_config_pda, find_negotiation_pda, find_price_feed_pda};
+use crate::utils::create_metadata;
+
+/// High-level API for one agent taking part in negotiations.
+///
+/// Token accounts are assumed to be the associated token accounts of the
+/// buyer, seller and treasury for the negotiation's mint, and
+/// quote-denominated negotiations are priced by Pyth's sponsored feed on
+/// shard 0.
+pub struct Negotiator<S: Signer> {
+    rpc: RpcClient,
+    signer: S,
+}
+
+impl<S: Signer> Negotiator<S> {
+    pub fn new(rpc: RpcClient, signer: S) -> Self {
+        Self { rpc, signer }
+    }
+
+    pub fn rpc(&self) -> &RpcClient {
+        &self.rpc
+    }
+
+    pub fn pubkey(&self) -> Pubkey {
+        self.signer.pubkey()
+    }
+
+    // ===== Write Operations =====
+
+    /// Opens a negotiation as buyer and escrows `params.escrow_amount`,
+    /// optionally making the first offer in the same transaction.
+    /// Returns the signature and the negotiation PDA.
+    pub fn create(
+        &self,
+        seller: &Pubkey,
+        session_id: u64,
+        token_mint: &Pubkey,
+        params: NegotiationParams,
+        opening_offer: Option<(u64, &str)>,
+    ) -> Result<(Signature, Pubkey)> {
+        let buyer = self.pubkey();
+        let negotiation = find_negotiation_pda(&buyer, seller, session_id).0;
+        let buyer_token_account = get_associated_token_address(&buyer, token_mint);
+        let price_update = params
+            .price_feed
+            .map(|feed_id| find_price_feed_pda(0, &feed_id).0);
+        let ix = instructions::create_negotiation(
+            &buyer,
+            seller,
+            token_mint,
+            &buyer_token_account,
+            session_id,
+            params,
+            opening_offer.map(opening),
+            price_update.as_ref(),
+        );
+        Ok((self.send(&[ix])?, negotiation))
+    }
+
+    /// Joins a negotiation as seller, optionally countering in the same
+    /// transaction.
+    pub fn accept_invitation(
+        &self,
+        negotiation: &Pubkey,
+        opening_offer: Option<(u64, &str)>,
+    ) -> Result<Signature> {
+        let state = self.fetch_negotiation(negotiation)?;
+        self.send(&[instructions::accept_invitation(
+            &self.pubkey(),
+            negotiation,
+            opening_offer.map(opening),
+            price_update(&state).as_ref(),
+        )])
+    }
+
+    /// Offers `amount`, in quote units if the negotiation is
+    /// quote-denominated. A final offer cannot be countered.
+    pub fn offer(
+        &self,
+        negotiation: &Pubkey,
+        amount: u64,
+        metadata: &str,
+        is_final: bool,
+    ) -> Result<Signature> {
+        let state = self.fetch_negotiation(negotiation)?;
+        self.send(&[instructions::submit_offer(
+            &self.pubkey(),
+            negotiation,
+            amount,
+            create_metadata(metadata),
+            is_final,
+            price_update(&state).as_ref(),
+        )])
+    }
+
+    /// Accepts the counterparty's current offer and settles, paying the
+    /// seller in the escrow mint. Cross-mint negotiations need `accept_via`
+    /// unless the seller accepts.
+    pub fn accept(&self, negotiation: &Pubkey) -> Result<Signature> {
+        self.accept_via(negotiation, None)
+    }
+
+    /// Accepts and settles, swapping a cross-mint negotiation's payout
+    /// through `swap_route` if given.
+    pub fn accept_via(
+        &self,
+        negotiation: &Pubkey,
+        swap_route: Option<&SwapRoute>,
+    ) -> Result<Signature> {
+        let state = self.fetch_negotiation(negotiation)?;
+        let config = self.fetch_config()?;
+        let mint = state.token_mint;
+        self.send(&[instructions::accept_offer(
+            &self.pubkey(),
+            negotiation,
+            &state.seller,
+            &get_associated_token_address(&state.seller, &mint),
+            &get_associated_token_address(&config.treasury, &mint),
+            &get_associated_token_address(&state.buyer, &mint),
+            price_update(&state).as_ref(),
+            swap_route,
+        )])
+    }
+
+    pub fn reject(
+        &self,
+        negotiation: &Pubkey,
+        reason: Option<TerminationReason>,
+    ) -> Result<Signature> {
+        let state = self.fetch_negotiation(negotiation)?;
+        let config = self.fetch_config()?;
+        self.send(&[instructions::reject_negotiation(
+            &self.pubkey(),
+            negotiation,
+            &state.seller,
+            &get_associated_token_address(&state.buyer, &state.token_mint),
+            &get_associated_token_address(&config.treasury, &state.token_mint),
+            reason,
+        )])
+    }
+
+    /// Refunds the buyer, less the escrow lost to decay, once the global
+    /// deadline has passed. Anyone can crank this for the negotiation's crank
+    /// reward; its share of the refund is paid to the signer's token account
+    /// for the mint, if there is one.
+    pub fn expire(&self, negotiation: &Pubkey) -> Result<Signature> {
+        let state = self.fetch_negotiation(negotiation)?;
+        let config = self.fetch_config()?;
+        let cranker_token_account = Some(get_associated_token_address(
+            &self.pubkey(),
+            &state.token_mint,
+        ))
+        .filter(|ata| state.crank_reward_bps > 0 && self.fetch_data(ata).is_ok());
+        self.send(&[instructions::expire_negotiation(
+            &self.pubkey(),
+            negotiation,
+            &state.seller,
+            &get_associated_token_address(&state.buyer, &state.token_mint),
+            &get_associated_token_address(&config.treasury, &state.token_mint),
+            cranker_token_account.as_ref(),
+        )])
+    }
+
+    /// Closes a terminal negotiation and its vault, returning rent to the
+    /// buyer. Anyone else may close once the grace period is over, keeping a
+    /// small bounty and any crank deposit still unpaid. Coalition members
+    /// still in the pool by then are paid to their associated token accounts.
+    pub fn close(&self, negotiation: &Pubkey) -> Result<Signature> {
+        let state = self.fetch_negotiation(negotiation)?;
+        let members = match self.fetch_data(&find_coalition_pda(negotiation).0) {
+            Ok(data) => decode_coalition(&data)?.members,
+            Err(ClientError::AccountNotFound(_)) => Vec::new(),
+            Err(err) => return Err(err),
+        };
+        let member_token_accounts: Vec<Pubkey> = members
+            .iter()
+            .map(|share| get_associated_token_address(&share.member, &state.token_mint))
+            .collect();
+        self.send(&[instructions::close_negotiation(
+            &self.pubkey(),
+            negotiation,
+            &state.buyer,
+            &get_associated_token_address(&state.buyer, &state.token_mint),
+            &member_token_accounts,
+        )])
+    }
+
+    /// Withdraws an invitation the seller has not accepted yet, refunding the
+    /// escrow and closing the negotiation in one transaction. Buyer only.
+    pub fn cancel_invitation(&self, negotiation: &Pubkey) -> Result<Signature> {
+        let state = self.fetch_negotiation(negotiation)?;
+        let config = self.fetch_config()?;
+        self.send(&[instructions::cancel_invitation(
+            &self.pubkey(),
+            negotiation,
+            &state.seller,
+            &get_associated_token_address(&self.pubkey(), &state.token_mint),
+            &get_associated_token_address(&config.treasury, &state.token_mint),
+        )])
+    }
+
+    /// Rewrites a negotiation from an older account layout in the current
+    /// one, paying any extra rent. Anyone can migrate.
+    pub fn migrate(&self, negotiation: &Pubkey) -> Result<Signature> {
+        self.send(&[instructions::migrate_negotiation(
+            &self.pubkey(),
+            negotiation,
+        )])
+    }
+
+    // ===== Read Operations =====
+
+    pub fn fetch_negotiations(
+        &self,
+        filter: &NegotiationFilter,
+    ) -> Result<Vec<(Pubkey, NegotiationState)>> {
+        fetch_negotiations(&self.rpc, filter)
+    }
+
+    pub fn fetch_negotiation(&self, negotiation: &Pubkey) -> Result<NegotiationState> {
+        decode_negotiation(&self.fetch_data(negotiation)?)
+    }
+
+    pub fn fetch_auction(&self, auction: &Pubkey) -> Result<Auction> {
+        decode_auction(&self.fetch_data(auction)?)
+    }
+
+    pub fn fetch_config(&self) -> Result<ProtocolConfig> {
+        decode_config(&self.fetch_data(&find_config_pda().0)?)
+    }
+
+    fn fetch_data(&self, address: &Pubkey) -> Result<Vec<u8>> {
+        let account = self
+            .rpc
+            .get_account_with_commitment(address, self.rpc.commitment())?
+            .value
+            .ok_or(ClientError::AccountNotFound(*address))?;
+        Ok(account.data)
+    }
+
+    pub fn send(&self, ixs: &[Instruction]) -> Result<Signature> {
+        let blockhash = self.rpc.get_latest_blockhash()?;
+        let tx = Transaction::new_signed_with_payer(
+            ixs,
+            Some(&self.pubkey()),
+            &[&self.signer],
+            blockhash,
+        );
+        Ok(self.rpc.send_and_confirm_transaction(&tx)?)
+    }
+}
+
+fn opening((amount, metadata): (u64, &str)) -> OpeningOffer {
+    OpeningOffer {
+        amount,
+        metadata: create_metadata(metadata),
+    }
+}
+
+fn price_update(state: &NegotiationState) -> Option<Pubkey> {
+    state
+        .is_quoted()
+        .then(|| find_price_feed_pda(0, &state.price_feed).0)
+}
@@ -0,0 +1,95 @@
+use anchor_lang::prelude::Pubkey;
+
+pub const CONFIG_SEED: &[u8] = b"config";
+pub const NEGOTIATION_SEED: &[u8] = b"negotiation";
+pub const AUCTION_SEED: &[u8] = b"auction";
+pub const REVERSE_AUCTION_SEED: &[u8] = b"reverse_auction";
+pub const BOND_SEED: &[u8] = b"bond";
+pub const TEMPLATE_SEED: &[u8] = b"template";
+pub const POLICY_SEED: &[u8] = b"policy";
+pub const COALITION_SEED: &[u8] = b"coalition";
+pub const SEALED_BID_SEED: &[u8] = b"sealed_bid";
+pub const VAULT_SEED: &[u8] = b"vault";
+pub const EVENT_AUTHORITY_SEED: &[u8] = b"__event_authority";
+
+pub fn find_config_pda() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[CONFIG_SEED], &haggle::ID)
+}
+
+pub fn find_negotiation_pda(buyer: &Pubkey, seller: &Pubkey, session_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            NEGOTIATION_SEED,
+            buyer.as_ref(),
+            seller.as_ref(),
+            &session_id.to_le_bytes(),
+        ],
+        &haggle::ID,
+    )
+}
+
+pub fn find_auction_pda(seller: &Pubkey, auction_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[AUCTION_SEED, seller.as_ref(), &auction_id.to_le_bytes()],
+        &haggle::ID,
+    )
+}
+
+pub fn find_sealed_bid_pda(auction: &Pubkey, bidder: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[SEALED_BID_SEED, auction.as_ref(), bidder.as_ref()],
+        &haggle::ID,
+    )
+}
+
+pub fn find_reverse_auction_pda(buyer: &Pubkey, auction_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            REVERSE_AUCTION_SEED,
+            buyer.as_ref(),
+            &auction_id.to_le_bytes(),
+        ],
+        &haggle::ID,
+    )
+}
+
+pub fn find_bond_pda(auction: &Pubkey, seller: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[BOND_SEED, auction.as_ref(), seller.as_ref()], &haggle::ID)
+}
+
+pub fn find_template_pda(owner: &Pubkey, template_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[TEMPLATE_SEED, owner.as_ref(), &template_id.to_le_bytes()],
+        &haggle::ID,
+    )
+}
+
+/// A seller's acceptance policy. Negotiation instructions take it whether or
+/// not the seller has created one.
+pub fn find_policy_pda(seller: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[POLICY_SEED, seller.as_ref()], &haggle::ID)
+}
+
+pub fn find_coalition_pda(negotiation: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[COALITION_SEED, negotiation.as_ref()], &haggle::ID)
+}
+
+/// Escrow vault of a negotiation or an auction.
+pub fn find_vault_pda(negotiation: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[VAULT_SEED, negotiation.as_ref()], &haggle::ID)
+}
+
+/// Signer of the program's event self-CPIs.
+pub fn find_event_authority_pda() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[EVENT_AUTHORITY_SEED], &haggle::ID)
+}
+
+/// Pyth sponsored price feed account for `feed_id` on `shard_id`, kept
+/// current by the push oracle. Quote-denominated negotiations pass it as
+/// their price update.
+pub fn find_price_feed_pda(shard_id: u16, feed_id: &[u8; 32]) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[&shard_id.to_le_bytes(), feed_id],
+        &haggle::oracle::PYTH_PUSH_ORACLE_ID,
+    )
+}
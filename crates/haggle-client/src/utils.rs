@@ -0,0 +1,83 @@
+use anchor_lang::prelude::Pubkey;
+use haggle::state::{NegotiationState, NegotiationStatus, OfferSide};
+
+pub fn create_service_hash(service: &str) -> [u8; 32] {
+    let mut buf = [0u8; 32];
+    let len = service.len().min(32);
+    buf[..len].copy_from_slice(&service.as_bytes()[..len]);
+    buf
+}
+
+pub fn create_metadata(data: &str) -> [u8; 64] {
+    let mut buf = [0u8; 64];
+    let len = data.len().min(64);
+    buf[..len].copy_from_slice(&data.as_bytes()[..len]);
+    buf
+}
+
+pub fn decode_metadata(metadata: &[u8]) -> String {
+    let end = metadata
+        .iter()
+        .position(|b| *b == 0)
+        .unwrap_or(metadata.len());
+    String::from_utf8_lossy(&metadata[..end]).into_owned()
+}
+
+pub fn is_terminal(status: NegotiationStatus) -> bool {
+    matches!(
+        status,
+        NegotiationStatus::Settled | NegotiationStatus::Expired | NegotiationStatus::Rejected
+    )
+}
+
+pub fn is_my_turn(negotiation: &NegotiationState, me: &Pubkey) -> bool {
+    if negotiation.current_round == 0 {
+        return *me == negotiation.buyer;
+    }
+    match negotiation.offer_side() {
+        Ok(OfferSide::Buyer) => *me == negotiation.seller,
+        Ok(OfferSide::Seller) => *me == negotiation.buyer,
+        Err(_) => false,
+    }
+}
+
+pub fn calculate_decay(effective_escrow: u64, decay_rate_bps: u16) -> u64 {
+    bps_of(effective_escrow, decay_rate_bps)
+}
+
+pub fn calculate_min_offer(effective_escrow: u64, min_offer_bps: u16) -> u64 {
+    bps_of(effective_escrow, min_offer_bps)
+}
+
+pub fn calculate_protocol_fee(amount: u64, fee_bps: u16) -> u64 {
+    bps_of(amount, fee_bps)
+}
+
+/// One row of a negotiation's decay schedule: the effective escrow and the
+/// minimum acceptable offer for an offer made in `round`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ScheduledRound {
+    pub round: u8,
+    pub effective_escrow: u64,
+    pub min_offer: u64,
+}
+
+/// Projects the effective escrow for every round up to `max_rounds`. Decay
+/// is applied before each offer, so round 1 is already decayed once.
+pub fn decay_schedule(negotiation: &NegotiationState) -> Vec<ScheduledRound> {
+    let mut effective = negotiation.escrow_amount;
+    (1..=negotiation.max_rounds)
+        .map(|round| {
+            effective -= calculate_decay(effective, negotiation.decay_rate_bps);
+            ScheduledRound {
+                round,
+                effective_escrow: effective,
+                min_offer: calculate_min_offer(effective, negotiation.min_offer_bps),
+            }
+        })
+        .collect()
+}
+
+fn bps_of(amount: u64, bps: u16) -> u64 {
+    (amount as u128 * bps as u128 / 10_000) as u64
+}
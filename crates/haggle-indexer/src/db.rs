@@ -0,0 +1,671 @@
+//! SQLite storage: raw events plus the tables materialised from them.
+
+use std::path::Path;
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use haggle_client::{
+    decode_event, parse_inner_instructions, parse_logs, HaggleEvent, NegotiationStatus, OfferSide,
+    TerminationReason, PROGRAM_ID,
+};
+use rusqlite::{params, types::Type, Connection, OptionalExtension, Row, Transaction};
+use solana_sdk::pubkey::Pubkey;
+
+use crate::error::{IndexerError, Result};
+use crate::TransactionLogs;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS transactions (
+    seq        INTEGER PRIMARY KEY AUTOINCREMENT,
+    signature  TEXT NOT NULL UNIQUE,
+    slot       INTEGER NOT NULL,
+    block_time INTEGER,
+    failed     INTEGER NOT NULL,
+    finalized  INTEGER NOT NULL DEFAULT 0
+);
+CREATE INDEX IF NOT EXISTS transactions_slot ON transactions (slot);
+
+CREATE TABLE IF NOT EXISTS events (
+    tx_seq      INTEGER NOT NULL REFERENCES transactions (seq) ON DELETE CASCADE,
+    idx         INTEGER NOT NULL,
+    name        TEXT NOT NULL,
+    negotiation TEXT,
+    data        BLOB NOT NULL,
+    PRIMARY KEY (tx_seq, idx)
+);
+CREATE INDEX IF NOT EXISTS events_negotiation ON events (negotiation);
+
+CREATE TABLE IF NOT EXISTS negotiations (
+    address              TEXT PRIMARY KEY,
+    buyer                TEXT NOT NULL,
+    seller               TEXT NOT NULL,
+    session_id           INTEGER NOT NULL,
+    token_mint           TEXT NOT NULL,
+    service_hash         BLOB NOT NULL,
+    status               TEXT NOT NULL,
+    escrow_amount        INTEGER NOT NULL,
+    effective_escrow     INTEGER NOT NULL,
+    max_rounds           INTEGER NOT NULL,
+    decay_rate_bps       INTEGER NOT NULL,
+    response_window      INTEGER NOT NULL,
+    global_deadline      INTEGER NOT NULL,
+    min_offer_bps        INTEGER NOT NULL,
+    protocol_fee_bps     INTEGER NOT NULL,
+    zopa_enabled         INTEGER NOT NULL,
+    crank_deposit        INTEGER NOT NULL,
+    crank_reward_bps     INTEGER NOT NULL,
+    admitted             INTEGER NOT NULL,
+    invitation_deadline  INTEGER,
+    current_round        INTEGER NOT NULL,
+    current_offer_amount INTEGER,
+    current_offer_by     TEXT,
+    offer_side           TEXT,
+    metadata             BLOB,
+    last_offer_at        INTEGER,
+    settled_amount       INTEGER,
+    settled_at           INTEGER,
+    protocol_fee         INTEGER,
+    refund_amount        INTEGER,
+    rejected_by          TEXT,
+    termination_reason   TEXT,
+    closed_at            INTEGER,
+    rent_reclaimed       INTEGER,
+    created_at           INTEGER NOT NULL,
+    updated_at           INTEGER NOT NULL,
+    created_slot         INTEGER NOT NULL,
+    updated_slot         INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS negotiations_buyer ON negotiations (buyer);
+CREATE INDEX IF NOT EXISTS negotiations_seller ON negotiations (seller);
+
+CREATE TABLE IF NOT EXISTS offers (
+    negotiation      TEXT NOT NULL,
+    round            INTEGER NOT NULL,
+    offerer          TEXT NOT NULL,
+    side             TEXT NOT NULL,
+    amount           INTEGER NOT NULL,
+    effective_escrow INTEGER NOT NULL,
+    metadata         BLOB NOT NULL,
+    is_final         INTEGER NOT NULL,
+    timestamp        INTEGER NOT NULL,
+    slot             INTEGER NOT NULL,
+    signature        TEXT NOT NULL,
+    PRIMARY KEY (negotiation, round)
+);
+";
+
+/// Where indexing stopped: the last transaction applied.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Checkpoint {
+    pub slot: u64,
+    pub signature: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NegotiationRow {
+    pub address: Pubkey,
+    pub buyer: Pubkey,
+    pub seller: Pubkey,
+    pub session_id: u64,
+    pub token_mint: Pubkey,
+    pub service_hash: [u8; 32],
+    pub status: String,
+    pub escrow_amount: u64,
+    pub effective_escrow: u64,
+    pub max_rounds: u8,
+    pub decay_rate_bps: u16,
+    pub response_window: i64,
+    pub global_deadline: i64,
+    pub min_offer_bps: u16,
+    pub protocol_fee_bps: u16,
+    pub zopa_enabled: bool,
+    /// Lamports prepaid for the cranker who ends the negotiation.
+    pub crank_deposit: u64,
+    pub crank_reward_bps: u16,
+    /// Whether it took one of the seller's acceptance policy slots when
+    /// created.
+    pub admitted: bool,
+    /// When an unaccepted invitation lapses, if the buyer set a TTL.
+    pub invitation_deadline: Option<i64>,
+    pub current_round: u8,
+    pub current_offer_amount: Option<u64>,
+    pub current_offer_by: Option<Pubkey>,
+    pub offer_side: Option<String>,
+    pub metadata: Option<[u8; 64]>,
+    pub last_offer_at: Option<i64>,
+    pub settled_amount: Option<u64>,
+    pub settled_at: Option<i64>,
+    pub protocol_fee: Option<u64>,
+    pub refund_amount: Option<u64>,
+    pub rejected_by: Option<Pubkey>,
+    /// Why a rejected, expired or cancelled negotiation ended.
+    pub termination_reason: Option<String>,
+    /// Set once the accounts are closed and their rent returned.
+    pub closed_at: Option<i64>,
+    pub rent_reclaimed: Option<u64>,
+    pub created_at: i64,
+    pub updated_at: i64,
+    pub created_slot: u64,
+    pub updated_slot: u64,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OfferRow {
+    pub negotiation: Pubkey,
+    pub round: u8,
+    pub offerer: Pubkey,
+    pub side: String,
+    pub amount: u64,
+    pub effective_escrow: u64,
+    pub metadata: [u8; 64],
+    pub is_final: bool,
+    pub timestamp: i64,
+    pub slot: u64,
+    pub signature: String,
+}
+
+pub struct Store {
+    conn: Connection,
+}
+
+impl Store {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        Self::init(Connection::open(path)?)
+    }
+
+    pub fn open_in_memory() -> Result<Self> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(conn: Connection) -> Result<Self> {
+        conn.pragma_update(None, "foreign_keys", true)?;
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self { conn })
+    }
+
+    pub fn checkpoint(&self) -> Result<Option<Checkpoint>> {
+        Ok(self
+            .conn
+            .query_row(
+                "SELECT slot, signature FROM transactions ORDER BY seq DESC LIMIT 1",
+                [],
+                |row| {
+                    Ok(Checkpoint {
+                        slot: row.get(0)?,
+                        signature: row.get(1)?,
+                    })
+                },
+            )
+            .optional()?)
+    }
+
+    /// Stores a transaction and applies its events. Failed transactions are
+    /// recorded so the checkpoint moves past them, but their events are
+    /// ignored. Returns `false` if the transaction was already indexed.
+    pub fn apply(&mut self, tx: &TransactionLogs) -> Result<bool> {
+        let db = self.conn.transaction()?;
+        let inserted = db.execute(
+            "INSERT INTO transactions (signature, slot, block_time, failed)
+             VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT (signature) DO NOTHING",
+            params![tx.signature, tx.slot, tx.block_time, tx.err.is_some()],
+        )?;
+        if inserted == 0 {
+            return Ok(false);
+        }
+        if tx.err.is_none() {
+            let seq = db.last_insert_rowid();
+            for (idx, event) in events(tx).iter().enumerate() {
+                db.execute(
+                    "INSERT INTO events (tx_seq, idx, name, negotiation, data)
+                     VALUES (?1, ?2, ?3, ?4, ?5)",
+                    params![
+                        seq,
+                        idx,
+                        event.name(),
+                        event.negotiation_id().map(|id| id.to_string()),
+                        event.data()
+                    ],
+                )?;
+                apply_event(&db, event, tx.slot, &tx.signature)?;
+            }
+        }
+        db.commit()?;
+        Ok(true)
+    }
+
+    /// Discards every transaction above `slot` and rebuilds the negotiations
+    /// they touched from the events that remain. Returns the number of
+    /// transactions removed.
+    pub fn rollback(&mut self, slot: u64) -> Result<usize> {
+        let db = self.conn.transaction()?;
+        let affected: Vec<String> = db
+            .prepare(
+                "SELECT DISTINCT e.negotiation FROM events e
+                 JOIN transactions t ON t.seq = e.tx_seq
+                 WHERE t.slot > ?1 AND e.negotiation IS NOT NULL",
+            )?
+            .query_map([slot], |row| row.get(0))?
+            .collect::<rusqlite::Result<_>>()?;
+        let removed = db.execute("DELETE FROM transactions WHERE slot > ?1", [slot])?;
+
+        for negotiation in &affected {
+            db.execute("DELETE FROM negotiations WHERE address = ?1", [negotiation])?;
+            db.execute("DELETE FROM offers WHERE negotiation = ?1", [negotiation])?;
+            replay(&db, negotiation)?;
+        }
+        db.commit()?;
+        Ok(removed)
+    }
+
+    /// Transactions at or below `slot` that have not yet been checked
+    /// against the finalized chain, oldest first.
+    pub fn unfinalized(&self, slot: u64) -> Result<Vec<(u64, String)>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT slot, signature FROM transactions
+             WHERE finalized = 0 AND slot <= ?1 ORDER BY seq",
+        )?;
+        let rows = stmt
+            .query_map([slot], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(rows)
+    }
+
+    pub fn mark_finalized(&mut self, slot: u64) -> Result<()> {
+        self.conn.execute(
+            "UPDATE transactions SET finalized = 1 WHERE finalized = 0 AND slot <= ?1",
+            [slot],
+        )?;
+        Ok(())
+    }
+
+    pub fn negotiation(&self, address: &Pubkey) -> Result<Option<NegotiationRow>> {
+        Ok(self
+            .conn
+            .query_row(
+                &format!("{NEGOTIATION_SELECT} WHERE address = ?1"),
+                [address.to_string()],
+                negotiation_row,
+            )
+            .optional()?)
+    }
+
+    /// Every negotiation, in creation order.
+    pub fn negotiations(&self) -> Result<Vec<NegotiationRow>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "{NEGOTIATION_SELECT} ORDER BY created_slot, address"
+        ))?;
+        let rows = stmt
+            .query_map([], negotiation_row)?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(rows)
+    }
+
+    pub fn offers(&self, negotiation: &Pubkey) -> Result<Vec<OfferRow>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT negotiation, round, offerer, side, amount, effective_escrow,
+                    metadata, is_final, timestamp, slot, signature
+             FROM offers WHERE negotiation = ?1 ORDER BY round",
+        )?;
+        let rows = stmt
+            .query_map([negotiation.to_string()], |row| {
+                Ok(OfferRow {
+                    negotiation: pubkey(row, 0)?,
+                    round: row.get(1)?,
+                    offerer: pubkey(row, 2)?,
+                    side: row.get(3)?,
+                    amount: row.get(4)?,
+                    effective_escrow: row.get(5)?,
+                    metadata: row.get(6)?,
+                    is_final: row.get(7)?,
+                    timestamp: row.get(8)?,
+                    slot: row.get(9)?,
+                    signature: row.get(10)?,
+                })
+            })?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(rows)
+    }
+}
+
+/// Re-applies the stored events of one negotiation in their original order.
+fn replay(db: &Transaction, negotiation: &str) -> Result<()> {
+    let mut stmt = db.prepare(
+        "SELECT e.tx_seq, e.idx, e.data, t.slot, t.signature FROM events e
+         JOIN transactions t ON t.seq = e.tx_seq
+         WHERE e.negotiation = ?1 ORDER BY e.tx_seq, e.idx",
+    )?;
+    let events: Vec<(i64, i64, Vec<u8>, u64, String)> = stmt
+        .query_map([negotiation], |row| {
+            Ok((
+                row.get(0)?,
+                row.get(1)?,
+                row.get(2)?,
+                row.get(3)?,
+                row.get(4)?,
+            ))
+        })?
+        .collect::<rusqlite::Result<_>>()?;
+    for (seq, idx, data, slot, signature) in events {
+        let event = decode_event(&data).ok_or(IndexerError::CorruptEvent(seq, idx))?;
+        apply_event(db, &event, slot, &signature)?;
+    }
+    Ok(())
+}
+
+/// Events from the program's self-CPIs, falling back to the logs for
+/// transactions from before the program emitted events that way.
+fn events(tx: &TransactionLogs) -> Vec<HaggleEvent> {
+    let inner: Vec<_> = tx
+        .inner_instructions
+        .iter()
+        .filter_map(|data| STANDARD.decode(data).ok())
+        .map(|data| (PROGRAM_ID, data))
+        .collect();
+    let events = parse_inner_instructions(&inner);
+    if events.is_empty() {
+        parse_logs(&tx.logs)
+    } else {
+        events
+    }
+}
+
+/// Folds one event into the materialised tables. Events for negotiations
+/// created before indexing started have no row to update and are skipped.
+fn apply_event(db: &Connection, event: &HaggleEvent, slot: u64, signature: &str) -> Result<()> {
+    let Some(id) = event.negotiation_id().map(|id| id.to_string()) else {
+        return Ok(());
+    };
+    match event {
+        // Protocol-level, payout and auction events are kept in `events`
+        // only.
+        HaggleEvent::ConfigInitialized(_)
+        | HaggleEvent::TemplateRegistered(_)
+        | HaggleEvent::AcceptancePolicySet(_)
+        | HaggleEvent::AcceptancePolicyClosed(_)
+        | HaggleEvent::Payout(_)
+        | HaggleEvent::AuctionCreated(_)
+        | HaggleEvent::BidPlaced(_)
+        | HaggleEvent::AuctionUnsold(_)
+        | HaggleEvent::AuctionSettled(_)
+        | HaggleEvent::AuctionClosed(_)
+        | HaggleEvent::BidCommitted(_)
+        | HaggleEvent::BidRevealed(_)
+        | HaggleEvent::BidWithdrawn(_)
+        | HaggleEvent::ReverseAuctionCreated(_)
+        | HaggleEvent::BondPosted(_)
+        | HaggleEvent::ReverseBidPlaced(_)
+        | HaggleEvent::ReverseAuctionSettled(_)
+        | HaggleEvent::ReverseAuctionClosed(_)
+        | HaggleEvent::CoalitionCreated(_) => {}
+        HaggleEvent::NegotiationCreated(e) => {
+            db.execute(
+                "INSERT INTO negotiations (
+                    address, buyer, seller, session_id, token_mint, service_hash, status,
+                    escrow_amount, effective_escrow, max_rounds, decay_rate_bps,
+                    response_window, global_deadline, min_offer_bps, protocol_fee_bps,
+                    zopa_enabled, crank_deposit, crank_reward_bps, admitted,
+                    invitation_deadline, current_round, created_at, updated_at, created_slot,
+                    updated_slot
+                 ) VALUES (
+                    ?1, ?2, ?3, ?4, ?5, ?6, 'created', ?7, ?7, ?8, ?9, ?10, ?11, ?12, ?13,
+                    ?14, ?15, ?16, ?17, ?18, 0, ?19, ?19, ?20, ?20
+                 )
+                 ON CONFLICT (address) DO NOTHING",
+                params![
+                    id,
+                    e.buyer.to_string(),
+                    e.seller.to_string(),
+                    e.session_id,
+                    e.token_mint.to_string(),
+                    e.service_hash,
+                    e.escrow_amount,
+                    e.max_rounds,
+                    e.decay_rate_bps,
+                    e.response_window,
+                    e.global_deadline,
+                    e.min_offer_bps,
+                    e.protocol_fee_bps,
+                    e.zopa_enabled,
+                    e.crank_deposit,
+                    e.crank_reward_bps,
+                    e.admitted,
+                    (e.invitation_deadline != 0).then_some(e.invitation_deadline),
+                    e.timestamp,
+                    slot
+                ],
+            )?;
+        }
+        HaggleEvent::InvitationAccepted(e) => {
+            db.execute(
+                "UPDATE negotiations SET
+                    status = 'proposed', last_offer_at = ?2, updated_at = ?2, updated_slot = ?3
+                 WHERE address = ?1",
+                params![id, e.timestamp, slot],
+            )?;
+        }
+        HaggleEvent::OfferSubmitted(e) => {
+            let offerer = e.offerer.to_string();
+            let side = side_name(e.offer_side);
+            db.execute(
+                "INSERT INTO offers (
+                    negotiation, round, offerer, side, amount, effective_escrow,
+                    metadata, is_final, timestamp, slot, signature
+                 )
+                 SELECT ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11
+                 FROM negotiations WHERE address = ?1
+                 ON CONFLICT (negotiation, round) DO UPDATE SET
+                    offerer = excluded.offerer, side = excluded.side,
+                    amount = excluded.amount, effective_escrow = excluded.effective_escrow,
+                    metadata = excluded.metadata, is_final = excluded.is_final,
+                    timestamp = excluded.timestamp,
+                    slot = excluded.slot, signature = excluded.signature",
+                params![
+                    id,
+                    e.round,
+                    offerer,
+                    side,
+                    e.amount,
+                    e.effective_escrow,
+                    e.metadata,
+                    e.is_final,
+                    e.timestamp,
+                    slot,
+                    signature
+                ],
+            )?;
+            db.execute(
+                "UPDATE negotiations SET
+                    status = ?2, current_round = ?3, current_offer_amount = ?4,
+                    current_offer_by = ?5, offer_side = ?6, effective_escrow = ?7,
+                    metadata = ?8, last_offer_at = ?9, updated_at = ?9, updated_slot = ?10
+                 WHERE address = ?1",
+                params![
+                    id,
+                    status_name(e.status),
+                    e.round,
+                    e.amount,
+                    offerer,
+                    side,
+                    e.effective_escrow,
+                    e.metadata,
+                    e.timestamp,
+                    slot
+                ],
+            )?;
+        }
+        HaggleEvent::NegotiationSettled(e) => {
+            db.execute(
+                "UPDATE negotiations SET
+                    status = 'settled', settled_amount = ?2, settled_at = ?4, protocol_fee = ?3,
+                    refund_amount = escrow_amount - ?2,
+                    updated_at = ?4, updated_slot = ?5
+                 WHERE address = ?1",
+                params![id, e.settled_amount, e.protocol_fee, e.timestamp, slot],
+            )?;
+        }
+        HaggleEvent::NegotiationExpired(e) => {
+            db.execute(
+                "UPDATE negotiations SET
+                    status = 'expired', refund_amount = ?2, termination_reason = ?3,
+                    updated_at = ?4, updated_slot = ?5
+                 WHERE address = ?1",
+                params![
+                    id,
+                    e.refund_amount,
+                    reason_name(e.reason),
+                    e.timestamp,
+                    slot
+                ],
+            )?;
+        }
+        HaggleEvent::NegotiationRejected(e) => {
+            db.execute(
+                "UPDATE negotiations SET
+                    status = 'rejected', refund_amount = ?2, rejected_by = ?3,
+                    termination_reason = ?4, updated_at = ?5, updated_slot = ?6
+                 WHERE address = ?1",
+                params![
+                    id,
+                    e.refund_amount,
+                    e.rejected_by.to_string(),
+                    reason_name(e.reason),
+                    e.timestamp,
+                    slot
+                ],
+            )?;
+        }
+        // Pooled escrow only moves before the seller accepts; later exits
+        // pay out refunds and leave the negotiation as it ended.
+        HaggleEvent::CoalitionJoined(e) => {
+            db.execute(
+                "UPDATE negotiations SET
+                    escrow_amount = ?2, effective_escrow = ?2, updated_at = ?3, updated_slot = ?4
+                 WHERE address = ?1 AND status = 'created'",
+                params![id, e.escrow_amount, e.timestamp, slot],
+            )?;
+        }
+        HaggleEvent::CoalitionExited(e) => {
+            db.execute(
+                "UPDATE negotiations SET
+                    escrow_amount = ?2, effective_escrow = ?2, updated_at = ?3, updated_slot = ?4
+                 WHERE address = ?1 AND status = 'created'",
+                params![id, e.escrow_amount, e.timestamp, slot],
+            )?;
+        }
+        HaggleEvent::NegotiationClosed(e) => {
+            db.execute(
+                "UPDATE negotiations SET
+                    closed_at = ?2, rent_reclaimed = ?3, updated_at = ?2, updated_slot = ?4
+                 WHERE address = ?1",
+                params![id, e.timestamp, e.rent_reclaimed, slot],
+            )?;
+        }
+        HaggleEvent::NegotiationCancelled(e) => {
+            db.execute(
+                "UPDATE negotiations SET
+                    status = 'cancelled', refund_amount = ?2, closed_at = ?3, rent_reclaimed = ?4,
+                    termination_reason = 'cancelled', updated_at = ?3, updated_slot = ?5
+                 WHERE address = ?1",
+                params![id, e.refund_amount, e.timestamp, e.rent_reclaimed, slot],
+            )?;
+        }
+    }
+    Ok(())
+}
+
+fn status_name(status: NegotiationStatus) -> &'static str {
+    match status {
+        NegotiationStatus::Created => "created",
+        NegotiationStatus::Proposed => "proposed",
+        NegotiationStatus::Countered => "countered",
+        NegotiationStatus::Accepted => "accepted",
+        NegotiationStatus::Settled => "settled",
+        NegotiationStatus::Expired => "expired",
+        NegotiationStatus::Rejected => "rejected",
+    }
+}
+
+fn side_name(side: OfferSide) -> &'static str {
+    match side {
+        OfferSide::Buyer => "buyer",
+        OfferSide::Seller => "seller",
+    }
+}
+
+fn reason_name(reason: TerminationReason) -> &'static str {
+    match reason {
+        TerminationReason::Unspecified => "unspecified",
+        TerminationReason::PriceGap => "priceGap",
+        TerminationReason::Timeout => "timeout",
+        TerminationReason::ZopaFailed => "zopaFailed",
+        TerminationReason::CounterpartyUnresponsive => "counterpartyUnresponsive",
+        TerminationReason::PolicyViolation => "policyViolation",
+        TerminationReason::Cancelled => "cancelled",
+    }
+}
+
+const NEGOTIATION_SELECT: &str = "
+SELECT address, buyer, seller, session_id, token_mint, service_hash, status, escrow_amount,
+       effective_escrow, max_rounds, decay_rate_bps, response_window, global_deadline,
+       min_offer_bps, protocol_fee_bps, zopa_enabled, crank_deposit, crank_reward_bps, admitted,
+       invitation_deadline, current_round, current_offer_amount, current_offer_by, offer_side,
+       metadata, last_offer_at, settled_amount, settled_at, protocol_fee, refund_amount,
+       rejected_by, termination_reason, closed_at, rent_reclaimed, created_at, updated_at,
+       created_slot, updated_slot
+FROM negotiations";
+
+fn negotiation_row(row: &Row) -> rusqlite::Result<NegotiationRow> {
+    Ok(NegotiationRow {
+        address: pubkey(row, 0)?,
+        buyer: pubkey(row, 1)?,
+        seller: pubkey(row, 2)?,
+        session_id: row.get(3)?,
+        token_mint: pubkey(row, 4)?,
+        service_hash: row.get(5)?,
+        status: row.get(6)?,
+        escrow_amount: row.get(7)?,
+        effective_escrow: row.get(8)?,
+        max_rounds: row.get(9)?,
+        decay_rate_bps: row.get(10)?,
+        response_window: row.get(11)?,
+        global_deadline: row.get(12)?,
+        min_offer_bps: row.get(13)?,
+        protocol_fee_bps: row.get(14)?,
+        zopa_enabled: row.get(15)?,
+        crank_deposit: row.get(16)?,
+        crank_reward_bps: row.get(17)?,
+        admitted: row.get(18)?,
+        invitation_deadline: row.get(19)?,
+        current_round: row.get(20)?,
+        current_offer_amount: row.get(21)?,
+        current_offer_by: optional_pubkey(row, 22)?,
+        offer_side: row.get(23)?,
+        metadata: row.get(24)?,
+        last_offer_at: row.get(25)?,
+        settled_amount: row.get(26)?,
+        settled_at: row.get(27)?,
+        protocol_fee: row.get(28)?,
+        refund_amount: row.get(29)?,
+        rejected_by: optional_pubkey(row, 30)?,
+        termination_reason: row.get(31)?,
+        closed_at: row.get(32)?,
+        rent_reclaimed: row.get(33)?,
+        created_at: row.get(34)?,
+        updated_at: row.get(35)?,
+        created_slot: row.get(36)?,
+        updated_slot: row.get(37)?,
+    })
+}
+
+fn pubkey(row: &Row, idx: usize) -> rusqlite::Result<Pubkey> {
+    let text: String = row.get(idx)?;
+    text.parse()
+        .map_err(|err| rusqlite::Error::FromSqlConversionFailure(idx, Type::Text, Box::new(err)))
+}
+
+fn optional_pubkey(row: &Row, idx: usize) -> rusqlite::Result<Option<Pubkey>> {
+    match row.get::<_, Option<String>>(idx)? {
+        Some(_) => pubkey(row, idx).map(Some),
+        None => Ok(None),
+    }
+}
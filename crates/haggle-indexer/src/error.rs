@@ -0,0 +1,19 @@
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum IndexerError {
+    #[error("rpc error: {0}")]
+    Rpc(Box<solana_client::client_error::ClientError>),
+    #[error("database error: {0}")]
+    Db(#[from] rusqlite::Error),
+    #[error("stored event could not be decoded: transaction {0}, index {1}")]
+    CorruptEvent(i64, i64),
+}
+
+impl From<solana_client::client_error::ClientError> for IndexerError {
+    fn from(err: solana_client::client_error::ClientError) -> Self {
+        IndexerError::Rpc(Box::new(err))
+    }
+}
+
+pub type Result<T> = std::result::Result<T, IndexerError>;
@@ -0,0 +1,39 @@
+//! Event indexer for the Haggle Protocol program.
+//!
+//! Transactions that touch the program are fetched oldest-first, their
+//! events decoded from the program's self-CPIs (or, for transactions from
+//! before the program emitted events that way, from the logs) and stored in
+//! SQLite. Events are the source
+//! of truth: the `negotiations` and `offers` tables are materialised from
+//! them, so a rollback simply discards the events above a slot and replays
+//! what is left for the negotiations they touched.
+//!
+//! [`db::Store`] holds all state and can be driven from any source of
+//! [`TransactionLogs`]; [`rpc::RpcSource`] is the live one.
+
+pub mod db;
+pub mod error;
+pub mod rpc;
+
+pub use db::{Checkpoint, NegotiationRow, OfferRow, Store};
+pub use error::{IndexerError, Result};
+pub use rpc::RpcSource;
+
+use serde::{Deserialize, Serialize};
+use solana_sdk::transaction::TransactionError;
+
+/// The parts of a confirmed transaction the indexer needs, in the shape
+/// `getTransaction` returns them.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionLogs {
+    pub slot: u64,
+    pub signature: String,
+    pub block_time: Option<i64>,
+    pub err: Option<TransactionError>,
+    pub logs: Vec<String>,
+    /// Base64 data of the inner instructions that invoked the program, in
+    /// execution order.
+    #[serde(default)]
+    pub inner_instructions: Vec<String>,
+}
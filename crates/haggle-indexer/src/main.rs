@@ -0,0 +1,83 @@
+//! `haggle-indexer`: follows the program's transactions and keeps a SQLite
+//! database of negotiations and offers up to date.
+
+use std::path::PathBuf;
+use std::thread;
+use std::time::Duration;
+
+use anyhow::Result;
+use clap::Parser;
+use haggle_indexer::{RpcSource, Store};
+
+#[derive(Parser)]
+#[command(
+    name = "haggle-indexer",
+    version,
+    about = "Index Haggle Protocol events into SQLite"
+)]
+struct Args {
+    /// RPC URL
+    #[arg(
+        short,
+        long,
+        env = "HAGGLE_URL",
+        default_value = "http://127.0.0.1:8899"
+    )]
+    url: String,
+
+    /// SQLite database; created if missing, resumed from if not
+    #[arg(long, default_value = "haggle-indexer.db")]
+    db: PathBuf,
+
+    /// Seconds between polls
+    #[arg(long, default_value_t = 5)]
+    poll_interval: u64,
+
+    /// Catch up to the current tip once and exit
+    #[arg(long)]
+    once: bool,
+}
+
+fn main() -> Result<()> {
+    let args = Args::parse();
+    let source = RpcSource::new(args.url);
+    let mut store = Store::open(&args.db)?;
+    if let Some(checkpoint) = store.checkpoint()? {
+        eprintln!(
+            "resuming after slot {} ({})",
+            checkpoint.slot, checkpoint.signature
+        );
+    }
+
+    loop {
+        // Drop anything confirmed earlier that did not make it into the
+        // finalized chain before fetching new transactions after it.
+        let finalized = source.finalized_slot()?;
+        if let Some(slot) = source.dropped(&store.unfinalized(finalized)?)? {
+            let removed = store.rollback(slot.saturating_sub(1))?;
+            eprintln!("rolled back {removed} transactions from slot {slot}");
+        }
+        store.mark_finalized(finalized)?;
+
+        let cursor = store.checkpoint()?.map(|c| c.signature);
+        let mut indexed = 0;
+        for tx in source.fetch_since(cursor.as_deref())? {
+            if store.apply(&tx)? {
+                indexed += 1;
+            }
+        }
+        if indexed > 0 {
+            if let Some(checkpoint) = store.checkpoint()? {
+                eprintln!(
+                    "indexed {indexed} transactions up to slot {}",
+                    checkpoint.slot
+                );
+            }
+        }
+
+        if args.once {
+            return Ok(());
+        }
+        thread::sleep(Duration::from_secs(args.poll_interval));
+    }
+}
@@ -0,0 +1,136 @@
+//! Live transaction source backed by JSON-RPC.
+//!
+//! New transactions are discovered with `getSignaturesForAddress` at
+//! `confirmed` commitment. Confirmed blocks can still be dropped, so once a
+//! transaction's slot is finalized its status is checked again and anything
+//! no longer on the chain is reported for rollback.
+
+use std::str::FromStr;
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use haggle_client::{inner_instructions, PROGRAM_ID};
+use solana_client::rpc_client::{GetConfirmedSignaturesForAddress2Config, RpcClient};
+use solana_client::rpc_config::RpcTransactionConfig;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::signature::Signature;
+use solana_transaction_status::UiTransactionEncoding;
+
+use crate::error::Result;
+use crate::TransactionLogs;
+
+// Page size of getSignaturesForAddress, and the most signatures
+// getSignatureStatuses accepts per call.
+const SIGNATURE_PAGE: usize = 1000;
+const STATUS_BATCH: usize = 256;
+
+pub struct RpcSource {
+    rpc: RpcClient,
+}
+
+impl RpcSource {
+    pub fn new(url: String) -> Self {
+        Self {
+            rpc: RpcClient::new_with_commitment(url, CommitmentConfig::confirmed()),
+        }
+    }
+
+    /// Every program transaction after `after` (exclusive), oldest first.
+    /// With no cursor the whole history the node retains is returned.
+    pub fn fetch_since(&self, after: Option<&str>) -> Result<Vec<TransactionLogs>> {
+        let until = after.and_then(|sig| Signature::from_str(sig).ok());
+        let mut statuses = Vec::new();
+        let mut before = None;
+        loop {
+            let page = self.rpc.get_signatures_for_address_with_config(
+                &PROGRAM_ID,
+                GetConfirmedSignaturesForAddress2Config {
+                    before,
+                    until,
+                    limit: Some(SIGNATURE_PAGE),
+                    commitment: Some(CommitmentConfig::confirmed()),
+                },
+            )?;
+            let full = page.len() == SIGNATURE_PAGE;
+            before = page
+                .last()
+                .and_then(|status| Signature::from_str(&status.signature).ok());
+            statuses.extend(page);
+            if !full {
+                break;
+            }
+        }
+
+        statuses
+            .into_iter()
+            .rev()
+            .map(|status| {
+                // Failed transactions carry no events; skip fetching them.
+                let (logs, inner_instructions) = match status.err {
+                    Some(_) => Default::default(),
+                    None => self.transaction(&status.signature)?,
+                };
+                Ok(TransactionLogs {
+                    slot: status.slot,
+                    signature: status.signature,
+                    block_time: status.block_time,
+                    err: status.err,
+                    logs,
+                    inner_instructions,
+                })
+            })
+            .collect()
+    }
+
+    /// Log messages and the base64 data of the inner instructions invoking
+    /// the program.
+    fn transaction(&self, signature: &str) -> Result<(Vec<String>, Vec<String>)> {
+        let signature = Signature::from_str(signature).unwrap_or_default();
+        let tx = self.rpc.get_transaction_with_config(
+            &signature,
+            RpcTransactionConfig {
+                encoding: Some(UiTransactionEncoding::Base64),
+                commitment: Some(CommitmentConfig::confirmed()),
+                max_supported_transaction_version: Some(0),
+            },
+        )?;
+        let inner = inner_instructions(&tx.transaction)
+            .into_iter()
+            .filter(|(program_id, _)| *program_id == PROGRAM_ID)
+            .map(|(_, data)| STANDARD.encode(data))
+            .collect();
+        let logs = tx
+            .transaction
+            .meta
+            .and_then(|meta| Option::from(meta.log_messages))
+            .unwrap_or_default();
+        Ok((logs, inner))
+    }
+
+    pub fn finalized_slot(&self) -> Result<u64> {
+        Ok(self
+            .rpc
+            .get_slot_with_commitment(CommitmentConfig::finalized())?)
+    }
+
+    /// Checks transactions in finalized slots against the chain and returns
+    /// the lowest slot holding one that was dropped or landed elsewhere.
+    pub fn dropped(&self, transactions: &[(u64, String)]) -> Result<Option<u64>> {
+        let mut lowest: Option<u64> = None;
+        for batch in transactions.chunks(STATUS_BATCH) {
+            let signatures: Vec<Signature> = batch
+                .iter()
+                .map(|(_, sig)| Signature::from_str(sig).unwrap_or_default())
+                .collect();
+            let statuses = self
+                .rpc
+                .get_signature_statuses_with_history(&signatures)?
+                .value;
+            for ((slot, _), status) in batch.iter().zip(statuses) {
+                if status.is_none_or(|status| status.slot != *slot) {
+                    lowest = Some(lowest.map_or(*slot, |l| l.min(*slot)));
+                }
+            }
+        }
+        Ok(lowest)
+    }
+}
@@ -0,0 +1,225 @@
+//! Replays logs recorded from the program into the store.
+//!
+//! `fixtures/negotiations.json` holds one transaction per program call:
+//! negotiation A is created, countered over four rounds (with one failed
+//! out-of-turn offer in between), settled and closed; B is created and
+//! rejected; C is opened, receives one offer and expires. Events are carried as the
+//! program's self-CPIs in `innerInstructions`.
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use haggle_client::{create_metadata, create_service_hash};
+use haggle_indexer::{NegotiationRow, OfferRow, Store, TransactionLogs};
+
+const ESCROW: u64 = 5_000_000;
+
+fn fixture() -> Vec<TransactionLogs> {
+    serde_json::from_str(include_str!("fixtures/negotiations.json")).unwrap()
+}
+
+fn replay(store: &mut Store, txs: &[TransactionLogs]) {
+    for tx in txs {
+        store.apply(tx).unwrap();
+    }
+}
+
+fn indexed() -> Store {
+    let mut store = Store::open_in_memory().unwrap();
+    replay(&mut store, &fixture());
+    store
+}
+
+fn snapshot(store: &Store) -> Vec<(NegotiationRow, Vec<OfferRow>)> {
+    store
+        .negotiations()
+        .unwrap()
+        .into_iter()
+        .map(|row| {
+            let offers = store.offers(&row.address).unwrap();
+            (row, offers)
+        })
+        .collect()
+}
+
+#[test]
+fn replay_materialises_every_negotiation() {
+    let store = indexed();
+    let rows = store.negotiations().unwrap();
+    assert_eq!(rows.len(), 3);
+    let (a, b, c) = (&rows[0], &rows[1], &rows[2]);
+
+    // Every parameter of the account is carried by NegotiationCreated.
+    assert_eq!(a.session_id, 1);
+    assert_eq!(a.service_hash, create_service_hash("test-service"));
+    assert_eq!(
+        (a.max_rounds, a.decay_rate_bps, a.response_window),
+        (10, 200, 3600)
+    );
+    assert_eq!((a.min_offer_bps, a.protocol_fee_bps), (1000, 100));
+    assert!(!a.zopa_enabled);
+    assert_eq!((a.crank_deposit, a.crank_reward_bps), (0, 0));
+    assert!(!a.admitted);
+    assert_eq!(a.invitation_deadline, None);
+
+    assert_eq!(a.status, "settled");
+    assert_eq!(a.current_round, 4);
+    assert_eq!(a.current_offer_amount, Some(3_500_000));
+    assert_eq!(a.current_offer_by, Some(a.seller));
+    assert_eq!(a.offer_side.as_deref(), Some("seller"));
+    assert_eq!(a.metadata, Some(create_metadata("offer")));
+    assert_eq!(a.effective_escrow, 4_611_841);
+    assert_eq!(a.settled_amount, Some(3_500_000));
+    assert_eq!(a.protocol_fee, Some(35_000));
+    assert_eq!(a.refund_amount, Some(ESCROW - 3_500_000));
+    assert!(a.settled_at > a.last_offer_at);
+    assert!(a.closed_at > a.settled_at);
+    assert!(a.rent_reclaimed.unwrap() > 0);
+
+    // The out-of-turn offer failed on chain and left no row behind.
+    let offers = store.offers(&a.address).unwrap();
+    let amounts: Vec<_> = offers
+        .iter()
+        .map(|o| (o.round, o.side.as_str(), o.amount))
+        .collect();
+    assert_eq!(
+        amounts,
+        [
+            (1, "buyer", 2_000_000),
+            (2, "seller", 4_000_000),
+            (3, "buyer", 3_000_000),
+            (4, "seller", 3_500_000),
+        ]
+    );
+    assert!(offers.iter().all(|o| !o.is_final));
+    assert!(offers
+        .windows(2)
+        .all(|w| w[0].effective_escrow > w[1].effective_escrow));
+
+    assert_eq!(b.status, "rejected");
+    assert_eq!(b.rejected_by, Some(b.seller));
+    assert_eq!(b.refund_amount, Some(ESCROW));
+    assert_eq!(b.termination_reason.as_deref(), Some("unspecified"));
+    assert!(store.offers(&b.address).unwrap().is_empty());
+
+    assert_eq!(b.closed_at, None);
+
+    assert_eq!(c.status, "expired");
+    assert_eq!(c.current_round, 1);
+    assert_eq!(c.offer_side.as_deref(), Some("buyer"));
+    assert_eq!(c.refund_amount, Some(ESCROW));
+    assert_eq!(c.termination_reason.as_deref(), Some("timeout"));
+
+    let last = fixture().pop().unwrap();
+    let checkpoint = store.checkpoint().unwrap().unwrap();
+    assert_eq!(
+        (checkpoint.slot, checkpoint.signature),
+        (last.slot, last.signature)
+    );
+}
+
+#[test]
+fn accepting_the_invitation_opens_the_first_round() {
+    let txs = fixture();
+    let mut store = Store::open_in_memory().unwrap();
+    replay(&mut store, &txs[..2]);
+    let a = &store.negotiations().unwrap()[0];
+    assert_eq!(a.status, "proposed");
+    assert_eq!(a.current_round, 0);
+    assert_eq!(a.last_offer_at, txs[1].block_time);
+}
+
+#[test]
+fn replaying_twice_is_a_no_op() {
+    let mut store = indexed();
+    let before = snapshot(&store);
+    for tx in fixture() {
+        assert!(!store.apply(&tx).unwrap());
+    }
+    assert_eq!(snapshot(&store), before);
+}
+
+#[test]
+fn rollback_rebuilds_from_remaining_events() {
+    let txs = fixture();
+    let mut store = indexed();
+    let full = snapshot(&store);
+
+    // Drop everything after B's rejection, as if those slots were on a
+    // fork that did not finalize.
+    let fork_slot = txs[5].slot;
+    let removed = store.rollback(fork_slot).unwrap();
+    assert_eq!(removed, txs.iter().filter(|tx| tx.slot > fork_slot).count());
+    assert_eq!(store.checkpoint().unwrap().unwrap().slot, fork_slot);
+
+    let rows = store.negotiations().unwrap();
+    assert_eq!(rows.len(), 2);
+    let (a, b) = (&rows[0], &rows[1]);
+    assert_eq!(a.status, "countered");
+    assert_eq!(a.current_round, 2);
+    assert_eq!(a.current_offer_amount, Some(4_000_000));
+    assert_eq!(a.settled_amount, None);
+    assert_eq!(store.offers(&a.address).unwrap().len(), 2);
+    assert_eq!(b.status, "rejected");
+
+    // The canonical chain brings the same transactions back.
+    replay(&mut store, &txs);
+    assert_eq!(snapshot(&store), full);
+}
+
+#[test]
+fn resumes_from_checkpoint() {
+    let txs = fixture();
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("index.db");
+
+    let mut store = Store::open(&path).unwrap();
+    replay(&mut store, &txs[..7]);
+    drop(store);
+
+    let mut store = Store::open(&path).unwrap();
+    let checkpoint = store.checkpoint().unwrap().unwrap();
+    assert_eq!(checkpoint.signature, txs[6].signature);
+    let rest: Vec<_> = txs
+        .iter()
+        .skip_while(|tx| tx.signature != checkpoint.signature)
+        .skip(1)
+        .cloned()
+        .collect();
+    replay(&mut store, &rest);
+
+    assert_eq!(snapshot(&store), snapshot(&indexed()));
+}
+
+#[test]
+fn finality_tracking() {
+    let txs = fixture();
+    let mut store = indexed();
+    let slot = txs[3].slot;
+
+    let pending = store.unfinalized(slot).unwrap();
+    assert_eq!(pending.len(), 4);
+    store.mark_finalized(slot).unwrap();
+    assert!(store.unfinalized(slot).unwrap().is_empty());
+    let tip = txs.last().unwrap().slot;
+    assert_eq!(store.unfinalized(tip).unwrap().len(), txs.len() - 4);
+}
+
+#[test]
+fn events_in_logs_are_indexed_too() {
+    // Before the switch to self-CPI the program wrote events to its logs.
+    let legacy: Vec<_> = fixture()
+        .into_iter()
+        .map(|mut tx| {
+            let last = tx.logs.pop().unwrap();
+            for data in tx.inner_instructions.drain(..) {
+                let event = &STANDARD.decode(data).unwrap()[8..];
+                tx.logs
+                    .push(format!("Program data: {}", STANDARD.encode(event)));
+            }
+            tx.logs.push(last);
+            tx
+        })
+        .collect();
+    let mut store = Store::open_in_memory().unwrap();
+    replay(&mut store, &legacy);
+    assert_eq!(snapshot(&store), snapshot(&indexed()));
+}
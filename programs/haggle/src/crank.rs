@@ -0,0 +1,65 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{self, Transfer};
+
+use crate::errors::HaggleError;
+use crate::state::ProtocolConfig;
+
+// Whoever cranks a negotiation's permissionless cleanup is paid from what the
+// buyer put in: the lamport deposit prepaid into the negotiation account at
+// creation, and a share of the refund on expiry. Both terms are copied from
+// the config when the negotiation is created, so later changes only apply to
+// new negotiations.
+
+/// Prepays the config's fixed crank reward from `buyer` into the new
+/// negotiation account. `create::initialize` records it as the deposit.
+pub(crate) fn prepay<'info>(
+    negotiation: &AccountInfo<'info>,
+    buyer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    config: &ProtocolConfig,
+) -> Result<()> {
+    if config.crank_reward_lamports == 0 {
+        return Ok(());
+    }
+    system_program::transfer(
+        CpiContext::new(
+            system_program.clone(),
+            Transfer {
+                from: buyer.clone(),
+                to: negotiation.clone(),
+            },
+        ),
+        config.crank_reward_lamports,
+    )
+}
+
+/// Moves `amount` lamports out of the negotiation account to `cranker`,
+/// leaving its rent in place.
+pub(crate) fn pay(negotiation: &AccountInfo, cranker: &AccountInfo, amount: u64) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+    let rent = Rent::get()?.minimum_balance(negotiation.data_len());
+    require!(negotiation.lamports().saturating_sub(rent) >= amount, HaggleError::InvalidState);
+
+    **negotiation.try_borrow_mut_lamports()? -= amount;
+    **cranker.try_borrow_mut_lamports()? += amount;
+    Ok(())
+}
+
+/// Adds a paid crank reward to the protocol totals.
+pub(crate) fn record(config: &mut ProtocolConfig, lamports: u64, tokens: u64) -> Result<()> {
+    if lamports == 0 && tokens == 0 {
+        return Ok(());
+    }
+    config.total_cranks = config.total_cranks
+        .checked_add(1)
+        .ok_or(HaggleError::Overflow)?;
+    config.total_crank_rewards_lamports = config.total_crank_rewards_lamports
+        .checked_add(lamports)
+        .ok_or(HaggleError::Overflow)?;
+    config.total_crank_rewards_tokens = config.total_crank_rewards_tokens
+        .checked_add(tokens)
+        .ok_or(HaggleError::Overflow)?;
+    Ok(())
+}
@@ -28,4 +28,40 @@ pub enum HaggleError {
     NoZopa,
     #[msg("Arithmetic overflow")]
     Overflow,
+    #[msg("Instruction does not apply to this auction kind")]
+    WrongAuctionKind,
+    #[msg("Auction has not ended yet")]
+    AuctionNotEnded,
+    #[msg("Current price is above the buyer's limit")]
+    PriceAboveLimit,
+    #[msg("Revealed bid does not match its commitment")]
+    BidCommitmentMismatch,
+    #[msg("Bids cannot be revealed until the commit phase ends")]
+    CommitPhaseOpen,
+    #[msg("Bid does not undercut the lowest bid by the minimum decrement")]
+    BidNotLowEnough,
+    #[msg("Seller must post a bond before bidding")]
+    BondRequired,
+    #[msg("Coalition has no room for another member")]
+    CoalitionFull,
+    #[msg("Seller's acceptance policy does not allow this buyer")]
+    BuyerNotAllowed,
+    #[msg("Negotiation does not meet the seller's acceptance policy")]
+    PolicyViolation,
+    #[msg("Seller has too many open negotiations")]
+    TooManyOpenNegotiations,
+    #[msg("Price account is not a verified update for the negotiation's feed")]
+    InvalidPriceFeed,
+    #[msg("Oracle price is too old")]
+    StalePrice,
+    #[msg("Oracle price confidence interval is too wide")]
+    PriceUncertain,
+    #[msg("Swap adapter did not deliver its quoted payout")]
+    SwapFailed,
+    #[msg("Account is already in the current layout")]
+    AlreadyMigrated,
+    #[msg("Payouts do not add up to the vault balance")]
+    PayoutMismatch,
+    #[msg("Counterparty made a final offer; it can only be accepted or left to end")]
+    FinalOffer,
 }
@@ -1,15 +1,55 @@
 use anchor_lang::prelude::*;
 
+use crate::settlement::PayoutReason;
+use crate::state::{AuctionKind, BuyerFilter, NegotiationStatus, OfferSide, TerminationReason};
+
+#[event]
+pub struct ConfigInitialized {
+    pub authority: Pubkey,
+    pub treasury: Pubkey,
+    pub default_decay_rate_bps: u16,
+    pub default_response_window: i64,
+    pub default_protocol_fee_bps: u16,
+    pub default_max_rounds: u8,
+    pub timestamp: i64,
+}
+
 #[event]
 pub struct NegotiationCreated {
     pub negotiation_id: Pubkey,
     pub buyer: Pubkey,
     pub seller: Pubkey,
+    pub session_id: u64,
+    pub service_hash: [u8; 32],
     pub escrow_amount: u64,
     pub token_mint: Pubkey,
     pub max_rounds: u8,
     pub decay_rate_bps: u16,
+    pub response_window: i64,
     pub global_deadline: i64,
+    pub min_offer_bps: u16,
+    pub protocol_fee_bps: u16,
+    pub zopa_enabled: bool,
+    pub price_feed: [u8; 32],
+    pub mint_decimals: u8,
+    pub payout_mint: Pubkey,
+    pub swap_adapter: Pubkey,
+    pub max_slippage_bps: u16,
+    pub template: Pubkey,
+    /// Lamports prepaid for the cranker who ends the negotiation.
+    pub crank_deposit: u64,
+    pub crank_reward_bps: u16,
+    /// Holds one of the seller's `AcceptancePolicy` open slots.
+    pub admitted: bool,
+    /// When an unaccepted invitation lapses; zero without a TTL.
+    pub invitation_deadline: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct InvitationAccepted {
+    pub negotiation_id: Pubkey,
+    pub seller: Pubkey,
     pub timestamp: i64,
 }
 
@@ -17,9 +57,14 @@ pub struct NegotiationCreated {
 pub struct OfferSubmitted {
     pub negotiation_id: Pubkey,
     pub offerer: Pubkey,
+    pub offer_side: OfferSide,
     pub amount: u64,
     pub round: u8,
     pub effective_escrow: u64,
+    pub status: NegotiationStatus,
+    pub metadata: [u8; 64],
+    /// The counterparty can only accept or let the negotiation end.
+    pub is_final: bool,
     pub timestamp: i64,
 }
 
@@ -32,6 +77,19 @@ pub struct NegotiationSettled {
     pub total_rounds: u8,
     pub protocol_fee: u64,
     pub escrow_decay_total: u64,
+    pub payout_mint: Pubkey,
+    pub payout_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct Payout {
+    pub negotiation_id: Pubkey,
+    pub destination: Pubkey,
+    pub reason: PayoutReason,
+    pub amount: u64,
+    pub mint: Pubkey,
+    pub received: u64,
     pub timestamp: i64,
 }
 
@@ -40,6 +98,11 @@ pub struct NegotiationExpired {
     pub negotiation_id: Pubkey,
     pub refund_amount: u64,
     pub rounds_completed: u8,
+    /// Crank deposit paid to the cranker, in lamports; any share of the
+    /// refund is a `CrankReward` payout.
+    pub crank_reward: u64,
+    /// `Timeout`, or `CounterpartyUnresponsive` when the invitation lapsed.
+    pub reason: TerminationReason,
     pub timestamp: i64,
 }
 
@@ -49,5 +112,243 @@ pub struct NegotiationRejected {
     pub rejected_by: Pubkey,
     pub refund_amount: u64,
     pub rounds_completed: u8,
+    pub reason: TerminationReason,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct NegotiationClosed {
+    pub negotiation_id: Pubkey,
+    pub closed_by: Pubkey,
+    pub rent_reclaimed: u64,
+    pub bounty: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct NegotiationCancelled {
+    pub negotiation_id: Pubkey,
+    pub buyer: Pubkey,
+    pub refund_amount: u64,
+    pub rent_reclaimed: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AuctionCreated {
+    pub auction: Pubkey,
+    pub seller: Pubkey,
+    pub auction_id: u64,
+    pub kind: AuctionKind,
+    pub token_mint: Pubkey,
+    pub service_hash: [u8; 32],
+    pub start_price: u64,
+    pub floor_price: u64,
+    pub min_increment_bps: u16,
+    pub decay_rate_bps: u16,
+    pub decay_interval: i64,
+    pub protocol_fee_bps: u16,
+    pub slash_bps: u16,
+    pub commit_ends_at: i64,
+    pub ends_at: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BidPlaced {
+    pub auction: Pubkey,
+    pub bidder: Pubkey,
+    pub amount: u64,
+    /// Bidder refunded by this bid; default when it was the first bid.
+    pub outbid: Pubkey,
+    pub refund_amount: u64,
+    pub bid_count: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AuctionUnsold {
+    pub auction: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AuctionSettled {
+    pub auction: Pubkey,
+    pub kind: AuctionKind,
+    pub buyer: Pubkey,
+    pub seller: Pubkey,
+    /// Highest English bid, Vickrey second price or Dutch price paid.
+    pub settled_amount: u64,
+    pub protocol_fee: u64,
+    pub seller_payment: u64,
+    /// Bids placed or revealed; one for a Dutch sale.
+    pub bid_count: u16,
+    /// How far the Dutch price had fallen from `start_price`; zero for
+    /// English and Vickrey auctions.
+    pub price_decay: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AuctionClosed {
+    pub auction: Pubkey,
+    pub seller: Pubkey,
+    pub rent_reclaimed: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BidCommitted {
+    pub auction: Pubkey,
+    pub bidder: Pubkey,
+    pub commitment: [u8; 32],
+    pub deposit: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BidRevealed {
+    pub auction: Pubkey,
+    pub bidder: Pubkey,
+    pub amount: u64,
+    pub highest_bid: u64,
+    pub second_bid: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BidWithdrawn {
+    pub auction: Pubkey,
+    pub bidder: Pubkey,
+    pub refund_amount: u64,
+    /// Forfeited to the seller for a bid that was never revealed.
+    pub slashed: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ReverseAuctionCreated {
+    pub auction: Pubkey,
+    pub buyer: Pubkey,
+    pub auction_id: u64,
+    pub token_mint: Pubkey,
+    pub service_hash: [u8; 32],
+    pub ceiling: u64,
+    pub min_decrement_bps: u16,
+    pub seller_bond: u64,
+    pub protocol_fee_bps: u16,
+    pub ends_at: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BondPosted {
+    pub auction: Pubkey,
+    pub seller: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ReverseBidPlaced {
+    pub auction: Pubkey,
+    pub seller: Pubkey,
+    pub amount: u64,
+    pub bid_count: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ReverseAuctionSettled {
+    pub auction: Pubkey,
+    pub buyer: Pubkey,
+    pub seller: Pubkey,
+    /// The lowest bid, paid out of the buyer's ceiling.
+    pub settled_amount: u64,
+    pub protocol_fee: u64,
+    pub seller_payment: u64,
+    /// What the ceiling held above the lowest bid, back to the buyer.
+    pub refund_amount: u64,
+    pub bid_count: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ReverseAuctionClosed {
+    pub auction: Pubkey,
+    pub buyer: Pubkey,
+    pub rent_reclaimed: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CoalitionCreated {
+    pub coalition: Pubkey,
+    pub negotiation: Pubkey,
+    pub lead: Pubkey,
+    pub contribution: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CoalitionJoined {
+    pub coalition: Pubkey,
+    pub negotiation: Pubkey,
+    pub member: Pubkey,
+    pub amount: u64,
+    pub share: u64,
+    pub escrow_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CoalitionExited {
+    pub coalition: Pubkey,
+    pub negotiation: Pubkey,
+    pub member: Pubkey,
+    pub share: u64,
+    pub refund_amount: u64,
+    pub fee_share: u64,
+    pub escrow_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TemplateRegistered {
+    pub template: Pubkey,
+    pub owner: Pubkey,
+    pub template_id: u64,
+    pub max_rounds: u8,
+    pub decay_rate_bps: u16,
+    pub response_window: i64,
+    pub global_deadline_offset: i64,
+    pub min_offer_bps: u16,
+    pub protocol_fee_bps: u16,
+    pub zopa_enabled: bool,
+    pub allowed_mints: Vec<Pubkey>,
+    pub arbiter: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AcceptancePolicySet {
+    pub policy: Pubkey,
+    pub seller: Pubkey,
+    pub buyer_filter: BuyerFilter,
+    pub buyers: Vec<Pubkey>,
+    pub min_escrow: u64,
+    pub accepted_mints: Vec<Pubkey>,
+    pub max_open_negotiations: u16,
+    pub open_negotiations: u16,
+    pub auto_accept: bool,
+    pub required_template: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AcceptancePolicyClosed {
+    pub policy: Pubkey,
+    pub seller: Pubkey,
     pub timestamp: i64,
 }
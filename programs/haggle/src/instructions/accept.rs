@@ -1,47 +1,53 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use anchor_spl::token::{Token, TokenAccount};
 
 use crate::errors::HaggleError;
 use crate::events::NegotiationSettled;
+use crate::migration::LoadCurrent;
+use crate::oracle;
+use crate::settlement::{negotiation_plan, record_sale, NegotiationSigner, Outcome, PayoutReason, Settlement};
+use crate::swap::SwapRoute;
+use crate::policy;
 use crate::state::*;
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct AcceptOffer<'info> {
     pub acceptor: Signer<'info>,
 
     #[account(
         mut,
-        seeds = [b"negotiation", negotiation.buyer.as_ref(), negotiation.seller.as_ref(), &negotiation.session_id.to_le_bytes()],
-        bump = negotiation.bump,
+        seeds = [b"negotiation", negotiation.load_current()?.buyer.as_ref(), negotiation.load_current()?.seller.as_ref(), &negotiation.load_current()?.session_id.to_le_bytes()],
+        bump = negotiation.load_current()?.bump,
     )]
-    pub negotiation: Box<Account<'info, NegotiationState>>,
+    pub negotiation: AccountLoader<'info, NegotiationState>,
 
     #[account(
         mut,
         seeds = [b"vault", negotiation.key().as_ref()],
         bump,
-        constraint = escrow_vault.mint == negotiation.token_mint @ HaggleError::InvalidParams,
+        constraint = escrow_vault.mint == negotiation.load_current()?.token_mint @ HaggleError::InvalidParams,
     )]
     pub escrow_vault: Box<Account<'info, TokenAccount>>,
 
     #[account(
         mut,
-        constraint = seller_token_account.owner == negotiation.seller @ HaggleError::InvalidParams,
-        constraint = seller_token_account.mint == negotiation.token_mint @ HaggleError::InvalidParams,
+        constraint = seller_token_account.owner == negotiation.load_current()?.seller @ HaggleError::InvalidParams,
+        constraint = seller_token_account.mint == negotiation.load_current()?.token_mint @ HaggleError::InvalidParams,
     )]
     pub seller_token_account: Box<Account<'info, TokenAccount>>,
 
     #[account(
         mut,
         constraint = treasury_token_account.owner == config.treasury @ HaggleError::InvalidParams,
-        constraint = treasury_token_account.mint == negotiation.token_mint @ HaggleError::InvalidParams,
+        constraint = treasury_token_account.mint == negotiation.load_current()?.token_mint @ HaggleError::InvalidParams,
     )]
     pub treasury_token_account: Box<Account<'info, TokenAccount>>,
 
     #[account(
         mut,
-        constraint = buyer_token_account.owner == negotiation.buyer @ HaggleError::InvalidParams,
-        constraint = buyer_token_account.mint == negotiation.token_mint @ HaggleError::InvalidParams,
+        constraint = buyer_token_account.owner == negotiation.load_current()?.buyer @ HaggleError::InvalidParams,
+        constraint = buyer_token_account.mint == negotiation.load_current()?.token_mint @ HaggleError::InvalidParams,
     )]
     pub buyer_token_account: Box<Account<'info, TokenAccount>>,
 
@@ -52,17 +58,40 @@ pub struct AcceptOffer<'info> {
     )]
     pub config: Box<Account<'info, ProtocolConfig>>,
 
+    /// CHECK: Seller's `AcceptancePolicy`, which may not exist; read by `policy::release`
+    #[account(mut, seeds = [b"policy", negotiation.load_current()?.seller.as_ref()], bump)]
+    pub policy: UncheckedAccount<'info>,
+
+    /// CHECK: Pyth `PriceUpdateV2` when offers are quote-denominated; checked by `oracle::quote_price`
+    pub price_update: Option<UncheckedAccount<'info>>,
+
+    #[account(
+        mut,
+        constraint = seller_payout_account.owner == negotiation.load_current()?.seller @ HaggleError::InvalidParams,
+        constraint = seller_payout_account.mint == negotiation.load_current()?.payout_mint @ HaggleError::InvalidParams,
+    )]
+    pub seller_payout_account: Option<Box<Account<'info, TokenAccount>>>,
+
+    /// CHECK: The negotiated swap adapter; its own accounts follow as remaining accounts
+    #[account(executable, address = negotiation.load_current()?.swap_adapter @ HaggleError::InvalidParams)]
+    pub swap_adapter: Option<UncheckedAccount<'info>>,
+
     pub token_program: Program<'info, Token>,
 }
 
-pub fn handler(ctx: Context<AcceptOffer>) -> Result<()> {
-    let negotiation = &ctx.accounts.negotiation;
+/// Settles at the current offer. A cross-mint negotiation needs the
+/// negotiated swap route unless the seller accepts and waives it; the seller
+/// is paid in the escrow mint when the quote slips past `max_slippage_bps`.
+/// A quote or swap that fails outright aborts the accept, since a failed CPI
+/// cannot be recovered from.
+pub fn handler<'info>(ctx: Context<'_, '_, '_, 'info, AcceptOffer<'info>>) -> Result<()> {
+    let negotiation = ctx.accounts.negotiation.load_current()?;
     let clock = Clock::get()?;
 
     // Validate state
     require!(
-        negotiation.status == NegotiationStatus::Proposed
-            || negotiation.status == NegotiationStatus::Countered,
+        negotiation.status()? == NegotiationStatus::Proposed
+            || negotiation.status()? == NegotiationStatus::Countered,
         HaggleError::InvalidState
     );
 
@@ -80,107 +109,89 @@ pub fn handler(ctx: Context<AcceptOffer>) -> Result<()> {
     // Validate not expired
     require!(clock.unix_timestamp < negotiation.global_deadline, HaggleError::Expired);
 
-    let settled_amount = negotiation.current_offer_amount;
-
-    // Calculate protocol fee
-    let protocol_fee = settled_amount
-        .checked_mul(negotiation.protocol_fee_bps as u64)
-        .ok_or(HaggleError::Overflow)?
-        .checked_div(10000)
-        .ok_or(HaggleError::Overflow)?;
-
-    let seller_payment = settled_amount
-        .checked_sub(protocol_fee)
-        .ok_or(HaggleError::Overflow)?;
-
-    // Calculate refund (remaining escrow after settled + fee)
-    let vault_balance = ctx.accounts.escrow_vault.amount;
-    let refund_amount = vault_balance
-        .checked_sub(settled_amount)
-        .ok_or(HaggleError::Overflow)?;
+    // Quote-denominated offers settle at the current price, which may have
+    // moved since the offer; the converted amount must still fit the vault
+    let settled_amount = match oracle::quote_price(&negotiation, ctx.accounts.price_update.as_deref(), clock.unix_timestamp)? {
+        Some(price) => price.to_mint_units(negotiation.current_offer_amount, negotiation.mint_decimals)?,
+        None => negotiation.current_offer_amount,
+    };
 
-    // PDA signer seeds
+    let signer = NegotiationSigner::new(&negotiation);
     let buyer_key = negotiation.buyer;
     let seller_key = negotiation.seller;
-    let session_bytes = negotiation.session_id.to_le_bytes();
-    let bump = negotiation.bump;
-    let signer_seeds: &[&[&[u8]]] = &[&[
-        b"negotiation",
-        buyer_key.as_ref(),
-        seller_key.as_ref(),
-        &session_bytes,
-        &[bump],
-    ]];
-
-    // Transfer payment to seller
-    if seller_payment > 0 {
-        token::transfer(
-            CpiContext::new_with_signer(
-                ctx.accounts.token_program.to_account_info(),
-                Transfer {
-                    from: ctx.accounts.escrow_vault.to_account_info(),
-                    to: ctx.accounts.seller_token_account.to_account_info(),
-                    authority: ctx.accounts.negotiation.to_account_info(),
-                },
-                signer_seeds,
-            ),
-            seller_payment,
-        )?;
-    }
-
-    // Transfer fee to treasury
-    if protocol_fee > 0 {
-        token::transfer(
-            CpiContext::new_with_signer(
-                ctx.accounts.token_program.to_account_info(),
-                Transfer {
-                    from: ctx.accounts.escrow_vault.to_account_info(),
-                    to: ctx.accounts.treasury_token_account.to_account_info(),
-                    authority: ctx.accounts.negotiation.to_account_info(),
-                },
-                signer_seeds,
-            ),
-            protocol_fee,
-        )?;
-    }
-
-    // Refund remaining to buyer
-    if refund_amount > 0 {
-        token::transfer(
-            CpiContext::new_with_signer(
-                ctx.accounts.token_program.to_account_info(),
-                Transfer {
-                    from: ctx.accounts.escrow_vault.to_account_info(),
-                    to: ctx.accounts.buyer_token_account.to_account_info(),
-                    authority: ctx.accounts.negotiation.to_account_info(),
-                },
-                signer_seeds,
-            ),
-            refund_amount,
-        )?;
-    }
-
-    // Update state
     let escrow_decay_total = negotiation.escrow_amount
         .checked_sub(negotiation.effective_escrow)
         .ok_or(HaggleError::Overflow)?;
     let total_rounds = negotiation.current_round;
+    let protocol_fee_bps = negotiation.protocol_fee_bps;
+    let max_slippage_bps = negotiation.max_slippage_bps;
+    let is_cross_mint = negotiation.is_cross_mint();
+    let is_pooled = negotiation.is_pooled();
+    let token_mint = negotiation.token_mint;
+    // The negotiation signs for the vault, so the borrow must end before the
+    // token program or the swap adapter is invoked
+    drop(negotiation);
+
+    let seeds = signer.seeds();
+    let settlement = Settlement {
+        token_program: ctx.accounts.token_program.to_account_info(),
+        vault: ctx.accounts.escrow_vault.to_account_info(),
+        vault_authority: ctx.accounts.negotiation.to_account_info(),
+        signer_seeds: &[&seeds],
+    };
+
+    // Pay the treasury and the seller, and refund whatever escrow is left to
+    // the buyer (coalition members claim theirs from the vault)
+    let plan = negotiation_plan(
+        ctx.accounts.escrow_vault.amount,
+        Outcome::Settled {
+            seller: ctx.accounts.seller_token_account.to_account_info(),
+            amount: settled_amount,
+            protocol_fee_bps,
+        },
+        is_pooled,
+        ctx.accounts.buyer_token_account.to_account_info(),
+        ctx.accounts.treasury_token_account.to_account_info(),
+    )?;
+    let protocol_fee = plan.paid(PayoutReason::ProtocolFee);
+
+    // The seller is paid in the payout mint they agreed to, falling back to
+    // the escrow mint only when the swap would slip too far. The buyer cannot
+    // leave the route out; only the seller may waive it
+    let route = match (&ctx.accounts.swap_adapter, &ctx.accounts.seller_payout_account) {
+        (Some(adapter), Some(payout_account)) if is_cross_mint => Some(SwapRoute {
+            adapter: adapter.to_account_info(),
+            destination: payout_account.to_account_info(),
+            accounts: ctx.remaining_accounts,
+        }),
+        _ => {
+            require!(!is_cross_mint || is_seller, HaggleError::InvalidParams);
+            None
+        }
+    };
+    let payouts = match &route {
+        Some(route) => settlement.payout_with_swap(&plan, route, max_slippage_bps)?,
+        None => settlement.payout(&plan)?,
+    };
+    let (payout_mint, payout_amount) = payouts
+        .iter()
+        .find(|payout| payout.reason == PayoutReason::Seller)
+        .map_or((token_mint, 0), |payout| (payout.mint, payout.received));
+    record_sale(&mut ctx.accounts.config, settled_amount, protocol_fee)?;
 
-    let negotiation = &mut ctx.accounts.negotiation;
-    negotiation.status = NegotiationStatus::Settled;
-    negotiation.settled_amount = settled_amount;
-    negotiation.settled_at = clock.unix_timestamp;
-
-    // Update config
-    let config = &mut ctx.accounts.config;
-    config.total_settled_volume = config.total_settled_volume
-        .checked_add(settled_amount)
-        .ok_or(HaggleError::Overflow)?;
-    config.total_fees_collected = config.total_fees_collected
-        .checked_add(protocol_fee)
-        .ok_or(HaggleError::Overflow)?;
+    // Update state
+    {
+        let mut negotiation = ctx.accounts.negotiation.load_current_mut()?;
+        negotiation.set_status(NegotiationStatus::Settled);
+        negotiation.settled_amount = settled_amount;
+        negotiation.settled_at = clock.unix_timestamp;
+        policy::release(&ctx.accounts.policy, &mut negotiation)?;
+    }
 
-    emit!(NegotiationSettled {
+    for payout in payouts {
+        emit_cpi!(payout);
+    }
+    emit_cpi!(NegotiationSettled {
         negotiation_id: ctx.accounts.negotiation.key(),
         buyer: buyer_key,
         seller: seller_key,
@@ -188,6 +199,8 @@ pub fn handler(ctx: Context<AcceptOffer>) -> Result<()> {
         total_rounds,
         protocol_fee,
         escrow_decay_total,
+        payout_mint,
+        payout_amount,
         timestamp: clock.unix_timestamp,
     });
 
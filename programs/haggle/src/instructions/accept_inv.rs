@@ -1,29 +1,59 @@
 use anchor_lang::prelude::*;
 
 use crate::errors::HaggleError;
+use crate::events::InvitationAccepted;
+use crate::instructions::offer::apply_offer;
+use crate::migration::LoadCurrent;
+use crate::oracle;
 use crate::state::*;
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct AcceptInvitation<'info> {
     pub seller: Signer<'info>,
 
     #[account(
         mut,
-        seeds = [b"negotiation", negotiation.buyer.as_ref(), seller.key().as_ref(), &negotiation.session_id.to_le_bytes()],
-        bump = negotiation.bump,
-        constraint = negotiation.seller == seller.key() @ HaggleError::Unauthorized,
-        constraint = negotiation.status == NegotiationStatus::Created @ HaggleError::InvalidState,
+        seeds = [b"negotiation", negotiation.load_current()?.buyer.as_ref(), seller.key().as_ref(), &negotiation.load_current()?.session_id.to_le_bytes()],
+        bump = negotiation.load_current()?.bump,
+        constraint = negotiation.load_current()?.seller == seller.key() @ HaggleError::Unauthorized,
+        constraint = negotiation.load_current()?.status()? == NegotiationStatus::Created @ HaggleError::InvalidState,
     )]
-    pub negotiation: Account<'info, NegotiationState>,
+    pub negotiation: AccountLoader<'info, NegotiationState>,
+
+    /// CHECK: Pyth `PriceUpdateV2` for a quote-denominated opening offer; checked by `oracle::quote_price`
+    pub price_update: Option<UncheckedAccount<'info>>,
 }
 
-pub fn handler(ctx: Context<AcceptInvitation>) -> Result<()> {
+/// Opens the first round, optionally with the seller's counter to the
+/// buyer's opening offer (or the first offer if the buyer made none).
+pub fn handler(ctx: Context<AcceptInvitation>, opening_offer: Option<OpeningOffer>) -> Result<()> {
+    let negotiation_key = ctx.accounts.negotiation.key();
+    let mut negotiation = ctx.accounts.negotiation.load_current_mut()?;
     let clock = Clock::get()?;
-    require!(clock.unix_timestamp < ctx.accounts.negotiation.global_deadline, HaggleError::Expired);
+    // The invitation may lapse before the global deadline
+    require!(clock.unix_timestamp < negotiation.expiry()?.0, HaggleError::Expired);
+
+    emit_cpi!(open_first_round(&mut negotiation, negotiation_key, clock.unix_timestamp));
 
-    let negotiation = &mut ctx.accounts.negotiation;
-    negotiation.status = NegotiationStatus::Proposed;
-    negotiation.last_offer_at = clock.unix_timestamp;
+    if let Some(offer) = opening_offer {
+        let seller = negotiation.seller;
+        let price = oracle::quote_price(&negotiation, ctx.accounts.price_update.as_deref(), clock.unix_timestamp)?;
+        emit_cpi!(apply_offer(&mut negotiation, negotiation_key, seller, offer.amount, offer.metadata, false, price, clock.unix_timestamp)?);
+    }
 
     Ok(())
 }
+
+/// Moves a created negotiation to `Proposed`. Shared with creation when the
+/// seller's acceptance policy auto-accepts.
+pub(crate) fn open_first_round(negotiation: &mut NegotiationState, negotiation_id: Pubkey, now: i64) -> InvitationAccepted {
+    negotiation.set_status(NegotiationStatus::Proposed);
+    negotiation.last_offer_at = now;
+
+    InvitationAccepted {
+        negotiation_id,
+        seller: negotiation.seller,
+        timestamp: now,
+    }
+}
@@ -0,0 +1,148 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+
+use crate::errors::HaggleError;
+use crate::events::AuctionSettled;
+use crate::settlement::{record_sale, Settlement};
+use crate::state::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct BuyAuction<'info> {
+    pub buyer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"auction", auction.seller.as_ref(), &auction.auction_id.to_le_bytes()],
+        bump = auction.bump,
+    )]
+    pub auction: Box<Account<'info, Auction>>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", auction.key().as_ref()],
+        bump,
+        constraint = escrow_vault.mint == auction.token_mint @ HaggleError::InvalidParams,
+    )]
+    pub escrow_vault: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        constraint = buyer_token_account.owner == buyer.key() @ HaggleError::Unauthorized,
+        constraint = buyer_token_account.mint == auction.token_mint @ HaggleError::InvalidParams,
+    )]
+    pub buyer_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        constraint = seller_token_account.owner == auction.seller @ HaggleError::InvalidParams,
+        constraint = seller_token_account.mint == auction.token_mint @ HaggleError::InvalidParams,
+    )]
+    pub seller_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        constraint = treasury_token_account.owner == config.treasury @ HaggleError::InvalidParams,
+        constraint = treasury_token_account.mint == auction.token_mint @ HaggleError::InvalidParams,
+    )]
+    pub treasury_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Box<Account<'info, ProtocolConfig>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Buys a Dutch auction at the current price, which must not exceed
+/// `max_price` since the price moves between signing and landing.
+pub fn handler(ctx: Context<BuyAuction>, max_price: u64) -> Result<()> {
+    let auction = &ctx.accounts.auction;
+    let clock = Clock::get()?;
+
+    // Validate state
+    require!(auction.kind == AuctionKind::Dutch, HaggleError::WrongAuctionKind);
+    require!(auction.status == AuctionStatus::Open, HaggleError::InvalidState);
+    require!(clock.unix_timestamp < auction.ends_at, HaggleError::Expired);
+    require!(ctx.accounts.buyer.key() != auction.seller, HaggleError::Unauthorized);
+
+    let price = auction.dutch_price(clock.unix_timestamp);
+    require!(price <= max_price, HaggleError::PriceAboveLimit);
+
+    // Escrow the price, then settle it out of the vault like an accepted
+    // offer. Nothing else is held for a Dutch auction, so anything sent to the
+    // vault besides the price goes back to the seller.
+    token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.buyer_token_account.to_account_info(),
+                to: ctx.accounts.escrow_vault.to_account_info(),
+                authority: ctx.accounts.buyer.to_account_info(),
+            },
+        ),
+        price,
+    )?;
+
+    let seller_key = auction.seller;
+    let auction_id_bytes = auction.auction_id.to_le_bytes();
+    let bump = auction.bump;
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        b"auction",
+        seller_key.as_ref(),
+        &auction_id_bytes,
+        &[bump],
+    ]];
+
+    let settlement = Settlement {
+        token_program: ctx.accounts.token_program.to_account_info(),
+        vault: ctx.accounts.escrow_vault.to_account_info(),
+        vault_authority: ctx.accounts.auction.to_account_info(),
+        signer_seeds,
+    };
+    let mut plan = settlement.plan()?;
+    let split = plan.sale(
+        price,
+        auction.protocol_fee_bps,
+        ctx.accounts.seller_token_account.to_account_info(),
+        ctx.accounts.treasury_token_account.to_account_info(),
+    )?;
+    plan.refund_rest(ctx.accounts.seller_token_account.to_account_info())?;
+    let payouts = settlement.payout(&plan)?;
+    record_sale(&mut ctx.accounts.config, price, split.protocol_fee)?;
+    for payout in payouts {
+        emit_cpi!(payout);
+    }
+
+    let price_decay = auction.start_price
+        .checked_sub(price)
+        .ok_or(HaggleError::Overflow)?;
+
+    // Update state
+    let buyer_key = ctx.accounts.buyer.key();
+    let auction = &mut ctx.accounts.auction;
+    auction.status = AuctionStatus::Settled;
+    auction.highest_bid = price;
+    auction.highest_bidder = buyer_key;
+    auction.bid_count = 1;
+    auction.settled_amount = price;
+    auction.settled_at = clock.unix_timestamp;
+
+    emit_cpi!(AuctionSettled {
+        auction: auction.key(),
+        kind: auction.kind,
+        buyer: buyer_key,
+        seller: seller_key,
+        settled_amount: price,
+        protocol_fee: split.protocol_fee,
+        seller_payment: split.seller_payment,
+        bid_count: auction.bid_count,
+        price_decay,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
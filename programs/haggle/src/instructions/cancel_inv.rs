@@ -0,0 +1,121 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount};
+
+use crate::errors::HaggleError;
+use crate::events::NegotiationCancelled;
+use crate::migration::LoadCurrent;
+use crate::policy;
+use crate::settlement::{negotiation_plan, NegotiationSigner, Outcome, PayoutReason, Settlement};
+use crate::state::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CancelInvitation<'info> {
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"negotiation", negotiation.load_current()?.buyer.as_ref(), negotiation.load_current()?.seller.as_ref(), &negotiation.load_current()?.session_id.to_le_bytes()],
+        bump = negotiation.load_current()?.bump,
+        constraint = negotiation.load_current()?.buyer == buyer.key() @ HaggleError::Unauthorized,
+        close = buyer,
+    )]
+    pub negotiation: AccountLoader<'info, NegotiationState>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", negotiation.key().as_ref()],
+        bump,
+        constraint = escrow_vault.mint == negotiation.load_current()?.token_mint @ HaggleError::InvalidParams,
+    )]
+    pub escrow_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = buyer_token_account.owner == buyer.key() @ HaggleError::InvalidParams,
+        constraint = buyer_token_account.mint == negotiation.load_current()?.token_mint @ HaggleError::InvalidParams,
+    )]
+    pub buyer_token_account: Account<'info, TokenAccount>,
+
+    /// Receives the escrow lost to decay, if the buyer already made an offer
+    #[account(
+        mut,
+        constraint = treasury_token_account.owner == config.treasury @ HaggleError::InvalidParams,
+        constraint = treasury_token_account.mint == negotiation.load_current()?.token_mint @ HaggleError::InvalidParams,
+    )]
+    pub treasury_token_account: Account<'info, TokenAccount>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProtocolConfig>,
+
+    /// CHECK: Seller's `AcceptancePolicy`, which may not exist; read by `policy::release`
+    #[account(mut, seeds = [b"policy", negotiation.load_current()?.seller.as_ref()], bump)]
+    pub policy: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Withdraws an invitation the seller has not accepted: refunds the escrow
+/// as a rejection would, then closes the vault and the negotiation, all in
+/// one instruction.
+pub fn handler(ctx: Context<CancelInvitation>) -> Result<()> {
+    let negotiation = ctx.accounts.negotiation.load_current()?;
+    let clock = Clock::get()?;
+
+    require!(negotiation.status()? == NegotiationStatus::Created, HaggleError::InvalidState);
+    // Coalition members claim their shares from the vault, so a pooled
+    // negotiation is rejected instead and closed once they have
+    require!(!negotiation.is_pooled(), HaggleError::InvalidState);
+
+    let signer = NegotiationSigner::new(&negotiation);
+    let escrow_decay = negotiation.escrow_amount
+        .checked_sub(negotiation.effective_escrow)
+        .ok_or(HaggleError::Overflow)?;
+    // The negotiation signs for the vault, so the borrow must end before the
+    // token program is invoked
+    drop(negotiation);
+
+    let plan = negotiation_plan(
+        ctx.accounts.escrow_vault.amount,
+        Outcome::Terminated { escrow_decay, crank_reward: None },
+        false,
+        ctx.accounts.buyer_token_account.to_account_info(),
+        ctx.accounts.treasury_token_account.to_account_info(),
+    )?;
+    let refund_amount = plan.paid(PayoutReason::Refund);
+    let seeds = signer.seeds();
+    let settlement = Settlement {
+        token_program: ctx.accounts.token_program.to_account_info(),
+        vault: ctx.accounts.escrow_vault.to_account_info(),
+        vault_authority: ctx.accounts.negotiation.to_account_info(),
+        signer_seeds: &[&seeds],
+    };
+    let payouts = settlement.payout(&plan)?;
+
+    // Rent (and any unpaid crank deposit) returned to the buyer once both
+    // accounts are closed
+    let rent_reclaimed = ctx.accounts.negotiation.to_account_info()
+        .lamports()
+        .checked_add(ctx.accounts.escrow_vault.to_account_info().lamports())
+        .ok_or(HaggleError::Overflow)?;
+    settlement.close_vault(ctx.accounts.buyer.to_account_info())?;
+
+    let mut negotiation = ctx.accounts.negotiation.load_current_mut()?;
+    policy::release(&ctx.accounts.policy, &mut negotiation)?;
+    drop(negotiation);
+
+    for payout in payouts {
+        emit_cpi!(payout);
+    }
+
+    emit_cpi!(NegotiationCancelled {
+        negotiation_id: ctx.accounts.negotiation.key(),
+        buyer: ctx.accounts.buyer.key(),
+        refund_amount,
+        rent_reclaimed,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
@@ -1,22 +1,32 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, CloseAccount, Token, TokenAccount};
+use anchor_spl::token::{Token, TokenAccount};
 
+use crate::crank;
 use crate::errors::HaggleError;
+use crate::events::{CoalitionExited, NegotiationClosed};
+use crate::instructions::exit_coalition::ended_share;
+use crate::migration::LoadCurrent;
+use crate::settlement::{NegotiationSigner, PayoutPlan, PayoutReason, Settlement};
 use crate::state::*;
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct CloseNegotiation<'info> {
+    /// The buyer at any time, anyone once the grace period is over
     #[account(mut)]
-    pub creator: Signer<'info>,
+    pub closer: Signer<'info>,
+
+    /// CHECK: Receives the rent, less any bounty
+    #[account(mut, address = negotiation.load_current()?.buyer @ HaggleError::InvalidParams)]
+    pub buyer: UncheckedAccount<'info>,
 
     #[account(
         mut,
-        seeds = [b"negotiation", negotiation.buyer.as_ref(), negotiation.seller.as_ref(), &negotiation.session_id.to_le_bytes()],
-        bump = negotiation.bump,
-        constraint = negotiation.buyer == creator.key() @ HaggleError::Unauthorized,
-        close = creator,
+        seeds = [b"negotiation", negotiation.load_current()?.buyer.as_ref(), negotiation.load_current()?.seller.as_ref(), &negotiation.load_current()?.session_id.to_le_bytes()],
+        bump = negotiation.load_current()?.bump,
+        close = buyer,
     )]
-    pub negotiation: Account<'info, NegotiationState>,
+    pub negotiation: AccountLoader<'info, NegotiationState>,
 
     #[account(
         mut,
@@ -25,42 +35,152 @@ pub struct CloseNegotiation<'info> {
     )]
     pub escrow_vault: Account<'info, TokenAccount>,
 
+    /// Receives anything left in the vault
+    #[account(
+        mut,
+        constraint = buyer_token_account.owner == negotiation.load_current()?.buyer @ HaggleError::InvalidParams,
+        constraint = buyer_token_account.mint == negotiation.load_current()?.token_mint @ HaggleError::InvalidParams,
+    )]
+    pub buyer_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: The negotiation's `BuyerCoalition`, which may not exist; read by `load_coalition`
+    #[account(mut, seeds = [b"coalition", negotiation.key().as_ref()], bump)]
+    pub coalition: UncheckedAccount<'info>,
+
+    #[account(mut, seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProtocolConfig>,
+
     pub token_program: Program<'info, Token>,
 }
 
-pub fn handler(ctx: Context<CloseNegotiation>) -> Result<()> {
-    let negotiation = &ctx.accounts.negotiation;
+/// Closes an ended negotiation and its vault. Anything still in the vault,
+/// such as tokens sent to it after settlement, goes back to the buyer.
+/// Coalition members who have not exited by the end of the grace period are
+/// paid their shares here: the remaining accounts are their token accounts,
+/// in the coalition's member order.
+pub fn handler<'info>(ctx: Context<'_, '_, '_, 'info, CloseNegotiation<'info>>) -> Result<()> {
+    let negotiation = ctx.accounts.negotiation.load_current()?;
+    let clock = Clock::get()?;
 
     // Can only close terminal states
     require!(
-        negotiation.status == NegotiationStatus::Settled
-            || negotiation.status == NegotiationStatus::Expired
-            || negotiation.status == NegotiationStatus::Rejected,
+        negotiation.status()? == NegotiationStatus::Settled
+            || negotiation.status()? == NegotiationStatus::Expired
+            || negotiation.status()? == NegotiationStatus::Rejected,
         HaggleError::InvalidState
     );
 
+    // Anyone but the buyer waits out the grace period and earns a bounty on
+    // top of any crank deposit still unpaid
+    let is_buyer = ctx.accounts.closer.key() == negotiation.buyer;
+    let crank_deposit = negotiation.crank_deposit;
+    let grace_over = clock.unix_timestamp >= negotiation.closable_at()?;
+    require!(is_buyer || grace_over, HaggleError::Unauthorized);
+
+    // Coalition members claim their own refunds until the grace period is
+    // over, and are then paid whatever they have not claimed
+    let coalition = load_coalition(&ctx.accounts.coalition)?;
+    let mut plan = PayoutPlan::new(ctx.accounts.escrow_vault.amount);
+    let mut exits = Vec::new();
+    if let Some(mut coalition) = coalition {
+        require!(grace_over, HaggleError::InvalidState);
+        require!(ctx.remaining_accounts.len() == coalition.members.len(), HaggleError::InvalidParams);
+        let members = std::mem::take(&mut coalition.members);
+        for (share, info) in members.iter().zip(ctx.remaining_accounts) {
+            let account = TokenAccount::try_deserialize(&mut &info.try_borrow_data()?[..])?;
+            require!(
+                account.owner == share.member && account.mint == negotiation.token_mint,
+                HaggleError::InvalidParams
+            );
+            let (refund_amount, fee_share) = ended_share(&coalition, &negotiation, plan.unallocated(), share.amount)?;
+            plan.pay(info.clone(), refund_amount, PayoutReason::Refund)?;
+            coalition.total_contributed = coalition.total_contributed
+                .checked_sub(share.amount)
+                .ok_or(HaggleError::Overflow)?;
+            coalition.fee_claimed = coalition.fee_claimed
+                .checked_add(fee_share)
+                .ok_or(HaggleError::Overflow)?;
+            exits.push(CoalitionExited {
+                coalition: ctx.accounts.coalition.key(),
+                negotiation: ctx.accounts.negotiation.key(),
+                member: share.member,
+                share: share.amount,
+                refund_amount,
+                fee_share,
+                escrow_amount: negotiation.escrow_amount,
+                timestamp: clock.unix_timestamp,
+            });
+        }
+    }
+    plan.refund_rest(ctx.accounts.buyer_token_account.to_account_info())?;
+
+    // Rent returned to the buyer once both accounts are closed
+    let negotiation_info = ctx.accounts.negotiation.to_account_info();
+    let rent_reclaimed = negotiation_info
+        .lamports()
+        .checked_add(ctx.accounts.escrow_vault.to_account_info().lamports())
+        .ok_or(HaggleError::Overflow)?;
+
     // Close the vault token account
-    let buyer_key = negotiation.buyer;
-    let seller_key = negotiation.seller;
-    let session_bytes = negotiation.session_id.to_le_bytes();
-    let bump = negotiation.bump;
-    let signer_seeds: &[&[&[u8]]] = &[&[
-        b"negotiation",
-        buyer_key.as_ref(),
-        seller_key.as_ref(),
-        &session_bytes,
-        &[bump],
-    ]];
-
-    token::close_account(CpiContext::new_with_signer(
-        ctx.accounts.token_program.to_account_info(),
-        CloseAccount {
-            account: ctx.accounts.escrow_vault.to_account_info(),
-            destination: ctx.accounts.creator.to_account_info(),
-            authority: ctx.accounts.negotiation.to_account_info(),
-        },
-        signer_seeds,
-    ))?;
+    let signer = NegotiationSigner::new(&negotiation);
+    drop(negotiation);
+
+    let seeds = signer.seeds();
+    let settlement = Settlement {
+        token_program: ctx.accounts.token_program.to_account_info(),
+        vault: ctx.accounts.escrow_vault.to_account_info(),
+        vault_authority: negotiation_info.clone(),
+        signer_seeds: &[&seeds],
+    };
+    let payouts = settlement.payout(&plan)?;
+    settlement.close_vault(ctx.accounts.buyer.to_account_info())?;
+
+    // The coalition's rent goes back to its lead, the buyer
+    if !exits.is_empty() {
+        let coalition_info = ctx.accounts.coalition.to_account_info();
+        let rent = coalition_info.lamports();
+        **coalition_info.try_borrow_mut_lamports()? = 0;
+        **ctx.accounts.buyer.to_account_info().try_borrow_mut_lamports()? += rent;
+        coalition_info.assign(&System::id());
+        coalition_info.realloc(0, false)?;
+    }
+
+    // The bounty comes out of the state account's lamports; the rest goes to
+    // the buyer when the account closes
+    let bounty = if is_buyer {
+        0
+    } else {
+        CLOSE_BOUNTY
+            .saturating_add(crank_deposit)
+            .min(negotiation_info.lamports())
+    };
+    if bounty > 0 {
+        **negotiation_info.try_borrow_mut_lamports()? -= bounty;
+        **ctx.accounts.closer.to_account_info().try_borrow_mut_lamports()? += bounty;
+    }
+    crank::record(&mut ctx.accounts.config, bounty, 0)?;
+
+    for payout in payouts {
+        emit_cpi!(payout);
+    }
+    for exit in exits {
+        emit_cpi!(exit);
+    }
+    emit_cpi!(NegotiationClosed {
+        negotiation_id: ctx.accounts.negotiation.key(),
+        closed_by: ctx.accounts.closer.key(),
+        rent_reclaimed,
+        bounty,
+        timestamp: clock.unix_timestamp,
+    });
 
     Ok(())
 }
+
+fn load_coalition(info: &AccountInfo) -> Result<Option<BuyerCoalition>> {
+    if info.owner != &crate::ID || info.data_is_empty() {
+        return Ok(None);
+    }
+    let data = info.try_borrow_data()?;
+    Ok(Some(BuyerCoalition::try_deserialize(&mut &data[..])?))
+}
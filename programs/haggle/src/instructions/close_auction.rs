@@ -0,0 +1,93 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount};
+
+use crate::errors::HaggleError;
+use crate::events::AuctionClosed;
+use crate::settlement::Settlement;
+use crate::state::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CloseAuction<'info> {
+    #[account(mut)]
+    pub seller: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"auction", auction.seller.as_ref(), &auction.auction_id.to_le_bytes()],
+        bump = auction.bump,
+        constraint = auction.seller == seller.key() @ HaggleError::Unauthorized,
+        close = seller,
+    )]
+    pub auction: Account<'info, Auction>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", auction.key().as_ref()],
+        bump,
+    )]
+    pub escrow_vault: Account<'info, TokenAccount>,
+
+    /// Receives anything left in the vault
+    #[account(
+        mut,
+        constraint = seller_token_account.owner == auction.seller @ HaggleError::InvalidParams,
+        constraint = seller_token_account.mint == auction.token_mint @ HaggleError::InvalidParams,
+    )]
+    pub seller_token_account: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn handler(ctx: Context<CloseAuction>) -> Result<()> {
+    let auction = &ctx.accounts.auction;
+
+    // Can only close terminal states
+    require!(
+        auction.status == AuctionStatus::Settled || auction.status == AuctionStatus::Unsold,
+        HaggleError::InvalidState
+    );
+    // Sealed-bid deposits must be withdrawn first
+    require!(auction.open_bids == 0, HaggleError::InvalidState);
+
+    // Rent returned to the seller once both accounts are closed
+    let rent_reclaimed = auction
+        .to_account_info()
+        .lamports()
+        .checked_add(ctx.accounts.escrow_vault.to_account_info().lamports())
+        .ok_or(HaggleError::Overflow)?;
+
+    // Close the vault token account
+    let seller_key = auction.seller;
+    let auction_id_bytes = auction.auction_id.to_le_bytes();
+    let bump = auction.bump;
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        b"auction",
+        seller_key.as_ref(),
+        &auction_id_bytes,
+        &[bump],
+    ]];
+
+    // Tokens sent to the vault after settlement would otherwise keep it open
+    let settlement = Settlement {
+        token_program: ctx.accounts.token_program.to_account_info(),
+        vault: ctx.accounts.escrow_vault.to_account_info(),
+        vault_authority: ctx.accounts.auction.to_account_info(),
+        signer_seeds,
+    };
+    let payouts = settlement.sweep(ctx.accounts.seller_token_account.to_account_info())?;
+    settlement.close_vault(ctx.accounts.seller.to_account_info())?;
+
+    for payout in payouts {
+        emit_cpi!(payout);
+    }
+
+    emit_cpi!(AuctionClosed {
+        auction: ctx.accounts.auction.key(),
+        seller: ctx.accounts.seller.key(),
+        rent_reclaimed,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
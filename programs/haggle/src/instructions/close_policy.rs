@@ -0,0 +1,32 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::HaggleError;
+use crate::events::AcceptancePolicyClosed;
+use crate::state::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CloseAcceptancePolicy<'info> {
+    #[account(mut)]
+    pub seller: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"policy", seller.key().as_ref()],
+        bump = policy.bump,
+        constraint = policy.seller == seller.key() @ HaggleError::Unauthorized,
+        close = seller,
+    )]
+    pub policy: Box<Account<'info, AcceptancePolicy>>,
+}
+
+/// Removes the policy; the seller accepts any buyer again.
+pub fn handler(ctx: Context<CloseAcceptancePolicy>) -> Result<()> {
+    emit_cpi!(AcceptancePolicyClosed {
+        policy: ctx.accounts.policy.key(),
+        seller: ctx.accounts.seller.key(),
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,93 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount};
+
+use crate::errors::HaggleError;
+use crate::events::ReverseAuctionClosed;
+use crate::settlement::Settlement;
+use crate::state::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CloseReverseAuction<'info> {
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"reverse_auction", auction.buyer.as_ref(), &auction.auction_id.to_le_bytes()],
+        bump = auction.bump,
+        constraint = auction.buyer == buyer.key() @ HaggleError::Unauthorized,
+        close = buyer,
+    )]
+    pub auction: Account<'info, ReverseAuction>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", auction.key().as_ref()],
+        bump,
+    )]
+    pub escrow_vault: Account<'info, TokenAccount>,
+
+    /// Receives anything left in the vault
+    #[account(
+        mut,
+        constraint = buyer_token_account.owner == auction.buyer @ HaggleError::InvalidParams,
+        constraint = buyer_token_account.mint == auction.token_mint @ HaggleError::InvalidParams,
+    )]
+    pub buyer_token_account: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn handler(ctx: Context<CloseReverseAuction>) -> Result<()> {
+    let auction = &ctx.accounts.auction;
+
+    // Can only close terminal states
+    require!(
+        auction.status == AuctionStatus::Settled || auction.status == AuctionStatus::Unsold,
+        HaggleError::InvalidState
+    );
+    // Seller bonds must be withdrawn first
+    require!(auction.open_bonds == 0, HaggleError::InvalidState);
+
+    // Rent returned to the buyer once both accounts are closed
+    let rent_reclaimed = auction
+        .to_account_info()
+        .lamports()
+        .checked_add(ctx.accounts.escrow_vault.to_account_info().lamports())
+        .ok_or(HaggleError::Overflow)?;
+
+    // Close the vault token account
+    let buyer_key = auction.buyer;
+    let auction_id_bytes = auction.auction_id.to_le_bytes();
+    let bump = auction.bump;
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        b"reverse_auction",
+        buyer_key.as_ref(),
+        &auction_id_bytes,
+        &[bump],
+    ]];
+
+    // Tokens sent to the vault after settlement would otherwise keep it open
+    let settlement = Settlement {
+        token_program: ctx.accounts.token_program.to_account_info(),
+        vault: ctx.accounts.escrow_vault.to_account_info(),
+        vault_authority: ctx.accounts.auction.to_account_info(),
+        signer_seeds,
+    };
+    let payouts = settlement.sweep(ctx.accounts.buyer_token_account.to_account_info())?;
+    settlement.close_vault(ctx.accounts.buyer.to_account_info())?;
+
+    for payout in payouts {
+        emit_cpi!(payout);
+    }
+
+    emit_cpi!(ReverseAuctionClosed {
+        auction: ctx.accounts.auction.key(),
+        buyer: ctx.accounts.buyer.key(),
+        rent_reclaimed,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
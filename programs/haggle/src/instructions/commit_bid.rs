@@ -0,0 +1,101 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+
+use crate::errors::HaggleError;
+use crate::events::BidCommitted;
+use crate::state::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CommitBid<'info> {
+    #[account(mut)]
+    pub bidder: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"auction", auction.seller.as_ref(), &auction.auction_id.to_le_bytes()],
+        bump = auction.bump,
+    )]
+    pub auction: Box<Account<'info, Auction>>,
+
+    #[account(
+        init,
+        payer = bidder,
+        space = 8 + SealedBid::INIT_SPACE,
+        seeds = [b"sealed_bid", auction.key().as_ref(), bidder.key().as_ref()],
+        bump,
+    )]
+    pub sealed_bid: Box<Account<'info, SealedBid>>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", auction.key().as_ref()],
+        bump,
+        constraint = escrow_vault.mint == auction.token_mint @ HaggleError::InvalidParams,
+    )]
+    pub escrow_vault: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        constraint = bidder_token_account.owner == bidder.key() @ HaggleError::Unauthorized,
+        constraint = bidder_token_account.mint == auction.token_mint @ HaggleError::InvalidParams,
+    )]
+    pub bidder_token_account: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Commits a sealed bid to a Vickrey auction. The deposit is escrowed now and
+/// must cover the bid revealed later, so it bounds what the bid can be.
+pub fn handler(ctx: Context<CommitBid>, commitment: [u8; 32], deposit: u64) -> Result<()> {
+    let auction = &ctx.accounts.auction;
+    let clock = Clock::get()?;
+
+    // Validate state
+    require!(auction.kind == AuctionKind::Vickrey, HaggleError::WrongAuctionKind);
+    require!(auction.status == AuctionStatus::Open, HaggleError::InvalidState);
+    require!(clock.unix_timestamp < auction.commit_ends_at, HaggleError::Expired);
+    require!(ctx.accounts.bidder.key() != auction.seller, HaggleError::Unauthorized);
+    require!(deposit >= auction.start_price, HaggleError::OfferTooLow);
+
+    // Escrow the deposit
+    token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.bidder_token_account.to_account_info(),
+                to: ctx.accounts.escrow_vault.to_account_info(),
+                authority: ctx.accounts.bidder.to_account_info(),
+            },
+        ),
+        deposit,
+    )?;
+
+    let sealed_bid = &mut ctx.accounts.sealed_bid;
+    sealed_bid.auction = ctx.accounts.auction.key();
+    sealed_bid.bidder = ctx.accounts.bidder.key();
+    sealed_bid.commitment = commitment;
+    sealed_bid.deposit = deposit;
+    sealed_bid.amount = 0;
+    sealed_bid.revealed = false;
+    sealed_bid.bump = ctx.bumps.sealed_bid;
+
+    let auction = &mut ctx.accounts.auction;
+    auction.bid_count = auction.bid_count
+        .checked_add(1)
+        .ok_or(HaggleError::Overflow)?;
+    auction.open_bids = auction.open_bids
+        .checked_add(1)
+        .ok_or(HaggleError::Overflow)?;
+
+    emit_cpi!(BidCommitted {
+        auction: auction.key(),
+        bidder: sealed_bid.bidder,
+        commitment,
+        deposit,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
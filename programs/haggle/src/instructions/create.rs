@@ -1,10 +1,16 @@
 use anchor_lang::prelude::*;
 use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
 
+use crate::crank;
 use crate::errors::HaggleError;
 use crate::events::NegotiationCreated;
+use crate::instructions::accept_inv::open_first_round;
+use crate::instructions::offer::apply_offer;
+use crate::oracle;
+use crate::policy;
 use crate::state::*;
 
+#[event_cpi]
 #[derive(Accounts)]
 #[instruction(session_id: u64, params: NegotiationParams)]
 pub struct CreateNegotiation<'info> {
@@ -14,14 +20,18 @@ pub struct CreateNegotiation<'info> {
     /// CHECK: Seller pubkey, validated on accept
     pub seller: UncheckedAccount<'info>,
 
+    /// CHECK: Seller's `AcceptancePolicy`, which may not exist; read by `policy::admit`
+    #[account(mut, seeds = [b"policy", seller.key().as_ref()], bump)]
+    pub policy: UncheckedAccount<'info>,
+
     #[account(
         init,
         payer = buyer,
-        space = 8 + NegotiationState::INIT_SPACE,
+        space = NegotiationState::LEN,
         seeds = [b"negotiation", buyer.key().as_ref(), seller.key().as_ref(), &session_id.to_le_bytes()],
         bump,
     )]
-    pub negotiation: Box<Account<'info, NegotiationState>>,
+    pub negotiation: AccountLoader<'info, NegotiationState>,
 
     #[account(
         init,
@@ -42,6 +52,9 @@ pub struct CreateNegotiation<'info> {
 
     pub token_mint: Account<'info, Mint>,
 
+    /// CHECK: Pyth `PriceUpdateV2` for a quote-denominated opening offer; checked by `oracle::quote_price`
+    pub price_update: Option<UncheckedAccount<'info>>,
+
     #[account(
         mut,
         seeds = [b"config"],
@@ -54,82 +67,208 @@ pub struct CreateNegotiation<'info> {
     pub system_program: Program<'info, System>,
 }
 
-pub fn handler(ctx: Context<CreateNegotiation>, session_id: u64, params: NegotiationParams) -> Result<()> {
-    // Validate params
+pub fn handler(
+    ctx: Context<CreateNegotiation>,
+    session_id: u64,
+    params: NegotiationParams,
+    opening_offer: Option<OpeningOffer>,
+) -> Result<()> {
+    let clock = Clock::get()?;
+
+    // Prepay the crank reward before the state is borrowed
+    crank::prepay(
+        &ctx.accounts.negotiation.to_account_info(),
+        &ctx.accounts.buyer.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        &ctx.accounts.config,
+    )?;
+
+    // Initialize negotiation state
+    let negotiation_key = ctx.accounts.negotiation.key();
+    let mut negotiation = ctx.accounts.negotiation.load_init()?;
+    initialize(
+        &mut negotiation,
+        ctx.accounts.buyer.key(),
+        ctx.accounts.seller.key(),
+        session_id,
+        ctx.accounts.token_mint.key(),
+        ctx.accounts.token_mint.decimals,
+        &params,
+        Pubkey::default(),
+        &ctx.accounts.config,
+        ctx.bumps.negotiation,
+        &clock,
+    )?;
+
+    // Transfer escrow from buyer to vault
+    token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.buyer_token_account.to_account_info(),
+                to: ctx.accounts.escrow_vault.to_account_info(),
+                authority: ctx.accounts.buyer.to_account_info(),
+            },
+        ),
+        params.escrow_amount,
+    )?;
+
+    // Update config counter
+    let config = &mut ctx.accounts.config;
+    config.total_negotiations = config.total_negotiations
+        .checked_add(1)
+        .ok_or(HaggleError::Overflow)?;
+
+    // The seller's policy may turn the buyer away or accept on their behalf
+    let auto_accept = policy::admit(&ctx.accounts.policy, &mut negotiation)?;
+
+    emit_cpi!(created_event(negotiation_key, &negotiation, clock.unix_timestamp));
+
+    if let Some(offer) = opening_offer {
+        let buyer = negotiation.buyer;
+        let price = oracle::quote_price(&negotiation, ctx.accounts.price_update.as_deref(), clock.unix_timestamp)?;
+        emit_cpi!(apply_offer(&mut negotiation, negotiation_key, buyer, offer.amount, offer.metadata, false, price, clock.unix_timestamp)?);
+    }
+
+    // An auto-accepting policy opens the first round for the seller
+    if auto_accept {
+        emit_cpi!(open_first_round(&mut negotiation, negotiation_key, clock.unix_timestamp));
+    }
+
+    Ok(())
+}
+
+/// Checks every term except the escrow amount, which templates leave to
+/// each negotiation.
+pub(crate) fn validate_terms(params: &NegotiationParams) -> Result<()> {
     require!(params.max_rounds > 0 && params.max_rounds <= 20, HaggleError::InvalidParams);
     require!(params.decay_rate_bps <= 1000, HaggleError::InvalidParams); // max 10%
     require!(params.response_window >= 60, HaggleError::InvalidParams); // min 1 minute
     require!(params.global_deadline_offset >= 300, HaggleError::InvalidParams); // min 5 minutes
-    require!(params.escrow_amount >= 100_000, HaggleError::InvalidParams); // min ~$0.10
     require!(params.min_offer_bps >= 100 && params.min_offer_bps <= 10000, HaggleError::InvalidParams);
     require!(params.protocol_fee_bps <= 500, HaggleError::InvalidParams); // max 5%
+    Ok(())
+}
+
+/// Validates `params` and writes a fresh negotiation, with the crank reward
+/// terms `config` sets at the time. Shared by `create_negotiation` and
+/// `create_negotiation_from_template`.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn initialize(
+    negotiation: &mut NegotiationState,
+    buyer: Pubkey,
+    seller: Pubkey,
+    session_id: u64,
+    token_mint: Pubkey,
+    mint_decimals: u8,
+    params: &NegotiationParams,
+    template: Pubkey,
+    config: &ProtocolConfig,
+    bump: u8,
+    clock: &Clock,
+) -> Result<()> {
+    // Validate params
+    validate_terms(params)?;
+    require!(params.escrow_amount >= 100_000, HaggleError::InvalidParams); // min ~$0.10
+    require!(params.price_feed != Some([0u8; 32]), HaggleError::InvalidParams);
+    if let Some(payout) = &params.payout {
+        require!(
+            payout.mint != Pubkey::default() && payout.mint != token_mint,
+            HaggleError::InvalidParams
+        );
+        require!(payout.max_slippage_bps <= 10000, HaggleError::InvalidParams);
+    }
+    if let Some(ttl) = params.invitation_ttl {
+        require!(ttl >= 60 && ttl < params.global_deadline_offset, HaggleError::InvalidParams);
+    }
 
-    let clock = Clock::get()?;
     let global_deadline = clock.unix_timestamp
         .checked_add(params.global_deadline_offset)
         .ok_or(HaggleError::Overflow)?;
 
-    // Initialize negotiation state
-    let negotiation = &mut ctx.accounts.negotiation;
-    negotiation.buyer = ctx.accounts.buyer.key();
-    negotiation.seller = ctx.accounts.seller.key();
+    negotiation.buyer = buyer;
+    negotiation.seller = seller;
     negotiation.session_id = session_id;
-    negotiation.status = NegotiationStatus::Created;
+    negotiation.set_status(NegotiationStatus::Created);
     negotiation.current_round = 0;
     negotiation.current_offer_amount = 0;
     negotiation.current_offer_by = Pubkey::default();
-    negotiation.offer_side = OfferSide::Buyer;
+    negotiation.set_offer_side(OfferSide::Buyer);
     negotiation.service_hash = params.service_hash;
     negotiation.escrow_amount = params.escrow_amount;
     negotiation.effective_escrow = params.escrow_amount;
-    negotiation.token_mint = ctx.accounts.token_mint.key();
+    negotiation.token_mint = token_mint;
     negotiation.max_rounds = params.max_rounds;
     negotiation.decay_rate_bps = params.decay_rate_bps;
     negotiation.response_window = params.response_window;
     negotiation.global_deadline = global_deadline;
     negotiation.min_offer_bps = params.min_offer_bps;
     negotiation.protocol_fee_bps = params.protocol_fee_bps;
-    negotiation.zopa_enabled = params.zopa_enabled;
+    negotiation.zopa_enabled = params.zopa_enabled as u8;
     negotiation.created_at = clock.unix_timestamp;
     negotiation.last_offer_at = 0;
     negotiation.settled_at = 0;
     negotiation.settled_amount = 0;
     negotiation.buyer_commitment = [0u8; 32];
     negotiation.seller_commitment = [0u8; 32];
-    negotiation.zopa_phase = ZopaPhase::Skipped;
+    negotiation.set_zopa_phase(ZopaPhase::Skipped);
+    negotiation.price_feed = params.price_feed.unwrap_or_default();
+    negotiation.mint_decimals = mint_decimals;
+    let payout = params.payout.unwrap_or(PayoutTerms {
+        mint: Pubkey::default(),
+        swap_adapter: Pubkey::default(),
+        max_slippage_bps: 0,
+    });
+    negotiation.payout_mint = payout.mint;
+    negotiation.swap_adapter = payout.swap_adapter;
+    negotiation.max_slippage_bps = payout.max_slippage_bps;
+    negotiation.template = template;
+    negotiation.coalition = Pubkey::default();
     negotiation.metadata = [0u8; 64];
-    negotiation.bump = ctx.bumps.negotiation;
-
-    // Transfer escrow from buyer to vault
-    token::transfer(
-        CpiContext::new(
-            ctx.accounts.token_program.to_account_info(),
-            Transfer {
-                from: ctx.accounts.buyer_token_account.to_account_info(),
-                to: ctx.accounts.escrow_vault.to_account_info(),
-                authority: ctx.accounts.buyer.to_account_info(),
-            },
-        ),
-        params.escrow_amount,
-    )?;
+    negotiation.bump = bump;
+    negotiation.version = NegotiationState::VERSION;
+    negotiation.padding = [0u8; 7];
+    negotiation.crank_deposit = config.crank_reward_lamports;
+    negotiation.crank_reward_bps = config.crank_reward_bps;
+    negotiation.crank_padding = [0u8; 6];
+    negotiation.invitation_deadline = match params.invitation_ttl {
+        Some(ttl) => clock.unix_timestamp.checked_add(ttl).ok_or(HaggleError::Overflow)?,
+        None => 0,
+    };
+    negotiation.set_termination_reason(TerminationReason::Unspecified);
+    negotiation.final_offer = 0;
+    negotiation.admitted = 0;
+    negotiation.reserved = [0u8; 101];
 
-    // Update config counter
-    let config = &mut ctx.accounts.config;
-    config.total_negotiations = config.total_negotiations
-        .checked_add(1)
-        .ok_or(HaggleError::Overflow)?;
+    Ok(())
+}
 
-    emit!(NegotiationCreated {
-        negotiation_id: negotiation.key(),
+pub(crate) fn created_event(negotiation_id: Pubkey, negotiation: &NegotiationState, timestamp: i64) -> NegotiationCreated {
+    NegotiationCreated {
+        negotiation_id,
         buyer: negotiation.buyer,
         seller: negotiation.seller,
+        session_id: negotiation.session_id,
+        service_hash: negotiation.service_hash,
         escrow_amount: negotiation.escrow_amount,
         token_mint: negotiation.token_mint,
         max_rounds: negotiation.max_rounds,
         decay_rate_bps: negotiation.decay_rate_bps,
+        response_window: negotiation.response_window,
         global_deadline: negotiation.global_deadline,
-        timestamp: clock.unix_timestamp,
-    });
-
-    Ok(())
+        min_offer_bps: negotiation.min_offer_bps,
+        protocol_fee_bps: negotiation.protocol_fee_bps,
+        zopa_enabled: negotiation.zopa_enabled(),
+        price_feed: negotiation.price_feed,
+        mint_decimals: negotiation.mint_decimals,
+        payout_mint: negotiation.payout_mint,
+        swap_adapter: negotiation.swap_adapter,
+        max_slippage_bps: negotiation.max_slippage_bps,
+        template: negotiation.template,
+        crank_deposit: negotiation.crank_deposit,
+        crank_reward_bps: negotiation.crank_reward_bps,
+        admitted: negotiation.is_admitted(),
+        invitation_deadline: negotiation.invitation_deadline,
+        timestamp,
+    }
 }
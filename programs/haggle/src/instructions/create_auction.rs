@@ -0,0 +1,134 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+
+use crate::errors::HaggleError;
+use crate::events::AuctionCreated;
+use crate::state::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(auction_id: u64)]
+pub struct CreateAuction<'info> {
+    #[account(mut)]
+    pub seller: Signer<'info>,
+
+    #[account(
+        init,
+        payer = seller,
+        space = 8 + Auction::INIT_SPACE,
+        seeds = [b"auction", seller.key().as_ref(), &auction_id.to_le_bytes()],
+        bump,
+    )]
+    pub auction: Box<Account<'info, Auction>>,
+
+    #[account(
+        init,
+        payer = seller,
+        token::mint = token_mint,
+        token::authority = auction,
+        seeds = [b"vault", auction.key().as_ref()],
+        bump,
+    )]
+    pub escrow_vault: Box<Account<'info, TokenAccount>>,
+
+    pub token_mint: Account<'info, Mint>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.is_paused @ HaggleError::Paused,
+    )]
+    pub config: Box<Account<'info, ProtocolConfig>>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<CreateAuction>, auction_id: u64, params: AuctionParams) -> Result<()> {
+    // Validate params
+    require!(params.start_price >= 100_000, HaggleError::InvalidParams); // min ~$0.10
+    require!(params.duration >= 300, HaggleError::InvalidParams); // min 5 minutes
+    require!(params.protocol_fee_bps <= 500, HaggleError::InvalidParams); // max 5%
+    match params.kind {
+        AuctionKind::English => {
+            require!(params.min_increment_bps <= 5000, HaggleError::InvalidParams); // max 50%
+        }
+        AuctionKind::Dutch => {
+            require!(params.decay_rate_bps > 0 && params.decay_rate_bps <= 1000, HaggleError::InvalidParams); // max 10%
+            require!(params.decay_interval >= 60, HaggleError::InvalidParams); // min 1 minute
+            require!(params.floor_price <= params.start_price, HaggleError::InvalidParams);
+            require!(
+                params.duration / params.decay_interval <= Auction::MAX_DECAY_STEPS,
+                HaggleError::InvalidParams
+            );
+        }
+        AuctionKind::Vickrey => {
+            require!(params.commit_duration >= 60, HaggleError::InvalidParams); // min 1 minute
+            require!(params.duration - params.commit_duration >= 60, HaggleError::InvalidParams); // reveal window
+            require!(params.slash_bps <= 10000, HaggleError::InvalidParams);
+        }
+    }
+
+    let clock = Clock::get()?;
+    let ends_at = clock.unix_timestamp
+        .checked_add(params.duration)
+        .ok_or(HaggleError::Overflow)?;
+    let is_english = params.kind == AuctionKind::English;
+    let is_dutch = params.kind == AuctionKind::Dutch;
+    let is_vickrey = params.kind == AuctionKind::Vickrey;
+    let commit_ends_at = if is_vickrey {
+        clock.unix_timestamp
+            .checked_add(params.commit_duration)
+            .ok_or(HaggleError::Overflow)?
+    } else {
+        0
+    };
+
+    // Initialize auction state; parameters of the other kind are zeroed
+    let auction = &mut ctx.accounts.auction;
+    auction.seller = ctx.accounts.seller.key();
+    auction.auction_id = auction_id;
+    auction.token_mint = ctx.accounts.token_mint.key();
+    auction.kind = params.kind;
+    auction.status = AuctionStatus::Open;
+    auction.service_hash = params.service_hash;
+    auction.start_price = params.start_price;
+    auction.floor_price = if is_dutch { params.floor_price } else { 0 };
+    auction.min_increment_bps = if is_english { params.min_increment_bps } else { 0 };
+    auction.decay_rate_bps = if is_dutch { params.decay_rate_bps } else { 0 };
+    auction.decay_interval = if is_dutch { params.decay_interval } else { 0 };
+    auction.protocol_fee_bps = params.protocol_fee_bps;
+    auction.slash_bps = if is_vickrey { params.slash_bps } else { 0 };
+    auction.highest_bid = 0;
+    auction.highest_bidder = Pubkey::default();
+    auction.second_bid = 0;
+    auction.bid_count = 0;
+    auction.open_bids = 0;
+    auction.created_at = clock.unix_timestamp;
+    auction.commit_ends_at = commit_ends_at;
+    auction.ends_at = ends_at;
+    auction.settled_at = 0;
+    auction.settled_amount = 0;
+    auction.bump = ctx.bumps.auction;
+
+    emit_cpi!(AuctionCreated {
+        auction: auction.key(),
+        seller: auction.seller,
+        auction_id,
+        kind: auction.kind,
+        token_mint: auction.token_mint,
+        service_hash: auction.service_hash,
+        start_price: auction.start_price,
+        floor_price: auction.floor_price,
+        min_increment_bps: auction.min_increment_bps,
+        decay_rate_bps: auction.decay_rate_bps,
+        decay_interval: auction.decay_interval,
+        protocol_fee_bps: auction.protocol_fee_bps,
+        slash_bps: auction.slash_bps,
+        commit_ends_at,
+        ends_at,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
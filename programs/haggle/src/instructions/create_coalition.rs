@@ -0,0 +1,66 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::HaggleError;
+use crate::events::CoalitionCreated;
+use crate::migration::LoadCurrent;
+use crate::state::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CreateCoalition<'info> {
+    #[account(mut)]
+    pub lead: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"negotiation", negotiation.load_current()?.buyer.as_ref(), negotiation.load_current()?.seller.as_ref(), &negotiation.load_current()?.session_id.to_le_bytes()],
+        bump = negotiation.load_current()?.bump,
+        constraint = negotiation.load_current()?.buyer == lead.key() @ HaggleError::Unauthorized,
+    )]
+    pub negotiation: AccountLoader<'info, NegotiationState>,
+
+    #[account(
+        init,
+        payer = lead,
+        space = 8 + BuyerCoalition::INIT_SPACE,
+        seeds = [b"coalition", negotiation.key().as_ref()],
+        bump,
+    )]
+    pub coalition: Box<Account<'info, BuyerCoalition>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Opens the buyer side of a negotiation to other buyers. The lead's escrow
+/// becomes their share, and they keep negotiating on everyone's behalf.
+pub fn handler(ctx: Context<CreateCoalition>) -> Result<()> {
+    let mut negotiation = ctx.accounts.negotiation.load_current_mut()?;
+    let clock = Clock::get()?;
+
+    // Validate state - the pool can only form before the seller accepts
+    require!(negotiation.status()? == NegotiationStatus::Created, HaggleError::InvalidState);
+
+    let contribution = negotiation.escrow_amount;
+    let coalition = &mut ctx.accounts.coalition;
+    coalition.negotiation = ctx.accounts.negotiation.key();
+    coalition.lead = ctx.accounts.lead.key();
+    coalition.total_contributed = contribution;
+    coalition.fee_claimed = 0;
+    coalition.members = vec![CoalitionShare {
+        member: coalition.lead,
+        amount: contribution,
+    }];
+    coalition.bump = ctx.bumps.coalition;
+
+    negotiation.coalition = coalition.key();
+
+    emit_cpi!(CoalitionCreated {
+        coalition: coalition.key(),
+        negotiation: ctx.accounts.negotiation.key(),
+        lead: coalition.lead,
+        contribution,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,148 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+
+use crate::crank;
+use crate::errors::HaggleError;
+use crate::instructions::create::{created_event, initialize};
+use crate::instructions::accept_inv::open_first_round;
+use crate::instructions::offer::apply_offer;
+use crate::policy;
+use crate::state::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(session_id: u64)]
+pub struct CreateNegotiationFromTemplate<'info> {
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    /// CHECK: Seller pubkey, validated on accept
+    pub seller: UncheckedAccount<'info>,
+
+    /// CHECK: Seller's `AcceptancePolicy`, which may not exist; read by `policy::admit`
+    #[account(mut, seeds = [b"policy", seller.key().as_ref()], bump)]
+    pub policy: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [b"template", template.owner.as_ref(), &template.template_id.to_le_bytes()],
+        bump = template.bump,
+    )]
+    pub template: Box<Account<'info, NegotiationTemplate>>,
+
+    #[account(
+        init,
+        payer = buyer,
+        space = NegotiationState::LEN,
+        seeds = [b"negotiation", buyer.key().as_ref(), seller.key().as_ref(), &session_id.to_le_bytes()],
+        bump,
+    )]
+    pub negotiation: AccountLoader<'info, NegotiationState>,
+
+    #[account(
+        init,
+        payer = buyer,
+        token::mint = token_mint,
+        token::authority = negotiation,
+        seeds = [b"vault", negotiation.key().as_ref()],
+        bump,
+    )]
+    pub escrow_vault: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        constraint = buyer_token_account.owner == buyer.key() @ HaggleError::Unauthorized,
+        constraint = buyer_token_account.mint == token_mint.key() @ HaggleError::InvalidParams,
+    )]
+    pub buyer_token_account: Box<Account<'info, TokenAccount>>,
+
+    pub token_mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.is_paused @ HaggleError::Paused,
+    )]
+    pub config: Box<Account<'info, ProtocolConfig>>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Creates a negotiation on a registered template's terms; the buyer only
+/// chooses the escrow, the service and how long the invitation stays open.
+pub fn handler(
+    ctx: Context<CreateNegotiationFromTemplate>,
+    session_id: u64,
+    escrow_amount: u64,
+    service_hash: [u8; 32],
+    opening_offer: Option<OpeningOffer>,
+    invitation_ttl: Option<i64>,
+) -> Result<()> {
+    let template = &ctx.accounts.template;
+    require!(template.allows_mint(&ctx.accounts.token_mint.key()), HaggleError::InvalidParams);
+
+    let clock = Clock::get()?;
+    let params = template.negotiation_params(escrow_amount, service_hash, invitation_ttl);
+
+    // Prepay the crank reward before the state is borrowed
+    crank::prepay(
+        &ctx.accounts.negotiation.to_account_info(),
+        &ctx.accounts.buyer.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        &ctx.accounts.config,
+    )?;
+
+    // Initialize negotiation state
+    let negotiation_key = ctx.accounts.negotiation.key();
+    let mut negotiation = ctx.accounts.negotiation.load_init()?;
+    initialize(
+        &mut negotiation,
+        ctx.accounts.buyer.key(),
+        ctx.accounts.seller.key(),
+        session_id,
+        ctx.accounts.token_mint.key(),
+        ctx.accounts.token_mint.decimals,
+        &params,
+        template.key(),
+        &ctx.accounts.config,
+        ctx.bumps.negotiation,
+        &clock,
+    )?;
+
+    // Transfer escrow from buyer to vault
+    token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.buyer_token_account.to_account_info(),
+                to: ctx.accounts.escrow_vault.to_account_info(),
+                authority: ctx.accounts.buyer.to_account_info(),
+            },
+        ),
+        escrow_amount,
+    )?;
+
+    // Update config counter
+    let config = &mut ctx.accounts.config;
+    config.total_negotiations = config.total_negotiations
+        .checked_add(1)
+        .ok_or(HaggleError::Overflow)?;
+
+    // The seller's policy may turn the buyer away or accept on their behalf
+    let auto_accept = policy::admit(&ctx.accounts.policy, &mut negotiation)?;
+
+    emit_cpi!(created_event(negotiation_key, &negotiation, clock.unix_timestamp));
+
+    if let Some(offer) = opening_offer {
+        let buyer = negotiation.buyer;
+        emit_cpi!(apply_offer(&mut negotiation, negotiation_key, buyer, offer.amount, offer.metadata, false, None, clock.unix_timestamp)?);
+    }
+
+    // An auto-accepting policy opens the first round for the seller
+    if auto_accept {
+        emit_cpi!(open_first_round(&mut negotiation, negotiation_key, clock.unix_timestamp));
+    }
+
+    Ok(())
+}
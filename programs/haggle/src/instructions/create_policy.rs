@@ -0,0 +1,67 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::HaggleError;
+use crate::events::AcceptancePolicySet;
+use crate::state::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CreateAcceptancePolicy<'info> {
+    #[account(mut)]
+    pub seller: Signer<'info>,
+
+    #[account(
+        init,
+        payer = seller,
+        space = 8 + AcceptancePolicy::INIT_SPACE,
+        seeds = [b"policy", seller.key().as_ref()],
+        bump,
+    )]
+    pub policy: Box<Account<'info, AcceptancePolicy>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<CreateAcceptancePolicy>, params: PolicyParams) -> Result<()> {
+    let policy = &mut ctx.accounts.policy;
+    policy.seller = ctx.accounts.seller.key();
+    policy.open_negotiations = 0;
+    policy.bump = ctx.bumps.policy;
+    apply(policy, params)?;
+
+    emit_cpi!(set_event(policy, Clock::get()?.unix_timestamp));
+
+    Ok(())
+}
+
+/// Writes `params` over the policy's terms, keeping its open count.
+pub(crate) fn apply(policy: &mut AcceptancePolicy, params: PolicyParams) -> Result<()> {
+    require!(params.buyers.len() <= MAX_POLICY_BUYERS, HaggleError::InvalidParams);
+    require!(params.accepted_mints.len() <= MAX_POLICY_MINTS, HaggleError::InvalidParams);
+
+    policy.buyer_filter = params.buyer_filter;
+    policy.buyers = params.buyers;
+    policy.min_escrow = params.min_escrow;
+    policy.accepted_mints = params.accepted_mints;
+    policy.max_open_negotiations = params.max_open_negotiations;
+    policy.auto_accept = params.auto_accept;
+    policy.required_template = params.required_template.unwrap_or_default();
+
+    Ok(())
+}
+
+pub(crate) fn set_event(policy: &Account<AcceptancePolicy>, timestamp: i64) -> AcceptancePolicySet {
+    AcceptancePolicySet {
+        policy: policy.key(),
+        seller: policy.seller,
+        buyer_filter: policy.buyer_filter,
+        buyers: policy.buyers.clone(),
+        min_escrow: policy.min_escrow,
+        accepted_mints: policy.accepted_mints.clone(),
+        max_open_negotiations: policy.max_open_negotiations,
+        open_negotiations: policy.open_negotiations,
+        auto_accept: policy.auto_accept,
+        required_template: policy.required_template,
+        timestamp,
+    }
+}
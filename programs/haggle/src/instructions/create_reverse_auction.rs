@@ -0,0 +1,119 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+
+use crate::errors::HaggleError;
+use crate::events::ReverseAuctionCreated;
+use crate::state::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(auction_id: u64)]
+pub struct CreateReverseAuction<'info> {
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    #[account(
+        init,
+        payer = buyer,
+        space = 8 + ReverseAuction::INIT_SPACE,
+        seeds = [b"reverse_auction", buyer.key().as_ref(), &auction_id.to_le_bytes()],
+        bump,
+    )]
+    pub auction: Box<Account<'info, ReverseAuction>>,
+
+    #[account(
+        init,
+        payer = buyer,
+        token::mint = token_mint,
+        token::authority = auction,
+        seeds = [b"vault", auction.key().as_ref()],
+        bump,
+    )]
+    pub escrow_vault: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        constraint = buyer_token_account.owner == buyer.key() @ HaggleError::Unauthorized,
+        constraint = buyer_token_account.mint == token_mint.key() @ HaggleError::InvalidParams,
+    )]
+    pub buyer_token_account: Box<Account<'info, TokenAccount>>,
+
+    pub token_mint: Account<'info, Mint>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.is_paused @ HaggleError::Paused,
+    )]
+    pub config: Box<Account<'info, ProtocolConfig>>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(
+    ctx: Context<CreateReverseAuction>,
+    auction_id: u64,
+    params: ReverseAuctionParams,
+) -> Result<()> {
+    // Validate params
+    require!(params.ceiling >= 100_000, HaggleError::InvalidParams); // min ~$0.10
+    require!(params.min_decrement_bps <= 5000, HaggleError::InvalidParams); // max 50%
+    require!(params.duration >= 300, HaggleError::InvalidParams); // min 5 minutes
+    require!(params.protocol_fee_bps <= 500, HaggleError::InvalidParams); // max 5%
+
+    let clock = Clock::get()?;
+    let ends_at = clock.unix_timestamp
+        .checked_add(params.duration)
+        .ok_or(HaggleError::Overflow)?;
+
+    // Initialize auction state
+    let auction = &mut ctx.accounts.auction;
+    auction.buyer = ctx.accounts.buyer.key();
+    auction.auction_id = auction_id;
+    auction.token_mint = ctx.accounts.token_mint.key();
+    auction.status = AuctionStatus::Open;
+    auction.service_hash = params.service_hash;
+    auction.ceiling = params.ceiling;
+    auction.min_decrement_bps = params.min_decrement_bps;
+    auction.seller_bond = params.seller_bond;
+    auction.protocol_fee_bps = params.protocol_fee_bps;
+    auction.open_bonds = 0;
+    auction.lowest_bid = 0;
+    auction.lowest_bidder = Pubkey::default();
+    auction.bid_count = 0;
+    auction.created_at = clock.unix_timestamp;
+    auction.ends_at = ends_at;
+    auction.settled_at = 0;
+    auction.settled_amount = 0;
+    auction.bump = ctx.bumps.auction;
+
+    // Escrow the ceiling
+    token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.buyer_token_account.to_account_info(),
+                to: ctx.accounts.escrow_vault.to_account_info(),
+                authority: ctx.accounts.buyer.to_account_info(),
+            },
+        ),
+        params.ceiling,
+    )?;
+
+    emit_cpi!(ReverseAuctionCreated {
+        auction: auction.key(),
+        buyer: auction.buyer,
+        auction_id,
+        token_mint: auction.token_mint,
+        service_hash: auction.service_hash,
+        ceiling: auction.ceiling,
+        min_decrement_bps: auction.min_decrement_bps,
+        seller_bond: auction.seller_bond,
+        protocol_fee_bps: auction.protocol_fee_bps,
+        ends_at,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
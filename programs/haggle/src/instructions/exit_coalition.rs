@@ -0,0 +1,178 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount};
+
+use crate::errors::HaggleError;
+use crate::events::CoalitionExited;
+use crate::migration::LoadCurrent;
+use crate::settlement::{NegotiationSigner, PayoutPlan, PayoutReason, Settlement};
+use crate::state::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ExitCoalition<'info> {
+    pub member: Signer<'info>,
+
+    /// CHECK: Receives the coalition account's rent when the last member exits
+    #[account(mut, address = coalition.lead @ HaggleError::InvalidParams)]
+    pub lead: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"negotiation", negotiation.load_current()?.buyer.as_ref(), negotiation.load_current()?.seller.as_ref(), &negotiation.load_current()?.session_id.to_le_bytes()],
+        bump = negotiation.load_current()?.bump,
+    )]
+    pub negotiation: AccountLoader<'info, NegotiationState>,
+
+    #[account(
+        mut,
+        seeds = [b"coalition", negotiation.key().as_ref()],
+        bump = coalition.bump,
+    )]
+    pub coalition: Box<Account<'info, BuyerCoalition>>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", negotiation.key().as_ref()],
+        bump,
+        constraint = escrow_vault.mint == negotiation.load_current()?.token_mint @ HaggleError::InvalidParams,
+    )]
+    pub escrow_vault: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        constraint = member_token_account.owner == member.key() @ HaggleError::Unauthorized,
+        constraint = member_token_account.mint == negotiation.load_current()?.token_mint @ HaggleError::InvalidParams,
+    )]
+    pub member_token_account: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Takes a member out of the pool. Before the seller accepts, the whole share
+/// comes back and the escrow shrinks by it, as long as it still covers the
+/// buyer's opening offer; once the negotiation has ended, the member is paid
+/// their pro rata part of what the vault refunded.
+pub fn handler(ctx: Context<ExitCoalition>) -> Result<()> {
+    let negotiation = ctx.accounts.negotiation.load_current()?;
+    let coalition = &ctx.accounts.coalition;
+    let clock = Clock::get()?;
+
+    let index = coalition
+        .share_of(&ctx.accounts.member.key())
+        .ok_or(HaggleError::Unauthorized)?;
+    let share = coalition.members[index].amount;
+
+    let status = negotiation.status()?;
+    let (refund_amount, fee_share) = match status {
+        NegotiationStatus::Created => {
+            // Any decay from an opening offer stays, and the offer must
+            // still be covered
+            let effective_escrow = negotiation.effective_escrow
+                .checked_sub(share)
+                .ok_or(HaggleError::OfferExceedsEscrow)?;
+            require!(
+                effective_escrow >= negotiation.current_offer_amount,
+                HaggleError::OfferExceedsEscrow
+            );
+            (share, 0)
+        }
+        NegotiationStatus::Settled
+        | NegotiationStatus::Rejected
+        | NegotiationStatus::Expired => {
+            ended_share(coalition, &negotiation, ctx.accounts.escrow_vault.amount, share)?
+        }
+        _ => return err!(HaggleError::InvalidState),
+    };
+
+    let signer = NegotiationSigner::new(&negotiation);
+    // The negotiation signs for the vault; release it before the CPI
+    drop(negotiation);
+
+    // The other members' shares stay in the vault
+    let mut plan = PayoutPlan::new(ctx.accounts.escrow_vault.amount);
+    plan.pay(ctx.accounts.member_token_account.to_account_info(), refund_amount, PayoutReason::Refund)?;
+    plan.retain_rest();
+    let seeds = signer.seeds();
+    let payouts = Settlement {
+        token_program: ctx.accounts.token_program.to_account_info(),
+        vault: ctx.accounts.escrow_vault.to_account_info(),
+        vault_authority: ctx.accounts.negotiation.to_account_info(),
+        signer_seeds: &[&seeds],
+    }
+    .payout(&plan)?;
+    for payout in payouts {
+        emit_cpi!(payout);
+    }
+
+    let mut negotiation = ctx.accounts.negotiation.load_current_mut()?;
+    if status == NegotiationStatus::Created {
+        negotiation.escrow_amount = negotiation.escrow_amount
+            .checked_sub(share)
+            .ok_or(HaggleError::Overflow)?;
+        negotiation.effective_escrow = negotiation.effective_escrow
+            .checked_sub(share)
+            .ok_or(HaggleError::Overflow)?;
+    }
+
+    let coalition = &mut ctx.accounts.coalition;
+    coalition.members.swap_remove(index);
+    coalition.total_contributed = coalition.total_contributed
+        .checked_sub(share)
+        .ok_or(HaggleError::Overflow)?;
+    coalition.fee_claimed = coalition.fee_claimed
+        .checked_add(fee_share)
+        .ok_or(HaggleError::Overflow)?;
+
+    emit_cpi!(CoalitionExited {
+        coalition: coalition.key(),
+        negotiation: ctx.accounts.negotiation.key(),
+        member: ctx.accounts.member.key(),
+        share,
+        refund_amount,
+        fee_share,
+        escrow_amount: negotiation.escrow_amount,
+        timestamp: clock.unix_timestamp,
+    });
+
+    // The pool dissolves once every share is out
+    if coalition.members.is_empty() {
+        coalition.close(ctx.accounts.lead.to_account_info())?;
+    }
+
+    Ok(())
+}
+
+/// A member's part of an ended negotiation: `share / total_contributed` of
+/// what the vault still holds and of the protocol fee not yet claimed. Both
+/// shrink with every exit, so the last member out takes any rounding.
+pub(crate) fn ended_share(
+    coalition: &BuyerCoalition,
+    negotiation: &NegotiationState,
+    vault_amount: u64,
+    share: u64,
+) -> Result<(u64, u64)> {
+    let refund_amount = pro_rata(vault_amount, share, coalition.total_contributed)?;
+    let protocol_fee = negotiation.settled_amount
+        .checked_mul(negotiation.protocol_fee_bps as u64)
+        .ok_or(HaggleError::Overflow)?
+        .checked_div(10000)
+        .ok_or(HaggleError::Overflow)?;
+    let unclaimed_fee = protocol_fee
+        .checked_sub(coalition.fee_claimed)
+        .ok_or(HaggleError::Overflow)?;
+    let fee_share = pro_rata(unclaimed_fee, share, coalition.total_contributed)?;
+    Ok((refund_amount, fee_share))
+}
+
+/// `amount * share / total`, rounded down.
+fn pro_rata(amount: u64, share: u64, total: u64) -> Result<u64> {
+    if total == 0 {
+        return Ok(0);
+    }
+    let value = (amount as u128)
+        .checked_mul(share as u128)
+        .ok_or(HaggleError::Overflow)?
+        .checked_div(total as u128)
+        .ok_or(HaggleError::Overflow)?;
+    u64::try_from(value).map_err(|_| error!(HaggleError::Overflow))
+}
@@ -1,93 +1,149 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use anchor_spl::token::{Token, TokenAccount};
 
+use crate::crank;
 use crate::errors::HaggleError;
 use crate::events::NegotiationExpired;
+use crate::migration::LoadCurrent;
+use crate::policy;
+use crate::settlement::{negotiation_plan, CrankReward, NegotiationSigner, Outcome, PayoutReason, Settlement};
 use crate::state::*;
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct ExpireNegotiation<'info> {
-    /// CHECK: Anyone can crank expiry (permissionless)
+    /// Anyone can crank expiry (permissionless), earning the crank reward
+    #[account(mut)]
     pub cranker: Signer<'info>,
 
     #[account(
         mut,
-        seeds = [b"negotiation", negotiation.buyer.as_ref(), negotiation.seller.as_ref(), &negotiation.session_id.to_le_bytes()],
-        bump = negotiation.bump,
+        seeds = [b"negotiation", negotiation.load_current()?.buyer.as_ref(), negotiation.load_current()?.seller.as_ref(), &negotiation.load_current()?.session_id.to_le_bytes()],
+        bump = negotiation.load_current()?.bump,
     )]
-    pub negotiation: Account<'info, NegotiationState>,
+    pub negotiation: AccountLoader<'info, NegotiationState>,
 
     #[account(
         mut,
         seeds = [b"vault", negotiation.key().as_ref()],
         bump,
-        constraint = escrow_vault.mint == negotiation.token_mint @ HaggleError::InvalidParams,
+        constraint = escrow_vault.mint == negotiation.load_current()?.token_mint @ HaggleError::InvalidParams,
     )]
     pub escrow_vault: Account<'info, TokenAccount>,
 
     #[account(
         mut,
-        constraint = buyer_token_account.owner == negotiation.buyer @ HaggleError::InvalidParams,
-        constraint = buyer_token_account.mint == negotiation.token_mint @ HaggleError::InvalidParams,
+        constraint = buyer_token_account.owner == negotiation.load_current()?.buyer @ HaggleError::InvalidParams,
+        constraint = buyer_token_account.mint == negotiation.load_current()?.token_mint @ HaggleError::InvalidParams,
     )]
     pub buyer_token_account: Account<'info, TokenAccount>,
 
+    /// Receives the escrow lost to decay
+    #[account(
+        mut,
+        constraint = treasury_token_account.owner == config.treasury @ HaggleError::InvalidParams,
+        constraint = treasury_token_account.mint == negotiation.load_current()?.token_mint @ HaggleError::InvalidParams,
+    )]
+    pub treasury_token_account: Account<'info, TokenAccount>,
+
+    /// Receives the cranker's share of the refund; without it the share
+    /// stays with the buyer
+    #[account(
+        mut,
+        constraint = cranker_token_account.owner == cranker.key() @ HaggleError::InvalidParams,
+        constraint = cranker_token_account.mint == negotiation.load_current()?.token_mint @ HaggleError::InvalidParams,
+    )]
+    pub cranker_token_account: Option<Account<'info, TokenAccount>>,
+
+    #[account(mut, seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProtocolConfig>,
+
+    /// CHECK: Seller's `AcceptancePolicy`, which may not exist; read by `policy::release`
+    #[account(mut, seeds = [b"policy", negotiation.load_current()?.seller.as_ref()], bump)]
+    pub policy: UncheckedAccount<'info>,
+
     pub token_program: Program<'info, Token>,
 }
 
 pub fn handler(ctx: Context<ExpireNegotiation>) -> Result<()> {
-    let negotiation = &ctx.accounts.negotiation;
+    let negotiation = ctx.accounts.negotiation.load_current()?;
     let clock = Clock::get()?;
 
     // Validate state - can expire from Created, Proposed, or Countered
     require!(
-        negotiation.status == NegotiationStatus::Created
-            || negotiation.status == NegotiationStatus::Proposed
-            || negotiation.status == NegotiationStatus::Countered,
+        negotiation.status()? == NegotiationStatus::Created
+            || negotiation.status()? == NegotiationStatus::Proposed
+            || negotiation.status()? == NegotiationStatus::Countered,
         HaggleError::InvalidState
     );
 
-    // Validate deadline has passed
-    require!(clock.unix_timestamp >= negotiation.global_deadline, HaggleError::InvalidState);
-
-    // Refund remaining escrow to buyer
-    let refund_amount = ctx.accounts.escrow_vault.amount;
-
-    let buyer_key = negotiation.buyer;
-    let seller_key = negotiation.seller;
-    let session_bytes = negotiation.session_id.to_le_bytes();
-    let bump = negotiation.bump;
-    let signer_seeds: &[&[&[u8]]] = &[&[
-        b"negotiation",
-        buyer_key.as_ref(),
-        seller_key.as_ref(),
-        &session_bytes,
-        &[bump],
-    ]];
-
-    if refund_amount > 0 {
-        token::transfer(
-            CpiContext::new_with_signer(
-                ctx.accounts.token_program.to_account_info(),
-                Transfer {
-                    from: ctx.accounts.escrow_vault.to_account_info(),
-                    to: ctx.accounts.buyer_token_account.to_account_info(),
-                    authority: ctx.accounts.negotiation.to_account_info(),
-                },
-                signer_seeds,
-            ),
-            refund_amount,
-        )?;
-    }
+    // Validate deadline has passed, or the invitation lapsed unaccepted
+    let (expires_at, reason) = negotiation.expiry()?;
+    require!(clock.unix_timestamp >= expires_at, HaggleError::InvalidState);
 
+    let signer = NegotiationSigner::new(&negotiation);
+    let escrow_decay = negotiation.escrow_amount
+        .checked_sub(negotiation.effective_escrow)
+        .ok_or(HaggleError::Overflow)?;
+    let is_pooled = negotiation.is_pooled();
     let rounds_completed = negotiation.current_round;
-    let negotiation = &mut ctx.accounts.negotiation;
-    negotiation.status = NegotiationStatus::Expired;
+    let crank_deposit = negotiation.crank_deposit;
+    let crank_reward = ctx.accounts.cranker_token_account.as_ref().map(|account| CrankReward {
+        cranker: account.to_account_info(),
+        bps: negotiation.crank_reward_bps,
+    });
+    // The negotiation signs for the vault, so the borrow must end before the
+    // token program is invoked
+    drop(negotiation);
+
+    // The escrow lost to decay goes to the treasury, the cranker's share to
+    // the cranker and the rest back to the buyer (coalition members claim
+    // their refunds from the vault)
+    let plan = negotiation_plan(
+        ctx.accounts.escrow_vault.amount,
+        Outcome::Terminated { escrow_decay, crank_reward },
+        is_pooled,
+        ctx.accounts.buyer_token_account.to_account_info(),
+        ctx.accounts.treasury_token_account.to_account_info(),
+    )?;
+    // Refunded to the buyer, or left for the coalition members
+    let refund_amount = plan.vault_balance()
+        - plan.paid(PayoutReason::Decay)
+        - plan.paid(PayoutReason::CrankReward);
+    let seeds = signer.seeds();
+    let payouts = Settlement {
+        token_program: ctx.accounts.token_program.to_account_info(),
+        vault: ctx.accounts.escrow_vault.to_account_info(),
+        vault_authority: ctx.accounts.negotiation.to_account_info(),
+        signer_seeds: &[&seeds],
+    }
+    .payout(&plan)?;
+
+    // The prepaid deposit is paid once, here or on a permissionless close
+    crank::pay(
+        &ctx.accounts.negotiation.to_account_info(),
+        &ctx.accounts.cranker.to_account_info(),
+        crank_deposit,
+    )?;
+    crank::record(&mut ctx.accounts.config, crank_deposit, plan.paid(PayoutReason::CrankReward))?;
+
+    let mut negotiation = ctx.accounts.negotiation.load_current_mut()?;
+    negotiation.set_status(NegotiationStatus::Expired);
+    negotiation.set_termination_reason(reason);
+    negotiation.crank_deposit = 0;
+    policy::release(&ctx.accounts.policy, &mut negotiation)?;
+    drop(negotiation);
+
+    for payout in payouts {
+        emit_cpi!(payout);
+    }
 
-    emit!(NegotiationExpired {
+    emit_cpi!(NegotiationExpired {
         negotiation_id: ctx.accounts.negotiation.key(),
         refund_amount,
         rounds_completed,
+        crank_reward: crank_deposit,
+        reason,
         timestamp: clock.unix_timestamp,
     });
 
@@ -0,0 +1,111 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+
+use crate::errors::HaggleError;
+use crate::events::CoalitionJoined;
+use crate::migration::LoadCurrent;
+use crate::state::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct JoinCoalition<'info> {
+    pub member: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"negotiation", negotiation.load_current()?.buyer.as_ref(), negotiation.load_current()?.seller.as_ref(), &negotiation.load_current()?.session_id.to_le_bytes()],
+        bump = negotiation.load_current()?.bump,
+    )]
+    pub negotiation: AccountLoader<'info, NegotiationState>,
+
+    #[account(
+        mut,
+        seeds = [b"coalition", negotiation.key().as_ref()],
+        bump = coalition.bump,
+    )]
+    pub coalition: Box<Account<'info, BuyerCoalition>>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", negotiation.key().as_ref()],
+        bump,
+        constraint = escrow_vault.mint == negotiation.load_current()?.token_mint @ HaggleError::InvalidParams,
+    )]
+    pub escrow_vault: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        constraint = member_token_account.owner == member.key() @ HaggleError::Unauthorized,
+        constraint = member_token_account.mint == negotiation.load_current()?.token_mint @ HaggleError::InvalidParams,
+    )]
+    pub member_token_account: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Adds `amount` to the pooled escrow, creating or topping up the member's
+/// share.
+pub fn handler(ctx: Context<JoinCoalition>, amount: u64) -> Result<()> {
+    let mut negotiation = ctx.accounts.negotiation.load_current_mut()?;
+    let clock = Clock::get()?;
+
+    // Validate state
+    require!(negotiation.status()? == NegotiationStatus::Created, HaggleError::InvalidState);
+    require!(clock.unix_timestamp < negotiation.global_deadline, HaggleError::Expired);
+    require!(ctx.accounts.member.key() != negotiation.seller, HaggleError::Unauthorized);
+    require!(amount > 0, HaggleError::InvalidParams);
+
+    token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.member_token_account.to_account_info(),
+                to: ctx.accounts.escrow_vault.to_account_info(),
+                authority: ctx.accounts.member.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+
+    let member = ctx.accounts.member.key();
+    let coalition = &mut ctx.accounts.coalition;
+    let index = match coalition.share_of(&member) {
+        Some(index) => index,
+        None => {
+            require!(
+                coalition.members.len() < MAX_COALITION_MEMBERS,
+                HaggleError::CoalitionFull
+            );
+            coalition.members.push(CoalitionShare { member, amount: 0 });
+            coalition.members.len() - 1
+        }
+    };
+    let share = &mut coalition.members[index];
+    share.amount = share.amount
+        .checked_add(amount)
+        .ok_or(HaggleError::Overflow)?;
+    let share = share.amount;
+    coalition.total_contributed = coalition.total_contributed
+        .checked_add(amount)
+        .ok_or(HaggleError::Overflow)?;
+
+    // An opening offer may already have decayed the escrow; keep that decay
+    negotiation.escrow_amount = negotiation.escrow_amount
+        .checked_add(amount)
+        .ok_or(HaggleError::Overflow)?;
+    negotiation.effective_escrow = negotiation.effective_escrow
+        .checked_add(amount)
+        .ok_or(HaggleError::Overflow)?;
+
+    emit_cpi!(CoalitionJoined {
+        coalition: coalition.key(),
+        negotiation: ctx.accounts.negotiation.key(),
+        member,
+        amount,
+        share,
+        escrow_amount: negotiation.escrow_amount,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,47 @@
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+
+use crate::errors::HaggleError;
+use crate::migration::{self, ProtocolConfigV0};
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct MigrateConfig<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// CHECK: Decoded by layout version in the handler
+    #[account(mut, seeds = [b"config"], bump)]
+    pub config: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<MigrateConfig>) -> Result<()> {
+    let info = ctx.accounts.config.to_account_info();
+
+    let config = {
+        let data = info.try_borrow_data()?;
+        require!(
+            data.len() >= 8 && data[..8] == ProtocolConfig::DISCRIMINATOR,
+            ErrorCode::AccountDiscriminatorMismatch
+        );
+        require!(data.len() == ProtocolConfigV0::LEN, HaggleError::AlreadyMigrated);
+        ProtocolConfigV0::deserialize(&mut &data[8..])?.upgrade()
+    };
+    require_keys_eq!(
+        config.authority,
+        ctx.accounts.authority.key(),
+        HaggleError::Unauthorized
+    );
+
+    migration::resize(
+        &info,
+        &ctx.accounts.authority.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        8 + ProtocolConfig::INIT_SPACE,
+    )?;
+    config.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+
+    Ok(())
+}
@@ -0,0 +1,46 @@
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+
+use crate::errors::HaggleError;
+use crate::migration::{self, NegotiationStateV0, NegotiationStateV1};
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct MigrateNegotiation<'info> {
+    /// Anyone can migrate a negotiation (permissionless); pays the extra rent
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: Decoded by layout version in the handler
+    #[account(mut, owner = crate::ID)]
+    pub negotiation: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<MigrateNegotiation>) -> Result<()> {
+    let info = ctx.accounts.negotiation.to_account_info();
+
+    let negotiation = {
+        let data = info.try_borrow_data()?;
+        require!(
+            data.len() >= 8 && data[..8] == NegotiationState::DISCRIMINATOR,
+            ErrorCode::AccountDiscriminatorMismatch
+        );
+        match data.len() {
+            NegotiationStateV0::LEN => NegotiationStateV0::deserialize(&mut &data[8..])?.upgrade().upgrade(),
+            NegotiationStateV1::LEN => NegotiationStateV1::deserialize(&mut &data[8..])?.upgrade(),
+            _ => return err!(HaggleError::AlreadyMigrated),
+        }
+    };
+
+    migration::resize(
+        &info,
+        &ctx.accounts.payer.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        NegotiationState::LEN,
+    )?;
+    info.try_borrow_mut_data()?[8..].copy_from_slice(bytemuck::bytes_of(&negotiation));
+
+    Ok(())
+}
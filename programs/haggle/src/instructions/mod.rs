@@ -1,3 +1,5 @@
+#![allow(ambiguous_glob_reexports)]
+
 pub mod create;
 pub mod accept_inv;
 pub mod offer;
@@ -5,6 +7,32 @@ pub mod accept;
 pub mod reject;
 pub mod expire;
 pub mod close;
+pub mod update_config;
+pub mod create_auction;
+pub mod place_bid;
+pub mod buy_auction;
+pub mod settle_auction;
+pub mod close_auction;
+pub mod commit_bid;
+pub mod reveal_bid;
+pub mod withdraw_bid;
+pub mod create_reverse_auction;
+pub mod post_bond;
+pub mod place_reverse_bid;
+pub mod settle_reverse_auction;
+pub mod withdraw_bond;
+pub mod close_reverse_auction;
+pub mod create_coalition;
+pub mod join_coalition;
+pub mod exit_coalition;
+pub mod register_template;
+pub mod create_from_template;
+pub mod create_policy;
+pub mod update_policy;
+pub mod close_policy;
+pub mod migrate_negotiation;
+pub mod migrate_config;
+pub mod cancel_inv;
 
 pub use create::*;
 pub use accept_inv::*;
@@ -13,3 +41,29 @@ pub use accept::*;
 pub use reject::*;
 pub use expire::*;
 pub use close::*;
+pub use update_config::*;
+pub use create_auction::*;
+pub use place_bid::*;
+pub use buy_auction::*;
+pub use settle_auction::*;
+pub use close_auction::*;
+pub use commit_bid::*;
+pub use reveal_bid::*;
+pub use withdraw_bid::*;
+pub use create_reverse_auction::*;
+pub use post_bond::*;
+pub use place_reverse_bid::*;
+pub use settle_reverse_auction::*;
+pub use withdraw_bond::*;
+pub use close_reverse_auction::*;
+pub use create_coalition::*;
+pub use join_coalition::*;
+pub use exit_coalition::*;
+pub use register_template::*;
+pub use create_from_template::*;
+pub use create_policy::*;
+pub use update_policy::*;
+pub use close_policy::*;
+pub use migrate_negotiation::*;
+pub use migrate_config::*;
+pub use cancel_inv::*;
@@ -2,39 +2,80 @@ use anchor_lang::prelude::*;
 
 use crate::errors::HaggleError;
 use crate::events::OfferSubmitted;
+use crate::migration::LoadCurrent;
+use crate::oracle::{self, Price};
 use crate::state::*;
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct SubmitOffer<'info> {
     pub offerer: Signer<'info>,
 
     #[account(
         mut,
-        seeds = [b"negotiation", negotiation.buyer.as_ref(), negotiation.seller.as_ref(), &negotiation.session_id.to_le_bytes()],
-        bump = negotiation.bump,
+        seeds = [b"negotiation", negotiation.load_current()?.buyer.as_ref(), negotiation.load_current()?.seller.as_ref(), &negotiation.load_current()?.session_id.to_le_bytes()],
+        bump = negotiation.load_current()?.bump,
     )]
-    pub negotiation: Account<'info, NegotiationState>,
+    pub negotiation: AccountLoader<'info, NegotiationState>,
+
+    /// CHECK: Pyth `PriceUpdateV2` when offers are quote-denominated; checked by `oracle::quote_price`
+    pub price_update: Option<UncheckedAccount<'info>>,
 }
 
-pub fn handler(ctx: Context<SubmitOffer>, amount: u64, metadata: [u8; 64]) -> Result<()> {
-    let negotiation = &mut ctx.accounts.negotiation;
+/// `is_final` makes this the offerer's last word: the counterparty can no
+/// longer counter, only accept or let the negotiation end.
+pub fn handler(ctx: Context<SubmitOffer>, amount: u64, metadata: [u8; 64], is_final: bool) -> Result<()> {
+    let mut negotiation = ctx.accounts.negotiation.load_current_mut()?;
     let clock = Clock::get()?;
 
     // Validate state
     require!(
-        negotiation.status == NegotiationStatus::Proposed
-            || negotiation.status == NegotiationStatus::Countered,
+        negotiation.status()? == NegotiationStatus::Proposed
+            || negotiation.status()? == NegotiationStatus::Countered,
         HaggleError::InvalidState
     );
 
+    let price = oracle::quote_price(&negotiation, ctx.accounts.price_update.as_deref(), clock.unix_timestamp)?;
+    let event = apply_offer(
+        &mut negotiation,
+        ctx.accounts.negotiation.key(),
+        ctx.accounts.offerer.key(),
+        amount,
+        metadata,
+        is_final,
+        price,
+        clock.unix_timestamp,
+    )?;
+    emit_cpi!(event);
+
+    Ok(())
+}
+
+/// Validates and records an offer by `offerer`: turn order, deadlines, round
+/// limit, decay and the min-offer floor. Quote-denominated amounts are held
+/// to the escrow bounds at `price`. Shared with the opening offers of
+/// `create_negotiation` and `accept_invitation`; a buyer's opening offer
+/// leaves the negotiation `Created` until the seller accepts. An offer in the
+/// last round is final even without `is_final`.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn apply_offer(
+    negotiation: &mut NegotiationState,
+    negotiation_id: Pubkey,
+    offerer: Pubkey,
+    amount: u64,
+    metadata: [u8; 64],
+    is_final: bool,
+    price: Option<Price>,
+    now: i64,
+) -> Result<OfferSubmitted> {
     // Validate it's the correct party
-    let is_buyer = ctx.accounts.offerer.key() == negotiation.buyer;
-    let is_seller = ctx.accounts.offerer.key() == negotiation.seller;
+    let is_buyer = offerer == negotiation.buyer;
+    let is_seller = offerer == negotiation.seller;
     require!(is_buyer || is_seller, HaggleError::Unauthorized);
 
     // Enforce alternating turns
     if negotiation.current_round > 0 {
-        let expected_side = if negotiation.offer_side == OfferSide::Buyer {
+        let expected_side = if negotiation.offer_side()? == OfferSide::Buyer {
             OfferSide::Seller
         } else {
             OfferSide::Buyer
@@ -47,12 +88,12 @@ pub fn handler(ctx: Context<SubmitOffer>, amount: u64, metadata: [u8; 64]) -> Re
     }
 
     // Validate deadline
-    require!(clock.unix_timestamp < negotiation.global_deadline, HaggleError::Expired);
+    require!(now < negotiation.global_deadline, HaggleError::Expired);
 
     // Validate response window
     if negotiation.last_offer_at > 0 {
         require!(
-            clock.unix_timestamp < negotiation.last_offer_at
+            now < negotiation.last_offer_at
                 .checked_add(negotiation.response_window)
                 .ok_or(HaggleError::Overflow)?,
             HaggleError::ResponseWindowExpired
@@ -62,6 +103,9 @@ pub fn handler(ctx: Context<SubmitOffer>, amount: u64, metadata: [u8; 64]) -> Re
     // Validate max rounds
     require!(negotiation.current_round < negotiation.max_rounds, HaggleError::MaxRoundsReached);
 
+    // A final offer cannot be countered
+    require!(!negotiation.is_final_offer(), HaggleError::FinalOffer);
+
     // Apply escrow decay
     let decay = negotiation.effective_escrow
         .checked_mul(negotiation.decay_rate_bps as u64)
@@ -78,33 +122,42 @@ pub fn handler(ctx: Context<SubmitOffer>, amount: u64, metadata: [u8; 64]) -> Re
         .ok_or(HaggleError::Overflow)?
         .checked_div(10000)
         .ok_or(HaggleError::Overflow)?;
-    require!(amount >= min_offer, HaggleError::OfferTooLow);
-    require!(amount <= negotiation.effective_escrow, HaggleError::OfferExceedsEscrow);
+    let value = match price {
+        Some(price) => price.to_mint_units(amount, negotiation.mint_decimals)?,
+        None => amount,
+    };
+    require!(value >= min_offer, HaggleError::OfferTooLow);
+    require!(value <= negotiation.effective_escrow, HaggleError::OfferExceedsEscrow);
 
     // Update state
     negotiation.current_offer_amount = amount;
-    negotiation.current_offer_by = ctx.accounts.offerer.key();
-    negotiation.offer_side = if is_buyer { OfferSide::Buyer } else { OfferSide::Seller };
+    negotiation.current_offer_by = offerer;
+    negotiation.set_offer_side(if is_buyer { OfferSide::Buyer } else { OfferSide::Seller });
     negotiation.current_round = negotiation.current_round
         .checked_add(1)
         .ok_or(HaggleError::Overflow)?;
-    negotiation.last_offer_at = clock.unix_timestamp;
+    negotiation.last_offer_at = now;
     negotiation.metadata = metadata;
+    negotiation.final_offer = (is_final || negotiation.current_round == negotiation.max_rounds) as u8;
 
-    negotiation.status = if is_buyer {
-        NegotiationStatus::Proposed
-    } else {
-        NegotiationStatus::Countered
-    };
+    if negotiation.status()? != NegotiationStatus::Created {
+        negotiation.set_status(if is_buyer {
+            NegotiationStatus::Proposed
+        } else {
+            NegotiationStatus::Countered
+        });
+    }
 
-    emit!(OfferSubmitted {
-        negotiation_id: negotiation.key(),
-        offerer: ctx.accounts.offerer.key(),
+    Ok(OfferSubmitted {
+        negotiation_id,
+        offerer,
+        offer_side: negotiation.offer_side()?,
         amount,
         round: negotiation.current_round,
         effective_escrow: negotiation.effective_escrow,
-        timestamp: clock.unix_timestamp,
-    });
-
-    Ok(())
+        status: negotiation.status()?,
+        metadata,
+        is_final: negotiation.is_final_offer(),
+        timestamp: now,
+    })
 }
@@ -0,0 +1,140 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+
+use crate::errors::HaggleError;
+use crate::events::BidPlaced;
+use crate::settlement::{PayoutReason, Settlement};
+use crate::state::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct PlaceBid<'info> {
+    pub bidder: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"auction", auction.seller.as_ref(), &auction.auction_id.to_le_bytes()],
+        bump = auction.bump,
+    )]
+    pub auction: Box<Account<'info, Auction>>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", auction.key().as_ref()],
+        bump,
+        constraint = escrow_vault.mint == auction.token_mint @ HaggleError::InvalidParams,
+    )]
+    pub escrow_vault: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        constraint = bidder_token_account.owner == bidder.key() @ HaggleError::Unauthorized,
+        constraint = bidder_token_account.mint == auction.token_mint @ HaggleError::InvalidParams,
+    )]
+    pub bidder_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// Receives the outbid bidder's refund; omitted for the first bid.
+    #[account(
+        mut,
+        constraint = outbid_token_account.owner == auction.highest_bidder @ HaggleError::InvalidParams,
+        constraint = outbid_token_account.mint == auction.token_mint @ HaggleError::InvalidParams,
+    )]
+    pub outbid_token_account: Option<Box<Account<'info, TokenAccount>>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn handler(ctx: Context<PlaceBid>, amount: u64) -> Result<()> {
+    let auction = &ctx.accounts.auction;
+    let clock = Clock::get()?;
+
+    // Validate state
+    require!(auction.kind == AuctionKind::English, HaggleError::WrongAuctionKind);
+    require!(auction.status == AuctionStatus::Open, HaggleError::InvalidState);
+    require!(clock.unix_timestamp < auction.ends_at, HaggleError::Expired);
+    require!(ctx.accounts.bidder.key() != auction.seller, HaggleError::Unauthorized);
+
+    // First bid must meet the reserve; later bids must beat the last by the increment
+    let outbid_amount = auction.highest_bid;
+    if auction.bid_count == 0 {
+        require!(amount >= auction.start_price, HaggleError::OfferTooLow);
+    } else {
+        let increment = outbid_amount
+            .checked_mul(auction.min_increment_bps as u64)
+            .ok_or(HaggleError::Overflow)?
+            .checked_div(10000)
+            .ok_or(HaggleError::Overflow)?;
+        let min_bid = outbid_amount
+            .checked_add(increment)
+            .ok_or(HaggleError::Overflow)?;
+        require!(amount > outbid_amount && amount >= min_bid, HaggleError::OfferTooLow);
+    }
+
+    // Escrow the new bid
+    token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.bidder_token_account.to_account_info(),
+                to: ctx.accounts.escrow_vault.to_account_info(),
+                authority: ctx.accounts.bidder.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+
+    // Refund the outbid bidder straight away
+    let outbid = auction.highest_bidder;
+    let payouts = if auction.bid_count > 0 {
+        let outbid_token_account = ctx.accounts.outbid_token_account
+            .as_ref()
+            .ok_or(HaggleError::InvalidParams)?;
+
+        let seller_key = auction.seller;
+        let auction_id_bytes = auction.auction_id.to_le_bytes();
+        let bump = auction.bump;
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"auction",
+            seller_key.as_ref(),
+            &auction_id_bytes,
+            &[bump],
+        ]];
+
+        let settlement = Settlement {
+            token_program: ctx.accounts.token_program.to_account_info(),
+            vault: ctx.accounts.escrow_vault.to_account_info(),
+            vault_authority: ctx.accounts.auction.to_account_info(),
+            signer_seeds,
+        };
+        // The new bid stays in the vault
+        let mut plan = settlement.plan()?;
+        plan.pay(outbid_token_account.to_account_info(), outbid_amount, PayoutReason::Refund)?;
+        plan.retain_rest();
+        settlement.payout(&plan)?
+    } else {
+        Vec::new()
+    };
+
+    // Update state
+    let auction = &mut ctx.accounts.auction;
+    auction.highest_bid = amount;
+    auction.highest_bidder = ctx.accounts.bidder.key();
+    auction.bid_count = auction.bid_count
+        .checked_add(1)
+        .ok_or(HaggleError::Overflow)?;
+
+    for payout in payouts {
+        emit_cpi!(payout);
+    }
+    emit_cpi!(BidPlaced {
+        auction: auction.key(),
+        bidder: auction.highest_bidder,
+        amount,
+        outbid,
+        refund_amount: outbid_amount,
+        bid_count: auction.bid_count,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
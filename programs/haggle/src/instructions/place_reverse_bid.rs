@@ -0,0 +1,73 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::HaggleError;
+use crate::events::ReverseBidPlaced;
+use crate::state::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct PlaceReverseBid<'info> {
+    pub seller: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"reverse_auction", auction.buyer.as_ref(), &auction.auction_id.to_le_bytes()],
+        bump = auction.bump,
+    )]
+    pub auction: Box<Account<'info, ReverseAuction>>,
+
+    /// Required when the auction asks for seller bonds.
+    #[account(
+        seeds = [b"bond", auction.key().as_ref(), seller.key().as_ref()],
+        bump = bond.bump,
+    )]
+    pub bond: Option<Box<Account<'info, SellerBond>>>,
+}
+
+/// Asks `amount` for the service. Sellers escrow nothing beyond their bond;
+/// the buyer's ceiling already covers any winning bid.
+pub fn handler(ctx: Context<PlaceReverseBid>, amount: u64) -> Result<()> {
+    let auction = &ctx.accounts.auction;
+    let clock = Clock::get()?;
+
+    // Validate state
+    require!(auction.status == AuctionStatus::Open, HaggleError::InvalidState);
+    require!(clock.unix_timestamp < auction.ends_at, HaggleError::Expired);
+    require!(ctx.accounts.seller.key() != auction.buyer, HaggleError::Unauthorized);
+    require!(
+        auction.seller_bond == 0 || ctx.accounts.bond.is_some(),
+        HaggleError::BondRequired
+    );
+
+    // First bid must fit the ceiling; later bids must undercut by the decrement
+    require!(amount > 0, HaggleError::InvalidParams);
+    require!(amount <= auction.ceiling, HaggleError::OfferExceedsEscrow);
+    if auction.bid_count > 0 {
+        let decrement = auction.lowest_bid
+            .checked_mul(auction.min_decrement_bps as u64)
+            .ok_or(HaggleError::Overflow)?
+            .checked_div(10000)
+            .ok_or(HaggleError::Overflow)?;
+        let max_bid = auction.lowest_bid
+            .checked_sub(decrement)
+            .ok_or(HaggleError::Overflow)?;
+        require!(amount < auction.lowest_bid && amount <= max_bid, HaggleError::BidNotLowEnough);
+    }
+
+    let auction = &mut ctx.accounts.auction;
+    auction.lowest_bid = amount;
+    auction.lowest_bidder = ctx.accounts.seller.key();
+    auction.bid_count = auction.bid_count
+        .checked_add(1)
+        .ok_or(HaggleError::Overflow)?;
+
+    emit_cpi!(ReverseBidPlaced {
+        auction: auction.key(),
+        seller: auction.lowest_bidder,
+        amount,
+        bid_count: auction.bid_count,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
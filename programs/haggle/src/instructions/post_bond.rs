@@ -0,0 +1,92 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+
+use crate::errors::HaggleError;
+use crate::events::BondPosted;
+use crate::state::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct PostBond<'info> {
+    #[account(mut)]
+    pub seller: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"reverse_auction", auction.buyer.as_ref(), &auction.auction_id.to_le_bytes()],
+        bump = auction.bump,
+    )]
+    pub auction: Box<Account<'info, ReverseAuction>>,
+
+    #[account(
+        init,
+        payer = seller,
+        space = 8 + SellerBond::INIT_SPACE,
+        seeds = [b"bond", auction.key().as_ref(), seller.key().as_ref()],
+        bump,
+    )]
+    pub bond: Box<Account<'info, SellerBond>>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", auction.key().as_ref()],
+        bump,
+        constraint = escrow_vault.mint == auction.token_mint @ HaggleError::InvalidParams,
+    )]
+    pub escrow_vault: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        constraint = seller_token_account.owner == seller.key() @ HaggleError::Unauthorized,
+        constraint = seller_token_account.mint == auction.token_mint @ HaggleError::InvalidParams,
+    )]
+    pub seller_token_account: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Posts the bond a reverse auction requires before a seller may bid.
+pub fn handler(ctx: Context<PostBond>) -> Result<()> {
+    let auction = &ctx.accounts.auction;
+    let clock = Clock::get()?;
+
+    // Validate state
+    require!(auction.seller_bond > 0, HaggleError::InvalidState);
+    require!(auction.status == AuctionStatus::Open, HaggleError::InvalidState);
+    require!(clock.unix_timestamp < auction.ends_at, HaggleError::Expired);
+    require!(ctx.accounts.seller.key() != auction.buyer, HaggleError::Unauthorized);
+
+    let amount = auction.seller_bond;
+    token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.seller_token_account.to_account_info(),
+                to: ctx.accounts.escrow_vault.to_account_info(),
+                authority: ctx.accounts.seller.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+
+    let bond = &mut ctx.accounts.bond;
+    bond.auction = ctx.accounts.auction.key();
+    bond.seller = ctx.accounts.seller.key();
+    bond.amount = amount;
+    bond.bump = ctx.bumps.bond;
+
+    let auction = &mut ctx.accounts.auction;
+    auction.open_bonds = auction.open_bonds
+        .checked_add(1)
+        .ok_or(HaggleError::Overflow)?;
+
+    emit_cpi!(BondPosted {
+        auction: auction.key(),
+        seller: bond.seller,
+        amount,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
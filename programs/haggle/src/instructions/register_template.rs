@@ -0,0 +1,66 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::HaggleError;
+use crate::events::TemplateRegistered;
+use crate::instructions::create::validate_terms;
+use crate::state::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(template_id: u64)]
+pub struct RegisterTemplate<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + NegotiationTemplate::INIT_SPACE,
+        seeds = [b"template", owner.key().as_ref(), &template_id.to_le_bytes()],
+        bump,
+    )]
+    pub template: Box<Account<'info, NegotiationTemplate>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<RegisterTemplate>, template_id: u64, params: TemplateParams) -> Result<()> {
+    require!(params.allowed_mints.len() <= MAX_TEMPLATE_MINTS, HaggleError::InvalidParams);
+
+    let clock = Clock::get()?;
+    let template = &mut ctx.accounts.template;
+    template.owner = ctx.accounts.owner.key();
+    template.template_id = template_id;
+    template.max_rounds = params.max_rounds;
+    template.decay_rate_bps = params.decay_rate_bps;
+    template.response_window = params.response_window;
+    template.global_deadline_offset = params.global_deadline_offset;
+    template.min_offer_bps = params.min_offer_bps;
+    template.protocol_fee_bps = params.protocol_fee_bps;
+    template.zopa_enabled = params.zopa_enabled;
+    template.allowed_mints = params.allowed_mints;
+    template.arbiter = params.arbiter.unwrap_or_default();
+    template.created_at = clock.unix_timestamp;
+    template.bump = ctx.bumps.template;
+
+    // Terms must be ones a negotiation could be created with
+    validate_terms(&template.negotiation_params(0, [0u8; 32], None))?;
+
+    emit_cpi!(TemplateRegistered {
+        template: template.key(),
+        owner: template.owner,
+        template_id,
+        max_rounds: template.max_rounds,
+        decay_rate_bps: template.decay_rate_bps,
+        response_window: template.response_window,
+        global_deadline_offset: template.global_deadline_offset,
+        min_offer_bps: template.min_offer_bps,
+        protocol_fee_bps: template.protocol_fee_bps,
+        zopa_enabled: template.zopa_enabled,
+        allowed_mints: template.allowed_mints.clone(),
+        arbiter: template.arbiter,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
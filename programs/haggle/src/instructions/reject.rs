@@ -1,48 +1,69 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use anchor_spl::token::{Token, TokenAccount};
 
 use crate::errors::HaggleError;
 use crate::events::NegotiationRejected;
+use crate::migration::LoadCurrent;
+use crate::policy;
+use crate::settlement::{negotiation_plan, NegotiationSigner, Outcome, PayoutReason, Settlement};
 use crate::state::*;
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct RejectNegotiation<'info> {
     pub rejector: Signer<'info>,
 
     #[account(
         mut,
-        seeds = [b"negotiation", negotiation.buyer.as_ref(), negotiation.seller.as_ref(), &negotiation.session_id.to_le_bytes()],
-        bump = negotiation.bump,
+        seeds = [b"negotiation", negotiation.load_current()?.buyer.as_ref(), negotiation.load_current()?.seller.as_ref(), &negotiation.load_current()?.session_id.to_le_bytes()],
+        bump = negotiation.load_current()?.bump,
     )]
-    pub negotiation: Account<'info, NegotiationState>,
+    pub negotiation: AccountLoader<'info, NegotiationState>,
 
     #[account(
         mut,
         seeds = [b"vault", negotiation.key().as_ref()],
         bump,
-        constraint = escrow_vault.mint == negotiation.token_mint @ HaggleError::InvalidParams,
+        constraint = escrow_vault.mint == negotiation.load_current()?.token_mint @ HaggleError::InvalidParams,
     )]
     pub escrow_vault: Account<'info, TokenAccount>,
 
     #[account(
         mut,
-        constraint = buyer_token_account.owner == negotiation.buyer @ HaggleError::InvalidParams,
-        constraint = buyer_token_account.mint == negotiation.token_mint @ HaggleError::InvalidParams,
+        constraint = buyer_token_account.owner == negotiation.load_current()?.buyer @ HaggleError::InvalidParams,
+        constraint = buyer_token_account.mint == negotiation.load_current()?.token_mint @ HaggleError::InvalidParams,
     )]
     pub buyer_token_account: Account<'info, TokenAccount>,
 
+    /// Receives the escrow lost to decay
+    #[account(
+        mut,
+        constraint = treasury_token_account.owner == config.treasury @ HaggleError::InvalidParams,
+        constraint = treasury_token_account.mint == negotiation.load_current()?.token_mint @ HaggleError::InvalidParams,
+    )]
+    pub treasury_token_account: Account<'info, TokenAccount>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProtocolConfig>,
+
+    /// CHECK: Seller's `AcceptancePolicy`, which may not exist; read by `policy::release`
+    #[account(mut, seeds = [b"policy", negotiation.load_current()?.seller.as_ref()], bump)]
+    pub policy: UncheckedAccount<'info>,
+
     pub token_program: Program<'info, Token>,
 }
 
-pub fn handler(ctx: Context<RejectNegotiation>) -> Result<()> {
-    let negotiation = &ctx.accounts.negotiation;
+/// Ends the negotiation with the rejector's `reason`, if they give one.
+/// `Timeout` is left to `expire_negotiation` and refused here.
+pub fn handler(ctx: Context<RejectNegotiation>, reason: Option<TerminationReason>) -> Result<()> {
+    let negotiation = ctx.accounts.negotiation.load_current()?;
     let clock = Clock::get()?;
 
     // Validate state - can reject from Created, Proposed, or Countered
     require!(
-        negotiation.status == NegotiationStatus::Created
-            || negotiation.status == NegotiationStatus::Proposed
-            || negotiation.status == NegotiationStatus::Countered,
+        negotiation.status()? == NegotiationStatus::Created
+            || negotiation.status()? == NegotiationStatus::Proposed
+            || negotiation.status()? == NegotiationStatus::Countered,
         HaggleError::InvalidState
     );
 
@@ -51,45 +72,56 @@ pub fn handler(ctx: Context<RejectNegotiation>) -> Result<()> {
     let is_seller = ctx.accounts.rejector.key() == negotiation.seller;
     require!(is_buyer || is_seller, HaggleError::Unauthorized);
 
-    // Refund effective escrow to buyer
-    let refund_amount = ctx.accounts.escrow_vault.amount;
-
-    let buyer_key = negotiation.buyer;
-    let seller_key = negotiation.seller;
-    let session_bytes = negotiation.session_id.to_le_bytes();
-    let bump = negotiation.bump;
-    let signer_seeds: &[&[&[u8]]] = &[&[
-        b"negotiation",
-        buyer_key.as_ref(),
-        seller_key.as_ref(),
-        &session_bytes,
-        &[bump],
-    ]];
-
-    if refund_amount > 0 {
-        token::transfer(
-            CpiContext::new_with_signer(
-                ctx.accounts.token_program.to_account_info(),
-                Transfer {
-                    from: ctx.accounts.escrow_vault.to_account_info(),
-                    to: ctx.accounts.buyer_token_account.to_account_info(),
-                    authority: ctx.accounts.negotiation.to_account_info(),
-                },
-                signer_seeds,
-            ),
-            refund_amount,
-        )?;
-    }
+    // Timeout is reserved for negotiations that actually ran out of time
+    require!(reason != Some(TerminationReason::Timeout), HaggleError::InvalidParams);
 
+    let signer = NegotiationSigner::new(&negotiation);
+    let escrow_decay = negotiation.escrow_amount
+        .checked_sub(negotiation.effective_escrow)
+        .ok_or(HaggleError::Overflow)?;
+    let is_pooled = negotiation.is_pooled();
     let rounds_completed = negotiation.current_round;
-    let negotiation = &mut ctx.accounts.negotiation;
-    negotiation.status = NegotiationStatus::Rejected;
+    // The negotiation signs for the vault, so the borrow must end before the
+    // token program is invoked
+    drop(negotiation);
+
+    // The escrow lost to decay goes to the treasury and the rest back to the
+    // buyer (coalition members claim their refunds from the vault)
+    let plan = negotiation_plan(
+        ctx.accounts.escrow_vault.amount,
+        Outcome::Terminated { escrow_decay, crank_reward: None },
+        is_pooled,
+        ctx.accounts.buyer_token_account.to_account_info(),
+        ctx.accounts.treasury_token_account.to_account_info(),
+    )?;
+    // Refunded to the buyer, or left for the coalition members
+    let refund_amount = plan.vault_balance() - plan.paid(PayoutReason::Decay);
+    let seeds = signer.seeds();
+    let payouts = Settlement {
+        token_program: ctx.accounts.token_program.to_account_info(),
+        vault: ctx.accounts.escrow_vault.to_account_info(),
+        vault_authority: ctx.accounts.negotiation.to_account_info(),
+        signer_seeds: &[&seeds],
+    }
+    .payout(&plan)?;
+
+    let reason = reason.unwrap_or(TerminationReason::Unspecified);
+    let mut negotiation = ctx.accounts.negotiation.load_current_mut()?;
+    negotiation.set_status(NegotiationStatus::Rejected);
+    negotiation.set_termination_reason(reason);
+    policy::release(&ctx.accounts.policy, &mut negotiation)?;
+    drop(negotiation);
+
+    for payout in payouts {
+        emit_cpi!(payout);
+    }
 
-    emit!(NegotiationRejected {
+    emit_cpi!(NegotiationRejected {
         negotiation_id: ctx.accounts.negotiation.key(),
         rejected_by: ctx.accounts.rejector.key(),
         refund_amount,
         rounds_completed,
+        reason,
         timestamp: clock.unix_timestamp,
     });
 
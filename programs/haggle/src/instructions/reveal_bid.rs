@@ -0,0 +1,73 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::HaggleError;
+use crate::events::BidRevealed;
+use crate::state::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RevealBid<'info> {
+    pub bidder: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"auction", auction.seller.as_ref(), &auction.auction_id.to_le_bytes()],
+        bump = auction.bump,
+    )]
+    pub auction: Box<Account<'info, Auction>>,
+
+    #[account(
+        mut,
+        seeds = [b"sealed_bid", auction.key().as_ref(), bidder.key().as_ref()],
+        bump = sealed_bid.bump,
+    )]
+    pub sealed_bid: Box<Account<'info, SealedBid>>,
+}
+
+/// Opens a sealed bid. Bids below the reserve are revealed (so the deposit
+/// is not slashed) but do not compete.
+pub fn handler(ctx: Context<RevealBid>, amount: u64, salt: [u8; 32]) -> Result<()> {
+    let auction = &ctx.accounts.auction;
+    let sealed_bid = &ctx.accounts.sealed_bid;
+    let clock = Clock::get()?;
+
+    // Validate state
+    require!(auction.status == AuctionStatus::Open, HaggleError::InvalidState);
+    require!(clock.unix_timestamp >= auction.commit_ends_at, HaggleError::CommitPhaseOpen);
+    require!(clock.unix_timestamp < auction.ends_at, HaggleError::Expired);
+    require!(!sealed_bid.revealed, HaggleError::InvalidState);
+
+    // Validate the reveal
+    require!(
+        SealedBid::commitment(amount, &salt) == sealed_bid.commitment,
+        HaggleError::BidCommitmentMismatch
+    );
+    require!(amount <= sealed_bid.deposit, HaggleError::OfferExceedsEscrow);
+
+    let sealed_bid = &mut ctx.accounts.sealed_bid;
+    sealed_bid.amount = amount;
+    sealed_bid.revealed = true;
+
+    // Ties go to the earlier reveal; the later one sets the second price
+    let auction = &mut ctx.accounts.auction;
+    if amount >= auction.start_price {
+        if amount > auction.highest_bid {
+            auction.second_bid = auction.highest_bid;
+            auction.highest_bid = amount;
+            auction.highest_bidder = sealed_bid.bidder;
+        } else if amount > auction.second_bid {
+            auction.second_bid = amount;
+        }
+    }
+
+    emit_cpi!(BidRevealed {
+        auction: auction.key(),
+        bidder: sealed_bid.bidder,
+        amount,
+        highest_bid: auction.highest_bid,
+        second_bid: auction.second_bid,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,133 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount};
+
+use crate::errors::HaggleError;
+use crate::events::{AuctionSettled, AuctionUnsold};
+use crate::settlement::{record_sale, Settlement};
+use crate::state::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SettleAuction<'info> {
+    /// CHECK: Anyone can crank settlement (permissionless)
+    pub cranker: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"auction", auction.seller.as_ref(), &auction.auction_id.to_le_bytes()],
+        bump = auction.bump,
+    )]
+    pub auction: Box<Account<'info, Auction>>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", auction.key().as_ref()],
+        bump,
+        constraint = escrow_vault.mint == auction.token_mint @ HaggleError::InvalidParams,
+    )]
+    pub escrow_vault: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        constraint = seller_token_account.owner == auction.seller @ HaggleError::InvalidParams,
+        constraint = seller_token_account.mint == auction.token_mint @ HaggleError::InvalidParams,
+    )]
+    pub seller_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        constraint = treasury_token_account.owner == config.treasury @ HaggleError::InvalidParams,
+        constraint = treasury_token_account.mint == auction.token_mint @ HaggleError::InvalidParams,
+    )]
+    pub treasury_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Box<Account<'info, ProtocolConfig>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Ends an auction once its time is up: an English auction with bids pays
+/// the highest bid to the seller and a Vickrey auction with a revealed bid at
+/// or above the reserve pays the second price (at least the reserve) out of
+/// the winner's deposit. Anything else is marked unsold.
+pub fn handler(ctx: Context<SettleAuction>) -> Result<()> {
+    let auction = &ctx.accounts.auction;
+    let clock = Clock::get()?;
+
+    // Validate state
+    require!(auction.status == AuctionStatus::Open, HaggleError::InvalidState);
+    require!(clock.unix_timestamp >= auction.ends_at, HaggleError::AuctionNotEnded);
+
+    if auction.highest_bid == 0 {
+        let auction = &mut ctx.accounts.auction;
+        auction.status = AuctionStatus::Unsold;
+
+        emit_cpi!(AuctionUnsold {
+            auction: auction.key(),
+            timestamp: clock.unix_timestamp,
+        });
+        return Ok(());
+    }
+
+    let settled_amount = match auction.kind {
+        AuctionKind::Vickrey => auction.second_bid.max(auction.start_price),
+        _ => auction.highest_bid,
+    };
+    let seller_key = auction.seller;
+    let auction_id_bytes = auction.auction_id.to_le_bytes();
+    let bump = auction.bump;
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        b"auction",
+        seller_key.as_ref(),
+        &auction_id_bytes,
+        &[bump],
+    ]];
+
+    let settlement = Settlement {
+        token_program: ctx.accounts.token_program.to_account_info(),
+        vault: ctx.accounts.escrow_vault.to_account_info(),
+        vault_authority: ctx.accounts.auction.to_account_info(),
+        signer_seeds,
+    };
+    // Losing Vickrey deposits, and what the winner's deposit holds above the
+    // price, stay for `withdraw_bid`
+    let mut plan = settlement.plan()?;
+    let split = plan.sale(
+        settled_amount,
+        auction.protocol_fee_bps,
+        ctx.accounts.seller_token_account.to_account_info(),
+        ctx.accounts.treasury_token_account.to_account_info(),
+    )?;
+    plan.retain_rest();
+    let payouts = settlement.payout(&plan)?;
+    record_sale(&mut ctx.accounts.config, settled_amount, split.protocol_fee)?;
+    for payout in payouts {
+        emit_cpi!(payout);
+    }
+
+    // Update state
+    let auction = &mut ctx.accounts.auction;
+    auction.status = AuctionStatus::Settled;
+    auction.settled_amount = settled_amount;
+    auction.settled_at = clock.unix_timestamp;
+
+    emit_cpi!(AuctionSettled {
+        auction: auction.key(),
+        kind: auction.kind,
+        buyer: auction.highest_bidder,
+        seller: seller_key,
+        settled_amount,
+        protocol_fee: split.protocol_fee,
+        seller_payment: split.seller_payment,
+        bid_count: auction.bid_count,
+        price_decay: 0,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
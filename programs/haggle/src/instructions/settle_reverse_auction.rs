@@ -0,0 +1,152 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount};
+
+use crate::errors::HaggleError;
+use crate::events::{AuctionUnsold, ReverseAuctionSettled};
+use crate::settlement::{record_sale, PayoutReason, Settlement};
+use crate::state::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SettleReverseAuction<'info> {
+    /// CHECK: Anyone can crank settlement (permissionless)
+    pub cranker: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"reverse_auction", auction.buyer.as_ref(), &auction.auction_id.to_le_bytes()],
+        bump = auction.bump,
+    )]
+    pub auction: Box<Account<'info, ReverseAuction>>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", auction.key().as_ref()],
+        bump,
+        constraint = escrow_vault.mint == auction.token_mint @ HaggleError::InvalidParams,
+    )]
+    pub escrow_vault: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        constraint = buyer_token_account.owner == auction.buyer @ HaggleError::InvalidParams,
+        constraint = buyer_token_account.mint == auction.token_mint @ HaggleError::InvalidParams,
+    )]
+    pub buyer_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// The lowest bidder's account; omitted when nobody bid.
+    #[account(
+        mut,
+        constraint = seller_token_account.owner == auction.lowest_bidder @ HaggleError::InvalidParams,
+        constraint = seller_token_account.mint == auction.token_mint @ HaggleError::InvalidParams,
+    )]
+    pub seller_token_account: Option<Box<Account<'info, TokenAccount>>>,
+
+    #[account(
+        mut,
+        constraint = treasury_token_account.owner == config.treasury @ HaggleError::InvalidParams,
+        constraint = treasury_token_account.mint == auction.token_mint @ HaggleError::InvalidParams,
+    )]
+    pub treasury_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Box<Account<'info, ProtocolConfig>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Ends a reverse auction once its time is up: the lowest bid is paid to its
+/// seller and the rest of the ceiling refunded to the buyer, as when an offer
+/// is accepted. Without bids the whole ceiling is refunded.
+pub fn handler(ctx: Context<SettleReverseAuction>) -> Result<()> {
+    let auction = &ctx.accounts.auction;
+    let clock = Clock::get()?;
+
+    // Validate state
+    require!(auction.status == AuctionStatus::Open, HaggleError::InvalidState);
+    require!(clock.unix_timestamp >= auction.ends_at, HaggleError::AuctionNotEnded);
+
+    let settled_amount = auction.lowest_bid;
+    let refund_amount = auction.ceiling
+        .checked_sub(settled_amount)
+        .ok_or(HaggleError::Overflow)?;
+
+    let buyer_key = auction.buyer;
+    let auction_id_bytes = auction.auction_id.to_le_bytes();
+    let bump = auction.bump;
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        b"reverse_auction",
+        buyer_key.as_ref(),
+        &auction_id_bytes,
+        &[bump],
+    ]];
+
+    let settlement = Settlement {
+        token_program: ctx.accounts.token_program.to_account_info(),
+        vault: ctx.accounts.escrow_vault.to_account_info(),
+        vault_authority: ctx.accounts.auction.to_account_info(),
+        signer_seeds,
+    };
+
+    // Sellers' bonds stay in the vault for `withdraw_bond`
+    let mut plan = settlement.plan()?;
+    if auction.bid_count == 0 {
+        plan.pay(ctx.accounts.buyer_token_account.to_account_info(), refund_amount, PayoutReason::Refund)?;
+        plan.retain_rest();
+        let payouts = settlement.payout(&plan)?;
+
+        let auction = &mut ctx.accounts.auction;
+        auction.status = AuctionStatus::Unsold;
+
+        for payout in payouts {
+            emit_cpi!(payout);
+        }
+        emit_cpi!(AuctionUnsold {
+            auction: auction.key(),
+            timestamp: clock.unix_timestamp,
+        });
+        return Ok(());
+    }
+
+    // Pay the lowest bidder and treasury, then refund the rest to the buyer
+    let seller_token_account = ctx.accounts.seller_token_account
+        .as_ref()
+        .ok_or(HaggleError::InvalidParams)?;
+    let split = plan.sale(
+        settled_amount,
+        auction.protocol_fee_bps,
+        seller_token_account.to_account_info(),
+        ctx.accounts.treasury_token_account.to_account_info(),
+    )?;
+    plan.pay(ctx.accounts.buyer_token_account.to_account_info(), refund_amount, PayoutReason::Refund)?;
+    plan.retain_rest();
+    let payouts = settlement.payout(&plan)?;
+    record_sale(&mut ctx.accounts.config, settled_amount, split.protocol_fee)?;
+    for payout in payouts {
+        emit_cpi!(payout);
+    }
+
+    // Update state
+    let auction = &mut ctx.accounts.auction;
+    auction.status = AuctionStatus::Settled;
+    auction.settled_amount = settled_amount;
+    auction.settled_at = clock.unix_timestamp;
+
+    emit_cpi!(ReverseAuctionSettled {
+        auction: auction.key(),
+        buyer: buyer_key,
+        seller: auction.lowest_bidder,
+        settled_amount,
+        protocol_fee: split.protocol_fee,
+        seller_payment: split.seller_payment,
+        refund_amount,
+        bid_count: auction.bid_count,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
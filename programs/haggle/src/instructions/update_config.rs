@@ -0,0 +1,67 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::HaggleError;
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = authority @ HaggleError::Unauthorized,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+}
+
+pub fn handler(ctx: Context<UpdateConfig>, params: ConfigUpdateParams) -> Result<()> {
+    // Defaults follow the same bounds create_negotiation enforces
+    if let Some(rate) = params.default_decay_rate_bps {
+        require!(rate <= 1000, HaggleError::InvalidParams);
+    }
+    if let Some(window) = params.default_response_window {
+        require!(window >= 60, HaggleError::InvalidParams);
+    }
+    if let Some(fee) = params.default_protocol_fee_bps {
+        require!(fee <= 500, HaggleError::InvalidParams);
+    }
+    if let Some(rounds) = params.default_max_rounds {
+        require!(rounds > 0 && rounds <= 20, HaggleError::InvalidParams);
+    }
+    if let Some(bps) = params.crank_reward_bps {
+        require!(bps <= 1000, HaggleError::InvalidParams); // max 10% of the refund
+    }
+
+    let config = &mut ctx.accounts.config;
+    if let Some(authority) = params.authority {
+        config.authority = authority;
+    }
+    if let Some(treasury) = params.treasury {
+        config.treasury = treasury;
+    }
+    if let Some(rate) = params.default_decay_rate_bps {
+        config.default_decay_rate_bps = rate;
+    }
+    if let Some(window) = params.default_response_window {
+        config.default_response_window = window;
+    }
+    if let Some(fee) = params.default_protocol_fee_bps {
+        config.default_protocol_fee_bps = fee;
+    }
+    if let Some(rounds) = params.default_max_rounds {
+        config.default_max_rounds = rounds;
+    }
+    if let Some(paused) = params.is_paused {
+        config.is_paused = paused;
+    }
+    if let Some(lamports) = params.crank_reward_lamports {
+        config.crank_reward_lamports = lamports;
+    }
+    if let Some(bps) = params.crank_reward_bps {
+        config.crank_reward_bps = bps;
+    }
+
+    Ok(())
+}
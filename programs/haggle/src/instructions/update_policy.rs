@@ -0,0 +1,30 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::HaggleError;
+use crate::instructions::create_policy::{apply, set_event};
+use crate::state::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct UpdateAcceptancePolicy<'info> {
+    pub seller: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"policy", seller.key().as_ref()],
+        bump = policy.bump,
+        constraint = policy.seller == seller.key() @ HaggleError::Unauthorized,
+    )]
+    pub policy: Box<Account<'info, AcceptancePolicy>>,
+}
+
+/// Replaces the policy's terms. Negotiations already open keep their slots,
+/// even if the new limit is lower.
+pub fn handler(ctx: Context<UpdateAcceptancePolicy>, params: PolicyParams) -> Result<()> {
+    let policy = &mut ctx.accounts.policy;
+    apply(policy, params)?;
+
+    emit_cpi!(set_event(policy, Clock::get()?.unix_timestamp));
+
+    Ok(())
+}
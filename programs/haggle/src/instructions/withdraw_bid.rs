@@ -0,0 +1,133 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount};
+
+use crate::errors::HaggleError;
+use crate::events::BidWithdrawn;
+use crate::settlement::{PayoutReason, Settlement};
+use crate::state::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct WithdrawBid<'info> {
+    /// CHECK: Anyone can crank withdrawals (permissionless)
+    pub cranker: Signer<'info>,
+
+    /// CHECK: Receives the sealed bid's rent; checked against the bid
+    #[account(mut, address = sealed_bid.bidder @ HaggleError::InvalidParams)]
+    pub bidder: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"auction", auction.seller.as_ref(), &auction.auction_id.to_le_bytes()],
+        bump = auction.bump,
+    )]
+    pub auction: Box<Account<'info, Auction>>,
+
+    #[account(
+        mut,
+        seeds = [b"sealed_bid", auction.key().as_ref(), sealed_bid.bidder.as_ref()],
+        bump = sealed_bid.bump,
+        close = bidder,
+    )]
+    pub sealed_bid: Box<Account<'info, SealedBid>>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", auction.key().as_ref()],
+        bump,
+        constraint = escrow_vault.mint == auction.token_mint @ HaggleError::InvalidParams,
+    )]
+    pub escrow_vault: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        constraint = bidder_token_account.owner == sealed_bid.bidder @ HaggleError::InvalidParams,
+        constraint = bidder_token_account.mint == auction.token_mint @ HaggleError::InvalidParams,
+    )]
+    pub bidder_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        constraint = seller_token_account.owner == auction.seller @ HaggleError::InvalidParams,
+        constraint = seller_token_account.mint == auction.token_mint @ HaggleError::InvalidParams,
+    )]
+    pub seller_token_account: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Returns a sealed bid's deposit once the auction has ended. The winner gets
+/// back what is left after paying the second price; an unrevealed bid
+/// forfeits `slash_bps` of its deposit to the seller.
+pub fn handler(ctx: Context<WithdrawBid>) -> Result<()> {
+    let auction = &ctx.accounts.auction;
+    let sealed_bid = &ctx.accounts.sealed_bid;
+    let clock = Clock::get()?;
+
+    // Validate state
+    require!(
+        auction.status == AuctionStatus::Settled || auction.status == AuctionStatus::Unsold,
+        HaggleError::InvalidState
+    );
+
+    let won = sealed_bid.revealed
+        && auction.status == AuctionStatus::Settled
+        && auction.highest_bidder == sealed_bid.bidder;
+    let paid = if won { auction.settled_amount } else { 0 };
+    let slashed = if sealed_bid.revealed {
+        0
+    } else {
+        sealed_bid.deposit
+            .checked_mul(auction.slash_bps as u64)
+            .ok_or(HaggleError::Overflow)?
+            .checked_div(10000)
+            .ok_or(HaggleError::Overflow)?
+    };
+    let refund_amount = sealed_bid.deposit
+        .checked_sub(paid)
+        .ok_or(HaggleError::Overflow)?
+        .checked_sub(slashed)
+        .ok_or(HaggleError::Overflow)?;
+
+    let seller_key = auction.seller;
+    let auction_id_bytes = auction.auction_id.to_le_bytes();
+    let bump = auction.bump;
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        b"auction",
+        seller_key.as_ref(),
+        &auction_id_bytes,
+        &[bump],
+    ]];
+
+    let settlement = Settlement {
+        token_program: ctx.accounts.token_program.to_account_info(),
+        vault: ctx.accounts.escrow_vault.to_account_info(),
+        vault_authority: ctx.accounts.auction.to_account_info(),
+        signer_seeds,
+    };
+    // Other bids stay in the vault until their bidders withdraw
+    let mut plan = settlement.plan()?;
+    plan.pay(ctx.accounts.bidder_token_account.to_account_info(), refund_amount, PayoutReason::Refund)?;
+    plan.pay(ctx.accounts.seller_token_account.to_account_info(), slashed, PayoutReason::Slash)?;
+    plan.retain_rest();
+    let payouts = settlement.payout(&plan)?;
+
+    let bidder = sealed_bid.bidder;
+    let auction = &mut ctx.accounts.auction;
+    auction.open_bids = auction.open_bids
+        .checked_sub(1)
+        .ok_or(HaggleError::Overflow)?;
+
+    for payout in payouts {
+        emit_cpi!(payout);
+    }
+    emit_cpi!(BidWithdrawn {
+        auction: auction.key(),
+        bidder,
+        refund_amount,
+        slashed,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
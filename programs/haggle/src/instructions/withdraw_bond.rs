@@ -0,0 +1,103 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount};
+
+use crate::errors::HaggleError;
+use crate::events::BidWithdrawn;
+use crate::settlement::{PayoutReason, Settlement};
+use crate::state::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct WithdrawBond<'info> {
+    /// CHECK: Anyone can crank withdrawals (permissionless)
+    pub cranker: Signer<'info>,
+
+    /// CHECK: Receives the bond account's rent; checked against the bond
+    #[account(mut, address = bond.seller @ HaggleError::InvalidParams)]
+    pub seller: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"reverse_auction", auction.buyer.as_ref(), &auction.auction_id.to_le_bytes()],
+        bump = auction.bump,
+    )]
+    pub auction: Box<Account<'info, ReverseAuction>>,
+
+    #[account(
+        mut,
+        seeds = [b"bond", auction.key().as_ref(), bond.seller.as_ref()],
+        bump = bond.bump,
+        close = seller,
+    )]
+    pub bond: Box<Account<'info, SellerBond>>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", auction.key().as_ref()],
+        bump,
+        constraint = escrow_vault.mint == auction.token_mint @ HaggleError::InvalidParams,
+    )]
+    pub escrow_vault: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        constraint = seller_token_account.owner == bond.seller @ HaggleError::InvalidParams,
+        constraint = seller_token_account.mint == auction.token_mint @ HaggleError::InvalidParams,
+    )]
+    pub seller_token_account: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Returns a seller's bond once the reverse auction has ended.
+pub fn handler(ctx: Context<WithdrawBond>) -> Result<()> {
+    let auction = &ctx.accounts.auction;
+    let clock = Clock::get()?;
+
+    // Validate state
+    require!(
+        auction.status == AuctionStatus::Settled || auction.status == AuctionStatus::Unsold,
+        HaggleError::InvalidState
+    );
+
+    let refund_amount = ctx.accounts.bond.amount;
+    let buyer_key = auction.buyer;
+    let auction_id_bytes = auction.auction_id.to_le_bytes();
+    let bump = auction.bump;
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        b"reverse_auction",
+        buyer_key.as_ref(),
+        &auction_id_bytes,
+        &[bump],
+    ]];
+
+    let settlement = Settlement {
+        token_program: ctx.accounts.token_program.to_account_info(),
+        vault: ctx.accounts.escrow_vault.to_account_info(),
+        vault_authority: ctx.accounts.auction.to_account_info(),
+        signer_seeds,
+    };
+    // Other sellers' bonds stay in the vault
+    let mut plan = settlement.plan()?;
+    plan.pay(ctx.accounts.seller_token_account.to_account_info(), refund_amount, PayoutReason::Refund)?;
+    plan.retain_rest();
+    let payouts = settlement.payout(&plan)?;
+
+    let auction = &mut ctx.accounts.auction;
+    auction.open_bonds = auction.open_bonds
+        .checked_sub(1)
+        .ok_or(HaggleError::Overflow)?;
+
+    for payout in payouts {
+        emit_cpi!(payout);
+    }
+    emit_cpi!(BidWithdrawn {
+        auction: auction.key(),
+        bidder: ctx.accounts.bond.seller,
+        refund_amount,
+        slashed: 0,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
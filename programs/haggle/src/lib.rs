@@ -1,10 +1,17 @@
 use anchor_lang::prelude::*;
 
+mod crank;
 pub mod errors;
 pub mod events;
 pub mod instructions;
+pub mod migration;
+pub mod oracle;
+mod policy;
+pub mod settlement;
 pub mod state;
+pub mod swap;
 
+use events::*;
 use instructions::*;
 use state::*;
 
@@ -34,42 +41,200 @@ pub mod haggle {
         config.total_fees_collected = 0;
         config.is_paused = false;
         config.bump = ctx.bumps.config;
+        config.version = ProtocolConfig::VERSION;
+        config.crank_reward_lamports = 0;
+        config.crank_reward_bps = 0;
+        config.total_cranks = 0;
+        config.total_crank_rewards_lamports = 0;
+        config.total_crank_rewards_tokens = 0;
+        config.reserved = [0u8; 30];
+
+        emit_cpi!(ConfigInitialized {
+            authority: config.authority,
+            treasury,
+            default_decay_rate_bps,
+            default_response_window,
+            default_protocol_fee_bps,
+            default_max_rounds,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
         Ok(())
     }
 
+    pub fn update_config(ctx: Context<UpdateConfig>, params: ConfigUpdateParams) -> Result<()> {
+        super::instructions::update_config::handler(ctx, params)
+    }
+
     pub fn create_negotiation(
         ctx: Context<CreateNegotiation>,
         session_id: u64,
         params: NegotiationParams,
+        opening_offer: Option<OpeningOffer>,
+    ) -> Result<()> {
+        super::instructions::create::handler(ctx, session_id, params, opening_offer)
+    }
+
+    pub fn register_template(
+        ctx: Context<RegisterTemplate>,
+        template_id: u64,
+        params: TemplateParams,
+    ) -> Result<()> {
+        super::instructions::register_template::handler(ctx, template_id, params)
+    }
+
+    pub fn create_negotiation_from_template(
+        ctx: Context<CreateNegotiationFromTemplate>,
+        session_id: u64,
+        escrow_amount: u64,
+        service_hash: [u8; 32],
+        opening_offer: Option<OpeningOffer>,
+        invitation_ttl: Option<i64>,
+    ) -> Result<()> {
+        super::instructions::create_from_template::handler(
+            ctx,
+            session_id,
+            escrow_amount,
+            service_hash,
+            opening_offer,
+            invitation_ttl,
+        )
+    }
+
+    pub fn create_acceptance_policy(
+        ctx: Context<CreateAcceptancePolicy>,
+        params: PolicyParams,
     ) -> Result<()> {
-        super::instructions::create::handler(ctx, session_id, params)
+        super::instructions::create_policy::handler(ctx, params)
     }
 
-    pub fn accept_invitation(ctx: Context<AcceptInvitation>) -> Result<()> {
-        super::instructions::accept_inv::handler(ctx)
+    pub fn update_acceptance_policy(
+        ctx: Context<UpdateAcceptancePolicy>,
+        params: PolicyParams,
+    ) -> Result<()> {
+        super::instructions::update_policy::handler(ctx, params)
     }
 
-    pub fn submit_offer(ctx: Context<SubmitOffer>, amount: u64, metadata: [u8; 64]) -> Result<()> {
-        super::instructions::offer::handler(ctx, amount, metadata)
+    pub fn close_acceptance_policy(ctx: Context<CloseAcceptancePolicy>) -> Result<()> {
+        super::instructions::close_policy::handler(ctx)
     }
 
-    pub fn accept_offer(ctx: Context<AcceptOffer>) -> Result<()> {
+    pub fn accept_invitation(
+        ctx: Context<AcceptInvitation>,
+        opening_offer: Option<OpeningOffer>,
+    ) -> Result<()> {
+        super::instructions::accept_inv::handler(ctx, opening_offer)
+    }
+
+    pub fn submit_offer(ctx: Context<SubmitOffer>, amount: u64, metadata: [u8; 64], is_final: bool) -> Result<()> {
+        super::instructions::offer::handler(ctx, amount, metadata, is_final)
+    }
+
+    pub fn accept_offer<'info>(ctx: Context<'_, '_, '_, 'info, AcceptOffer<'info>>) -> Result<()> {
         super::instructions::accept::handler(ctx)
     }
 
-    pub fn reject_negotiation(ctx: Context<RejectNegotiation>) -> Result<()> {
-        super::instructions::reject::handler(ctx)
+    pub fn reject_negotiation(ctx: Context<RejectNegotiation>, reason: Option<TerminationReason>) -> Result<()> {
+        super::instructions::reject::handler(ctx, reason)
     }
 
     pub fn expire_negotiation(ctx: Context<ExpireNegotiation>) -> Result<()> {
         super::instructions::expire::handler(ctx)
     }
 
-    pub fn close_negotiation(ctx: Context<CloseNegotiation>) -> Result<()> {
+    pub fn close_negotiation<'info>(ctx: Context<'_, '_, '_, 'info, CloseNegotiation<'info>>) -> Result<()> {
         super::instructions::close::handler(ctx)
     }
+
+    pub fn cancel_invitation(ctx: Context<CancelInvitation>) -> Result<()> {
+        super::instructions::cancel_inv::handler(ctx)
+    }
+
+    pub fn create_auction(
+        ctx: Context<CreateAuction>,
+        auction_id: u64,
+        params: AuctionParams,
+    ) -> Result<()> {
+        super::instructions::create_auction::handler(ctx, auction_id, params)
+    }
+
+    pub fn place_bid(ctx: Context<PlaceBid>, amount: u64) -> Result<()> {
+        super::instructions::place_bid::handler(ctx, amount)
+    }
+
+    pub fn buy_auction(ctx: Context<BuyAuction>, max_price: u64) -> Result<()> {
+        super::instructions::buy_auction::handler(ctx, max_price)
+    }
+
+    pub fn settle_auction(ctx: Context<SettleAuction>) -> Result<()> {
+        super::instructions::settle_auction::handler(ctx)
+    }
+
+    pub fn close_auction(ctx: Context<CloseAuction>) -> Result<()> {
+        super::instructions::close_auction::handler(ctx)
+    }
+
+    pub fn commit_bid(ctx: Context<CommitBid>, commitment: [u8; 32], deposit: u64) -> Result<()> {
+        super::instructions::commit_bid::handler(ctx, commitment, deposit)
+    }
+
+    pub fn reveal_bid(ctx: Context<RevealBid>, amount: u64, salt: [u8; 32]) -> Result<()> {
+        super::instructions::reveal_bid::handler(ctx, amount, salt)
+    }
+
+    pub fn withdraw_bid(ctx: Context<WithdrawBid>) -> Result<()> {
+        super::instructions::withdraw_bid::handler(ctx)
+    }
+
+    pub fn create_reverse_auction(
+        ctx: Context<CreateReverseAuction>,
+        auction_id: u64,
+        params: ReverseAuctionParams,
+    ) -> Result<()> {
+        super::instructions::create_reverse_auction::handler(ctx, auction_id, params)
+    }
+
+    pub fn post_bond(ctx: Context<PostBond>) -> Result<()> {
+        super::instructions::post_bond::handler(ctx)
+    }
+
+    pub fn place_reverse_bid(ctx: Context<PlaceReverseBid>, amount: u64) -> Result<()> {
+        super::instructions::place_reverse_bid::handler(ctx, amount)
+    }
+
+    pub fn settle_reverse_auction(ctx: Context<SettleReverseAuction>) -> Result<()> {
+        super::instructions::settle_reverse_auction::handler(ctx)
+    }
+
+    pub fn withdraw_bond(ctx: Context<WithdrawBond>) -> Result<()> {
+        super::instructions::withdraw_bond::handler(ctx)
+    }
+
+    pub fn close_reverse_auction(ctx: Context<CloseReverseAuction>) -> Result<()> {
+        super::instructions::close_reverse_auction::handler(ctx)
+    }
+
+    pub fn create_coalition(ctx: Context<CreateCoalition>) -> Result<()> {
+        super::instructions::create_coalition::handler(ctx)
+    }
+
+    pub fn join_coalition(ctx: Context<JoinCoalition>, amount: u64) -> Result<()> {
+        super::instructions::join_coalition::handler(ctx, amount)
+    }
+
+    pub fn exit_coalition(ctx: Context<ExitCoalition>) -> Result<()> {
+        super::instructions::exit_coalition::handler(ctx)
+    }
+
+    pub fn migrate_negotiation(ctx: Context<MigrateNegotiation>) -> Result<()> {
+        super::instructions::migrate_negotiation::handler(ctx)
+    }
+
+    pub fn migrate_config(ctx: Context<MigrateConfig>) -> Result<()> {
+        super::instructions::migrate_config::handler(ctx)
+    }
 }
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct InitializeConfig<'info> {
     #[account(mut)]
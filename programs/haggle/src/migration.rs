@@ -0,0 +1,291 @@
+use std::cell::{Ref, RefMut};
+
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{self, Transfer};
+
+use crate::state::{NegotiationState, NegotiationStatus, OfferSide, ProtocolConfig, ZopaPhase};
+
+// Older account layouts, which programs deployed at that point still hold.
+// They share the current discriminators and are told apart by their exact
+// length; `migrate_negotiation` and `migrate_config` rewrite them in the
+// current layout. Version 1 added `version` and the reserved space, so most
+// later versions only need to carve their fields out of it. Version 2 of
+// `NegotiationState` is the zero-copy layout, which reorders the fields.
+
+/// `NegotiationState` version 0: no quote, payout, template or coalition.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct NegotiationStateV0 {
+    pub buyer: Pubkey,
+    pub seller: Pubkey,
+    pub session_id: u64,
+    pub status: NegotiationStatus,
+    pub current_round: u8,
+    pub current_offer_amount: u64,
+    pub current_offer_by: Pubkey,
+    pub offer_side: OfferSide,
+    pub service_hash: [u8; 32],
+    pub escrow_amount: u64,
+    pub effective_escrow: u64,
+    pub token_mint: Pubkey,
+    pub max_rounds: u8,
+    pub decay_rate_bps: u16,
+    pub response_window: i64,
+    pub global_deadline: i64,
+    pub min_offer_bps: u16,
+    pub protocol_fee_bps: u16,
+    pub zopa_enabled: bool,
+    pub created_at: i64,
+    pub last_offer_at: i64,
+    pub settled_at: i64,
+    pub settled_amount: u64,
+    pub buyer_commitment: [u8; 32],
+    pub seller_commitment: [u8; 32],
+    pub zopa_phase: ZopaPhase,
+    pub metadata: [u8; 64],
+    pub bump: u8,
+}
+
+impl NegotiationStateV0 {
+    /// Account size, discriminator included.
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+
+    /// The same negotiation in the version 1 layout. Fields added since take
+    /// the values a negotiation created without them gets.
+    pub fn upgrade(self) -> NegotiationStateV1 {
+        NegotiationStateV1 {
+            buyer: self.buyer,
+            seller: self.seller,
+            session_id: self.session_id,
+            status: self.status,
+            current_round: self.current_round,
+            current_offer_amount: self.current_offer_amount,
+            current_offer_by: self.current_offer_by,
+            offer_side: self.offer_side,
+            service_hash: self.service_hash,
+            escrow_amount: self.escrow_amount,
+            effective_escrow: self.effective_escrow,
+            token_mint: self.token_mint,
+            max_rounds: self.max_rounds,
+            decay_rate_bps: self.decay_rate_bps,
+            response_window: self.response_window,
+            global_deadline: self.global_deadline,
+            min_offer_bps: self.min_offer_bps,
+            protocol_fee_bps: self.protocol_fee_bps,
+            zopa_enabled: self.zopa_enabled,
+            created_at: self.created_at,
+            last_offer_at: self.last_offer_at,
+            settled_at: self.settled_at,
+            settled_amount: self.settled_amount,
+            buyer_commitment: self.buyer_commitment,
+            seller_commitment: self.seller_commitment,
+            zopa_phase: self.zopa_phase,
+            price_feed: [0u8; 32],
+            mint_decimals: 0,
+            payout_mint: Pubkey::default(),
+            swap_adapter: Pubkey::default(),
+            max_slippage_bps: 0,
+            template: Pubkey::default(),
+            coalition: Pubkey::default(),
+            metadata: self.metadata,
+            bump: self.bump,
+            version: 1,
+            reserved: [0u8; 128],
+        }
+    }
+}
+
+/// `NegotiationState` version 1: Borsh-serialized, before zero-copy.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct NegotiationStateV1 {
+    pub buyer: Pubkey,
+    pub seller: Pubkey,
+    pub session_id: u64,
+    pub status: NegotiationStatus,
+    pub current_round: u8,
+    pub current_offer_amount: u64,
+    pub current_offer_by: Pubkey,
+    pub offer_side: OfferSide,
+    pub service_hash: [u8; 32],
+    pub escrow_amount: u64,
+    pub effective_escrow: u64,
+    pub token_mint: Pubkey,
+    pub max_rounds: u8,
+    pub decay_rate_bps: u16,
+    pub response_window: i64,
+    pub global_deadline: i64,
+    pub min_offer_bps: u16,
+    pub protocol_fee_bps: u16,
+    pub zopa_enabled: bool,
+    pub created_at: i64,
+    pub last_offer_at: i64,
+    pub settled_at: i64,
+    pub settled_amount: u64,
+    pub buyer_commitment: [u8; 32],
+    pub seller_commitment: [u8; 32],
+    pub zopa_phase: ZopaPhase,
+    pub price_feed: [u8; 32],
+    pub mint_decimals: u8,
+    pub payout_mint: Pubkey,
+    pub swap_adapter: Pubkey,
+    pub max_slippage_bps: u16,
+    pub template: Pubkey,
+    pub coalition: Pubkey,
+    pub metadata: [u8; 64],
+    pub bump: u8,
+    pub version: u8,
+    pub reserved: [u8; 128],
+}
+
+impl NegotiationStateV1 {
+    /// Account size, discriminator included.
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+
+    /// The same negotiation in the current, zero-copy layout.
+    pub fn upgrade(self) -> NegotiationState {
+        NegotiationState {
+            buyer: self.buyer,
+            seller: self.seller,
+            session_id: self.session_id,
+            status: self.status as u8,
+            current_round: self.current_round,
+            offer_side: self.offer_side as u8,
+            zopa_phase: self.zopa_phase as u8,
+            zopa_enabled: self.zopa_enabled as u8,
+            max_rounds: self.max_rounds,
+            decay_rate_bps: self.decay_rate_bps,
+            current_offer_amount: self.current_offer_amount,
+            escrow_amount: self.escrow_amount,
+            effective_escrow: self.effective_escrow,
+            response_window: self.response_window,
+            global_deadline: self.global_deadline,
+            created_at: self.created_at,
+            last_offer_at: self.last_offer_at,
+            settled_at: self.settled_at,
+            settled_amount: self.settled_amount,
+            current_offer_by: self.current_offer_by,
+            service_hash: self.service_hash,
+            token_mint: self.token_mint,
+            buyer_commitment: self.buyer_commitment,
+            seller_commitment: self.seller_commitment,
+            price_feed: self.price_feed,
+            payout_mint: self.payout_mint,
+            swap_adapter: self.swap_adapter,
+            template: self.template,
+            coalition: self.coalition,
+            metadata: self.metadata,
+            min_offer_bps: self.min_offer_bps,
+            protocol_fee_bps: self.protocol_fee_bps,
+            max_slippage_bps: self.max_slippage_bps,
+            mint_decimals: self.mint_decimals,
+            bump: self.bump,
+            version: NegotiationState::VERSION,
+            padding: [0u8; 7],
+            crank_deposit: 0,
+            crank_reward_bps: 0,
+            crank_padding: [0u8; 6],
+            invitation_deadline: 0,
+            termination_reason: 0,
+            final_offer: 0,
+            admitted: 0,
+            reserved: [0u8; 101],
+        }
+    }
+}
+
+/// `ProtocolConfig` version 0.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct ProtocolConfigV0 {
+    pub authority: Pubkey,
+    pub treasury: Pubkey,
+    pub default_decay_rate_bps: u16,
+    pub default_response_window: i64,
+    pub default_protocol_fee_bps: u16,
+    pub default_max_rounds: u8,
+    pub total_negotiations: u64,
+    pub total_settled_volume: u64,
+    pub total_fees_collected: u64,
+    pub is_paused: bool,
+    pub bump: u8,
+}
+
+impl ProtocolConfigV0 {
+    /// Account size, discriminator included.
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+
+    pub fn upgrade(self) -> ProtocolConfig {
+        ProtocolConfig {
+            authority: self.authority,
+            treasury: self.treasury,
+            default_decay_rate_bps: self.default_decay_rate_bps,
+            default_response_window: self.default_response_window,
+            default_protocol_fee_bps: self.default_protocol_fee_bps,
+            default_max_rounds: self.default_max_rounds,
+            total_negotiations: self.total_negotiations,
+            total_settled_volume: self.total_settled_volume,
+            total_fees_collected: self.total_fees_collected,
+            is_paused: self.is_paused,
+            bump: self.bump,
+            version: ProtocolConfig::VERSION,
+            crank_reward_lamports: 0,
+            crank_reward_bps: 0,
+            total_cranks: 0,
+            total_crank_rewards_lamports: 0,
+            total_crank_rewards_tokens: 0,
+            reserved: [0u8; 30],
+        }
+    }
+}
+
+/// Resizes `account` to `len` bytes, topping its rent up from `payer`.
+pub(crate) fn resize<'info>(
+    account: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    len: usize,
+) -> Result<()> {
+    let shortfall = Rent::get()?
+        .minimum_balance(len)
+        .saturating_sub(account.lamports());
+    if shortfall > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                system_program.clone(),
+                Transfer {
+                    from: payer.clone(),
+                    to: account.clone(),
+                },
+            ),
+            shortfall,
+        )?;
+    }
+    account.realloc(len, true)?;
+    Ok(())
+}
+
+/// `AccountLoader::load` slices the current layout out of the account and
+/// panics on the shorter layouts of negotiations not yet migrated. Handlers
+/// load through this instead, so those fail as they did before zero-copy.
+pub(crate) trait LoadCurrent {
+    fn load_current(&self) -> Result<Ref<'_, NegotiationState>>;
+    fn load_current_mut(&self) -> Result<RefMut<'_, NegotiationState>>;
+}
+
+impl LoadCurrent for AccountLoader<'_, NegotiationState> {
+    fn load_current(&self) -> Result<Ref<'_, NegotiationState>> {
+        require_current(self)?;
+        self.load()
+    }
+
+    fn load_current_mut(&self) -> Result<RefMut<'_, NegotiationState>> {
+        require_current(self)?;
+        self.load_mut()
+    }
+}
+
+fn require_current(loader: &AccountLoader<NegotiationState>) -> Result<()> {
+    require!(
+        loader.as_ref().data_len() >= NegotiationState::LEN,
+        ErrorCode::AccountDidNotDeserialize
+    );
+    Ok(())
+}
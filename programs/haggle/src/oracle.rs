@@ -0,0 +1,131 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::HaggleError;
+use crate::state::NegotiationState;
+
+// Quote-denominated negotiations price offers with a Pyth pull-oracle
+// `PriceUpdateV2` account. The layout is mirrored here rather than pulling in
+// the receiver SDK, whose Solana version does not match ours.
+
+/// Pyth Solana receiver program, which owns every `PriceUpdateV2` account.
+pub const PYTH_RECEIVER_ID: Pubkey = pubkey!("rec5EKMGg6MxZYaMdyBfgwp4d5rB9T1VQH5pJv5LtFJ");
+
+/// Pyth push oracle program, whose PDAs hold the continuously updated
+/// sponsored feeds.
+pub const PYTH_PUSH_ORACLE_ID: Pubkey = pubkey!("pythWSnswVUd12oZpeFP8e9CVaEqJg25g1Vtc2biRsT");
+
+/// Offers in a quote-denominated negotiation count millionths of the quote
+/// currency, so 1_000_000 is one US dollar on a USD feed.
+pub const QUOTE_DECIMALS: u8 = 6;
+
+/// Oldest price, in seconds, an offer or settlement may be valued at.
+pub const MAX_PRICE_AGE: i64 = 60;
+
+/// Widest confidence interval accepted, in basis points of the price.
+pub const MAX_PRICE_CONF_BPS: u64 = 200;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum VerificationLevel {
+    Partial { num_signatures: u8 },
+    Full,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct PriceFeedMessage {
+    pub feed_id: [u8; 32],
+    pub price: i64,
+    pub conf: u64,
+    pub exponent: i32,
+    pub publish_time: i64,
+    pub prev_publish_time: i64,
+    pub ema_price: i64,
+    pub ema_conf: u64,
+}
+
+/// Mirror of the receiver program's `PriceUpdateV2` account.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct PriceUpdateV2 {
+    pub write_authority: Pubkey,
+    pub verification_level: VerificationLevel,
+    pub price_message: PriceFeedMessage,
+    pub posted_slot: u64,
+}
+
+impl PriceUpdateV2 {
+    pub const DISCRIMINATOR: [u8; 8] = [34, 241, 35, 99, 157, 126, 244, 205];
+}
+
+/// A checked price: quote currency per whole token is `price * 10^exponent`.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Price {
+    price: u64,
+    exponent: i32,
+}
+
+impl Price {
+    /// Converts `quote_amount` (see `QUOTE_DECIMALS`) to base units of a mint
+    /// with `mint_decimals`, rounding down.
+    pub(crate) fn to_mint_units(self, quote_amount: u64, mint_decimals: u8) -> Result<u64> {
+        let shift = mint_decimals as i32 - QUOTE_DECIMALS as i32 - self.exponent;
+        let scale = 10u128
+            .checked_pow(shift.unsigned_abs())
+            .ok_or(HaggleError::Overflow)?;
+        let amount = if shift >= 0 {
+            (quote_amount as u128)
+                .checked_mul(scale)
+                .ok_or(HaggleError::Overflow)?
+                / self.price as u128
+        } else {
+            quote_amount as u128
+                / (self.price as u128)
+                    .checked_mul(scale)
+                    .ok_or(HaggleError::Overflow)?
+        };
+        u64::try_from(amount).map_err(|_| error!(HaggleError::Overflow))
+    }
+}
+
+/// Reads and checks the price for `feed_id`: it must come from a fully
+/// verified update no older than `MAX_PRICE_AGE` with a confidence interval
+/// within `MAX_PRICE_CONF_BPS`.
+fn load(info: &AccountInfo, feed_id: &[u8; 32], now: i64) -> Result<Price> {
+    require!(info.owner == &PYTH_RECEIVER_ID, HaggleError::InvalidPriceFeed);
+    let data = info.try_borrow_data()?;
+    require!(
+        data.len() > 8 && data[..8] == PriceUpdateV2::DISCRIMINATOR,
+        HaggleError::InvalidPriceFeed
+    );
+    let update = PriceUpdateV2::deserialize(&mut &data[8..])
+        .map_err(|_| error!(HaggleError::InvalidPriceFeed))?;
+    let message = update.price_message;
+
+    require!(update.verification_level == VerificationLevel::Full, HaggleError::InvalidPriceFeed);
+    require!(message.feed_id == *feed_id, HaggleError::InvalidPriceFeed);
+    require!(message.price > 0, HaggleError::InvalidPriceFeed);
+    require!(
+        now.saturating_sub(message.publish_time) <= MAX_PRICE_AGE,
+        HaggleError::StalePrice
+    );
+
+    let price = message.price as u64;
+    require!(
+        (message.conf as u128) * 10000 <= (price as u128) * (MAX_PRICE_CONF_BPS as u128),
+        HaggleError::PriceUncertain
+    );
+
+    Ok(Price { price, exponent: message.exponent })
+}
+
+/// The price `negotiation`'s quote-unit offers are valued at, or `None` when
+/// its offers are already in mint units.
+pub(crate) fn quote_price(
+    negotiation: &NegotiationState,
+    price_update: Option<&AccountInfo>,
+    now: i64,
+) -> Result<Option<Price>> {
+    if !negotiation.is_quoted() {
+        return Ok(None);
+    }
+    let info = price_update.ok_or(HaggleError::InvalidPriceFeed)?;
+    load(info, &negotiation.price_feed, now).map(Some)
+}
@@ -0,0 +1,62 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::HaggleError;
+use crate::state::{AcceptancePolicy, NegotiationState};
+
+// Instructions that open or end a negotiation take the seller's policy PDA
+// whether or not the seller has created it; an empty account means no policy.
+
+fn load(info: &AccountInfo) -> Result<Option<AcceptancePolicy>> {
+    if info.owner != &crate::ID || info.data_is_empty() {
+        return Ok(None);
+    }
+    let data = info.try_borrow_data()?;
+    Ok(Some(AcceptancePolicy::try_deserialize(&mut &data[..])?))
+}
+
+fn store(info: &AccountInfo, policy: &AcceptancePolicy) -> Result<()> {
+    let mut data = info.try_borrow_mut_data()?;
+    policy.try_serialize(&mut &mut data[..])
+}
+
+/// Checks a new negotiation against the seller's policy and takes one of its
+/// open slots, marking the negotiation admitted. Returns whether the seller
+/// auto-accepts the invitation.
+pub(crate) fn admit(info: &AccountInfo, negotiation: &mut NegotiationState) -> Result<bool> {
+    let Some(mut policy) = load(info)? else {
+        return Ok(false);
+    };
+
+    require!(policy.allows_buyer(&negotiation.buyer), HaggleError::BuyerNotAllowed);
+    require!(negotiation.escrow_amount >= policy.min_escrow, HaggleError::PolicyViolation);
+    require!(policy.accepts_mint(&negotiation.token_mint), HaggleError::PolicyViolation);
+    require!(
+        policy.required_template == Pubkey::default()
+            || policy.required_template == negotiation.template,
+        HaggleError::PolicyViolation
+    );
+    require!(policy.has_room(), HaggleError::TooManyOpenNegotiations);
+
+    policy.open_negotiations = policy.open_negotiations
+        .checked_add(1)
+        .ok_or(HaggleError::Overflow)?;
+    store(info, &policy)?;
+    negotiation.admitted = 1;
+
+    Ok(policy.auto_accept)
+}
+
+/// Frees the slot an ending negotiation held. Negotiations opened before the
+/// policy existed never took one and leave the count alone. One admitted by a
+/// policy since closed and recreated can find the count already at zero.
+pub(crate) fn release(info: &AccountInfo, negotiation: &mut NegotiationState) -> Result<()> {
+    if !negotiation.is_admitted() {
+        return Ok(());
+    }
+    negotiation.admitted = 0;
+    if let Some(mut policy) = load(info)? {
+        policy.open_negotiations = policy.open_negotiations.saturating_sub(1);
+        store(info, &policy)?;
+    }
+    Ok(())
+}
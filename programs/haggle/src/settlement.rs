@@ -0,0 +1,376 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, CloseAccount, TokenAccount, Transfer};
+
+use crate::errors::HaggleError;
+use crate::events::Payout;
+use crate::state::{NegotiationState, ProtocolConfig};
+use crate::swap::SwapRoute;
+
+// Every token that leaves a negotiation or auction vault does so through a
+// `PayoutPlan`: a list of (destination, amount, reason) legs, plus whatever
+// deliberately stays behind for later claims. Plans are plain data, built and
+// checked without any accounts, and `Settlement::payout` executes one,
+// emitting a `Payout` event per leg.
+
+/// Why tokens leave a vault.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PayoutReason {
+    /// The agreed price less the protocol fee, to the seller.
+    Seller,
+    /// The protocol fee on the agreed price, to the treasury.
+    ProtocolFee,
+    /// Escrow lost to decay by a negotiation that ended without a deal, to
+    /// the treasury.
+    Decay,
+    /// Unspent escrow, an outbid bid or a bond, back to its owner.
+    Refund,
+    /// The forfeited part of an unrevealed Vickrey deposit, to the seller.
+    Slash,
+    /// A share of an expired negotiation's refund, to whoever expired it.
+    CrankReward,
+}
+
+/// One transfer out of a vault.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PayoutLeg<T> {
+    pub destination: T,
+    pub amount: u64,
+    pub reason: PayoutReason,
+}
+
+/// Every transfer an instruction makes out of a vault, and what it leaves
+/// there. Legs can never overdraw the balance the plan was made for, and
+/// `verify` requires the legs and the retained amount to add up to it
+/// exactly, so nothing is paid twice or stranded by accident.
+#[derive(Clone, Debug)]
+pub struct PayoutPlan<T> {
+    vault_balance: u64,
+    legs: Vec<PayoutLeg<T>>,
+    retained: u64,
+    unallocated: u64,
+}
+
+/// How a settled amount divides between the treasury and the seller.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Split {
+    pub protocol_fee: u64,
+    pub seller_payment: u64,
+}
+
+impl Split {
+    pub fn new(amount: u64, protocol_fee_bps: u16) -> Result<Self> {
+        let protocol_fee = bps_of(amount, protocol_fee_bps)?;
+
+        let seller_payment = amount
+            .checked_sub(protocol_fee)
+            .ok_or(HaggleError::Overflow)?;
+
+        Ok(Split { protocol_fee, seller_payment })
+    }
+}
+
+/// `bps` of `amount`, rounded down.
+fn bps_of(amount: u64, bps: u16) -> Result<u64> {
+    Ok(amount
+        .checked_mul(bps as u64)
+        .ok_or(HaggleError::Overflow)?
+        / 10000)
+}
+
+impl<T> PayoutPlan<T> {
+    pub fn new(vault_balance: u64) -> Self {
+        PayoutPlan {
+            vault_balance,
+            legs: Vec::new(),
+            retained: 0,
+            unallocated: vault_balance,
+        }
+    }
+
+    pub fn vault_balance(&self) -> u64 {
+        self.vault_balance
+    }
+
+    pub fn legs(&self) -> &[PayoutLeg<T>] {
+        &self.legs
+    }
+
+    /// What stays in the vault after the plan is paid.
+    pub fn retained(&self) -> u64 {
+        self.retained
+    }
+
+    /// What is neither paid nor retained yet.
+    pub fn unallocated(&self) -> u64 {
+        self.unallocated
+    }
+
+    /// Total of the legs paid for `reason`.
+    pub fn paid(&self, reason: PayoutReason) -> u64 {
+        self.legs
+            .iter()
+            .filter(|leg| leg.reason == reason)
+            .map(|leg| leg.amount)
+            .sum()
+    }
+
+    /// Adds a leg, skipping empty ones. Fails if the vault cannot cover it on
+    /// top of the legs already planned.
+    pub fn pay(&mut self, destination: T, amount: u64, reason: PayoutReason) -> Result<()> {
+        if amount == 0 {
+            return Ok(());
+        }
+        self.unallocated = self.unallocated
+            .checked_sub(amount)
+            .ok_or(HaggleError::PayoutMismatch)?;
+        self.legs.push(PayoutLeg { destination, amount, reason });
+        Ok(())
+    }
+
+    /// Pays `amount` as a sale: the protocol fee to `treasury` and the rest
+    /// to `seller`.
+    pub fn sale(&mut self, amount: u64, protocol_fee_bps: u16, seller: T, treasury: T) -> Result<Split> {
+        let split = Split::new(amount, protocol_fee_bps)?;
+        self.pay(treasury, split.protocol_fee, PayoutReason::ProtocolFee)?;
+        self.pay(seller, split.seller_payment, PayoutReason::Seller)?;
+        Ok(split)
+    }
+
+    /// Refunds everything not yet allocated to `destination`.
+    pub fn refund_rest(&mut self, destination: T) -> Result<()> {
+        self.pay(destination, self.unallocated, PayoutReason::Refund)
+    }
+
+    /// Leaves everything not yet allocated in the vault, for claims made by
+    /// later instructions.
+    pub fn retain_rest(&mut self) {
+        self.retained += self.unallocated;
+        self.unallocated = 0;
+    }
+
+    /// Checks that the legs and the retained amount add up to the vault
+    /// balance.
+    pub fn verify(&self) -> Result<()> {
+        let accounted = self.legs
+            .iter()
+            .try_fold(self.retained, |total, leg| total.checked_add(leg.amount));
+        require!(accounted == Some(self.vault_balance), HaggleError::PayoutMismatch);
+        Ok(())
+    }
+}
+
+/// How a negotiation ended, for dividing its vault.
+#[derive(Clone, Debug)]
+pub enum Outcome<T> {
+    /// Accepted at `amount` mint units: `seller` is paid that less the
+    /// protocol fee.
+    Settled { seller: T, amount: u64, protocol_fee_bps: u16 },
+    /// Rejected or expired: the escrow lost to decay goes to the treasury,
+    /// and `crank_reward` to whoever expired it.
+    Terminated { escrow_decay: u64, crank_reward: Option<CrankReward<T>> },
+}
+
+/// Whoever cranked a negotiation's expiry, and their share of the refund.
+#[derive(Clone, Debug)]
+pub struct CrankReward<T> {
+    pub cranker: T,
+    pub bps: u16,
+}
+
+/// Divides a negotiation's vault at its end. Whatever the outcome leaves is
+/// refunded to `buyer`, or stays for coalition members to claim when the
+/// escrow is `pooled`.
+pub fn negotiation_plan<T>(
+    vault_balance: u64,
+    outcome: Outcome<T>,
+    pooled: bool,
+    buyer: T,
+    treasury: T,
+) -> Result<PayoutPlan<T>> {
+    let mut plan = PayoutPlan::new(vault_balance);
+    match outcome {
+        Outcome::Settled { seller, amount, protocol_fee_bps } => {
+            require!(amount <= vault_balance, HaggleError::OfferExceedsEscrow);
+            plan.sale(amount, protocol_fee_bps, seller, treasury)?;
+        }
+        Outcome::Terminated { escrow_decay, crank_reward } => {
+            plan.pay(treasury, escrow_decay.min(vault_balance), PayoutReason::Decay)?;
+            if let Some(CrankReward { cranker, bps }) = crank_reward {
+                let reward = bps_of(plan.unallocated(), bps)?;
+                plan.pay(cranker, reward, PayoutReason::CrankReward)?;
+            }
+        }
+    }
+    if pooled {
+        plan.retain_rest();
+    } else {
+        plan.refund_rest(buyer)?;
+    }
+    Ok(plan)
+}
+
+/// Adds a sale to the protocol totals.
+pub(crate) fn record_sale(config: &mut ProtocolConfig, amount: u64, protocol_fee: u64) -> Result<()> {
+    config.total_settled_volume = config.total_settled_volume
+        .checked_add(amount)
+        .ok_or(HaggleError::Overflow)?;
+    config.total_fees_collected = config.total_fees_collected
+        .checked_add(protocol_fee)
+        .ok_or(HaggleError::Overflow)?;
+    Ok(())
+}
+
+/// A negotiation's PDA seeds, copied out of the state so they outlive its
+/// borrow: the negotiation signs for its vault, so the borrow has to end
+/// before any CPI.
+pub(crate) struct NegotiationSigner {
+    buyer: Pubkey,
+    seller: Pubkey,
+    session_id: [u8; 8],
+    bump: [u8; 1],
+}
+
+impl NegotiationSigner {
+    pub fn new(negotiation: &NegotiationState) -> Self {
+        NegotiationSigner {
+            buyer: negotiation.buyer,
+            seller: negotiation.seller,
+            session_id: negotiation.session_id.to_le_bytes(),
+            bump: [negotiation.bump],
+        }
+    }
+
+    pub fn seeds(&self) -> [&[u8]; 5] {
+        [
+            b"negotiation",
+            self.buyer.as_ref(),
+            self.seller.as_ref(),
+            &self.session_id,
+            &self.bump,
+        ]
+    }
+}
+
+/// Vault-side accounts a plan pays out of. Shared by every negotiation and
+/// auction instruction that moves tokens out of a vault.
+pub(crate) struct Settlement<'a, 'info> {
+    pub token_program: AccountInfo<'info>,
+    pub vault: AccountInfo<'info>,
+    /// PDA that owns the vault (the negotiation or auction account).
+    pub vault_authority: AccountInfo<'info>,
+    pub signer_seeds: &'a [&'a [&'a [u8]]],
+}
+
+impl<'a, 'info> Settlement<'a, 'info> {
+    /// The vault's current balance, including any deposit made earlier in
+    /// the same instruction.
+    pub fn vault_balance(&self) -> Result<u64> {
+        Ok(self.vault_account()?.amount)
+    }
+
+    /// An empty plan for the vault's current balance.
+    pub fn plan(&self) -> Result<PayoutPlan<AccountInfo<'info>>> {
+        Ok(PayoutPlan::new(self.vault_balance()?))
+    }
+
+    /// Pays every leg of `plan` and returns the `Payout` events for the
+    /// caller to emit.
+    pub fn payout(&self, plan: &PayoutPlan<AccountInfo<'info>>) -> Result<Vec<Payout>> {
+        self.execute(plan, None)
+    }
+
+    /// As `payout`, but swaps the seller's leg through `route` into the
+    /// payout mint when the adapter quotes it within `max_slippage_bps`. A
+    /// swap that would slip further pays the leg in the escrow mint.
+    pub fn payout_with_swap(
+        &self,
+        plan: &PayoutPlan<AccountInfo<'info>>,
+        route: &SwapRoute<'_, 'info>,
+        max_slippage_bps: u16,
+    ) -> Result<Vec<Payout>> {
+        self.execute(plan, Some((route, max_slippage_bps)))
+    }
+
+    /// Refunds whatever is left in the vault, such as tokens sent to it
+    /// directly, to `destination`, so that it can be closed.
+    pub fn sweep(&self, destination: AccountInfo<'info>) -> Result<Vec<Payout>> {
+        let mut plan = self.plan()?;
+        plan.refund_rest(destination)?;
+        self.payout(&plan)
+    }
+
+    /// Closes the emptied vault, sending its rent to `destination`.
+    pub fn close_vault(&self, destination: AccountInfo<'info>) -> Result<()> {
+        require!(self.vault_balance()? == 0, HaggleError::InvalidState);
+        token::close_account(CpiContext::new_with_signer(
+            self.token_program.clone(),
+            CloseAccount {
+                account: self.vault.clone(),
+                destination,
+                authority: self.vault_authority.clone(),
+            },
+            self.signer_seeds,
+        ))
+    }
+
+    fn execute(
+        &self,
+        plan: &PayoutPlan<AccountInfo<'info>>,
+        swap: Option<(&SwapRoute<'_, 'info>, u16)>,
+    ) -> Result<Vec<Payout>> {
+        plan.verify()?;
+        let vault = self.vault_account()?;
+        require!(vault.amount == plan.vault_balance(), HaggleError::PayoutMismatch);
+
+        let timestamp = Clock::get()?.unix_timestamp;
+        let mut events = Vec::with_capacity(plan.legs().len());
+        for leg in plan.legs() {
+            let swapped = match swap {
+                Some((route, max_slippage_bps)) if leg.reason == PayoutReason::Seller => route
+                    .try_swap(self, leg.amount, max_slippage_bps)?
+                    .map(|received| (route.destination.clone(), received)),
+                _ => None,
+            };
+            let (destination, mint, received) = match swapped {
+                Some((destination, received)) => {
+                    let mint = TokenAccount::try_deserialize(&mut &destination.try_borrow_data()?[..])?.mint;
+                    (destination.key(), mint, received)
+                }
+                None => {
+                    self.transfer(leg.destination.clone(), leg.amount)?;
+                    (leg.destination.key(), vault.mint, leg.amount)
+                }
+            };
+            events.push(Payout {
+                negotiation_id: self.vault_authority.key(),
+                destination,
+                reason: leg.reason,
+                amount: leg.amount,
+                mint,
+                received,
+                timestamp,
+            });
+        }
+        Ok(events)
+    }
+
+    fn vault_account(&self) -> Result<TokenAccount> {
+        TokenAccount::try_deserialize(&mut &self.vault.try_borrow_data()?[..])
+    }
+
+    /// Moves `amount` from the vault to `to`.
+    fn transfer(&self, to: AccountInfo<'info>, amount: u64) -> Result<()> {
+        token::transfer(
+            CpiContext::new_with_signer(
+                self.token_program.clone(),
+                Transfer {
+                    from: self.vault.clone(),
+                    to,
+                    authority: self.vault_authority.clone(),
+                },
+                self.signer_seeds,
+            ),
+            amount,
+        )
+    }
+}
@@ -1,55 +1,366 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
 
-#[account]
-#[derive(InitSpace)]
+use crate::errors::HaggleError;
+
+/// Zero-copy so that handlers borrow the account in place instead of
+/// deserializing and re-serializing all of it. `repr(C)` with every field
+/// aligned and the tail padded to 8 bytes; the enum fields are stored as
+/// their variant index and read through the accessors below.
+#[account(zero_copy)]
 pub struct NegotiationState {
     // === Identity (72 bytes) ===
     pub buyer: Pubkey,
     pub seller: Pubkey,
     pub session_id: u64,
 
-    // === State (2 bytes) ===
-    pub status: NegotiationStatus,
+    // === State and small parameters (8 bytes) ===
+    /// `NegotiationStatus`; see `status()`.
+    pub status: u8,
     pub current_round: u8,
+    /// `OfferSide`; see `offer_side()`.
+    pub offer_side: u8,
+    /// `ZopaPhase`; see `zopa_phase()`.
+    pub zopa_phase: u8,
+    /// `bool`; see `zopa_enabled()`.
+    pub zopa_enabled: u8,
+    pub max_rounds: u8,
+    pub decay_rate_bps: u16,
 
-    // === Current Offer (73 bytes) ===
+    // === Amounts and timestamps (72 bytes) ===
     pub current_offer_amount: u64,
-    pub current_offer_by: Pubkey,
-    pub offer_side: OfferSide,
-    pub service_hash: [u8; 32],
-
-    // === Escrow (48 bytes) ===
     pub escrow_amount: u64,
     pub effective_escrow: u64,
-    pub token_mint: Pubkey,
-
-    // === Parameters (22 bytes) ===
-    pub max_rounds: u8,
-    pub decay_rate_bps: u16,
     pub response_window: i64,
     pub global_deadline: i64,
-    pub min_offer_bps: u16,
-    pub protocol_fee_bps: u16,
-    pub zopa_enabled: bool,
-
-    // === Timestamps (24 bytes) ===
     pub created_at: i64,
     pub last_offer_at: i64,
     pub settled_at: i64,
-
-    // === Settlement (8 bytes) ===
     pub settled_amount: u64,
 
-    // === ZOPA Detection (65 bytes) ===
+    // === Parties, terms and commitments (320 bytes) ===
+    pub current_offer_by: Pubkey,
+    pub service_hash: [u8; 32],
+    pub token_mint: Pubkey,
     pub buyer_commitment: [u8; 32],
     pub seller_commitment: [u8; 32],
-    pub zopa_phase: ZopaPhase,
+    /// Pyth feed id of the quote currency offers are denominated in, or zero
+    /// when offers are in base units of `token_mint`.
+    pub price_feed: [u8; 32],
+    /// Mint the seller is paid in through `swap_adapter`, or default to be
+    /// paid in `token_mint`.
+    pub payout_mint: Pubkey,
+    pub swap_adapter: Pubkey,
+    /// `NegotiationTemplate` the terms were copied from, or default.
+    pub template: Pubkey,
+    /// `BuyerCoalition` pooling the buyer side, or default when the buyer
+    /// escrows alone.
+    pub coalition: Pubkey,
 
     // === Metadata (64 bytes) ===
     pub metadata: [u8; 64],
 
-    // === Bump (1 byte) ===
+    // === Fees and slippage (6 bytes) ===
+    pub min_offer_bps: u16,
+    pub protocol_fee_bps: u16,
+    /// Most the swap may fall short of the adapter's spot price.
+    pub max_slippage_bps: u16,
+
+    // === Decimals, bump and version (3 bytes + 7 padding) ===
+    pub mint_decimals: u8,
     pub bump: u8,
+    /// Layout version; see `migration` for older layouts.
+    pub version: u8,
+    pub padding: [u8; 7],
+
+    // === Crank reward (10 bytes + 6 padding) ===
+    /// Lamports the buyer prepaid at creation, on top of the rent, for
+    /// whoever cranks the negotiation's cleanup; zero once paid out.
+    pub crank_deposit: u64,
+    /// Share of the refund paid to whoever expires the negotiation.
+    pub crank_reward_bps: u16,
+    pub crank_padding: [u8; 6],
+
+    // === Invitation (8 bytes) ===
+    /// When the invitation lapses if the seller has not accepted it, or zero
+    /// to keep it open until `global_deadline`.
+    pub invitation_deadline: i64,
+
+    // === Termination (1 byte) ===
+    /// `TerminationReason`; see `termination_reason()`.
+    pub termination_reason: u8,
+
+    // === Final offer (1 byte) ===
+    /// `bool`; see `is_final_offer()`.
+    pub final_offer: u8,
+
+    // === Acceptance policy (1 byte) ===
+    /// `bool`; see `is_admitted()`.
+    pub admitted: u8,
+
+    // === Reserved (101 bytes) ===
+    /// Zeroed space that later fields are carved out of, so adding one does
+    /// not change the account size.
+    pub reserved: [u8; 101],
+}
+
+impl NegotiationState {
+    pub const VERSION: u8 = 2;
+
+    /// Account size, discriminator included.
+    pub const LEN: usize = 8 + std::mem::size_of::<Self>();
+
+    pub fn status(&self) -> Result<NegotiationStatus> {
+        NegotiationStatus::ALL
+            .get(self.status as usize)
+            .copied()
+            .ok_or_else(|| error!(HaggleError::InvalidState))
+    }
+
+    pub fn set_status(&mut self, status: NegotiationStatus) {
+        self.status = status as u8;
+    }
+
+    pub fn offer_side(&self) -> Result<OfferSide> {
+        OfferSide::ALL
+            .get(self.offer_side as usize)
+            .copied()
+            .ok_or_else(|| error!(HaggleError::InvalidState))
+    }
+
+    pub fn set_offer_side(&mut self, side: OfferSide) {
+        self.offer_side = side as u8;
+    }
+
+    pub fn zopa_phase(&self) -> Result<ZopaPhase> {
+        ZopaPhase::ALL
+            .get(self.zopa_phase as usize)
+            .copied()
+            .ok_or_else(|| error!(HaggleError::InvalidState))
+    }
+
+    pub fn set_zopa_phase(&mut self, phase: ZopaPhase) {
+        self.zopa_phase = phase as u8;
+    }
+
+    pub fn termination_reason(&self) -> Result<TerminationReason> {
+        TerminationReason::ALL
+            .get(self.termination_reason as usize)
+            .copied()
+            .ok_or_else(|| error!(HaggleError::InvalidState))
+    }
+
+    pub fn set_termination_reason(&mut self, reason: TerminationReason) {
+        self.termination_reason = reason as u8;
+    }
+
+    pub fn zopa_enabled(&self) -> bool {
+        self.zopa_enabled != 0
+    }
+
+    /// The current offer was made final, explicitly or by being in the last
+    /// round: the counterparty can only accept it or let the deal end.
+    pub fn is_final_offer(&self) -> bool {
+        self.final_offer != 0
+    }
+
+    /// The negotiation holds one of the seller's `AcceptancePolicy` open
+    /// slots, to be given back when it ends.
+    pub fn is_admitted(&self) -> bool {
+        self.admitted != 0
+    }
+
+    /// Pooled negotiations keep refunds in the vault for coalition members to
+    /// claim pro rata instead of paying them to the buyer.
+    pub fn is_pooled(&self) -> bool {
+        self.coalition != Pubkey::default()
+    }
+
+    /// Quote-denominated negotiations are valued against `price_feed` and
+    /// converted to the escrow mint at settlement.
+    pub fn is_quoted(&self) -> bool {
+        self.price_feed != [0u8; 32]
+    }
+
+    /// Cross-mint negotiations swap the seller's proceeds into `payout_mint`
+    /// at settlement.
+    pub fn is_cross_mint(&self) -> bool {
+        self.payout_mint != Pubkey::default()
+    }
+
+    /// When the negotiation can be expired, and why. An invitation the
+    /// seller has not accepted lapses at `invitation_deadline`, if that comes
+    /// first.
+    pub fn expiry(&self) -> Result<(i64, TerminationReason)> {
+        Ok(if self.status()? == NegotiationStatus::Created
+            && self.invitation_deadline != 0
+            && self.invitation_deadline < self.global_deadline
+        {
+            (self.invitation_deadline, TerminationReason::CounterpartyUnresponsive)
+        } else {
+            (self.global_deadline, TerminationReason::Timeout)
+        })
+    }
+
+    /// When anyone, not just the buyer, may close the ended negotiation:
+    /// `CLOSE_GRACE_PERIOD` after settlement, or after the global deadline
+    /// when it was rejected or expired.
+    pub fn closable_at(&self) -> Result<i64> {
+        let ended_at = match self.status()? {
+            NegotiationStatus::Settled => self.settled_at,
+            _ => self.global_deadline,
+        };
+        Ok(ended_at.saturating_add(CLOSE_GRACE_PERIOD))
+    }
+}
+
+/// How long an ended negotiation stays the buyer's alone to close.
+pub const CLOSE_GRACE_PERIOD: i64 = 7 * 24 * 60 * 60;
+/// Lamports of the reclaimed rent paid to anyone else who closes it.
+pub const CLOSE_BOUNTY: u64 = 100_000;
+
+pub const MAX_TEMPLATE_MINTS: usize = 8;
+
+/// Standard negotiation terms an owner registers once so that every
+/// negotiation created from it gets the same parameters.
+#[account]
+#[derive(InitSpace)]
+pub struct NegotiationTemplate {
+    pub owner: Pubkey,
+    pub template_id: u64,
+    pub max_rounds: u8,
+    pub decay_rate_bps: u16,
+    pub response_window: i64,
+    pub global_deadline_offset: i64,
+    pub min_offer_bps: u16,
+    pub protocol_fee_bps: u16,
+    pub zopa_enabled: bool,
+    /// Mints negotiations may escrow; empty allows any.
+    #[max_len(MAX_TEMPLATE_MINTS)]
+    pub allowed_mints: Vec<Pubkey>,
+    /// Party the owner names to resolve disputes, or default when none.
+    pub arbiter: Pubkey,
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+impl NegotiationTemplate {
+    /// The template's terms with the per-negotiation escrow, service and
+    /// invitation TTL.
+    pub fn negotiation_params(
+        &self,
+        escrow_amount: u64,
+        service_hash: [u8; 32],
+        invitation_ttl: Option<i64>,
+    ) -> NegotiationParams {
+        NegotiationParams {
+            escrow_amount,
+            service_hash,
+            max_rounds: self.max_rounds,
+            decay_rate_bps: self.decay_rate_bps,
+            response_window: self.response_window,
+            global_deadline_offset: self.global_deadline_offset,
+            min_offer_bps: self.min_offer_bps,
+            protocol_fee_bps: self.protocol_fee_bps,
+            zopa_enabled: self.zopa_enabled,
+            price_feed: None,
+            payout: None,
+            invitation_ttl,
+        }
+    }
+
+    pub fn allows_mint(&self, mint: &Pubkey) -> bool {
+        self.allowed_mints.is_empty() || self.allowed_mints.contains(mint)
+    }
+}
+
+pub const MAX_POLICY_BUYERS: usize = 32;
+pub const MAX_POLICY_MINTS: usize = 8;
+
+/// A seller's terms for who may open negotiations with them. Checked by
+/// `create_negotiation` and `create_negotiation_from_template`; sellers
+/// without one accept any buyer.
+#[account]
+#[derive(InitSpace)]
+pub struct AcceptancePolicy {
+    pub seller: Pubkey,
+    pub buyer_filter: BuyerFilter,
+    /// Buyers the filter allows or denies.
+    #[max_len(MAX_POLICY_BUYERS)]
+    pub buyers: Vec<Pubkey>,
+    pub min_escrow: u64,
+    /// Mints negotiations may escrow; empty allows any.
+    #[max_len(MAX_POLICY_MINTS)]
+    pub accepted_mints: Vec<Pubkey>,
+    /// Most negotiations that may be open at once; zero for no limit.
+    pub max_open_negotiations: u16,
+    /// Negotiations created under this policy that have not yet settled,
+    /// been rejected or expired.
+    pub open_negotiations: u16,
+    /// Move negotiations that pass the policy straight to `Proposed`.
+    pub auto_accept: bool,
+    /// `NegotiationTemplate` buyers must create from, or default for any terms.
+    pub required_template: Pubkey,
+    pub bump: u8,
+}
+
+impl AcceptancePolicy {
+    pub fn allows_buyer(&self, buyer: &Pubkey) -> bool {
+        match self.buyer_filter {
+            BuyerFilter::Any => true,
+            BuyerFilter::Allow => self.buyers.contains(buyer),
+            BuyerFilter::Deny => !self.buyers.contains(buyer),
+        }
+    }
+
+    pub fn accepts_mint(&self, mint: &Pubkey) -> bool {
+        self.accepted_mints.is_empty() || self.accepted_mints.contains(mint)
+    }
+
+    pub fn has_room(&self) -> bool {
+        self.max_open_negotiations == 0 || self.open_negotiations < self.max_open_negotiations
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub enum BuyerFilter {
+    Any,
+    /// Only the listed buyers.
+    Allow,
+    /// Anyone but the listed buyers.
+    Deny,
+}
+
+pub const MAX_COALITION_MEMBERS: usize = 16;
+
+/// Buyers pooling escrow into one negotiation. The lead is the negotiation's
+/// buyer and makes every offer; members hold shares of the escrow and bear
+/// the settled price, fee and refund in proportion to them.
+#[account]
+#[derive(InitSpace)]
+pub struct BuyerCoalition {
+    pub negotiation: Pubkey,
+    pub lead: Pubkey,
+    /// Sum of the shares still in the pool.
+    pub total_contributed: u64,
+    /// Protocol fee already attributed to members who exited after the
+    /// negotiation ended.
+    pub fee_claimed: u64,
+    #[max_len(MAX_COALITION_MEMBERS)]
+    pub members: Vec<CoalitionShare>,
+    pub bump: u8,
+}
+
+impl BuyerCoalition {
+    pub fn share_of(&self, member: &Pubkey) -> Option<usize> {
+        self.members.iter().position(|share| share.member == *member)
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub struct CoalitionShare {
+    pub member: Pubkey,
+    pub amount: u64,
 }
 
 #[account]
@@ -66,9 +377,199 @@ pub struct ProtocolConfig {
     pub total_fees_collected: u64,
     pub is_paused: bool,
     pub bump: u8,
+
+    /// Layout version; see `migration` for older layouts.
+    pub version: u8,
+
+    /// Lamports each new negotiation prepays for whoever cranks its cleanup.
+    pub crank_reward_lamports: u64,
+    /// Share of the refund paid to whoever expires a negotiation.
+    pub crank_reward_bps: u16,
+    /// Permissionless cleanups that paid a reward.
+    pub total_cranks: u64,
+    pub total_crank_rewards_lamports: u64,
+    /// Crank rewards paid in escrow tokens, across mints.
+    pub total_crank_rewards_tokens: u64,
+
+    /// Zeroed space that later fields are carved out of.
+    pub reserved: [u8; 30],
+}
+
+impl ProtocolConfig {
+    pub const VERSION: u8 = 1;
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Auction {
+    // === Identity (72 bytes) ===
+    pub seller: Pubkey,
+    pub auction_id: u64,
+    pub token_mint: Pubkey,
+
+    // === State (2 bytes) ===
+    pub kind: AuctionKind,
+    pub status: AuctionStatus,
+
+    // === Parameters (48 bytes) ===
+    pub service_hash: [u8; 32],
+    /// English: lowest acceptable first bid. Dutch: opening price.
+    pub start_price: u64,
+    /// Dutch only: the price never decays below this.
+    pub floor_price: u64,
+    /// English only: each bid must beat the last by this much.
+    pub min_increment_bps: u16,
+    /// Dutch only: price decay per `decay_interval`, as for negotiation rounds.
+    pub decay_rate_bps: u16,
+    pub decay_interval: i64,
+    pub protocol_fee_bps: u16,
+    /// Vickrey only: share of an unrevealed deposit forfeited to the seller.
+    pub slash_bps: u16,
+
+    // === Bidding (52 bytes) ===
+    pub highest_bid: u64,
+    pub highest_bidder: Pubkey,
+    /// Vickrey only: second-highest revealed bid, the price the winner pays.
+    pub second_bid: u64,
+    pub bid_count: u16,
+    /// Vickrey only: sealed bids whose deposits are still in the vault.
+    pub open_bids: u16,
+
+    // === Timestamps (32 bytes) ===
+    pub created_at: i64,
+    /// Vickrey only: commits close here and reveals run until `ends_at`.
+    pub commit_ends_at: i64,
+    pub ends_at: i64,
+    pub settled_at: i64,
+
+    // === Settlement (8 bytes) ===
+    pub settled_amount: u64,
+
+    // === Bump (1 byte) ===
+    pub bump: u8,
+}
+
+impl Auction {
+    /// Most decay steps a Dutch auction may run for; also bounds the loop in
+    /// `dutch_price`.
+    pub const MAX_DECAY_STEPS: i64 = 255;
+
+    /// Decay steps elapsed at `now`, capped at the auction's end.
+    pub fn decay_steps(&self, now: i64) -> u8 {
+        if self.decay_interval <= 0 {
+            return 0;
+        }
+        let elapsed = now.min(self.ends_at).saturating_sub(self.created_at).max(0);
+        (elapsed / self.decay_interval).min(Self::MAX_DECAY_STEPS) as u8
+    }
+
+    /// Dutch price at `now`: the opening price decayed once per elapsed
+    /// interval, compounding like escrow decay, never below the floor.
+    pub fn dutch_price(&self, now: i64) -> u64 {
+        let mut price = self.start_price;
+        for _ in 0..self.decay_steps(now) {
+            price -= (price as u128 * self.decay_rate_bps as u128 / 10_000) as u64;
+            if price <= self.floor_price {
+                return self.floor_price;
+            }
+        }
+        price
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub enum AuctionKind {
+    /// Ascending escrowed bids; the highest bid when time runs out wins.
+    English,
+    /// Descending price; the first buyer to accept wins.
+    Dutch,
+    /// Sealed bids committed then revealed; the highest bidder pays the
+    /// second-highest price.
+    Vickrey,
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+/// One bidder's sealed bid in a Vickrey auction. The deposit is held in the
+/// auction vault and must cover the revealed bid.
+#[account]
+#[derive(InitSpace)]
+pub struct SealedBid {
+    pub auction: Pubkey,
+    pub bidder: Pubkey,
+    /// SHA-256 of the bid amount (little-endian) followed by the salt.
+    pub commitment: [u8; 32],
+    pub deposit: u64,
+    pub amount: u64,
+    pub revealed: bool,
+    pub bump: u8,
+}
+
+impl SealedBid {
+    pub fn commitment(amount: u64, salt: &[u8; 32]) -> [u8; 32] {
+        hashv(&[&amount.to_le_bytes(), salt]).to_bytes()
+    }
+}
+
+/// Procurement auction: the buyer escrows a ceiling and sellers bid down
+/// against it. Shares `AuctionStatus` with seller-side auctions.
+#[account]
+#[derive(InitSpace)]
+pub struct ReverseAuction {
+    // === Identity (72 bytes) ===
+    pub buyer: Pubkey,
+    pub auction_id: u64,
+    pub token_mint: Pubkey,
+
+    // === State (1 byte) ===
+    pub status: AuctionStatus,
+
+    // === Parameters (62 bytes) ===
+    pub service_hash: [u8; 32],
+    /// Escrowed by the buyer; the most any bid can ask for.
+    pub ceiling: u64,
+    /// Each bid must undercut the lowest by this much.
+    pub min_decrement_bps: u16,
+    /// Bond a seller posts before bidding; zero disables bonds.
+    pub seller_bond: u64,
+    pub protocol_fee_bps: u16,
+    pub open_bonds: u16,
+
+    // === Bidding (42 bytes) ===
+    pub lowest_bid: u64,
+    pub lowest_bidder: Pubkey,
+    pub bid_count: u16,
+
+    // === Timestamps (24 bytes) ===
+    pub created_at: i64,
+    pub ends_at: i64,
+    pub settled_at: i64,
+
+    // === Settlement (8 bytes) ===
+    pub settled_amount: u64,
+
+    // === Bump (1 byte) ===
+    pub bump: u8,
+}
+
+/// A seller's bond in a reverse auction, held in the auction vault until the
+/// auction ends.
+#[account]
+#[derive(InitSpace)]
+pub struct SellerBond {
+    pub auction: Pubkey,
+    pub seller: Pubkey,
+    pub amount: u64,
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub enum AuctionStatus {
+    Open,
+    Settled,
+    /// Ended without a winning bid.
+    Unsold,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
 pub enum NegotiationStatus {
     Created,
     Proposed,
@@ -79,13 +580,65 @@ pub enum NegotiationStatus {
     Rejected,
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+impl NegotiationStatus {
+    /// Every variant, indexed by the byte `NegotiationState` stores.
+    pub const ALL: [Self; 7] = [
+        Self::Created,
+        Self::Proposed,
+        Self::Countered,
+        Self::Accepted,
+        Self::Settled,
+        Self::Expired,
+        Self::Rejected,
+    ];
+}
+
+/// Why a negotiation ended without settling: given by the rejector, or
+/// set by `expire_negotiation`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TerminationReason {
+    /// Still running, settled, or rejected without a reason.
+    Unspecified,
+    /// The parties' prices were too far apart.
+    PriceGap,
+    /// `global_deadline` passed.
+    Timeout,
+    /// The ZOPA check found no overlap.
+    ZopaFailed,
+    /// The seller did not accept the invitation within `invitation_ttl`, or
+    /// the counterparty stopped responding.
+    CounterpartyUnresponsive,
+    /// The counterparty broke the rejector's rules.
+    PolicyViolation,
+    /// The buyer withdrew.
+    Cancelled,
+}
+
+impl TerminationReason {
+    /// Every variant, indexed by the byte `NegotiationState` stores.
+    pub const ALL: [Self; 7] = [
+        Self::Unspecified,
+        Self::PriceGap,
+        Self::Timeout,
+        Self::ZopaFailed,
+        Self::CounterpartyUnresponsive,
+        Self::PolicyViolation,
+        Self::Cancelled,
+    ];
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
 pub enum OfferSide {
     Buyer,
     Seller,
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+impl OfferSide {
+    /// Every variant, indexed by the byte `NegotiationState` stores.
+    pub const ALL: [Self; 2] = [Self::Buyer, Self::Seller];
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
 pub enum ZopaPhase {
     NotStarted,
     BuyerCommitted,
@@ -94,7 +647,18 @@ pub enum ZopaPhase {
     Skipped,
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+impl ZopaPhase {
+    /// Every variant, indexed by the byte `NegotiationState` stores.
+    pub const ALL: [Self; 5] = [
+        Self::NotStarted,
+        Self::BuyerCommitted,
+        Self::BothCommitted,
+        Self::Revealed,
+        Self::Skipped,
+    ];
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
 pub struct NegotiationParams {
     pub escrow_amount: u64,
     pub service_hash: [u8; 32],
@@ -105,4 +669,92 @@ pub struct NegotiationParams {
     pub min_offer_bps: u16,
     pub protocol_fee_bps: u16,
     pub zopa_enabled: bool,
+    /// Pyth feed id to denominate offers in its quote currency.
+    pub price_feed: Option<[u8; 32]>,
+    /// Pay the seller in another mint through a swap at settlement.
+    pub payout: Option<PayoutTerms>,
+    /// Seconds the seller has to accept the invitation before anyone can
+    /// expire the negotiation; none keeps it open until the global deadline.
+    pub invitation_ttl: Option<i64>,
+}
+
+/// How a seller is paid in a mint other than the escrow's, agreed when the
+/// negotiation is created.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct PayoutTerms {
+    pub mint: Pubkey,
+    /// Program implementing the swap adapter interface (see `swap.rs`).
+    pub swap_adapter: Pubkey,
+    pub max_slippage_bps: u16,
+}
+
+/// An offer made in the same instruction that creates or joins a
+/// negotiation, validated exactly like `submit_offer`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
+pub struct OpeningOffer {
+    pub amount: u64,
+    pub metadata: [u8; 64],
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct TemplateParams {
+    pub max_rounds: u8,
+    pub decay_rate_bps: u16,
+    pub response_window: i64,
+    pub global_deadline_offset: i64,
+    pub min_offer_bps: u16,
+    pub protocol_fee_bps: u16,
+    pub zopa_enabled: bool,
+    pub allowed_mints: Vec<Pubkey>,
+    pub arbiter: Option<Pubkey>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct PolicyParams {
+    pub buyer_filter: BuyerFilter,
+    pub buyers: Vec<Pubkey>,
+    pub min_escrow: u64,
+    pub accepted_mints: Vec<Pubkey>,
+    pub max_open_negotiations: u16,
+    pub auto_accept: bool,
+    pub required_template: Option<Pubkey>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct AuctionParams {
+    pub kind: AuctionKind,
+    pub service_hash: [u8; 32],
+    pub start_price: u64,
+    pub floor_price: u64,
+    pub min_increment_bps: u16,
+    pub decay_rate_bps: u16,
+    pub decay_interval: i64,
+    pub duration: i64,
+    pub protocol_fee_bps: u16,
+    /// Vickrey only: length of the commit phase within `duration`.
+    pub commit_duration: i64,
+    pub slash_bps: u16,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct ReverseAuctionParams {
+    pub service_hash: [u8; 32],
+    pub ceiling: u64,
+    pub min_decrement_bps: u16,
+    pub seller_bond: u64,
+    pub duration: i64,
+    pub protocol_fee_bps: u16,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default)]
+pub struct ConfigUpdateParams {
+    pub authority: Option<Pubkey>,
+    pub treasury: Option<Pubkey>,
+    pub default_decay_rate_bps: Option<u16>,
+    pub default_response_window: Option<i64>,
+    pub default_protocol_fee_bps: Option<u16>,
+    pub default_max_rounds: Option<u8>,
+    pub is_paused: Option<bool>,
+    pub crank_reward_lamports: Option<u64>,
+    pub crank_reward_bps: Option<u16>,
 }
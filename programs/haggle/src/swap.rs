@@ -0,0 +1,146 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::{get_return_data, invoke, invoke_signed};
+use anchor_spl::token::TokenAccount;
+
+use crate::errors::HaggleError;
+use crate::settlement::Settlement;
+
+// A swap adapter is any program with two Anchor-style instructions:
+//
+//   quote(amount_in: u64) -> SwapQuote            [adapter accounts...]
+//   swap(amount_in: u64, min_amount_out: u64)     [source, destination,
+//                                                  authority (signer),
+//                                                  token_program,
+//                                                  adapter accounts...]
+//
+// `quote` returns what the swap would pay now and at the spot price, which
+// bounds slippage. The adapter's own accounts are the remaining accounts of
+// `accept_offer`. The vault authority signs `swap`, so the vault and payout
+// balances are checked afterwards to hold the adapter to its quote, and the
+// vault must come back without a delegate or close authority.
+
+const QUOTE_DISCRIMINATOR: [u8; 8] = [149, 42, 109, 247, 134, 146, 213, 123];
+const SWAP_DISCRIMINATOR: [u8; 8] = [248, 198, 158, 145, 225, 117, 135, 200];
+
+/// Return data of an adapter's `quote`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
+pub struct SwapQuote {
+    pub amount_out: u64,
+    /// What `amount_in` would pay at the spot price, without price impact.
+    pub spot_amount_out: u64,
+}
+
+/// Where a cross-mint settlement swaps the seller's proceeds.
+pub(crate) struct SwapRoute<'a, 'info> {
+    pub adapter: AccountInfo<'info>,
+    /// Seller's token account for the payout mint.
+    pub destination: AccountInfo<'info>,
+    pub accounts: &'a [AccountInfo<'info>],
+}
+
+impl<'a, 'info> SwapRoute<'a, 'info> {
+    /// Swaps `amount_in` out of the settlement vault to the seller if the
+    /// adapter quotes it within `max_slippage_bps` of its spot price, and
+    /// returns what the seller received. `None` means the swap would slip
+    /// too far and the caller should pay in the escrow mint instead.
+    pub fn try_swap(
+        &self,
+        settlement: &Settlement<'_, 'info>,
+        amount_in: u64,
+        max_slippage_bps: u16,
+    ) -> Result<Option<u64>> {
+        if amount_in == 0 {
+            return Ok(None);
+        }
+
+        let adapter_metas: Vec<AccountMeta> = self.accounts.iter().map(meta).collect();
+        let mut infos = self.accounts.to_vec();
+        infos.push(self.adapter.clone());
+
+        invoke(
+            &Instruction {
+                program_id: self.adapter.key(),
+                accounts: adapter_metas.clone(),
+                data: [&QUOTE_DISCRIMINATOR[..], &amount_in.to_le_bytes()].concat(),
+            },
+            &infos,
+        )?;
+        let quote = match get_return_data() {
+            Some((program_id, data)) if program_id == self.adapter.key() => {
+                SwapQuote::try_from_slice(&data).map_err(|_| error!(HaggleError::SwapFailed))?
+            }
+            _ => return Ok(None),
+        };
+        let floor = (quote.spot_amount_out as u128)
+            .checked_mul(10000u128 - max_slippage_bps as u128)
+            .ok_or(HaggleError::Overflow)?;
+        if quote.amount_out == 0 || (quote.amount_out as u128) * 10000 < floor {
+            return Ok(None);
+        }
+
+        let vault_before = token_balance(&settlement.vault)?;
+        let payout_before = token_balance(&self.destination)?;
+
+        let mut accounts = vec![
+            AccountMeta::new(settlement.vault.key(), false),
+            AccountMeta::new(self.destination.key(), false),
+            AccountMeta::new_readonly(settlement.vault_authority.key(), true),
+            AccountMeta::new_readonly(settlement.token_program.key(), false),
+        ];
+        accounts.extend(adapter_metas);
+        infos.extend([
+            settlement.vault.clone(),
+            self.destination.clone(),
+            settlement.vault_authority.clone(),
+            settlement.token_program.clone(),
+        ]);
+        invoke_signed(
+            &Instruction {
+                program_id: self.adapter.key(),
+                accounts,
+                data: [
+                    &SWAP_DISCRIMINATOR[..],
+                    &amount_in.to_le_bytes(),
+                    &quote.amount_out.to_le_bytes(),
+                ]
+                .concat(),
+            },
+            &infos,
+            settlement.signer_seeds,
+        )?;
+
+        // The adapter may only have taken `amount_in` and must have paid
+        // what it quoted. It held the vault authority's signature, so it must
+        // not have left a delegate or close authority behind to drain what
+        // stays in the vault later
+        let vault = TokenAccount::try_deserialize(&mut &settlement.vault.try_borrow_data()?[..])?;
+        require!(
+            vault.owner == settlement.vault_authority.key()
+                && vault.amount.checked_add(amount_in) == Some(vault_before),
+            HaggleError::SwapFailed
+        );
+        require!(
+            vault.delegate.is_none() && vault.delegated_amount == 0 && vault.close_authority.is_none(),
+            HaggleError::SwapFailed
+        );
+        let received = token_balance(&self.destination)?
+            .checked_sub(payout_before)
+            .ok_or(HaggleError::SwapFailed)?;
+        require!(received >= quote.amount_out, HaggleError::SwapFailed);
+
+        Ok(Some(received))
+    }
+}
+
+fn meta(info: &AccountInfo) -> AccountMeta {
+    AccountMeta {
+        pubkey: info.key(),
+        is_signer: info.is_signer,
+        is_writable: info.is_writable,
+    }
+}
+
+fn token_balance(info: &AccountInfo) -> Result<u64> {
+    Ok(TokenAccount::try_deserialize(&mut &info.try_borrow_data()?[..])?.amount)
+}
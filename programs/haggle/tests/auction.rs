@@ -0,0 +1,445 @@
+//! English and Dutch auctions: escrowed bids, outbid refunds, price decay
+//! and the settlement they share with `accept_offer`.
+
+mod common;
+
+use common::*;
+use haggle::errors::HaggleError;
+use haggle::settlement::PayoutReason;
+use haggle::state::{AuctionKind, AuctionStatus};
+use haggle_client::{calculate_protocol_fee, HaggleEvent};
+use solana_sdk::signer::Signer;
+
+#[tokio::test]
+async fn english_auction_refunds_outbid_bidders_and_pays_the_highest_bid() {
+    let mut h = Harness::new().await;
+    let buyer = h.buyer.insecure_clone();
+    let seller = h.seller.insecure_clone();
+    let outsider = h.outsider.insecure_clone();
+    h.mint_to(&outsider.pubkey(), BUYER_FUNDS).await;
+
+    let auction = h.create_auction(1, english_params()).await;
+    let vault = h.vault_pda(&auction);
+    let state = h.auction(&auction).await;
+    assert_eq!(state.kind, AuctionKind::English);
+    assert_eq!(state.status, AuctionStatus::Open);
+    assert_eq!(state.ends_at, state.created_at + AUCTION_DURATION);
+    assert!(matches!(
+        h.events().as_slice(),
+        [HaggleEvent::AuctionCreated(e)] if e.auction == auction && e.start_price == START_PRICE
+    ));
+
+    h.bid(&buyer, &auction, START_PRICE).await.unwrap();
+    assert_eq!(h.balance(&vault).await, START_PRICE);
+    assert_eq!(
+        h.token_balance(&buyer.pubkey()).await,
+        BUYER_FUNDS - START_PRICE
+    );
+
+    // Outbidding refunds the previous bidder in the same transaction.
+    h.bid(&outsider, &auction, 2_200_000).await.unwrap();
+    assert_eq!(h.balance(&vault).await, 2_200_000);
+    assert_eq!(h.token_balance(&buyer.pubkey()).await, BUYER_FUNDS);
+    assert!(matches!(
+        h.events().as_slice(),
+        [HaggleEvent::Payout(p), HaggleEvent::BidPlaced(e)]
+            if p.reason == PayoutReason::Refund
+                && p.amount == START_PRICE
+                && p.destination == h.ata(&buyer.pubkey())
+                && e.bidder == outsider.pubkey()
+                && e.outbid == buyer.pubkey()
+                && e.refund_amount == START_PRICE
+                && e.bid_count == 2
+    ));
+
+    h.bid(&buyer, &auction, 2_420_000).await.unwrap();
+    assert_eq!(h.balance(&vault).await, 2_420_000);
+    assert_eq!(h.token_balance(&outsider.pubkey()).await, BUYER_FUNDS);
+
+    let ends_at = h.auction(&auction).await.ends_at;
+    h.warp_to(ends_at).await;
+    h.settle_auction(&outsider, &auction).await.unwrap();
+
+    let fee = calculate_protocol_fee(2_420_000, FEE_BPS);
+    assert_eq!(h.balance(&vault).await, 0);
+    assert_eq!(h.token_balance(&seller.pubkey()).await, 2_420_000 - fee);
+    assert_eq!(h.token_balance(&h.treasury.pubkey()).await, fee);
+    assert_eq!(
+        h.token_balance(&buyer.pubkey()).await,
+        BUYER_FUNDS - 2_420_000
+    );
+
+    let state = h.auction(&auction).await;
+    assert_eq!(state.status, AuctionStatus::Settled);
+    assert_eq!(state.highest_bidder, buyer.pubkey());
+    assert_eq!(state.settled_amount, 2_420_000);
+    assert_eq!(state.settled_at, ends_at);
+    assert!(matches!(
+        h.events().as_slice(),
+        [.., HaggleEvent::AuctionSettled(e)]
+            if e.auction == auction
+                && e.kind == AuctionKind::English
+                && e.buyer == buyer.pubkey()
+                && e.settled_amount == 2_420_000
+                && e.bid_count == 3
+                && e.protocol_fee == fee
+                && e.seller_payment == 2_420_000 - fee
+    ));
+
+    let config = h.config().await;
+    assert_eq!(config.total_settled_volume, 2_420_000);
+    assert_eq!(config.total_fees_collected, fee);
+
+    let rent = h.lamports(&auction).await + h.lamports(&vault).await;
+    let before = h.lamports(&seller.pubkey()).await;
+    h.close_auction(&seller, &auction).await.unwrap();
+    assert!(!h.exists(&auction).await);
+    assert!(!h.exists(&vault).await);
+    assert_eq!(h.lamports(&seller.pubkey()).await, before + rent);
+    assert!(matches!(
+        h.events().as_slice(),
+        [HaggleEvent::AuctionClosed(e)]
+            if e.auction == auction && e.seller == seller.pubkey() && e.rent_reclaimed == rent
+    ));
+}
+
+#[tokio::test]
+async fn english_bids_must_meet_the_reserve_and_the_increment() {
+    let mut h = Harness::new().await;
+    let buyer = h.buyer.insecure_clone();
+    let seller = h.seller.insecure_clone();
+    let auction = h.create_auction(1, english_params()).await;
+
+    assert_error(
+        h.bid(&buyer, &auction, START_PRICE - 1).await,
+        HaggleError::OfferTooLow,
+    );
+    h.bid(&buyer, &auction, START_PRICE).await.unwrap();
+
+    // 10% over the last bid is required; the current bidder may raise too.
+    assert_error(
+        h.bid(&buyer, &auction, 2_199_999).await,
+        HaggleError::OfferTooLow,
+    );
+    h.bid(&buyer, &auction, 2_200_000).await.unwrap();
+    assert_eq!(
+        h.token_balance(&buyer.pubkey()).await,
+        BUYER_FUNDS - 2_200_000
+    );
+
+    h.mint_to(&seller.pubkey(), BUYER_FUNDS).await;
+    assert_error(
+        h.bid(&seller, &auction, 3_000_000).await,
+        HaggleError::Unauthorized,
+    );
+
+    let ends_at = h.auction(&auction).await.ends_at;
+    h.warp_to(ends_at).await;
+    assert_error(
+        h.bid(&buyer, &auction, 3_000_000).await,
+        HaggleError::Expired,
+    );
+}
+
+#[tokio::test]
+async fn dutch_price_decays_until_the_first_buyer_settles() {
+    let mut h = Harness::new().await;
+    let buyer = h.buyer.insecure_clone();
+    let seller = h.seller.insecure_clone();
+    let outsider = h.outsider.insecure_clone();
+    h.mint_to(&outsider.pubkey(), BUYER_FUNDS).await;
+
+    let auction = h.create_auction(1, dutch_params()).await;
+    let vault = h.vault_pda(&auction);
+    let created_at = h.auction(&auction).await.created_at;
+
+    // Two full intervals compound the decay twice.
+    h.warp_to(created_at + 2 * DECAY_INTERVAL + 1).await;
+    let price = START_PRICE - START_PRICE * DECAY_BPS as u64 / 10_000;
+    let price = price - price * DECAY_BPS as u64 / 10_000;
+    let state = h.auction(&auction).await;
+    assert_eq!(state.dutch_price(h.now().await), price);
+
+    assert_error(
+        h.buy(&buyer, &auction, price - 1).await,
+        HaggleError::PriceAboveLimit,
+    );
+    h.buy(&buyer, &auction, price).await.unwrap();
+
+    let fee = calculate_protocol_fee(price, FEE_BPS);
+    assert_eq!(h.balance(&vault).await, 0);
+    assert_eq!(h.token_balance(&buyer.pubkey()).await, BUYER_FUNDS - price);
+    assert_eq!(h.token_balance(&seller.pubkey()).await, price - fee);
+    assert_eq!(h.token_balance(&h.treasury.pubkey()).await, fee);
+
+    let state = h.auction(&auction).await;
+    assert_eq!(state.status, AuctionStatus::Settled);
+    assert_eq!(state.highest_bidder, buyer.pubkey());
+    assert_eq!(state.settled_amount, price);
+    assert!(matches!(
+        h.events().as_slice(),
+        [.., HaggleEvent::AuctionSettled(e)]
+            if e.auction == auction
+                && e.kind == AuctionKind::Dutch
+                && e.settled_amount == price
+                && e.bid_count == 1
+                && e.price_decay == START_PRICE - price
+    ));
+
+    // Only the first buyer wins.
+    assert_error(
+        h.buy(&outsider, &auction, START_PRICE).await,
+        HaggleError::InvalidState,
+    );
+}
+
+#[tokio::test]
+async fn dutch_price_never_drops_below_the_floor() {
+    let mut h = Harness::new().await;
+    let buyer = h.buyer.insecure_clone();
+    let mut params = dutch_params();
+    params.decay_rate_bps = 1000;
+    let auction = h.create_auction(1, params).await;
+
+    let ends_at = h.auction(&auction).await.ends_at;
+    h.warp_to(ends_at - 1).await;
+    let floor = START_PRICE / 2;
+    let state = h.auction(&auction).await;
+    assert_eq!(state.dutch_price(h.now().await), floor);
+
+    h.buy(&buyer, &auction, floor).await.unwrap();
+    assert_eq!(h.auction(&auction).await.settled_amount, floor);
+}
+
+#[tokio::test]
+async fn dutch_buy_returns_tokens_sent_to_the_vault_to_the_seller() {
+    let mut h = Harness::new().await;
+    let buyer = h.buyer.insecure_clone();
+    let seller = h.seller.insecure_clone();
+    let auction = h.create_auction(1, dutch_params()).await;
+    let vault = h.vault_pda(&auction);
+    h.mint_to_account(&vault, 1).await;
+
+    h.buy(&buyer, &auction, START_PRICE).await.unwrap();
+
+    let fee = calculate_protocol_fee(START_PRICE, FEE_BPS);
+    assert_eq!(h.balance(&vault).await, 0);
+    assert_eq!(
+        h.token_balance(&seller.pubkey()).await,
+        START_PRICE - fee + 1
+    );
+    assert!(matches!(
+        h.events().as_slice(),
+        [
+            HaggleEvent::Payout(fee_leg),
+            HaggleEvent::Payout(seller_leg),
+            HaggleEvent::Payout(dust),
+            HaggleEvent::AuctionSettled(e),
+        ] if fee_leg.reason == PayoutReason::ProtocolFee
+            && seller_leg.reason == PayoutReason::Seller
+            && dust.reason == PayoutReason::Refund
+            && dust.amount == 1
+            && e.settled_amount == START_PRICE
+    ));
+
+    // Tokens sent after settlement cannot keep the vault open either.
+    h.mint_to_account(&vault, 2).await;
+    h.close_auction(&seller, &auction).await.unwrap();
+    assert!(!h.exists(&auction).await);
+    assert!(!h.exists(&vault).await);
+    assert_eq!(
+        h.token_balance(&seller.pubkey()).await,
+        START_PRICE - fee + 3
+    );
+}
+
+#[tokio::test]
+async fn auctions_without_a_winner_end_unsold() {
+    let mut h = Harness::new().await;
+    let buyer = h.buyer.insecure_clone();
+    let seller = h.seller.insecure_clone();
+    let outsider = h.outsider.insecure_clone();
+    let english = h.create_auction(1, english_params()).await;
+    let dutch = h.create_auction(2, dutch_params()).await;
+
+    let ends_at = h.auction(&dutch).await.ends_at;
+    h.warp_to(ends_at).await;
+    assert_error(
+        h.buy(&buyer, &dutch, START_PRICE).await,
+        HaggleError::Expired,
+    );
+
+    for auction in [english, dutch] {
+        h.settle_auction(&outsider, &auction).await.unwrap();
+        assert_eq!(h.auction(&auction).await.status, AuctionStatus::Unsold);
+        assert!(matches!(
+            h.events().as_slice(),
+            [HaggleEvent::AuctionUnsold(e)] if e.auction == auction
+        ));
+        assert_error(
+            h.settle_auction(&outsider, &auction).await,
+            HaggleError::InvalidState,
+        );
+
+        h.close_auction(&seller, &auction).await.unwrap();
+        assert!(!h.exists(&auction).await);
+    }
+    assert_eq!(h.token_balance(&seller.pubkey()).await, 0);
+    assert_eq!(h.config().await.total_settled_volume, 0);
+}
+
+#[tokio::test]
+async fn vickrey_winner_pays_the_second_highest_bid() {
+    let mut h = Harness::new().await;
+    let buyer = h.buyer.insecure_clone();
+    let seller = h.seller.insecure_clone();
+    let outsider = h.outsider.insecure_clone();
+    h.mint_to(&outsider.pubkey(), BUYER_FUNDS).await;
+
+    let auction = h.create_auction(1, vickrey_params()).await;
+    let vault = h.vault_pda(&auction);
+    h.commit_bid(&buyer, &auction, 3_000_000, 4_000_000)
+        .await
+        .unwrap();
+    assert!(matches!(
+        h.events().as_slice(),
+        [HaggleEvent::BidCommitted(e)] if e.bidder == buyer.pubkey() && e.deposit == 4_000_000
+    ));
+    h.commit_bid(&outsider, &auction, 2_500_000, 3_000_000)
+        .await
+        .unwrap();
+    assert_eq!(h.balance(&vault).await, 7_000_000);
+
+    let state = h.auction(&auction).await;
+    h.warp_to(state.commit_ends_at).await;
+    h.reveal_bid(&outsider, &auction, 2_500_000).await.unwrap();
+    h.reveal_bid(&buyer, &auction, 3_000_000).await.unwrap();
+    assert!(matches!(
+        h.events().as_slice(),
+        [HaggleEvent::BidRevealed(e)]
+            if e.highest_bid == 3_000_000 && e.second_bid == 2_500_000
+    ));
+
+    h.warp_to(state.ends_at).await;
+    h.settle_auction(&outsider, &auction).await.unwrap();
+    let fee = calculate_protocol_fee(2_500_000, FEE_BPS);
+    assert_eq!(h.token_balance(&seller.pubkey()).await, 2_500_000 - fee);
+    assert_eq!(h.token_balance(&h.treasury.pubkey()).await, fee);
+    let state = h.auction(&auction).await;
+    assert_eq!(state.status, AuctionStatus::Settled);
+    assert_eq!(state.highest_bidder, buyer.pubkey());
+    assert_eq!(state.settled_amount, 2_500_000);
+
+    // Deposits stay in the vault until each bid is withdrawn.
+    assert_error(
+        h.close_auction(&seller, &auction).await,
+        HaggleError::InvalidState,
+    );
+    for bidder in [buyer.pubkey(), outsider.pubkey()] {
+        let rent = h
+            .lamports(&haggle_client::find_sealed_bid_pda(&auction, &bidder).0)
+            .await;
+        let before = h.lamports(&bidder).await;
+        h.withdraw_bid(&seller, &auction, &bidder).await.unwrap();
+        assert_eq!(h.lamports(&bidder).await, before + rent);
+    }
+    assert_eq!(
+        h.token_balance(&buyer.pubkey()).await,
+        BUYER_FUNDS - 2_500_000
+    );
+    assert_eq!(h.token_balance(&outsider.pubkey()).await, BUYER_FUNDS);
+    assert_eq!(h.balance(&vault).await, 0);
+
+    h.close_auction(&seller, &auction).await.unwrap();
+    assert!(!h.exists(&auction).await);
+}
+
+#[tokio::test]
+async fn vickrey_unrevealed_bids_are_slashed() {
+    let mut h = Harness::new().await;
+    let buyer = h.buyer.insecure_clone();
+    let seller = h.seller.insecure_clone();
+    let outsider = h.outsider.insecure_clone();
+    h.mint_to(&outsider.pubkey(), BUYER_FUNDS).await;
+
+    let auction = h.create_auction(1, vickrey_params()).await;
+    h.commit_bid(&buyer, &auction, 3_000_000, 3_000_000)
+        .await
+        .unwrap();
+    h.commit_bid(&outsider, &auction, 5_000_000, 5_000_000)
+        .await
+        .unwrap();
+
+    // Each bid is revealed once; the outsider never reveals.
+    let state = h.auction(&auction).await;
+    h.warp_to(state.commit_ends_at).await;
+    h.reveal_bid(&buyer, &auction, 3_000_000).await.unwrap();
+    assert_error(
+        h.reveal_bid(&buyer, &auction, 3_000_000).await,
+        HaggleError::InvalidState,
+    );
+
+    // With no second bid the winner pays the reserve.
+    h.warp_to(state.ends_at).await;
+    assert_error(
+        h.reveal_bid(&outsider, &auction, 5_000_000).await,
+        HaggleError::Expired,
+    );
+    h.settle_auction(&outsider, &auction).await.unwrap();
+    assert_eq!(h.auction(&auction).await.settled_amount, START_PRICE);
+
+    let slashed = 5_000_000 * 2000 / 10_000;
+    h.withdraw_bid(&outsider, &auction, &outsider.pubkey())
+        .await
+        .unwrap();
+    assert!(matches!(
+        h.events().as_slice(),
+        [HaggleEvent::Payout(refund), HaggleEvent::Payout(slash), HaggleEvent::BidWithdrawn(e)]
+            if refund.reason == PayoutReason::Refund
+                && slash.reason == PayoutReason::Slash
+                && slash.amount == slashed
+                && e.slashed == slashed && e.refund_amount == 5_000_000 - slashed
+    ));
+    h.withdraw_bid(&outsider, &auction, &buyer.pubkey())
+        .await
+        .unwrap();
+
+    let fee = calculate_protocol_fee(START_PRICE, FEE_BPS);
+    assert_eq!(
+        h.token_balance(&seller.pubkey()).await,
+        START_PRICE - fee + slashed
+    );
+    assert_eq!(
+        h.token_balance(&outsider.pubkey()).await,
+        BUYER_FUNDS - slashed
+    );
+    assert_eq!(
+        h.token_balance(&buyer.pubkey()).await,
+        BUYER_FUNDS - START_PRICE
+    );
+    assert_eq!(h.balance(&h.vault_pda(&auction)).await, 0);
+}
+
+#[tokio::test]
+async fn vickrey_commits_close_and_reveals_must_fit_the_deposit() {
+    let mut h = Harness::new().await;
+    let buyer = h.buyer.insecure_clone();
+    let outsider = h.outsider.insecure_clone();
+    h.mint_to(&outsider.pubkey(), BUYER_FUNDS).await;
+    let auction = h.create_auction(1, vickrey_params()).await;
+    h.commit_bid(&buyer, &auction, 5_000_000, 3_000_000)
+        .await
+        .unwrap();
+
+    let state = h.auction(&auction).await;
+    h.warp_to(state.commit_ends_at).await;
+    assert_error(
+        h.commit_bid(&outsider, &auction, 5_000_000, 5_000_000)
+            .await,
+        HaggleError::Expired,
+    );
+    assert_error(
+        h.reveal_bid(&buyer, &auction, 5_000_000).await,
+        HaggleError::OfferExceedsEscrow,
+    );
+}
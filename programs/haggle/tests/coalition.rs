@@ -0,0 +1,323 @@
+//! Buyer coalitions: pooled escrow negotiated by a lead, with members paid
+//! their pro rata refunds after the negotiation ends.
+
+mod common;
+
+use common::*;
+use haggle::errors::HaggleError;
+use haggle::settlement::PayoutReason;
+use haggle::state::{NegotiationStatus, CLOSE_GRACE_PERIOD};
+use haggle_client::{calculate_protocol_fee, find_coalition_pda, HaggleEvent};
+use solana_sdk::signer::Signer;
+
+#[tokio::test]
+async fn pooled_settlement_pays_the_seller_once_and_splits_the_refund() {
+    let mut h = Harness::new().await;
+    let buyer = h.buyer.insecure_clone();
+    let seller = h.seller.insecure_clone();
+    let outsider = h.outsider.insecure_clone();
+    h.mint_to(&outsider.pubkey(), 3_000_000).await;
+    let member = h.funded_keypair(2_000_000).await;
+
+    let negotiation = h.create(1).await;
+    let coalition = find_coalition_pda(&negotiation).0;
+    let vault = h.vault_pda(&negotiation);
+    h.create_coalition(&buyer, &negotiation).await.unwrap();
+    assert!(matches!(
+        h.events().as_slice(),
+        [HaggleEvent::CoalitionCreated(e)]
+            if e.coalition == coalition && e.lead == buyer.pubkey() && e.contribution == ESCROW
+    ));
+    assert_eq!(h.negotiation(&negotiation).await.coalition, coalition);
+
+    h.join_coalition(&outsider, &negotiation, 3_000_000)
+        .await
+        .unwrap();
+    h.join_coalition(&member, &negotiation, 2_000_000)
+        .await
+        .unwrap();
+    assert!(matches!(
+        h.events().as_slice(),
+        [HaggleEvent::CoalitionJoined(e)]
+            if e.member == member.pubkey() && e.share == 2_000_000 && e.escrow_amount == 10_000_000
+    ));
+    let state = h.negotiation(&negotiation).await;
+    assert_eq!(state.escrow_amount, 10_000_000);
+    assert_eq!(state.effective_escrow, 10_000_000);
+    assert_eq!(h.balance(&vault).await, 10_000_000);
+    assert_eq!(h.coalition(&negotiation).await.members.len(), 3);
+
+    // The lead negotiates for everyone through the usual flow.
+    h.accept_invitation(&seller, &negotiation).await.unwrap();
+    h.offer(&buyer, &negotiation, 6_000_000).await.unwrap();
+    h.accept(&seller, &negotiation).await.unwrap();
+
+    let fee = calculate_protocol_fee(6_000_000, FEE_BPS);
+    assert_eq!(h.token_balance(&seller.pubkey()).await, 6_000_000 - fee);
+    assert_eq!(h.token_balance(&h.treasury.pubkey()).await, fee);
+    assert_eq!(h.token_balance(&buyer.pubkey()).await, BUYER_FUNDS - ESCROW);
+    assert_eq!(h.balance(&vault).await, 4_000_000);
+
+    // Refunds wait in the vault until every member has claimed theirs.
+    assert_error(
+        h.close(&buyer, &negotiation).await,
+        HaggleError::InvalidState,
+    );
+
+    h.exit_coalition(&outsider, &negotiation).await.unwrap();
+    assert!(matches!(
+        h.events().as_slice(),
+        [HaggleEvent::Payout(p), HaggleEvent::CoalitionExited(e)]
+            if p.reason == PayoutReason::Refund
+                && p.amount == 1_200_000
+                && e.member == outsider.pubkey()
+                && e.share == 3_000_000
+                && e.refund_amount == 1_200_000
+                && e.fee_share == fee * 3 / 10
+    ));
+    assert_eq!(h.token_balance(&outsider.pubkey()).await, 1_200_000);
+
+    h.exit_coalition(&member, &negotiation).await.unwrap();
+    assert_eq!(h.token_balance(&member.pubkey()).await, 800_000);
+
+    // The last member out sweeps the vault and dissolves the coalition.
+    let rent = h.lamports(&coalition).await;
+    let lamports = h.lamports(&buyer.pubkey()).await;
+    h.exit_coalition(&buyer, &negotiation).await.unwrap();
+    assert_eq!(
+        h.token_balance(&buyer.pubkey()).await,
+        BUYER_FUNDS - ESCROW + 2_000_000
+    );
+    assert_eq!(h.balance(&vault).await, 0);
+    assert!(!h.exists(&coalition).await);
+    assert_eq!(h.lamports(&buyer.pubkey()).await, lamports + rent);
+
+    h.close(&buyer, &negotiation).await.unwrap();
+    assert!(!h.exists(&negotiation).await);
+}
+
+#[tokio::test]
+async fn members_exit_in_full_until_the_seller_accepts() {
+    let mut h = Harness::new().await;
+    let buyer = h.buyer.insecure_clone();
+    let seller = h.seller.insecure_clone();
+    let outsider = h.outsider.insecure_clone();
+    h.mint_to(&outsider.pubkey(), 4_000_000).await;
+
+    let negotiation = h.create(1).await;
+    assert_error(
+        h.create_coalition(&seller, &negotiation).await,
+        HaggleError::Unauthorized,
+    );
+    h.create_coalition(&buyer, &negotiation).await.unwrap();
+    assert_error(
+        h.join_coalition(&seller, &negotiation, 1).await,
+        HaggleError::Unauthorized,
+    );
+
+    // Joining twice tops up a single share.
+    h.join_coalition(&outsider, &negotiation, 3_000_000)
+        .await
+        .unwrap();
+    h.join_coalition(&outsider, &negotiation, 1_000_000)
+        .await
+        .unwrap();
+    let coalition = h.coalition(&negotiation).await;
+    assert_eq!(coalition.members.len(), 2);
+    assert_eq!(coalition.total_contributed, ESCROW + 4_000_000);
+
+    h.exit_coalition(&outsider, &negotiation).await.unwrap();
+    assert!(matches!(
+        h.events().as_slice(),
+        [HaggleEvent::Payout(_), HaggleEvent::CoalitionExited(e)]
+            if e.refund_amount == 4_000_000 && e.fee_share == 0 && e.escrow_amount == ESCROW
+    ));
+    assert_eq!(h.token_balance(&outsider.pubkey()).await, 4_000_000);
+    assert_eq!(h.negotiation(&negotiation).await.escrow_amount, ESCROW);
+    assert_error(
+        h.exit_coalition(&outsider, &negotiation).await,
+        HaggleError::Unauthorized,
+    );
+
+    h.join_coalition(&outsider, &negotiation, 2_000_000)
+        .await
+        .unwrap();
+    h.accept_invitation(&seller, &negotiation).await.unwrap();
+    assert_error(
+        h.exit_coalition(&outsider, &negotiation).await,
+        HaggleError::InvalidState,
+    );
+    assert_error(
+        h.join_coalition(&outsider, &negotiation, 1_000_000).await,
+        HaggleError::InvalidState,
+    );
+
+    // A rejection leaves the whole pool in the vault for the members.
+    h.reject(&seller, &negotiation).await.unwrap();
+    assert!(matches!(
+        h.events().as_slice(),
+        [HaggleEvent::NegotiationRejected(e)] if e.refund_amount == ESCROW + 2_000_000
+    ));
+    assert_eq!(
+        h.negotiation(&negotiation).await.status().unwrap(),
+        NegotiationStatus::Rejected
+    );
+    assert_eq!(h.token_balance(&buyer.pubkey()).await, BUYER_FUNDS - ESCROW);
+
+    h.exit_coalition(&outsider, &negotiation).await.unwrap();
+    h.exit_coalition(&buyer, &negotiation).await.unwrap();
+    assert_eq!(h.token_balance(&outsider.pubkey()).await, 4_000_000);
+    assert_eq!(h.token_balance(&buyer.pubkey()).await, BUYER_FUNDS);
+    h.close(&buyer, &negotiation).await.unwrap();
+}
+
+#[tokio::test]
+async fn joins_and_exits_keep_the_opening_offer_decay() {
+    let mut h = Harness::new().await;
+    let buyer = h.buyer.insecure_clone();
+    let seller = h.seller.insecure_clone();
+    let outsider = h.outsider.insecure_clone();
+    h.mint_to(&outsider.pubkey(), 4_000_000).await;
+
+    let negotiation = h
+        .create_with_offer(1, params(), Some(4_000_000))
+        .await
+        .unwrap();
+    let decayed = h.negotiation(&negotiation).await.effective_escrow;
+    assert!(decayed < ESCROW);
+    h.create_coalition(&buyer, &negotiation).await.unwrap();
+
+    h.join_coalition(&outsider, &negotiation, 4_000_000)
+        .await
+        .unwrap();
+    let state = h.negotiation(&negotiation).await;
+    assert_eq!(state.escrow_amount, ESCROW + 4_000_000);
+    assert_eq!(state.effective_escrow, decayed + 4_000_000);
+
+    // The lead cannot pull out the escrow backing their opening offer.
+    assert_error(
+        h.exit_coalition(&buyer, &negotiation).await,
+        HaggleError::OfferExceedsEscrow,
+    );
+
+    h.exit_coalition(&outsider, &negotiation).await.unwrap();
+    let state = h.negotiation(&negotiation).await;
+    assert_eq!(state.escrow_amount, ESCROW);
+    assert_eq!(state.effective_escrow, decayed);
+    assert_eq!(h.token_balance(&outsider.pubkey()).await, 4_000_000);
+
+    // Rejecting still sends the opening offer's decay to the treasury.
+    h.reject(&seller, &negotiation).await.unwrap();
+    assert_eq!(
+        h.token_balance(&h.treasury.pubkey()).await,
+        ESCROW - decayed
+    );
+}
+
+#[tokio::test]
+async fn exits_after_settlement_add_up_to_the_refund_and_the_fee() {
+    let mut h = Harness::new().await;
+    let buyer = h.buyer.insecure_clone();
+    let seller = h.seller.insecure_clone();
+    let outsider = h.outsider.insecure_clone();
+    h.mint_to(&outsider.pubkey(), 1_000_003).await;
+    let member = h.funded_keypair(2_333_333).await;
+
+    let negotiation = h.create(1).await;
+    let vault = h.vault_pda(&negotiation);
+    h.create_coalition(&buyer, &negotiation).await.unwrap();
+    h.join_coalition(&outsider, &negotiation, 1_000_003)
+        .await
+        .unwrap();
+    h.join_coalition(&member, &negotiation, 2_333_333)
+        .await
+        .unwrap();
+    h.accept_invitation(&seller, &negotiation).await.unwrap();
+    h.offer(&buyer, &negotiation, 6_999_999).await.unwrap();
+    h.accept(&seller, &negotiation).await.unwrap();
+    let fee = calculate_protocol_fee(6_999_999, FEE_BPS);
+    let refund = h.balance(&vault).await;
+
+    // Shares that do not divide the refund or the fee evenly still account
+    // for every unit of both.
+    let (mut refunds, mut fee_shares) = (0, 0);
+    for exiter in [&member, &outsider, &buyer] {
+        h.exit_coalition(exiter, &negotiation).await.unwrap();
+        let events = h.events();
+        let [.., HaggleEvent::CoalitionExited(e)] = events.as_slice() else {
+            panic!("no CoalitionExited event");
+        };
+        refunds += e.refund_amount;
+        fee_shares += e.fee_share;
+    }
+    assert_eq!(refunds, refund);
+    assert_eq!(fee_shares, fee);
+    assert_eq!(h.balance(&vault).await, 0);
+}
+
+#[tokio::test]
+async fn close_pays_members_who_never_exited() {
+    let mut h = Harness::new().await;
+    let buyer = h.buyer.insecure_clone();
+    let seller = h.seller.insecure_clone();
+    let outsider = h.outsider.insecure_clone();
+    h.mint_to(&outsider.pubkey(), 3_000_000).await;
+    let member = h.funded_keypair(2_000_000).await;
+
+    let negotiation = h.create(1).await;
+    let coalition = find_coalition_pda(&negotiation).0;
+    let vault = h.vault_pda(&negotiation);
+    h.create_coalition(&buyer, &negotiation).await.unwrap();
+    h.join_coalition(&outsider, &negotiation, 3_000_000)
+        .await
+        .unwrap();
+    h.join_coalition(&member, &negotiation, 2_000_000)
+        .await
+        .unwrap();
+    h.accept_invitation(&seller, &negotiation).await.unwrap();
+    h.offer(&buyer, &negotiation, 6_000_000).await.unwrap();
+    h.accept(&seller, &negotiation).await.unwrap();
+    h.exit_coalition(&outsider, &negotiation).await.unwrap();
+
+    // Members who stay in the pool hold the close off until the grace
+    // period is over, and are then paid by whoever closes.
+    assert_error(
+        h.close(&buyer, &negotiation).await,
+        HaggleError::InvalidState,
+    );
+    let settled_at = h.negotiation(&negotiation).await.settled_at;
+    h.warp_to(settled_at + CLOSE_GRACE_PERIOD).await;
+    let rent = h.lamports(&coalition).await;
+    let lamports = h.lamports(&buyer.pubkey()).await;
+    h.close(&outsider, &negotiation).await.unwrap();
+
+    assert_eq!(h.token_balance(&member.pubkey()).await, 800_000);
+    assert_eq!(
+        h.token_balance(&buyer.pubkey()).await,
+        BUYER_FUNDS - ESCROW + 2_000_000
+    );
+    assert!(!h.exists(&vault).await);
+    assert!(!h.exists(&coalition).await);
+    assert!(!h.exists(&negotiation).await);
+    assert!(h.lamports(&buyer.pubkey()).await > lamports + rent);
+    let fee = calculate_protocol_fee(6_000_000, FEE_BPS);
+    let events = h.events();
+    let fee_shares: u64 = events
+        .iter()
+        .filter_map(|event| match event {
+            HaggleEvent::CoalitionExited(e) => Some(e.fee_share),
+            _ => None,
+        })
+        .sum();
+    assert_eq!(fee_shares, fee - fee * 3 / 10);
+    assert!(matches!(
+        events.as_slice(),
+        [
+            HaggleEvent::Payout(_),
+            HaggleEvent::Payout(_),
+            HaggleEvent::CoalitionExited(_),
+            HaggleEvent::CoalitionExited(_),
+            HaggleEvent::NegotiationClosed(e),
+        ] if e.closed_by == outsider.pubkey()
+    ));
+}
@@ -0,0 +1,1287 @@
+//! Shared harness for the program-test suite.
+//!
+//! The program runs in-process through `solana-program-test`. By default the
+//! native build of the program is used; when `SBF_OUT_DIR` points at a
+//! directory containing `haggle.so` (as `cargo test-sbf` does), the BPF build
+//! is loaded instead.
+
+#![allow(dead_code)]
+
+use std::collections::HashSet;
+use std::sync::Once;
+
+use anchor_lang::{AccountDeserialize, AnchorSerialize, InstructionData, ToAccountMetas};
+use anchor_spl::associated_token::{get_associated_token_address, spl_associated_token_account};
+use anchor_spl::token::spl_token;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use haggle::errors::HaggleError;
+use haggle::oracle::{PriceFeedMessage, PriceUpdateV2, VerificationLevel, PYTH_RECEIVER_ID};
+use haggle::state::{
+    AcceptancePolicy, Auction, AuctionKind, AuctionParams, BuyerCoalition, BuyerFilter,
+    ConfigUpdateParams, NegotiationParams, NegotiationState, OpeningOffer, PolicyParams,
+    ProtocolConfig, ReverseAuction, ReverseAuctionParams, SealedBid, TemplateParams,
+    TerminationReason,
+};
+use haggle_client::{
+    create_metadata, find_auction_pda, find_coalition_pda, find_config_pda, find_negotiation_pda,
+    find_policy_pda, find_reverse_auction_pda, find_template_pda, find_vault_pda, instructions,
+    parse_inner_instructions, HaggleEvent, SwapRoute,
+};
+use solana_program_test::{processor, BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account::Account,
+    account_info::AccountInfo,
+    clock::Clock,
+    entrypoint::ProgramResult,
+    instruction::{AccountMeta, Instruction, InstructionError},
+    native_token::LAMPORTS_PER_SOL,
+    program_pack::Pack,
+    program_stubs::{set_syscall_stubs, SyscallStubs},
+    pubkey::Pubkey,
+    signature::{Keypair, Signature},
+    signer::Signer,
+    system_instruction, system_program,
+    transaction::{Transaction, TransactionError},
+};
+
+pub const ESCROW: u64 = 5_000_000;
+pub const BUYER_FUNDS: u64 = 100_000_000;
+pub const DECAY_BPS: u16 = 200;
+pub const MIN_OFFER_BPS: u16 = 1000;
+pub const FEE_BPS: u16 = 100;
+pub const RESPONSE_WINDOW: i64 = 3600;
+pub const DEADLINE_OFFSET: i64 = 86_400;
+pub const MAX_ROUNDS: u8 = 10;
+pub const START_PRICE: u64 = 2_000_000;
+pub const AUCTION_DURATION: i64 = 3600;
+pub const DECAY_INTERVAL: i64 = 300;
+/// Pyth feed id of the mocked quote currency.
+pub const FEED_ID: [u8; 32] = [7; 32];
+
+/// Transaction metadata from the banks client carries no inner
+/// instructions, so events emitted by self-CPI would be lost. Every CPI is
+/// also written to the log behind a marker, which `send` strips back out
+/// into `inner_instructions`.
+struct CpiStubs(Box<dyn SyscallStubs>);
+
+struct NoStubs;
+impl SyscallStubs for NoStubs {}
+
+const CPI_MARKER: &str = "Program log: \0cpi: ";
+
+impl SyscallStubs for CpiStubs {
+    fn sol_log(&self, message: &str) {
+        self.0.sol_log(message)
+    }
+    fn sol_log_compute_units(&self) {
+        self.0.sol_log_compute_units()
+    }
+    fn sol_remaining_compute_units(&self) -> u64 {
+        self.0.sol_remaining_compute_units()
+    }
+    fn sol_invoke_signed(
+        &self,
+        instruction: &Instruction,
+        account_infos: &[AccountInfo],
+        signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        self.0.sol_log(&format!(
+            "\0cpi: {} {}",
+            instruction.program_id,
+            STANDARD.encode(&instruction.data)
+        ));
+        self.0
+            .sol_invoke_signed(instruction, account_infos, signers_seeds)
+    }
+    fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+        self.0.sol_get_clock_sysvar(var_addr)
+    }
+    fn sol_get_epoch_schedule_sysvar(&self, var_addr: *mut u8) -> u64 {
+        self.0.sol_get_epoch_schedule_sysvar(var_addr)
+    }
+    fn sol_get_fees_sysvar(&self, var_addr: *mut u8) -> u64 {
+        self.0.sol_get_fees_sysvar(var_addr)
+    }
+    fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+        self.0.sol_get_rent_sysvar(var_addr)
+    }
+    fn sol_get_epoch_rewards_sysvar(&self, var_addr: *mut u8) -> u64 {
+        self.0.sol_get_epoch_rewards_sysvar(var_addr)
+    }
+    fn sol_get_last_restart_slot(&self, var_addr: *mut u8) -> u64 {
+        self.0.sol_get_last_restart_slot(var_addr)
+    }
+    unsafe fn sol_memcpy(&self, dst: *mut u8, src: *const u8, n: usize) {
+        self.0.sol_memcpy(dst, src, n)
+    }
+    unsafe fn sol_memmove(&self, dst: *mut u8, src: *const u8, n: usize) {
+        self.0.sol_memmove(dst, src, n)
+    }
+    unsafe fn sol_memcmp(&self, s1: *const u8, s2: *const u8, n: usize, result: *mut i32) {
+        self.0.sol_memcmp(s1, s2, n, result)
+    }
+    unsafe fn sol_memset(&self, s: *mut u8, c: u8, n: usize) {
+        self.0.sol_memset(s, c, n)
+    }
+    fn sol_get_return_data(&self) -> Option<(Pubkey, Vec<u8>)> {
+        self.0.sol_get_return_data()
+    }
+    fn sol_set_return_data(&self, data: &[u8]) {
+        self.0.sol_set_return_data(data)
+    }
+    fn sol_log_data(&self, fields: &[&[u8]]) {
+        self.0.sol_log_data(fields)
+    }
+    fn sol_get_processed_sibling_instruction(&self, index: usize) -> Option<Instruction> {
+        self.0.sol_get_processed_sibling_instruction(index)
+    }
+    fn sol_get_stack_height(&self) -> u64 {
+        self.0.sol_get_stack_height()
+    }
+}
+
+fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    // Anchor's entry ties the slice and the account infos to one lifetime.
+    let accounts = Box::leak(Box::new(accounts.to_vec()));
+    haggle::entry(program_id, accounts, data)
+}
+
+fn process_mock_amm(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let accounts = Box::leak(Box::new(accounts.to_vec()));
+    mock_amm::entry(program_id, accounts, data)
+}
+
+pub fn params() -> NegotiationParams {
+    NegotiationParams {
+        escrow_amount: ESCROW,
+        service_hash: haggle_client::create_service_hash("test-service"),
+        max_rounds: MAX_ROUNDS,
+        decay_rate_bps: DECAY_BPS,
+        response_window: RESPONSE_WINDOW,
+        global_deadline_offset: DEADLINE_OFFSET,
+        min_offer_bps: MIN_OFFER_BPS,
+        protocol_fee_bps: FEE_BPS,
+        zopa_enabled: false,
+        price_feed: None,
+        payout: None,
+        invitation_ttl: None,
+    }
+}
+
+pub fn opening_offer(amount: u64) -> OpeningOffer {
+    OpeningOffer {
+        amount,
+        metadata: create_metadata("opening"),
+    }
+}
+
+/// The terms of `params()` as a template open to any mint, without arbiter.
+pub fn template_params() -> TemplateParams {
+    TemplateParams {
+        max_rounds: MAX_ROUNDS,
+        decay_rate_bps: DECAY_BPS,
+        response_window: RESPONSE_WINDOW,
+        global_deadline_offset: DEADLINE_OFFSET,
+        min_offer_bps: MIN_OFFER_BPS,
+        protocol_fee_bps: FEE_BPS,
+        zopa_enabled: false,
+        allowed_mints: Vec::new(),
+        arbiter: None,
+    }
+}
+
+/// A policy that admits anyone, for tests to tighten.
+pub fn policy_params() -> PolicyParams {
+    PolicyParams {
+        buyer_filter: BuyerFilter::Any,
+        buyers: Vec::new(),
+        min_escrow: 0,
+        accepted_mints: Vec::new(),
+        max_open_negotiations: 0,
+        auto_accept: false,
+        required_template: None,
+    }
+}
+
+/// English auction with a 10% minimum increment.
+pub fn english_params() -> AuctionParams {
+    AuctionParams {
+        kind: AuctionKind::English,
+        service_hash: haggle_client::create_service_hash("test-service"),
+        start_price: START_PRICE,
+        floor_price: 0,
+        min_increment_bps: 1000,
+        decay_rate_bps: 0,
+        decay_interval: 0,
+        duration: AUCTION_DURATION,
+        protocol_fee_bps: FEE_BPS,
+        commit_duration: 0,
+        slash_bps: 0,
+    }
+}
+
+/// Dutch auction decaying by `DECAY_BPS` every `DECAY_INTERVAL` down to
+/// half the opening price.
+pub fn dutch_params() -> AuctionParams {
+    AuctionParams {
+        kind: AuctionKind::Dutch,
+        floor_price: START_PRICE / 2,
+        min_increment_bps: 0,
+        decay_rate_bps: DECAY_BPS,
+        decay_interval: DECAY_INTERVAL,
+        ..english_params()
+    }
+}
+
+/// Vickrey auction committing for the first half and slashing unrevealed
+/// deposits by 20%.
+pub fn vickrey_params() -> AuctionParams {
+    AuctionParams {
+        kind: AuctionKind::Vickrey,
+        min_increment_bps: 0,
+        commit_duration: AUCTION_DURATION / 2,
+        slash_bps: 2000,
+        ..english_params()
+    }
+}
+
+/// Reverse auction with the full `ESCROW` as ceiling, a 5% minimum
+/// decrement and no seller bonds.
+pub fn reverse_params() -> ReverseAuctionParams {
+    ReverseAuctionParams {
+        service_hash: haggle_client::create_service_hash("test-service"),
+        ceiling: ESCROW,
+        min_decrement_bps: 500,
+        seller_bond: 0,
+        duration: AUCTION_DURATION,
+        protocol_fee_bps: FEE_BPS,
+    }
+}
+
+pub struct Harness {
+    pub ctx: ProgramTestContext,
+    pub buyer: Keypair,
+    pub seller: Keypair,
+    pub treasury: Keypair,
+    pub outsider: Keypair,
+    pub mint: Pubkey,
+    /// Pyth price account passed to negotiation instructions, once
+    /// `set_price` has written one.
+    pub price_update: Option<Pubkey>,
+    /// Route `accept` swaps cross-mint payouts through, once `create_pool`
+    /// has opened one.
+    pub swap_route: Option<SwapRoute>,
+    /// Log messages of the last transaction sent, successful or not.
+    pub logs: Vec<String>,
+    /// `(program id, data)` of every CPI the last transaction made.
+    pub inner_instructions: Vec<(Pubkey, Vec<u8>)>,
+    /// Compute units the last transaction consumed. Native builtins are not
+    /// metered, so only the BPF build gives a meaningful figure.
+    pub compute_units: u64,
+    sent: HashSet<Signature>,
+}
+
+impl Harness {
+    /// Starts a bank with an initialized config, a 6-decimal mint and ATAs
+    /// for buyer, seller, treasury and outsider; only the buyer's is funded.
+    pub async fn new() -> Self {
+        let mut pt = ProgramTest::new("haggle", haggle::ID, processor!(process_instruction));
+        pt.add_program("mock_amm", mock_amm::ID, processor!(process_mock_amm));
+        let buyer = Keypair::new();
+        let seller = Keypair::new();
+        let treasury = Keypair::new();
+        let outsider = Keypair::new();
+        for key in [&buyer, &seller, &treasury, &outsider] {
+            pt.add_account(
+                key.pubkey(),
+                Account::new(10 * LAMPORTS_PER_SOL, 0, &system_program::ID),
+            );
+        }
+
+        let ctx = pt.start_with_context().await;
+        static CPI: Once = Once::new();
+        CPI.call_once(|| {
+            let stubs = set_syscall_stubs(Box::new(NoStubs));
+            set_syscall_stubs(Box::new(CpiStubs(stubs)));
+        });
+        let mut h = Self {
+            ctx,
+            buyer,
+            seller,
+            treasury,
+            outsider,
+            mint: Pubkey::default(),
+            price_update: None,
+            swap_route: None,
+            logs: Vec::new(),
+            inner_instructions: Vec::new(),
+            compute_units: 0,
+            sent: HashSet::new(),
+        };
+
+        let authority = h.ctx.payer.pubkey();
+        h.send(
+            &[instructions::initialize_config(
+                &authority,
+                &h.treasury.pubkey(),
+                DECAY_BPS,
+                RESPONSE_WINDOW,
+                FEE_BPS,
+                MAX_ROUNDS,
+            )],
+            &[],
+        )
+        .await
+        .unwrap();
+
+        let mint = Keypair::new();
+        h.mint = mint.pubkey();
+        let rent = h.ctx.banks_client.get_rent().await.unwrap();
+        let mut ixs = vec![
+            system_instruction::create_account(
+                &authority,
+                &h.mint,
+                rent.minimum_balance(spl_token::state::Mint::LEN),
+                spl_token::state::Mint::LEN as u64,
+                &spl_token::ID,
+            ),
+            spl_token::instruction::initialize_mint(&spl_token::ID, &h.mint, &authority, None, 6)
+                .unwrap(),
+        ];
+        for owner in [
+            h.buyer.pubkey(),
+            h.seller.pubkey(),
+            h.treasury.pubkey(),
+            h.outsider.pubkey(),
+        ] {
+            ixs.push(
+                spl_associated_token_account::instruction::create_associated_token_account(
+                    &authority,
+                    &owner,
+                    &h.mint,
+                    &spl_token::ID,
+                ),
+            );
+        }
+        h.send(&ixs, &[&mint]).await.unwrap();
+        let buyer = h.buyer.pubkey();
+        h.mint_to(&buyer, BUYER_FUNDS).await;
+        h
+    }
+
+    /// Mints tokens to `owner`'s ATA.
+    pub async fn mint_to(&mut self, owner: &Pubkey, amount: u64) {
+        let ata = self.ata(owner);
+        self.mint_to_account(&ata, amount).await;
+    }
+
+    /// Mints tokens straight into a token account, such as a vault.
+    pub async fn mint_to_account(&mut self, account: &Pubkey, amount: u64) {
+        let ix = spl_token::instruction::mint_to(
+            &spl_token::ID,
+            &self.mint,
+            account,
+            &self.ctx.payer.pubkey(),
+            &[],
+            amount,
+        )
+        .unwrap();
+        self.send(&[ix], &[]).await.unwrap();
+    }
+
+    /// Opens a mock AMM pool swapping the escrow mint for a new 6-decimal
+    /// payout mint, gives the seller an ATA for it and routes `accept`
+    /// through the pool. Returns the payout mint.
+    pub async fn create_pool(&mut self, reserve_in: u64, reserve_out: u64) -> Pubkey {
+        let authority = self.ctx.payer.pubkey();
+        let payout_mint = Keypair::new();
+        let rent = self.ctx.banks_client.get_rent().await.unwrap();
+        let pool = Pubkey::find_program_address(
+            &[b"pool", self.mint.as_ref(), payout_mint.pubkey().as_ref()],
+            &mock_amm::ID,
+        )
+        .0;
+        let vault_in = Pubkey::find_program_address(&[b"vault_in", pool.as_ref()], &mock_amm::ID).0;
+        let vault_out =
+            Pubkey::find_program_address(&[b"vault_out", pool.as_ref()], &mock_amm::ID).0;
+        let ixs = [
+            system_instruction::create_account(
+                &authority,
+                &payout_mint.pubkey(),
+                rent.minimum_balance(spl_token::state::Mint::LEN),
+                spl_token::state::Mint::LEN as u64,
+                &spl_token::ID,
+            ),
+            spl_token::instruction::initialize_mint(
+                &spl_token::ID,
+                &payout_mint.pubkey(),
+                &authority,
+                None,
+                6,
+            )
+            .unwrap(),
+            spl_associated_token_account::instruction::create_associated_token_account(
+                &authority,
+                &self.seller.pubkey(),
+                &payout_mint.pubkey(),
+                &spl_token::ID,
+            ),
+            Instruction {
+                program_id: mock_amm::ID,
+                accounts: mock_amm::accounts::InitializePool {
+                    payer: authority,
+                    mint_in: self.mint,
+                    mint_out: payout_mint.pubkey(),
+                    pool,
+                    vault_in,
+                    vault_out,
+                    token_program: spl_token::ID,
+                    system_program: system_program::ID,
+                }
+                .to_account_metas(None),
+                data: mock_amm::instruction::InitializePool {}.data(),
+            },
+            spl_token::instruction::mint_to(
+                &spl_token::ID,
+                &self.mint,
+                &vault_in,
+                &authority,
+                &[],
+                reserve_in,
+            )
+            .unwrap(),
+            spl_token::instruction::mint_to(
+                &spl_token::ID,
+                &payout_mint.pubkey(),
+                &vault_out,
+                &authority,
+                &[],
+                reserve_out,
+            )
+            .unwrap(),
+        ];
+        self.send(&ixs, &[&payout_mint]).await.unwrap();
+
+        self.swap_route = Some(SwapRoute {
+            swap_adapter: mock_amm::ID,
+            seller_payout_account: get_associated_token_address(
+                &self.seller.pubkey(),
+                &payout_mint.pubkey(),
+            ),
+            adapter_accounts: vec![
+                AccountMeta::new_readonly(pool, false),
+                AccountMeta::new(vault_in, false),
+                AccountMeta::new(vault_out, false),
+            ],
+        });
+        payout_mint.pubkey()
+    }
+
+    /// Sets how the pool `accept` routes through behaves on `swap`.
+    pub async fn set_pool_mode(&mut self, mode: mock_amm::Mode) {
+        let pool = self.swap_route.as_ref().unwrap().adapter_accounts[0].pubkey;
+        let ix = Instruction {
+            program_id: mock_amm::ID,
+            accounts: mock_amm::accounts::SetMode { pool }.to_account_metas(None),
+            data: mock_amm::instruction::SetMode { mode }.data(),
+        };
+        self.send(&[ix], &[]).await.unwrap();
+    }
+
+    /// A fresh keypair with an ATA holding `amount`. It holds no SOL, so it
+    /// can only sign instructions the context payer pays for.
+    pub async fn funded_keypair(&mut self, amount: u64) -> Keypair {
+        let keypair = Keypair::new();
+        let ix = spl_associated_token_account::instruction::create_associated_token_account(
+            &self.ctx.payer.pubkey(),
+            &keypair.pubkey(),
+            &self.mint,
+            &spl_token::ID,
+        );
+        self.send(&[ix], &[]).await.unwrap();
+        self.mint_to(&keypair.pubkey(), amount).await;
+        keypair
+    }
+
+    /// Signs and processes a transaction paid for by the context payer.
+    ///
+    /// Resending an identical transaction on the same blockhash makes the
+    /// banks client wait for the blockhash to expire, so a fresh one is
+    /// fetched whenever a signature repeats.
+    pub async fn send(
+        &mut self,
+        ixs: &[Instruction],
+        signers: &[&Keypair],
+    ) -> Result<(), BanksClientError> {
+        let fee_payer = self.ctx.payer.insecure_clone();
+        let mut all: Vec<&Keypair> = vec![&fee_payer];
+        all.extend_from_slice(signers);
+        let payer = fee_payer.pubkey();
+
+        let mut blockhash = self.ctx.banks_client.get_latest_blockhash().await?;
+        let mut tx = Transaction::new_signed_with_payer(ixs, Some(&payer), &all, blockhash);
+        // `get_new_latest_blockhash` compares against the context's cached
+        // hash, which can be older than the one just used, so wait for a
+        // hash that actually differs.
+        while !self.sent.insert(tx.signatures[0]) {
+            self.ctx.last_blockhash = blockhash;
+            blockhash = self.ctx.get_new_latest_blockhash().await?;
+            tx = Transaction::new_signed_with_payer(ixs, Some(&payer), &all, blockhash);
+        }
+        let processed = self
+            .ctx
+            .banks_client
+            .process_transaction_with_metadata(tx)
+            .await?;
+        let (logs, compute_units) = processed
+            .metadata
+            .map(|meta| (meta.log_messages, meta.compute_units_consumed))
+            .unwrap_or_default();
+        self.compute_units = compute_units;
+        self.logs.clear();
+        self.inner_instructions.clear();
+        for line in logs {
+            match line
+                .strip_prefix(CPI_MARKER)
+                .and_then(|cpi| cpi.split_once(' '))
+            {
+                Some((program_id, data)) => self
+                    .inner_instructions
+                    .push((program_id.parse().unwrap(), STANDARD.decode(data).unwrap())),
+                None => self.logs.push(line),
+            }
+        }
+        Ok(processed.result?)
+    }
+
+    /// Events emitted by the last transaction sent.
+    pub fn events(&self) -> Vec<HaggleEvent> {
+        parse_inner_instructions(&self.inner_instructions)
+    }
+
+    // ===== Clock =====
+
+    pub async fn now(&mut self) -> i64 {
+        self.clock().await.unix_timestamp
+    }
+
+    async fn clock(&mut self) -> Clock {
+        self.ctx.banks_client.get_sysvar::<Clock>().await.unwrap()
+    }
+
+    /// Moves to the next slot (for a fresh blockhash) and sets the clock to
+    /// `unix_timestamp`.
+    pub async fn warp_to(&mut self, unix_timestamp: i64) {
+        let slot = self.clock().await.slot;
+        self.ctx.warp_to_slot(slot + 1).unwrap();
+        let mut clock = self.clock().await;
+        clock.unix_timestamp = unix_timestamp;
+        self.ctx.set_sysvar(&clock);
+    }
+
+    pub async fn warp_by(&mut self, seconds: i64) {
+        let now = self.now().await;
+        self.warp_to(now + seconds).await;
+    }
+
+    // ===== Oracle =====
+
+    /// Writes a fully verified Pyth price update for `FEED_ID` published at
+    /// the current time: `price * 10^exponent` quote units per whole token,
+    /// give or take `conf`.
+    pub async fn set_price(&mut self, price: i64, conf: u64, exponent: i32) {
+        let publish_time = self.now().await;
+        let update = PriceUpdateV2 {
+            write_authority: Pubkey::new_unique(),
+            verification_level: VerificationLevel::Full,
+            price_message: PriceFeedMessage {
+                feed_id: FEED_ID,
+                price,
+                conf,
+                exponent,
+                publish_time,
+                prev_publish_time: publish_time,
+                ema_price: price,
+                ema_conf: conf,
+            },
+            posted_slot: 0,
+        };
+        let mut data = PriceUpdateV2::DISCRIMINATOR.to_vec();
+        update.serialize(&mut data).unwrap();
+        let account = Account {
+            lamports: LAMPORTS_PER_SOL,
+            data,
+            owner: PYTH_RECEIVER_ID,
+            executable: false,
+            rent_epoch: 0,
+        };
+        let address = *self.price_update.get_or_insert_with(Pubkey::new_unique);
+        self.ctx.set_account(&address, &account.into());
+    }
+
+    // ===== Accounts =====
+
+    pub fn ata(&self, owner: &Pubkey) -> Pubkey {
+        get_associated_token_address(owner, &self.mint)
+    }
+
+    pub async fn balance(&mut self, token_account: &Pubkey) -> u64 {
+        let account = self
+            .ctx
+            .banks_client
+            .get_account(*token_account)
+            .await
+            .unwrap()
+            .unwrap();
+        spl_token::state::Account::unpack(&account.data)
+            .unwrap()
+            .amount
+    }
+
+    pub async fn token_balance(&mut self, owner: &Pubkey) -> u64 {
+        let ata = self.ata(owner);
+        self.balance(&ata).await
+    }
+
+    pub async fn lamports(&mut self, address: &Pubkey) -> u64 {
+        self.ctx.banks_client.get_balance(*address).await.unwrap()
+    }
+
+    pub async fn exists(&mut self, address: &Pubkey) -> bool {
+        self.ctx
+            .banks_client
+            .get_account(*address)
+            .await
+            .unwrap()
+            .is_some()
+    }
+
+    pub async fn negotiation(&mut self, negotiation: &Pubkey) -> NegotiationState {
+        let account = self
+            .ctx
+            .banks_client
+            .get_account(*negotiation)
+            .await
+            .unwrap()
+            .unwrap();
+        NegotiationState::try_deserialize(&mut account.data.as_slice()).unwrap()
+    }
+
+    pub async fn auction(&mut self, auction: &Pubkey) -> Auction {
+        let account = self
+            .ctx
+            .banks_client
+            .get_account(*auction)
+            .await
+            .unwrap()
+            .unwrap();
+        Auction::try_deserialize(&mut account.data.as_slice()).unwrap()
+    }
+
+    pub async fn reverse_auction(&mut self, auction: &Pubkey) -> ReverseAuction {
+        let account = self
+            .ctx
+            .banks_client
+            .get_account(*auction)
+            .await
+            .unwrap()
+            .unwrap();
+        ReverseAuction::try_deserialize(&mut account.data.as_slice()).unwrap()
+    }
+
+    pub async fn coalition(&mut self, negotiation: &Pubkey) -> BuyerCoalition {
+        let account = self
+            .ctx
+            .banks_client
+            .get_account(find_coalition_pda(negotiation).0)
+            .await
+            .unwrap()
+            .unwrap();
+        BuyerCoalition::try_deserialize(&mut account.data.as_slice()).unwrap()
+    }
+
+    pub async fn policy(&mut self) -> AcceptancePolicy {
+        let account = self
+            .ctx
+            .banks_client
+            .get_account(find_policy_pda(&self.seller.pubkey()).0)
+            .await
+            .unwrap()
+            .unwrap();
+        AcceptancePolicy::try_deserialize(&mut account.data.as_slice()).unwrap()
+    }
+
+    pub async fn config(&mut self) -> ProtocolConfig {
+        let account = self
+            .ctx
+            .banks_client
+            .get_account(find_config_pda().0)
+            .await
+            .unwrap()
+            .unwrap();
+        ProtocolConfig::try_deserialize(&mut account.data.as_slice()).unwrap()
+    }
+
+    pub async fn update_config(
+        &mut self,
+        authority: &Keypair,
+        params: ConfigUpdateParams,
+    ) -> Result<(), BanksClientError> {
+        let ix = instructions::update_config(&authority.pubkey(), params);
+        self.send(&[ix], &[authority]).await
+    }
+
+    pub fn negotiation_pda(&self, session_id: u64) -> Pubkey {
+        find_negotiation_pda(&self.buyer.pubkey(), &self.seller.pubkey(), session_id).0
+    }
+
+    pub fn vault_pda(&self, negotiation: &Pubkey) -> Pubkey {
+        find_vault_pda(negotiation).0
+    }
+
+    pub fn auction_pda(&self, auction_id: u64) -> Pubkey {
+        find_auction_pda(&self.seller.pubkey(), auction_id).0
+    }
+
+    pub fn reverse_auction_pda(&self, auction_id: u64) -> Pubkey {
+        find_reverse_auction_pda(&self.buyer.pubkey(), auction_id).0
+    }
+
+    // ===== Instructions =====
+
+    pub async fn create_with(
+        &mut self,
+        session_id: u64,
+        params: NegotiationParams,
+    ) -> Result<Pubkey, BanksClientError> {
+        self.create_with_offer(session_id, params, None).await
+    }
+
+    /// Creates a negotiation with the buyer's opening offer of `amount`.
+    pub async fn create_with_offer(
+        &mut self,
+        session_id: u64,
+        params: NegotiationParams,
+        amount: Option<u64>,
+    ) -> Result<Pubkey, BanksClientError> {
+        let buyer = self.buyer.insecure_clone();
+        let ix = instructions::create_negotiation(
+            &buyer.pubkey(),
+            &self.seller.pubkey(),
+            &self.mint,
+            &self.ata(&buyer.pubkey()),
+            session_id,
+            params,
+            amount.map(opening_offer),
+            self.price_update.as_ref(),
+        );
+        self.send(&[ix], &[&buyer]).await?;
+        Ok(self.negotiation_pda(session_id))
+    }
+
+    /// Registers a template owned by the seller.
+    pub async fn register_template(
+        &mut self,
+        template_id: u64,
+        params: TemplateParams,
+    ) -> Result<Pubkey, BanksClientError> {
+        let seller = self.seller.insecure_clone();
+        let ix = instructions::register_template(&seller.pubkey(), template_id, params);
+        self.send(&[ix], &[&seller]).await?;
+        Ok(find_template_pda(&seller.pubkey(), template_id).0)
+    }
+
+    pub async fn create_from_template(
+        &mut self,
+        session_id: u64,
+        template: &Pubkey,
+        escrow_amount: u64,
+    ) -> Result<Pubkey, BanksClientError> {
+        self.create_from_template_with(session_id, template, escrow_amount, None)
+            .await
+    }
+
+    pub async fn create_from_template_with(
+        &mut self,
+        session_id: u64,
+        template: &Pubkey,
+        escrow_amount: u64,
+        invitation_ttl: Option<i64>,
+    ) -> Result<Pubkey, BanksClientError> {
+        let buyer = self.buyer.insecure_clone();
+        let ix = instructions::create_negotiation_from_template(
+            &buyer.pubkey(),
+            &self.seller.pubkey(),
+            template,
+            &self.mint,
+            &self.ata(&buyer.pubkey()),
+            session_id,
+            escrow_amount,
+            haggle_client::create_service_hash("test-service"),
+            None,
+            invitation_ttl,
+        );
+        self.send(&[ix], &[&buyer]).await?;
+        Ok(self.negotiation_pda(session_id))
+    }
+
+    /// Sets the seller's acceptance policy, creating it on first use.
+    pub async fn set_policy(&mut self, params: PolicyParams) -> Result<(), BanksClientError> {
+        let seller = self.seller.insecure_clone();
+        let policy = find_policy_pda(&seller.pubkey()).0;
+        let ix = if self.exists(&policy).await {
+            instructions::update_acceptance_policy(&seller.pubkey(), params)
+        } else {
+            instructions::create_acceptance_policy(&seller.pubkey(), params)
+        };
+        self.send(&[ix], &[&seller]).await
+    }
+
+    pub async fn close_policy(&mut self) -> Result<(), BanksClientError> {
+        let seller = self.seller.insecure_clone();
+        let ix = instructions::close_acceptance_policy(&seller.pubkey());
+        self.send(&[ix], &[&seller]).await
+    }
+
+    pub async fn create(&mut self, session_id: u64) -> Pubkey {
+        self.create_with(session_id, params()).await.unwrap()
+    }
+
+    /// Creates a negotiation and has the seller accept the invitation.
+    pub async fn open(&mut self, session_id: u64) -> Pubkey {
+        let negotiation = self.create(session_id).await;
+        let seller = self.seller.insecure_clone();
+        self.accept_invitation(&seller, &negotiation).await.unwrap();
+        negotiation
+    }
+
+    pub async fn accept_invitation(
+        &mut self,
+        seller: &Keypair,
+        negotiation: &Pubkey,
+    ) -> Result<(), BanksClientError> {
+        self.accept_invitation_with_offer(seller, negotiation, None)
+            .await
+    }
+
+    /// Accepts the invitation with the seller's opening offer of `amount`.
+    pub async fn accept_invitation_with_offer(
+        &mut self,
+        seller: &Keypair,
+        negotiation: &Pubkey,
+        amount: Option<u64>,
+    ) -> Result<(), BanksClientError> {
+        let ix = instructions::accept_invitation(
+            &seller.pubkey(),
+            negotiation,
+            amount.map(opening_offer),
+            self.price_update.as_ref(),
+        );
+        self.send(&[ix], &[seller]).await
+    }
+
+    pub async fn offer(
+        &mut self,
+        offerer: &Keypair,
+        negotiation: &Pubkey,
+        amount: u64,
+    ) -> Result<(), BanksClientError> {
+        self.offer_with(offerer, negotiation, amount, false).await
+    }
+
+    pub async fn offer_with(
+        &mut self,
+        offerer: &Keypair,
+        negotiation: &Pubkey,
+        amount: u64,
+        is_final: bool,
+    ) -> Result<(), BanksClientError> {
+        let ix = instructions::submit_offer(
+            &offerer.pubkey(),
+            negotiation,
+            amount,
+            create_metadata("offer"),
+            is_final,
+            self.price_update.as_ref(),
+        );
+        self.send(&[ix], &[offerer]).await
+    }
+
+    pub async fn accept(
+        &mut self,
+        acceptor: &Keypair,
+        negotiation: &Pubkey,
+    ) -> Result<(), BanksClientError> {
+        let ix = instructions::accept_offer(
+            &acceptor.pubkey(),
+            negotiation,
+            &self.seller.pubkey(),
+            &self.ata(&self.seller.pubkey()),
+            &self.ata(&self.treasury.pubkey()),
+            &self.ata(&self.buyer.pubkey()),
+            self.price_update.as_ref(),
+            self.swap_route.as_ref(),
+        );
+        self.send(&[ix], &[acceptor]).await
+    }
+
+    pub async fn reject(
+        &mut self,
+        rejector: &Keypair,
+        negotiation: &Pubkey,
+    ) -> Result<(), BanksClientError> {
+        self.reject_with(rejector, negotiation, None).await
+    }
+
+    pub async fn reject_with(
+        &mut self,
+        rejector: &Keypair,
+        negotiation: &Pubkey,
+        reason: Option<TerminationReason>,
+    ) -> Result<(), BanksClientError> {
+        let ix = instructions::reject_negotiation(
+            &rejector.pubkey(),
+            negotiation,
+            &self.seller.pubkey(),
+            &self.ata(&self.buyer.pubkey()),
+            &self.ata(&self.treasury.pubkey()),
+            reason,
+        );
+        self.send(&[ix], &[rejector]).await
+    }
+
+    pub async fn cancel(
+        &mut self,
+        buyer: &Keypair,
+        negotiation: &Pubkey,
+    ) -> Result<(), BanksClientError> {
+        let ix = instructions::cancel_invitation(
+            &buyer.pubkey(),
+            negotiation,
+            &self.seller.pubkey(),
+            &self.ata(&self.buyer.pubkey()),
+            &self.ata(&self.treasury.pubkey()),
+        );
+        self.send(&[ix], &[buyer]).await
+    }
+
+    pub async fn expire(
+        &mut self,
+        cranker: &Keypair,
+        negotiation: &Pubkey,
+    ) -> Result<(), BanksClientError> {
+        let ix = instructions::expire_negotiation(
+            &cranker.pubkey(),
+            negotiation,
+            &self.seller.pubkey(),
+            &self.ata(&self.buyer.pubkey()),
+            &self.ata(&self.treasury.pubkey()),
+            Some(&self.ata(&cranker.pubkey())),
+        );
+        self.send(&[ix], &[cranker]).await
+    }
+
+    pub async fn close(
+        &mut self,
+        closer: &Keypair,
+        negotiation: &Pubkey,
+    ) -> Result<(), BanksClientError> {
+        let buyer = self.negotiation(negotiation).await.buyer;
+        let members = if self.exists(&find_coalition_pda(negotiation).0).await {
+            self.coalition(negotiation).await.members
+        } else {
+            Vec::new()
+        };
+        let member_token_accounts: Vec<Pubkey> = members
+            .iter()
+            .map(|share| self.ata(&share.member))
+            .collect();
+        let ix = instructions::close_negotiation(
+            &closer.pubkey(),
+            negotiation,
+            &buyer,
+            &self.ata(&buyer),
+            &member_token_accounts,
+        );
+        self.send(&[ix], &[closer]).await
+    }
+
+    // ===== Coalitions =====
+
+    pub async fn create_coalition(
+        &mut self,
+        lead: &Keypair,
+        negotiation: &Pubkey,
+    ) -> Result<(), BanksClientError> {
+        let ix = instructions::create_coalition(&lead.pubkey(), negotiation);
+        self.send(&[ix], &[lead]).await
+    }
+
+    pub async fn join_coalition(
+        &mut self,
+        member: &Keypair,
+        negotiation: &Pubkey,
+        amount: u64,
+    ) -> Result<(), BanksClientError> {
+        let ix = instructions::join_coalition(
+            &member.pubkey(),
+            negotiation,
+            &self.ata(&member.pubkey()),
+            amount,
+        );
+        self.send(&[ix], &[member]).await
+    }
+
+    pub async fn exit_coalition(
+        &mut self,
+        member: &Keypair,
+        negotiation: &Pubkey,
+    ) -> Result<(), BanksClientError> {
+        let lead = self.coalition(negotiation).await.lead;
+        let ix = instructions::exit_coalition(
+            &member.pubkey(),
+            &lead,
+            negotiation,
+            &self.ata(&member.pubkey()),
+        );
+        self.send(&[ix], &[member]).await
+    }
+
+    // ===== Auctions =====
+
+    pub async fn create_auction_with(
+        &mut self,
+        auction_id: u64,
+        params: AuctionParams,
+    ) -> Result<Pubkey, BanksClientError> {
+        let seller = self.seller.insecure_clone();
+        let ix = instructions::create_auction(&seller.pubkey(), &self.mint, auction_id, params);
+        self.send(&[ix], &[&seller]).await?;
+        Ok(self.auction_pda(auction_id))
+    }
+
+    pub async fn create_auction(&mut self, auction_id: u64, params: AuctionParams) -> Pubkey {
+        self.create_auction_with(auction_id, params).await.unwrap()
+    }
+
+    /// Bids from `bidder`'s ATA, refunding the current highest bidder's ATA.
+    pub async fn bid(
+        &mut self,
+        bidder: &Keypair,
+        auction: &Pubkey,
+        amount: u64,
+    ) -> Result<(), BanksClientError> {
+        let state = self.auction(auction).await;
+        let outbid = (state.bid_count > 0).then(|| self.ata(&state.highest_bidder));
+        let ix = instructions::place_bid(
+            &bidder.pubkey(),
+            auction,
+            &self.ata(&bidder.pubkey()),
+            outbid.as_ref(),
+            amount,
+        );
+        self.send(&[ix], &[bidder]).await
+    }
+
+    pub async fn buy(
+        &mut self,
+        buyer: &Keypair,
+        auction: &Pubkey,
+        max_price: u64,
+    ) -> Result<(), BanksClientError> {
+        let ix = instructions::buy_auction(
+            &buyer.pubkey(),
+            auction,
+            &self.ata(&buyer.pubkey()),
+            &self.ata(&self.seller.pubkey()),
+            &self.ata(&self.treasury.pubkey()),
+            max_price,
+        );
+        self.send(&[ix], &[buyer]).await
+    }
+
+    pub async fn settle_auction(
+        &mut self,
+        cranker: &Keypair,
+        auction: &Pubkey,
+    ) -> Result<(), BanksClientError> {
+        let ix = instructions::settle_auction(
+            &cranker.pubkey(),
+            auction,
+            &self.ata(&self.seller.pubkey()),
+            &self.ata(&self.treasury.pubkey()),
+        );
+        self.send(&[ix], &[cranker]).await
+    }
+
+    pub async fn close_auction(
+        &mut self,
+        seller: &Keypair,
+        auction: &Pubkey,
+    ) -> Result<(), BanksClientError> {
+        let ix =
+            instructions::close_auction(&seller.pubkey(), auction, &self.ata(&seller.pubkey()));
+        self.send(&[ix], &[seller]).await
+    }
+
+    /// Commits `amount` under a salt derived from the bidder's key, escrowing
+    /// `deposit`.
+    pub async fn commit_bid(
+        &mut self,
+        bidder: &Keypair,
+        auction: &Pubkey,
+        amount: u64,
+        deposit: u64,
+    ) -> Result<(), BanksClientError> {
+        let commitment = SealedBid::commitment(amount, &salt(bidder));
+        let ix = instructions::commit_bid(
+            &bidder.pubkey(),
+            auction,
+            &self.ata(&bidder.pubkey()),
+            commitment,
+            deposit,
+        );
+        self.send(&[ix], &[bidder]).await
+    }
+
+    pub async fn reveal_bid(
+        &mut self,
+        bidder: &Keypair,
+        auction: &Pubkey,
+        amount: u64,
+    ) -> Result<(), BanksClientError> {
+        let ix = instructions::reveal_bid(&bidder.pubkey(), auction, amount, salt(bidder));
+        self.send(&[ix], &[bidder]).await
+    }
+
+    pub async fn withdraw_bid(
+        &mut self,
+        cranker: &Keypair,
+        auction: &Pubkey,
+        bidder: &Pubkey,
+    ) -> Result<(), BanksClientError> {
+        let ix = instructions::withdraw_bid(
+            &cranker.pubkey(),
+            auction,
+            bidder,
+            &self.ata(bidder),
+            &self.ata(&self.seller.pubkey()),
+        );
+        self.send(&[ix], &[cranker]).await
+    }
+
+    // ===== Reverse auctions =====
+
+    pub async fn create_reverse_auction_with(
+        &mut self,
+        auction_id: u64,
+        params: ReverseAuctionParams,
+    ) -> Result<Pubkey, BanksClientError> {
+        let buyer = self.buyer.insecure_clone();
+        let ix = instructions::create_reverse_auction(
+            &buyer.pubkey(),
+            &self.mint,
+            &self.ata(&buyer.pubkey()),
+            auction_id,
+            params,
+        );
+        self.send(&[ix], &[&buyer]).await?;
+        Ok(self.reverse_auction_pda(auction_id))
+    }
+
+    pub async fn create_reverse_auction(
+        &mut self,
+        auction_id: u64,
+        params: ReverseAuctionParams,
+    ) -> Pubkey {
+        self.create_reverse_auction_with(auction_id, params)
+            .await
+            .unwrap()
+    }
+
+    pub async fn post_bond(
+        &mut self,
+        seller: &Keypair,
+        auction: &Pubkey,
+    ) -> Result<(), BanksClientError> {
+        let ix = instructions::post_bond(&seller.pubkey(), auction, &self.ata(&seller.pubkey()));
+        self.send(&[ix], &[seller]).await
+    }
+
+    /// Bids down, passing the seller's bond when the auction requires one.
+    pub async fn reverse_bid(
+        &mut self,
+        seller: &Keypair,
+        auction: &Pubkey,
+        amount: u64,
+    ) -> Result<(), BanksClientError> {
+        let bonded = self.reverse_auction(auction).await.seller_bond > 0;
+        let ix = instructions::place_reverse_bid(&seller.pubkey(), auction, bonded, amount);
+        self.send(&[ix], &[seller]).await
+    }
+
+    pub async fn settle_reverse_auction(
+        &mut self,
+        cranker: &Keypair,
+        auction: &Pubkey,
+    ) -> Result<(), BanksClientError> {
+        let state = self.reverse_auction(auction).await;
+        let winner = (state.bid_count > 0).then(|| self.ata(&state.lowest_bidder));
+        let ix = instructions::settle_reverse_auction(
+            &cranker.pubkey(),
+            auction,
+            &self.ata(&self.buyer.pubkey()),
+            winner.as_ref(),
+            &self.ata(&self.treasury.pubkey()),
+        );
+        self.send(&[ix], &[cranker]).await
+    }
+
+    pub async fn withdraw_bond(
+        &mut self,
+        cranker: &Keypair,
+        auction: &Pubkey,
+        seller: &Pubkey,
+    ) -> Result<(), BanksClientError> {
+        let ix = instructions::withdraw_bond(&cranker.pubkey(), auction, seller, &self.ata(seller));
+        self.send(&[ix], &[cranker]).await
+    }
+
+    pub async fn close_reverse_auction(
+        &mut self,
+        buyer: &Keypair,
+        auction: &Pubkey,
+    ) -> Result<(), BanksClientError> {
+        let ix = instructions::close_reverse_auction(
+            &buyer.pubkey(),
+            auction,
+            &self.ata(&buyer.pubkey()),
+        );
+        self.send(&[ix], &[buyer]).await
+    }
+}
+
+/// Deterministic per-bidder salt for sealed bids.
+pub fn salt(bidder: &Keypair) -> [u8; 32] {
+    bidder.pubkey().to_bytes()
+}
+
+/// Asserts that a transaction failed with the given program error.
+#[track_caller]
+pub fn assert_error<T: std::fmt::Debug>(
+    result: Result<T, BanksClientError>,
+    expected: HaggleError,
+) {
+    let code = u32::from(expected);
+    match result {
+        Err(BanksClientError::TransactionError(TransactionError::InstructionError(
+            _,
+            InstructionError::Custom(actual),
+        ))) => assert_eq!(actual, code, "expected {expected:?} ({code}), got {actual}"),
+        other => panic!("expected {expected:?} ({code}), got {other:?}"),
+    }
+}
@@ -0,0 +1,69 @@
+//! Compute units of the instructions that load `NegotiationState`.
+//!
+//! Native builtins are not metered, so the units are only measured against
+//! the BPF build:
+//!
+//! ```text
+//! cargo test-sbf -p haggle --test compute -- --nocapture
+//! ```
+//!
+//! which prints one line per instruction and holds each to the default
+//! per-instruction limit. The native run still exercises every path but
+//! checks no units. No Borsh versus zero-copy numbers have been recorded:
+//! neither layout has been measured against the BPF build yet.
+
+mod common;
+
+use std::path::Path;
+
+use common::*;
+
+/// Compute units an instruction gets without a compute budget request.
+const DEFAULT_LIMIT: u64 = 200_000;
+
+fn is_sbf() -> bool {
+    std::env::var_os("SBF_OUT_DIR").is_some_and(|dir| Path::new(&dir).join("haggle.so").exists())
+}
+
+/// Units the last transaction consumed, printed and checked when metered.
+fn record(h: &Harness, instruction: &str) {
+    if !is_sbf() {
+        return;
+    }
+    println!("{instruction:<20} {:>7} CU", h.compute_units);
+    assert!(h.compute_units > 0, "{instruction} was not metered");
+    assert!(
+        h.compute_units <= DEFAULT_LIMIT,
+        "{instruction} used {} CU",
+        h.compute_units
+    );
+}
+
+#[tokio::test]
+async fn negotiation_instructions_fit_the_compute_limit() {
+    let mut h = Harness::new().await;
+    let buyer = h.buyer.insecure_clone();
+    let seller = h.seller.insecure_clone();
+    let outsider = h.outsider.insecure_clone();
+
+    let negotiation = h.create(1).await;
+    record(&h, "create_negotiation");
+    h.accept_invitation(&seller, &negotiation).await.unwrap();
+    record(&h, "accept_invitation");
+    h.offer(&buyer, &negotiation, 3_000_000).await.unwrap();
+    record(&h, "submit_offer");
+    h.offer(&seller, &negotiation, 4_000_000).await.unwrap();
+    record(&h, "submit_offer (counter)");
+    h.accept(&buyer, &negotiation).await.unwrap();
+    record(&h, "accept_offer");
+
+    let negotiation = h.open(2).await;
+    h.reject(&seller, &negotiation).await.unwrap();
+    record(&h, "reject_negotiation");
+
+    let negotiation = h.create(3).await;
+    let deadline = h.negotiation(&negotiation).await.global_deadline;
+    h.warp_to(deadline).await;
+    h.expire(&outsider, &negotiation).await.unwrap();
+    record(&h, "expire_negotiation");
+}
@@ -0,0 +1,98 @@
+//! Protocol config updates.
+
+mod common;
+
+use common::*;
+use haggle::errors::HaggleError;
+use haggle::state::ConfigUpdateParams;
+use solana_sdk::{signature::Keypair, signer::Signer};
+
+#[tokio::test]
+async fn update_config_changes_only_given_fields() {
+    let mut h = Harness::new().await;
+    let authority = h.ctx.payer.insecure_clone();
+    let before = h.config().await;
+
+    let treasury = Keypair::new().pubkey();
+    let update = ConfigUpdateParams {
+        treasury: Some(treasury),
+        default_protocol_fee_bps: Some(250),
+        ..Default::default()
+    };
+    h.update_config(&authority, update).await.unwrap();
+
+    let after = h.config().await;
+    assert_eq!(after.treasury, treasury);
+    assert_eq!(after.default_protocol_fee_bps, 250);
+    assert_eq!(after.authority, before.authority);
+    assert_eq!(after.default_decay_rate_bps, before.default_decay_rate_bps);
+    assert_eq!(
+        after.default_response_window,
+        before.default_response_window
+    );
+    assert_eq!(after.default_max_rounds, before.default_max_rounds);
+    assert!(!after.is_paused);
+}
+
+#[tokio::test]
+async fn authority_can_be_handed_over() {
+    let mut h = Harness::new().await;
+    let authority = h.ctx.payer.insecure_clone();
+    let next = h.outsider.insecure_clone();
+
+    let handover = ConfigUpdateParams {
+        authority: Some(next.pubkey()),
+        ..Default::default()
+    };
+    h.update_config(&authority, handover).await.unwrap();
+    assert_eq!(h.config().await.authority, next.pubkey());
+
+    let pause = ConfigUpdateParams {
+        is_paused: Some(true),
+        ..Default::default()
+    };
+    assert_error(
+        h.update_config(&authority, pause.clone()).await,
+        HaggleError::Unauthorized,
+    );
+    h.update_config(&next, pause).await.unwrap();
+    assert!(h.config().await.is_paused);
+}
+
+#[tokio::test]
+async fn update_config_rejects_out_of_range_defaults() {
+    let mut h = Harness::new().await;
+    let authority = h.ctx.payer.insecure_clone();
+    let invalid = [
+        ConfigUpdateParams {
+            default_decay_rate_bps: Some(1001),
+            ..Default::default()
+        },
+        ConfigUpdateParams {
+            default_response_window: Some(59),
+            ..Default::default()
+        },
+        ConfigUpdateParams {
+            default_protocol_fee_bps: Some(501),
+            ..Default::default()
+        },
+        ConfigUpdateParams {
+            default_max_rounds: Some(0),
+            ..Default::default()
+        },
+        ConfigUpdateParams {
+            default_max_rounds: Some(21),
+            ..Default::default()
+        },
+        ConfigUpdateParams {
+            crank_reward_bps: Some(1001),
+            ..Default::default()
+        },
+    ];
+    for update in invalid {
+        assert_error(
+            h.update_config(&authority, update).await,
+            HaggleError::InvalidParams,
+        );
+    }
+}
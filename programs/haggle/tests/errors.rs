@@ -0,0 +1,503 @@
+//! One test per `HaggleError` variant that an instruction can return.
+//!
+//! `ZopaCommitmentMismatch` and `NoZopa` are reserved for the ZOPA
+//! commit/reveal flow and are not returned by any instruction yet.
+
+mod common;
+
+use common::*;
+use haggle::errors::HaggleError;
+use haggle::state::{
+    AuctionParams, ConfigUpdateParams, NegotiationParams, NegotiationState, ReverseAuctionParams,
+};
+use haggle_client::decode_negotiation;
+use solana_sdk::signer::Signer;
+
+#[tokio::test]
+async fn invalid_state() {
+    let mut h = Harness::new().await;
+    let buyer = h.buyer.insecure_clone();
+    let seller = h.seller.insecure_clone();
+    let negotiation = h.create(1).await;
+
+    // Offers need the seller to have joined.
+    assert_error(
+        h.offer(&buyer, &negotiation, 2_000_000).await,
+        HaggleError::InvalidState,
+    );
+    // Nothing to accept yet.
+    assert_error(
+        h.accept(&seller, &negotiation).await,
+        HaggleError::InvalidState,
+    );
+    // Only terminal negotiations can be closed.
+    assert_error(
+        h.close(&buyer, &negotiation).await,
+        HaggleError::InvalidState,
+    );
+
+    h.accept_invitation(&seller, &negotiation).await.unwrap();
+    h.warp_by(1).await;
+    assert_error(
+        h.accept_invitation(&seller, &negotiation).await,
+        HaggleError::InvalidState,
+    );
+    // Once accepted, the invitation can only be rejected.
+    assert_error(
+        h.cancel(&buyer, &negotiation).await,
+        HaggleError::InvalidState,
+    );
+
+    h.reject(&buyer, &negotiation).await.unwrap();
+    assert_error(
+        h.reject(&seller, &negotiation).await,
+        HaggleError::InvalidState,
+    );
+    assert_error(
+        h.offer(&seller, &negotiation, 2_000_000).await,
+        HaggleError::InvalidState,
+    );
+
+    // Pooled escrow is claimed by the members, so it cannot be cancelled.
+    let pooled = h.create(2).await;
+    h.create_coalition(&buyer, &pooled).await.unwrap();
+    assert_error(h.cancel(&buyer, &pooled).await, HaggleError::InvalidState);
+
+    // A status byte outside the enum is refused rather than read.
+    let corrupt = h.create(3).await;
+    let mut account = h
+        .ctx
+        .banks_client
+        .get_account(corrupt)
+        .await
+        .unwrap()
+        .unwrap();
+    let status = 8 + std::mem::offset_of!(NegotiationState, status);
+    account.data[status] = u8::MAX;
+    h.ctx.set_account(&corrupt, &account.clone().into());
+    assert_error(h.cancel(&buyer, &corrupt).await, HaggleError::InvalidState);
+    assert!(decode_negotiation(&account.data).is_err());
+}
+
+#[tokio::test]
+async fn unauthorized() {
+    let mut h = Harness::new().await;
+    let buyer = h.buyer.insecure_clone();
+    let seller = h.seller.insecure_clone();
+    let outsider = h.outsider.insecure_clone();
+    let negotiation = h.open(1).await;
+
+    assert_error(
+        h.offer(&outsider, &negotiation, 2_000_000).await,
+        HaggleError::Unauthorized,
+    );
+    assert_error(
+        h.reject(&outsider, &negotiation).await,
+        HaggleError::Unauthorized,
+    );
+    // Only the buyer may withdraw an invitation.
+    let invitation = h.create(2).await;
+    assert_error(
+        h.cancel(&seller, &invitation).await,
+        HaggleError::Unauthorized,
+    );
+
+    h.offer(&buyer, &negotiation, 2_000_000).await.unwrap();
+    // Neither an outsider nor the offerer may accept.
+    assert_error(
+        h.accept(&outsider, &negotiation).await,
+        HaggleError::Unauthorized,
+    );
+    assert_error(
+        h.accept(&buyer, &negotiation).await,
+        HaggleError::Unauthorized,
+    );
+
+    h.accept(&seller, &negotiation).await.unwrap();
+    // Only the buyer may close before the grace period is over.
+    assert_error(
+        h.close(&seller, &negotiation).await,
+        HaggleError::Unauthorized,
+    );
+
+    // Only the config authority may update it.
+    let pause = ConfigUpdateParams {
+        is_paused: Some(true),
+        ..Default::default()
+    };
+    assert_error(
+        h.update_config(&outsider, pause).await,
+        HaggleError::Unauthorized,
+    );
+}
+
+#[tokio::test]
+async fn not_your_turn() {
+    let mut h = Harness::new().await;
+    let buyer = h.buyer.insecure_clone();
+    let seller = h.seller.insecure_clone();
+    let negotiation = h.open(1).await;
+
+    h.offer(&buyer, &negotiation, 2_000_000).await.unwrap();
+    assert_error(
+        h.offer(&buyer, &negotiation, 2_100_000).await,
+        HaggleError::NotYourTurn,
+    );
+
+    h.offer(&seller, &negotiation, 4_000_000).await.unwrap();
+    assert_error(
+        h.offer(&seller, &negotiation, 3_900_000).await,
+        HaggleError::NotYourTurn,
+    );
+}
+
+#[tokio::test]
+async fn expired() {
+    let mut h = Harness::new().await;
+    let buyer = h.buyer.insecure_clone();
+    let negotiation = h.open(1).await;
+
+    h.warp_by(DEADLINE_OFFSET).await;
+    assert_error(
+        h.offer(&buyer, &negotiation, 2_000_000).await,
+        HaggleError::Expired,
+    );
+}
+
+#[tokio::test]
+async fn response_window_expired() {
+    let mut h = Harness::new().await;
+    let buyer = h.buyer.insecure_clone();
+    let negotiation = h.open(1).await;
+
+    h.warp_by(RESPONSE_WINDOW).await;
+    assert_error(
+        h.offer(&buyer, &negotiation, 2_000_000).await,
+        HaggleError::ResponseWindowExpired,
+    );
+}
+
+#[tokio::test]
+async fn offer_too_low() {
+    let mut h = Harness::new().await;
+    let buyer = h.buyer.insecure_clone();
+    let negotiation = h.open(1).await;
+
+    // The minimum is taken from the escrow after this round's decay.
+    let effective = ESCROW - haggle_client::calculate_decay(ESCROW, DECAY_BPS);
+    let min_offer = haggle_client::calculate_min_offer(effective, MIN_OFFER_BPS);
+    assert_error(
+        h.offer(&buyer, &negotiation, min_offer - 1).await,
+        HaggleError::OfferTooLow,
+    );
+    h.offer(&buyer, &negotiation, min_offer).await.unwrap();
+}
+
+#[tokio::test]
+async fn offer_exceeds_escrow() {
+    let mut h = Harness::new().await;
+    let buyer = h.buyer.insecure_clone();
+    let negotiation = h.open(1).await;
+
+    let effective = ESCROW - haggle_client::calculate_decay(ESCROW, DECAY_BPS);
+    assert_error(
+        h.offer(&buyer, &negotiation, effective + 1).await,
+        HaggleError::OfferExceedsEscrow,
+    );
+    h.offer(&buyer, &negotiation, effective).await.unwrap();
+}
+
+#[tokio::test]
+async fn max_rounds_reached() {
+    let mut h = Harness::new().await;
+    let buyer = h.buyer.insecure_clone();
+    let seller = h.seller.insecure_clone();
+    let mut params = params();
+    params.max_rounds = 2;
+    let negotiation = h.create_with(1, params).await.unwrap();
+    h.accept_invitation(&seller, &negotiation).await.unwrap();
+
+    h.offer(&buyer, &negotiation, 2_000_000).await.unwrap();
+    h.offer(&seller, &negotiation, 4_000_000).await.unwrap();
+    assert_error(
+        h.offer(&buyer, &negotiation, 3_000_000).await,
+        HaggleError::MaxRoundsReached,
+    );
+
+    // The last offer can still be accepted.
+    h.accept(&buyer, &negotiation).await.unwrap();
+}
+
+#[tokio::test]
+async fn final_offer() {
+    let mut h = Harness::new().await;
+    let buyer = h.buyer.insecure_clone();
+    let seller = h.seller.insecure_clone();
+    let negotiation = h.open(1).await;
+
+    h.offer_with(&buyer, &negotiation, 2_000_000, true)
+        .await
+        .unwrap();
+    assert_error(
+        h.offer(&seller, &negotiation, 4_000_000).await,
+        HaggleError::FinalOffer,
+    );
+}
+
+type ParamsMutation = fn(&mut NegotiationParams);
+
+#[tokio::test]
+async fn invalid_params() {
+    let mut h = Harness::new().await;
+    let invalid: [(&str, ParamsMutation); 11] = [
+        ("zero rounds", |p| p.max_rounds = 0),
+        ("too many rounds", |p| p.max_rounds = 21),
+        ("decay above 10%", |p| p.decay_rate_bps = 1001),
+        ("response window under a minute", |p| p.response_window = 59),
+        ("deadline under five minutes", |p| {
+            p.global_deadline_offset = 299
+        }),
+        ("escrow below minimum", |p| p.escrow_amount = 99_999),
+        ("min offer below 1%", |p| p.min_offer_bps = 99),
+        ("min offer above 100%", |p| p.min_offer_bps = 10_001),
+        ("fee above 5%", |p| p.protocol_fee_bps = 501),
+        ("invitation ttl under a minute", |p| {
+            p.invitation_ttl = Some(59)
+        }),
+        ("invitation ttl past the deadline", |p| {
+            p.invitation_ttl = Some(p.global_deadline_offset)
+        }),
+    ];
+    for (session_id, (case, mutate)) in invalid.into_iter().enumerate() {
+        let mut params = params();
+        mutate(&mut params);
+        let result = h.create_with(session_id as u64, params).await;
+        assert!(result.is_err(), "{case}: expected InvalidParams");
+        assert_error(result, HaggleError::InvalidParams);
+    }
+
+    // Settlement accounts must belong to the right parties.
+    let buyer = h.buyer.insecure_clone();
+    let seller = h.seller.insecure_clone();
+    let negotiation = h.open(100).await;
+    h.offer(&buyer, &negotiation, 2_000_000).await.unwrap();
+    let ix = haggle_client::instructions::accept_offer(
+        &seller.pubkey(),
+        &negotiation,
+        &seller.pubkey(),
+        &h.ata(&buyer.pubkey()),
+        &h.ata(&h.treasury.pubkey()),
+        &h.ata(&buyer.pubkey()),
+        None,
+        None,
+    );
+    assert_error(h.send(&[ix], &[&seller]).await, HaggleError::InvalidParams);
+}
+
+type AuctionMutation = fn(&mut AuctionParams);
+
+#[tokio::test]
+async fn invalid_auction_params() {
+    let mut h = Harness::new().await;
+    let invalid: [(&str, AuctionParams, AuctionMutation); 10] = [
+        ("start price below minimum", english_params(), |p| {
+            p.start_price = 99_999
+        }),
+        ("duration under five minutes", english_params(), |p| {
+            p.duration = 299
+        }),
+        ("fee above 5%", english_params(), |p| {
+            p.protocol_fee_bps = 501
+        }),
+        ("increment above 50%", english_params(), |p| {
+            p.min_increment_bps = 5001
+        }),
+        ("no decay", dutch_params(), |p| p.decay_rate_bps = 0),
+        ("floor above start", dutch_params(), |p| {
+            p.floor_price = START_PRICE + 1
+        }),
+        ("too many decay steps", dutch_params(), |p| {
+            p.decay_interval = 60;
+            p.duration = 256 * 60;
+        }),
+        ("commit phase under a minute", vickrey_params(), |p| {
+            p.commit_duration = 59
+        }),
+        ("reveal phase under a minute", vickrey_params(), |p| {
+            p.commit_duration = p.duration - 59
+        }),
+        ("slash above 100%", vickrey_params(), |p| {
+            p.slash_bps = 10_001
+        }),
+    ];
+    for (auction_id, (case, mut params, mutate)) in invalid.into_iter().enumerate() {
+        mutate(&mut params);
+        let result = h.create_auction_with(auction_id as u64, params).await;
+        assert!(result.is_err(), "{case}: expected InvalidParams");
+        assert_error(result, HaggleError::InvalidParams);
+    }
+}
+
+#[tokio::test]
+async fn paused() {
+    let mut h = Harness::new().await;
+    let authority = h.ctx.payer.insecure_clone();
+    let pause = ConfigUpdateParams {
+        is_paused: Some(true),
+        ..Default::default()
+    };
+    h.update_config(&authority, pause).await.unwrap();
+
+    assert_error(h.create_with(1, params()).await, HaggleError::Paused);
+}
+
+#[tokio::test]
+async fn wrong_auction_kind() {
+    let mut h = Harness::new().await;
+    let buyer = h.buyer.insecure_clone();
+    let english = h.create_auction(1, english_params()).await;
+    let dutch = h.create_auction(2, dutch_params()).await;
+
+    assert_error(
+        h.buy(&buyer, &english, START_PRICE).await,
+        HaggleError::WrongAuctionKind,
+    );
+    assert_error(
+        h.bid(&buyer, &dutch, START_PRICE).await,
+        HaggleError::WrongAuctionKind,
+    );
+    assert_error(
+        h.commit_bid(&buyer, &english, START_PRICE, START_PRICE)
+            .await,
+        HaggleError::WrongAuctionKind,
+    );
+}
+
+#[tokio::test]
+async fn auction_not_ended() {
+    let mut h = Harness::new().await;
+    let outsider = h.outsider.insecure_clone();
+    let auction = h.create_auction(1, english_params()).await;
+    assert_error(
+        h.settle_auction(&outsider, &auction).await,
+        HaggleError::AuctionNotEnded,
+    );
+}
+
+#[tokio::test]
+async fn price_above_limit() {
+    let mut h = Harness::new().await;
+    let buyer = h.buyer.insecure_clone();
+    let auction = h.create_auction(1, dutch_params()).await;
+    assert_error(
+        h.buy(&buyer, &auction, START_PRICE - 1).await,
+        HaggleError::PriceAboveLimit,
+    );
+}
+
+#[tokio::test]
+async fn bid_commitment_mismatch() {
+    let mut h = Harness::new().await;
+    let buyer = h.buyer.insecure_clone();
+    let auction = h.create_auction(1, vickrey_params()).await;
+    h.commit_bid(&buyer, &auction, 3_000_000, 4_000_000)
+        .await
+        .unwrap();
+    let commit_ends_at = h.auction(&auction).await.commit_ends_at;
+    h.warp_to(commit_ends_at).await;
+    assert_error(
+        h.reveal_bid(&buyer, &auction, 3_000_001).await,
+        HaggleError::BidCommitmentMismatch,
+    );
+}
+
+#[tokio::test]
+async fn commit_phase_open() {
+    let mut h = Harness::new().await;
+    let buyer = h.buyer.insecure_clone();
+    let auction = h.create_auction(1, vickrey_params()).await;
+    h.commit_bid(&buyer, &auction, 3_000_000, 4_000_000)
+        .await
+        .unwrap();
+    assert_error(
+        h.reveal_bid(&buyer, &auction, 3_000_000).await,
+        HaggleError::CommitPhaseOpen,
+    );
+}
+
+#[tokio::test]
+async fn overflow() {
+    let mut h = Harness::new().await;
+    let mut params = params();
+    params.global_deadline_offset = i64::MAX;
+    assert_error(h.create_with(1, params).await, HaggleError::Overflow);
+}
+
+type ReverseAuctionMutation = fn(&mut ReverseAuctionParams);
+
+#[tokio::test]
+async fn invalid_reverse_auction_params() {
+    let mut h = Harness::new().await;
+    let invalid: [(&str, ReverseAuctionMutation); 4] = [
+        ("ceiling below minimum", |p| p.ceiling = 99_999),
+        ("duration under five minutes", |p| p.duration = 299),
+        ("fee above 5%", |p| p.protocol_fee_bps = 501),
+        ("decrement above 50%", |p| p.min_decrement_bps = 5001),
+    ];
+    for (auction_id, (case, mutate)) in invalid.into_iter().enumerate() {
+        let mut params = reverse_params();
+        mutate(&mut params);
+        let result = h
+            .create_reverse_auction_with(auction_id as u64, params)
+            .await;
+        assert!(result.is_err(), "{case}: expected InvalidParams");
+        assert_error(result, HaggleError::InvalidParams);
+    }
+}
+
+#[tokio::test]
+async fn bid_not_low_enough() {
+    let mut h = Harness::new().await;
+    let seller = h.seller.insecure_clone();
+    let auction = h.create_reverse_auction(1, reverse_params()).await;
+    h.reverse_bid(&seller, &auction, 4_000_000).await.unwrap();
+    assert_error(
+        h.reverse_bid(&seller, &auction, 4_000_000).await,
+        HaggleError::BidNotLowEnough,
+    );
+}
+
+#[tokio::test]
+async fn bond_required() {
+    let mut h = Harness::new().await;
+    let seller = h.seller.insecure_clone();
+    let mut params = reverse_params();
+    params.seller_bond = 250_000;
+    let auction = h.create_reverse_auction(1, params).await;
+    let ix = haggle_client::instructions::place_reverse_bid(
+        &seller.pubkey(),
+        &auction,
+        false,
+        4_000_000,
+    );
+    assert_error(h.send(&[ix], &[&seller]).await, HaggleError::BondRequired);
+}
+
+#[tokio::test]
+async fn coalition_full() {
+    let mut h = Harness::new().await;
+    let buyer = h.buyer.insecure_clone();
+    let negotiation = h.create(1).await;
+    h.create_coalition(&buyer, &negotiation).await.unwrap();
+    for _ in 1..haggle::state::MAX_COALITION_MEMBERS {
+        let member = h.funded_keypair(100_000).await;
+        h.join_coalition(&member, &negotiation, 100_000)
+            .await
+            .unwrap();
+    }
+    let member = h.funded_keypair(100_000).await;
+    assert_error(
+        h.join_coalition(&member, &negotiation, 100_000).await,
+        HaggleError::CoalitionFull,
+    );
+}
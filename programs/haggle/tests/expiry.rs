@@ -0,0 +1,342 @@
+//! Deadline, response-window and expiry boundaries, driven by warping the
+//! `Clock` sysvar.
+
+mod common;
+
+use common::*;
+use haggle::errors::HaggleError;
+use haggle::settlement::PayoutReason;
+use haggle::state::{
+    ConfigUpdateParams, NegotiationParams, NegotiationStatus, TerminationReason, CLOSE_BOUNTY,
+    CLOSE_GRACE_PERIOD,
+};
+use haggle_client::HaggleEvent;
+use solana_sdk::signer::Signer;
+
+#[tokio::test]
+async fn expire_is_rejected_until_global_deadline() {
+    let mut h = Harness::new().await;
+    let outsider = h.outsider.insecure_clone();
+    let negotiation = h.create(1).await;
+    let deadline = h.negotiation(&negotiation).await.global_deadline;
+
+    h.warp_to(deadline - 1).await;
+    assert_error(
+        h.expire(&outsider, &negotiation).await,
+        HaggleError::InvalidState,
+    );
+
+    // Permissionless: anyone can crank once the deadline is reached.
+    h.warp_to(deadline).await;
+    h.expire(&outsider, &negotiation).await.unwrap();
+
+    let state = h.negotiation(&negotiation).await;
+    assert_eq!(state.status().unwrap(), NegotiationStatus::Expired);
+    let vault = h.vault_pda(&negotiation);
+    assert_eq!(h.balance(&vault).await, 0);
+    let buyer = h.buyer.pubkey();
+    assert_eq!(h.token_balance(&buyer).await, BUYER_FUNDS);
+}
+
+const INVITATION_TTL: i64 = 600;
+
+fn invitation_params() -> NegotiationParams {
+    NegotiationParams {
+        invitation_ttl: Some(INVITATION_TTL),
+        ..params()
+    }
+}
+
+#[tokio::test]
+async fn unaccepted_invitation_lapses_before_the_deadline() {
+    let mut h = Harness::new().await;
+    let seller = h.seller.insecure_clone();
+    let outsider = h.outsider.insecure_clone();
+    let negotiation = h.create_with(1, invitation_params()).await.unwrap();
+    let state = h.negotiation(&negotiation).await;
+    let lapses_at = state.created_at + INVITATION_TTL;
+    assert_eq!(state.invitation_deadline, lapses_at);
+    assert!(matches!(
+        h.events().as_slice(),
+        [HaggleEvent::NegotiationCreated(e)] if e.invitation_deadline == lapses_at
+    ));
+
+    h.warp_to(lapses_at - 1).await;
+    assert_error(
+        h.expire(&outsider, &negotiation).await,
+        HaggleError::InvalidState,
+    );
+
+    // The seller can no longer accept, and anyone can crank it early.
+    h.warp_to(lapses_at).await;
+    assert_error(
+        h.accept_invitation(&seller, &negotiation).await,
+        HaggleError::Expired,
+    );
+    h.expire(&outsider, &negotiation).await.unwrap();
+    assert!(matches!(
+        h.events().as_slice(),
+        [HaggleEvent::Payout(refund), HaggleEvent::NegotiationExpired(e)]
+            if refund.amount == ESCROW
+                && e.refund_amount == ESCROW
+                && e.reason == TerminationReason::CounterpartyUnresponsive
+    ));
+    let state = h.negotiation(&negotiation).await;
+    assert_eq!(state.status().unwrap(), NegotiationStatus::Expired);
+    assert_eq!(
+        state.termination_reason().unwrap(),
+        TerminationReason::CounterpartyUnresponsive
+    );
+    assert_eq!(h.token_balance(&h.buyer.pubkey()).await, BUYER_FUNDS);
+}
+
+#[tokio::test]
+async fn accepted_invitation_runs_to_the_global_deadline() {
+    let mut h = Harness::new().await;
+    let seller = h.seller.insecure_clone();
+    let outsider = h.outsider.insecure_clone();
+    let negotiation = h.create_with(1, invitation_params()).await.unwrap();
+    h.accept_invitation(&seller, &negotiation).await.unwrap();
+    let deadline = h.negotiation(&negotiation).await.global_deadline;
+
+    h.warp_by(INVITATION_TTL).await;
+    assert_error(
+        h.expire(&outsider, &negotiation).await,
+        HaggleError::InvalidState,
+    );
+
+    h.warp_to(deadline).await;
+    h.expire(&outsider, &negotiation).await.unwrap();
+    assert!(matches!(
+        h.events().as_slice(),
+        [.., HaggleEvent::NegotiationExpired(e)] if e.reason == TerminationReason::Timeout
+    ));
+}
+
+#[tokio::test]
+async fn expire_from_every_active_status() {
+    let mut h = Harness::new().await;
+    let buyer = h.buyer.insecure_clone();
+    let seller = h.seller.insecure_clone();
+    let outsider = h.outsider.insecure_clone();
+
+    let created = h.create(1).await;
+    let proposed = h.open(2).await;
+    let countered = h.open(3).await;
+    h.offer(&buyer, &countered, 2_000_000).await.unwrap();
+    h.offer(&seller, &countered, 4_000_000).await.unwrap();
+    let state = h.negotiation(&countered).await;
+    let decay = state.escrow_amount - state.effective_escrow;
+
+    h.warp_by(DEADLINE_OFFSET).await;
+    for negotiation in [created, proposed, countered] {
+        h.expire(&outsider, &negotiation).await.unwrap();
+        assert_eq!(
+            h.negotiation(&negotiation).await.status().unwrap(),
+            NegotiationStatus::Expired
+        );
+    }
+    // The countered negotiation forfeits its decay to the treasury.
+    assert!(matches!(
+        h.events().as_slice(),
+        [HaggleEvent::Payout(forfeit), HaggleEvent::Payout(refund), HaggleEvent::NegotiationExpired(e)]
+            if forfeit.reason == PayoutReason::Decay
+                && forfeit.amount == decay
+                && refund.reason == PayoutReason::Refund
+                && e.refund_amount == ESCROW - decay
+    ));
+    assert_eq!(h.token_balance(&buyer.pubkey()).await, BUYER_FUNDS - decay);
+    assert_eq!(h.token_balance(&h.treasury.pubkey()).await, decay);
+}
+
+#[tokio::test]
+async fn actions_fail_once_global_deadline_is_reached() {
+    let mut h = Harness::new().await;
+    let buyer = h.buyer.insecure_clone();
+    let seller = h.seller.insecure_clone();
+
+    let invited = h.create(1).await;
+    let open = h.open(2).await;
+    h.offer(&buyer, &open, 2_000_000).await.unwrap();
+
+    let deadline = h.negotiation(&open).await.global_deadline;
+    h.warp_to(deadline).await;
+
+    assert_error(
+        h.accept_invitation(&seller, &invited).await,
+        HaggleError::Expired,
+    );
+    assert_error(h.accept(&seller, &open).await, HaggleError::Expired);
+    // The response window has lapsed too; the deadline check comes first.
+    assert_error(
+        h.offer(&seller, &open, 3_000_000).await,
+        HaggleError::Expired,
+    );
+}
+
+#[tokio::test]
+async fn accept_is_allowed_one_second_before_deadline() {
+    let mut h = Harness::new().await;
+    let buyer = h.buyer.insecure_clone();
+    let seller = h.seller.insecure_clone();
+    let negotiation = h.open(1).await;
+    h.offer(&buyer, &negotiation, 2_000_000).await.unwrap();
+
+    let deadline = h.negotiation(&negotiation).await.global_deadline;
+    h.warp_to(deadline - 1).await;
+    h.accept(&seller, &negotiation).await.unwrap();
+    assert_eq!(
+        h.negotiation(&negotiation).await.status().unwrap(),
+        NegotiationStatus::Settled
+    );
+}
+
+#[tokio::test]
+async fn response_window_boundary() {
+    let mut h = Harness::new().await;
+    let buyer = h.buyer.insecure_clone();
+    let seller = h.seller.insecure_clone();
+    let negotiation = h.open(1).await;
+
+    // accept_invitation starts the first window.
+    let opened_at = h.negotiation(&negotiation).await.last_offer_at;
+    h.warp_to(opened_at + RESPONSE_WINDOW - 1).await;
+    h.offer(&buyer, &negotiation, 2_000_000).await.unwrap();
+
+    let offered_at = h.negotiation(&negotiation).await.last_offer_at;
+    assert_eq!(offered_at, opened_at + RESPONSE_WINDOW - 1);
+
+    h.warp_to(offered_at + RESPONSE_WINDOW).await;
+    assert_error(
+        h.offer(&seller, &negotiation, 4_000_000).await,
+        HaggleError::ResponseWindowExpired,
+    );
+
+    // A lapsed window does not terminate the negotiation on its own.
+    assert_eq!(
+        h.negotiation(&negotiation).await.status().unwrap(),
+        NegotiationStatus::Proposed
+    );
+    h.accept(&seller, &negotiation).await.unwrap();
+}
+
+#[tokio::test]
+async fn terminal_states_cannot_be_expired() {
+    let mut h = Harness::new().await;
+    let buyer = h.buyer.insecure_clone();
+    let seller = h.seller.insecure_clone();
+    let outsider = h.outsider.insecure_clone();
+
+    let settled = h.open(1).await;
+    h.offer(&buyer, &settled, 2_000_000).await.unwrap();
+    h.accept(&seller, &settled).await.unwrap();
+    let rejected = h.create(2).await;
+    h.reject(&buyer, &rejected).await.unwrap();
+    let expired = h.create(3).await;
+
+    h.warp_by(DEADLINE_OFFSET).await;
+    h.expire(&outsider, &expired).await.unwrap();
+
+    h.warp_by(1).await;
+    for negotiation in [settled, rejected, expired] {
+        assert_error(
+            h.expire(&outsider, &negotiation).await,
+            HaggleError::InvalidState,
+        );
+    }
+}
+
+const CRANK_DEPOSIT: u64 = 1_000_000;
+
+/// Sets a crank reward of `CRANK_DEPOSIT` lamports plus 50 bps of the refund.
+async fn set_crank_reward(h: &mut Harness) {
+    let authority = h.ctx.payer.insecure_clone();
+    let update = ConfigUpdateParams {
+        crank_reward_lamports: Some(CRANK_DEPOSIT),
+        crank_reward_bps: Some(50),
+        ..Default::default()
+    };
+    h.update_config(&authority, update).await.unwrap();
+}
+
+#[tokio::test]
+async fn expire_pays_the_cranker_its_reward() {
+    let mut h = Harness::new().await;
+    let buyer = h.buyer.insecure_clone();
+    let outsider = h.outsider.insecure_clone();
+    set_crank_reward(&mut h).await;
+
+    let negotiation = h.create(1).await;
+    let state = h.negotiation(&negotiation).await;
+    assert_eq!(state.crank_deposit, CRANK_DEPOSIT);
+    assert_eq!(state.crank_reward_bps, 50);
+    let rent = h.lamports(&negotiation).await - CRANK_DEPOSIT;
+
+    h.warp_to(state.global_deadline).await;
+    let before = h.lamports(&outsider.pubkey()).await;
+    h.expire(&outsider, &negotiation).await.unwrap();
+
+    let share = ESCROW * 50 / 10_000;
+    assert_eq!(h.lamports(&outsider.pubkey()).await, before + CRANK_DEPOSIT);
+    assert_eq!(h.token_balance(&outsider.pubkey()).await, share);
+    assert_eq!(h.token_balance(&buyer.pubkey()).await, BUYER_FUNDS - share);
+    assert_eq!(h.lamports(&negotiation).await, rent);
+    assert_eq!(h.negotiation(&negotiation).await.crank_deposit, 0);
+    assert!(matches!(
+        h.events().as_slice(),
+        [HaggleEvent::Payout(crank), HaggleEvent::Payout(refund), HaggleEvent::NegotiationExpired(e)]
+            if crank.reason == PayoutReason::CrankReward
+                && crank.destination == h.ata(&outsider.pubkey())
+                && crank.amount == share
+                && refund.amount == ESCROW - share
+                && e.refund_amount == ESCROW - share
+                && e.crank_reward == CRANK_DEPOSIT
+    ));
+
+    let config = h.config().await;
+    assert_eq!(config.total_cranks, 1);
+    assert_eq!(config.total_crank_rewards_lamports, CRANK_DEPOSIT);
+    assert_eq!(config.total_crank_rewards_tokens, share);
+
+    // The deposit is paid once: closing later only earns the bounty.
+    h.warp_by(CLOSE_GRACE_PERIOD).await;
+    h.close(&outsider, &negotiation).await.unwrap();
+    assert!(matches!(
+        h.events().as_slice(),
+        [HaggleEvent::NegotiationClosed(e)] if e.bounty == CLOSE_BOUNTY
+    ));
+}
+
+#[tokio::test]
+async fn unpaid_crank_deposit_goes_to_the_closer_or_back_to_the_buyer() {
+    let mut h = Harness::new().await;
+    let buyer = h.buyer.insecure_clone();
+    let outsider = h.outsider.insecure_clone();
+    set_crank_reward(&mut h).await;
+
+    // The buyer closing gets the deposit back with the rent.
+    let cancelled = h.create(1).await;
+    h.reject(&buyer, &cancelled).await.unwrap();
+    let lamports = h.lamports(&cancelled).await + h.lamports(&h.vault_pda(&cancelled)).await;
+    let before = h.lamports(&buyer.pubkey()).await;
+    h.close(&buyer, &cancelled).await.unwrap();
+    assert_eq!(h.lamports(&buyer.pubkey()).await, before + lamports);
+
+    // Anyone else closing after the grace period keeps it with the bounty.
+    let abandoned = h.create(2).await;
+    h.reject(&buyer, &abandoned).await.unwrap();
+    let deadline = h.negotiation(&abandoned).await.global_deadline;
+    h.warp_to(deadline + CLOSE_GRACE_PERIOD).await;
+    let before = h.lamports(&outsider.pubkey()).await;
+    h.close(&outsider, &abandoned).await.unwrap();
+
+    let bounty = CLOSE_BOUNTY + CRANK_DEPOSIT;
+    assert_eq!(h.lamports(&outsider.pubkey()).await, before + bounty);
+    assert!(matches!(
+        h.events().as_slice(),
+        [HaggleEvent::NegotiationClosed(e)] if e.bounty == bounty
+    ));
+    let config = h.config().await;
+    assert_eq!(config.total_cranks, 1);
+    assert_eq!(config.total_crank_rewards_lamports, bounty);
+}
@@ -0,0 +1,624 @@
+//! Status transitions and settlement balances.
+//!
+//! `NegotiationStatus::Accepted` is never assigned by the program (settlement
+//! goes straight from Proposed/Countered to Settled), so it has no transition
+//! to cover here.
+
+mod common;
+
+use anchor_lang::error::ErrorCode;
+use common::*;
+use haggle::errors::HaggleError;
+use haggle::settlement::PayoutReason;
+use haggle::state::{
+    NegotiationStatus, OfferSide, TerminationReason, CLOSE_BOUNTY, CLOSE_GRACE_PERIOD,
+};
+use haggle_client::{
+    calculate_decay, create_metadata, decay_schedule, find_event_authority_pda, HaggleEvent,
+};
+use solana_program_test::BanksClientError;
+use solana_sdk::instruction::{AccountMeta, Instruction, InstructionError};
+use solana_sdk::signer::Signer;
+use solana_sdk::transaction::TransactionError;
+
+#[tokio::test]
+async fn full_negotiation_settles_and_pays_out() {
+    let mut h = Harness::new().await;
+    let buyer = h.buyer.insecure_clone();
+    let seller = h.seller.insecure_clone();
+
+    let negotiation = h.create(1).await;
+    let vault = h.vault_pda(&negotiation);
+    let state = h.negotiation(&negotiation).await;
+    assert_eq!(state.status().unwrap(), NegotiationStatus::Created);
+    assert_eq!(state.effective_escrow, ESCROW);
+    assert_eq!(h.balance(&vault).await, ESCROW);
+    assert_eq!(h.token_balance(&buyer.pubkey()).await, BUYER_FUNDS - ESCROW);
+
+    let events = h.events();
+    let [HaggleEvent::NegotiationCreated(event)] = events.as_slice() else {
+        panic!("expected a single NegotiationCreated event");
+    };
+    let params = params();
+    assert_eq!(event.session_id, 1);
+    assert_eq!(event.service_hash, params.service_hash);
+    assert_eq!(event.response_window, params.response_window);
+    assert_eq!(event.min_offer_bps, params.min_offer_bps);
+    assert_eq!(event.protocol_fee_bps, params.protocol_fee_bps);
+    assert_eq!(event.global_deadline, state.global_deadline);
+    assert_eq!(event.crank_deposit, state.crank_deposit);
+    assert_eq!(event.crank_reward_bps, state.crank_reward_bps);
+    assert!(!event.admitted);
+
+    // Created -> Proposed
+    h.accept_invitation(&seller, &negotiation).await.unwrap();
+    assert!(matches!(
+        h.events().as_slice(),
+        [HaggleEvent::InvitationAccepted(e)] if e.seller == seller.pubkey()
+    ));
+    assert_eq!(
+        h.negotiation(&negotiation).await.status().unwrap(),
+        NegotiationStatus::Proposed
+    );
+
+    // Proposed -> Proposed -> Countered -> Proposed -> Countered
+    let mut effective = ESCROW;
+    for (round, (offerer, amount)) in [
+        (&buyer, 2_000_000),
+        (&seller, 4_000_000),
+        (&buyer, 3_000_000),
+        (&seller, 3_500_000),
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        h.offer(offerer, &negotiation, amount).await.unwrap();
+        effective -= calculate_decay(effective, DECAY_BPS);
+
+        let events = h.events();
+        let [HaggleEvent::OfferSubmitted(event)] = events.as_slice() else {
+            panic!("expected a single OfferSubmitted event");
+        };
+        assert_eq!(event.offerer, offerer.pubkey());
+        assert_eq!(event.amount, amount);
+        assert_eq!(event.round as usize, round + 1);
+        assert_eq!(event.effective_escrow, effective);
+        assert_eq!(event.metadata, create_metadata("offer"));
+
+        let state = h.negotiation(&negotiation).await;
+        assert_eq!(
+            (event.offer_side, event.status),
+            (state.offer_side().unwrap(), state.status().unwrap())
+        );
+        let by_buyer = offerer.pubkey() == buyer.pubkey();
+        assert_eq!(state.current_round as usize, round + 1);
+        assert_eq!(state.current_offer_amount, amount);
+        assert_eq!(state.current_offer_by, offerer.pubkey());
+        assert_eq!(state.effective_escrow, effective);
+        if by_buyer {
+            assert_eq!(state.status().unwrap(), NegotiationStatus::Proposed);
+            assert_eq!(state.offer_side().unwrap(), OfferSide::Buyer);
+        } else {
+            assert_eq!(state.status().unwrap(), NegotiationStatus::Countered);
+            assert_eq!(state.offer_side().unwrap(), OfferSide::Seller);
+        }
+    }
+    assert_eq!(effective, 4_611_841);
+
+    // The client's projected schedule agrees with the on-chain decay.
+    let state = h.negotiation(&negotiation).await;
+    assert_eq!(decay_schedule(&state)[3].effective_escrow, effective);
+
+    // Countered -> Settled
+    h.accept(&buyer, &negotiation).await.unwrap();
+    let settled = 3_500_000;
+    let fee = settled * FEE_BPS as u64 / 10_000;
+
+    let events = h.events();
+    let [HaggleEvent::Payout(to_treasury), HaggleEvent::Payout(to_seller), HaggleEvent::Payout(to_buyer), HaggleEvent::NegotiationSettled(event)] =
+        events.as_slice()
+    else {
+        panic!("expected three Payout events and a NegotiationSettled event");
+    };
+    let treasury = h.treasury.pubkey();
+    for (payout, reason, owner, amount) in [
+        (to_treasury, PayoutReason::ProtocolFee, treasury, fee),
+        (
+            to_seller,
+            PayoutReason::Seller,
+            seller.pubkey(),
+            settled - fee,
+        ),
+        (
+            to_buyer,
+            PayoutReason::Refund,
+            buyer.pubkey(),
+            ESCROW - settled,
+        ),
+    ] {
+        assert_eq!(payout.negotiation_id, negotiation);
+        assert_eq!(payout.reason, reason);
+        assert_eq!(payout.destination, h.ata(&owner));
+        assert_eq!((payout.amount, payout.received), (amount, amount));
+    }
+    assert_eq!(event.settled_amount, settled);
+    assert_eq!(event.protocol_fee, fee);
+    assert_eq!(event.total_rounds, 4);
+    assert_eq!(event.escrow_decay_total, ESCROW - effective);
+
+    let state = h.negotiation(&negotiation).await;
+    assert_eq!(state.status().unwrap(), NegotiationStatus::Settled);
+    assert_eq!(state.settled_amount, settled);
+    assert!(state.settled_at > 0);
+
+    assert_eq!(h.balance(&vault).await, 0);
+    assert_eq!(h.token_balance(&seller.pubkey()).await, settled - fee);
+    assert_eq!(h.token_balance(&treasury).await, fee);
+    assert_eq!(
+        h.token_balance(&buyer.pubkey()).await,
+        BUYER_FUNDS - settled
+    );
+
+    let config = h.config().await;
+    assert_eq!(config.total_negotiations, 1);
+    assert_eq!(config.total_settled_volume, settled);
+    assert_eq!(config.total_fees_collected, fee);
+}
+
+#[tokio::test]
+async fn seller_accepts_buyer_proposal() {
+    let mut h = Harness::new().await;
+    let buyer = h.buyer.insecure_clone();
+    let seller = h.seller.insecure_clone();
+    let negotiation = h.open(1).await;
+
+    h.offer(&buyer, &negotiation, 4_000_000).await.unwrap();
+
+    // Proposed -> Settled
+    h.accept(&seller, &negotiation).await.unwrap();
+    assert_eq!(
+        h.negotiation(&negotiation).await.status().unwrap(),
+        NegotiationStatus::Settled
+    );
+
+    let fee = 4_000_000 * FEE_BPS as u64 / 10_000;
+    assert_eq!(h.token_balance(&seller.pubkey()).await, 4_000_000 - fee);
+    let treasury = h.treasury.pubkey();
+    assert_eq!(h.token_balance(&treasury).await, fee);
+    assert_eq!(
+        h.token_balance(&buyer.pubkey()).await,
+        BUYER_FUNDS - 4_000_000
+    );
+}
+
+#[tokio::test]
+async fn final_offer_can_only_be_accepted_or_rejected() {
+    let mut h = Harness::new().await;
+    let buyer = h.buyer.insecure_clone();
+    let seller = h.seller.insecure_clone();
+    let negotiation = h.open(1).await;
+    let other = h.open(2).await;
+
+    h.offer(&buyer, &negotiation, 2_000_000).await.unwrap();
+    h.offer_with(&seller, &negotiation, 4_000_000, true)
+        .await
+        .unwrap();
+    assert!(matches!(
+        h.events().as_slice(),
+        [HaggleEvent::OfferSubmitted(e)] if e.round == 2 && e.is_final
+    ));
+    assert!(h.negotiation(&negotiation).await.is_final_offer());
+
+    // The buyer can no longer counter, but can still take the offer...
+    assert_error(
+        h.offer(&buyer, &negotiation, 3_000_000).await,
+        HaggleError::FinalOffer,
+    );
+    h.accept(&buyer, &negotiation).await.unwrap();
+    let state = h.negotiation(&negotiation).await;
+    assert_eq!(state.status().unwrap(), NegotiationStatus::Settled);
+    assert_eq!(state.settled_amount, 4_000_000);
+
+    // ...or walk away from it.
+    h.offer_with(&buyer, &other, 2_000_000, true).await.unwrap();
+    assert_error(
+        h.offer(&seller, &other, 4_000_000).await,
+        HaggleError::FinalOffer,
+    );
+    h.reject_with(&seller, &other, Some(TerminationReason::PriceGap))
+        .await
+        .unwrap();
+    assert_eq!(
+        h.negotiation(&other).await.status().unwrap(),
+        NegotiationStatus::Rejected
+    );
+}
+
+#[tokio::test]
+async fn last_round_offer_is_implicitly_final() {
+    let mut h = Harness::new().await;
+    let buyer = h.buyer.insecure_clone();
+    let seller = h.seller.insecure_clone();
+    let mut params = params();
+    params.max_rounds = 3;
+    let negotiation = h.create_with(1, params).await.unwrap();
+    h.accept_invitation(&seller, &negotiation).await.unwrap();
+
+    h.offer(&buyer, &negotiation, 2_000_000).await.unwrap();
+    h.offer(&seller, &negotiation, 4_000_000).await.unwrap();
+    assert!(!h.negotiation(&negotiation).await.is_final_offer());
+
+    h.offer(&buyer, &negotiation, 3_000_000).await.unwrap();
+    assert!(matches!(
+        h.events().as_slice(),
+        [HaggleEvent::OfferSubmitted(e)] if e.round == 3 && e.is_final
+    ));
+    assert!(h.negotiation(&negotiation).await.is_final_offer());
+
+    h.accept(&seller, &negotiation).await.unwrap();
+    assert_eq!(h.negotiation(&negotiation).await.settled_amount, 3_000_000);
+}
+
+#[tokio::test]
+async fn opening_offers_ride_on_create_and_accept_invitation() {
+    let mut h = Harness::new().await;
+    let buyer = h.buyer.insecure_clone();
+    let seller = h.seller.insecure_clone();
+
+    // The buyer's opening offer is round 1 but leaves the invitation open.
+    let negotiation = h
+        .create_with_offer(1, params(), Some(2_000_000))
+        .await
+        .unwrap();
+    let effective = ESCROW - ESCROW * DECAY_BPS as u64 / 10_000;
+    let state = h.negotiation(&negotiation).await;
+    assert_eq!(state.status().unwrap(), NegotiationStatus::Created);
+    assert_eq!(
+        (state.current_round, state.offer_side().unwrap()),
+        (1, OfferSide::Buyer)
+    );
+    assert_eq!(state.current_offer_amount, 2_000_000);
+    assert_eq!(state.effective_escrow, effective);
+    assert!(matches!(
+        h.events().as_slice(),
+        [HaggleEvent::NegotiationCreated(_), HaggleEvent::OfferSubmitted(e)]
+            if e.round == 1 && e.status == NegotiationStatus::Created
+    ));
+
+    // The seller accepts and counters in one instruction.
+    h.accept_invitation_with_offer(&seller, &negotiation, Some(4_000_000))
+        .await
+        .unwrap();
+    let effective = effective - effective * DECAY_BPS as u64 / 10_000;
+    let state = h.negotiation(&negotiation).await;
+    assert_eq!(state.status().unwrap(), NegotiationStatus::Countered);
+    assert_eq!(
+        (state.current_round, state.offer_side().unwrap()),
+        (2, OfferSide::Seller)
+    );
+    assert_eq!(state.effective_escrow, effective);
+    assert!(matches!(
+        h.events().as_slice(),
+        [HaggleEvent::InvitationAccepted(_), HaggleEvent::OfferSubmitted(e)]
+            if e.round == 2 && e.amount == 4_000_000
+                && e.status == NegotiationStatus::Countered
+    ));
+    h.accept(&buyer, &negotiation).await.unwrap();
+
+    // Without a buyer offer the seller's counter opens round 1.
+    let negotiation = h.create(2).await;
+    h.accept_invitation_with_offer(&seller, &negotiation, Some(4_000_000))
+        .await
+        .unwrap();
+    let state = h.negotiation(&negotiation).await;
+    assert_eq!(state.status().unwrap(), NegotiationStatus::Countered);
+    assert_eq!(
+        (state.current_round, state.offer_side().unwrap()),
+        (1, OfferSide::Seller)
+    );
+    assert_error(
+        h.offer(&seller, &negotiation, 3_000_000).await,
+        HaggleError::NotYourTurn,
+    );
+
+    // Opening offers are held to the same floor and ceiling as any other.
+    assert_error(
+        h.create_with_offer(3, params(), Some(100_000)).await,
+        HaggleError::OfferTooLow,
+    );
+    assert_error(
+        h.create_with_offer(3, params(), Some(ESCROW)).await,
+        HaggleError::OfferExceedsEscrow,
+    );
+    let negotiation = h.create(3).await;
+    assert_error(
+        h.accept_invitation_with_offer(&seller, &negotiation, Some(100_000))
+            .await,
+        HaggleError::OfferTooLow,
+    );
+    assert_eq!(
+        h.negotiation(&negotiation).await.status().unwrap(),
+        NegotiationStatus::Created
+    );
+}
+
+#[tokio::test]
+async fn settlement_at_full_escrow_leaves_no_refund() {
+    let mut h = Harness::new().await;
+    let buyer = h.buyer.insecure_clone();
+    let seller = h.seller.insecure_clone();
+    let mut params = params();
+    params.decay_rate_bps = 0;
+    params.protocol_fee_bps = 0;
+    let negotiation = h.create_with(1, params).await.unwrap();
+    h.accept_invitation(&seller, &negotiation).await.unwrap();
+
+    h.offer(&buyer, &negotiation, ESCROW).await.unwrap();
+    h.accept(&seller, &negotiation).await.unwrap();
+
+    assert_eq!(h.token_balance(&seller.pubkey()).await, ESCROW);
+    let treasury = h.treasury.pubkey();
+    assert_eq!(h.token_balance(&treasury).await, 0);
+    assert_eq!(h.token_balance(&buyer.pubkey()).await, BUYER_FUNDS - ESCROW);
+}
+
+#[tokio::test]
+async fn reject_from_every_active_status_refunds_vault_less_decay() {
+    let mut h = Harness::new().await;
+    let buyer = h.buyer.insecure_clone();
+    let seller = h.seller.insecure_clone();
+
+    // Created -> Rejected
+    let created = h.create(1).await;
+    h.reject(&seller, &created).await.unwrap();
+
+    // Proposed -> Rejected
+    let proposed = h.open(2).await;
+    h.offer(&buyer, &proposed, 2_000_000).await.unwrap();
+    h.reject(&buyer, &proposed).await.unwrap();
+
+    // Countered -> Rejected
+    let countered = h.open(3).await;
+    h.offer(&buyer, &countered, 2_000_000).await.unwrap();
+    h.offer(&seller, &countered, 4_000_000).await.unwrap();
+    assert_eq!(
+        h.negotiation(&countered).await.status().unwrap(),
+        NegotiationStatus::Countered
+    );
+    // Only an expiry can record a timeout.
+    assert_error(
+        h.reject_with(&seller, &countered, Some(TerminationReason::Timeout))
+            .await,
+        HaggleError::InvalidParams,
+    );
+    h.reject_with(&seller, &countered, Some(TerminationReason::PriceGap))
+        .await
+        .unwrap();
+    assert!(matches!(
+        h.events().as_slice(),
+        [HaggleEvent::Payout(forfeit), HaggleEvent::Payout(refund), HaggleEvent::NegotiationRejected(e)]
+            if forfeit.reason == PayoutReason::Decay
+                && forfeit.destination == h.ata(&h.treasury.pubkey())
+                && refund.reason == PayoutReason::Refund
+                && forfeit.amount + refund.amount == ESCROW
+                && e.refund_amount == refund.amount
+                && e.reason == TerminationReason::PriceGap
+    ));
+
+    // The escrow lost to decay goes to the treasury.
+    let mut decay = 0;
+    for negotiation in [created, proposed, countered] {
+        let state = h.negotiation(&negotiation).await;
+        assert_eq!(state.status().unwrap(), NegotiationStatus::Rejected);
+        // Rejecting without a reason records it as unspecified.
+        let reason = if negotiation == countered {
+            TerminationReason::PriceGap
+        } else {
+            TerminationReason::Unspecified
+        };
+        assert_eq!(state.termination_reason().unwrap(), reason);
+        decay += state.escrow_amount - state.effective_escrow;
+        let vault = h.vault_pda(&negotiation);
+        assert_eq!(h.balance(&vault).await, 0);
+    }
+    assert!(decay > 0);
+    assert_eq!(h.token_balance(&buyer.pubkey()).await, BUYER_FUNDS - decay);
+    assert_eq!(h.token_balance(&h.treasury.pubkey()).await, decay);
+    assert_eq!(h.token_balance(&seller.pubkey()).await, 0);
+}
+
+#[tokio::test]
+async fn close_reclaims_rent_from_every_terminal_status() {
+    let mut h = Harness::new().await;
+    let buyer = h.buyer.insecure_clone();
+    let seller = h.seller.insecure_clone();
+
+    let settled = h.open(1).await;
+    h.offer(&buyer, &settled, 3_000_000).await.unwrap();
+    h.accept(&seller, &settled).await.unwrap();
+
+    let rejected = h.create(2).await;
+    h.reject(&buyer, &rejected).await.unwrap();
+
+    let expired = h.open(3).await;
+    h.warp_by(DEADLINE_OFFSET).await;
+    h.expire(&seller, &expired).await.unwrap();
+    assert_eq!(
+        h.negotiation(&expired).await.status().unwrap(),
+        NegotiationStatus::Expired
+    );
+
+    for negotiation in [settled, rejected, expired] {
+        let vault = h.vault_pda(&negotiation);
+        let rent = h.lamports(&negotiation).await + h.lamports(&vault).await;
+        let before = h.lamports(&buyer.pubkey()).await;
+
+        h.close(&buyer, &negotiation).await.unwrap();
+
+        assert!(!h.exists(&negotiation).await);
+        assert!(!h.exists(&vault).await);
+        assert_eq!(h.lamports(&buyer.pubkey()).await, before + rent);
+
+        let events = h.events();
+        let [HaggleEvent::NegotiationClosed(event)] = events.as_slice() else {
+            panic!("expected a single NegotiationClosed event");
+        };
+        assert_eq!(event.negotiation_id, negotiation);
+        assert_eq!(event.closed_by, buyer.pubkey());
+        assert_eq!(event.rent_reclaimed, rent);
+    }
+}
+
+#[tokio::test]
+async fn cancel_refunds_and_closes_an_unaccepted_invitation() {
+    let mut h = Harness::new().await;
+    let buyer = h.buyer.insecure_clone();
+
+    let negotiation = h.create(1).await;
+    let vault = h.vault_pda(&negotiation);
+    let rent = h.lamports(&negotiation).await + h.lamports(&vault).await;
+    let before = h.lamports(&buyer.pubkey()).await;
+
+    h.cancel(&buyer, &negotiation).await.unwrap();
+
+    assert!(!h.exists(&negotiation).await);
+    assert!(!h.exists(&vault).await);
+    assert_eq!(h.lamports(&buyer.pubkey()).await, before + rent);
+    assert_eq!(h.token_balance(&buyer.pubkey()).await, BUYER_FUNDS);
+    assert_eq!(h.token_balance(&h.treasury.pubkey()).await, 0);
+    assert!(matches!(
+        h.events().as_slice(),
+        [HaggleEvent::Payout(refund), HaggleEvent::NegotiationCancelled(e)]
+            if refund.reason == PayoutReason::Refund
+                && refund.amount == ESCROW
+                && e.negotiation_id == negotiation
+                && e.buyer == buyer.pubkey()
+                && e.refund_amount == ESCROW
+                && e.rent_reclaimed == rent
+    ));
+}
+
+#[tokio::test]
+async fn anyone_closes_after_the_grace_period_for_a_bounty() {
+    let mut h = Harness::new().await;
+    let buyer = h.buyer.insecure_clone();
+    let seller = h.seller.insecure_clone();
+    let cranker = h.outsider.insecure_clone();
+
+    let negotiation = h.open(1).await;
+    h.offer(&buyer, &negotiation, 3_000_000).await.unwrap();
+    h.accept(&seller, &negotiation).await.unwrap();
+    let settled_at = h.negotiation(&negotiation).await.settled_at;
+
+    h.warp_to(settled_at + CLOSE_GRACE_PERIOD - 1).await;
+    assert_error(
+        h.close(&cranker, &negotiation).await,
+        HaggleError::Unauthorized,
+    );
+
+    h.warp_to(settled_at + CLOSE_GRACE_PERIOD).await;
+    let vault = h.vault_pda(&negotiation);
+    let rent = h.lamports(&negotiation).await + h.lamports(&vault).await;
+    let buyer_before = h.lamports(&buyer.pubkey()).await;
+    let cranker_before = h.lamports(&cranker.pubkey()).await;
+
+    h.close(&cranker, &negotiation).await.unwrap();
+
+    assert!(!h.exists(&negotiation).await);
+    assert!(!h.exists(&vault).await);
+    assert_eq!(
+        h.lamports(&cranker.pubkey()).await,
+        cranker_before + CLOSE_BOUNTY
+    );
+    assert_eq!(
+        h.lamports(&buyer.pubkey()).await,
+        buyer_before + rent - CLOSE_BOUNTY
+    );
+
+    let events = h.events();
+    let [HaggleEvent::NegotiationClosed(event)] = events.as_slice() else {
+        panic!("expected a single NegotiationClosed event");
+    };
+    assert_eq!(event.closed_by, cranker.pubkey());
+    assert_eq!(event.rent_reclaimed, rent);
+    assert_eq!(event.bounty, CLOSE_BOUNTY);
+
+    // Rejected and expired negotiations wait out the grace period from the
+    // deadline.
+    let expired = h.open(2).await;
+    let deadline = h.negotiation(&expired).await.global_deadline;
+    h.warp_to(deadline).await;
+    h.expire(&seller, &expired).await.unwrap();
+    assert_error(h.close(&cranker, &expired).await, HaggleError::Unauthorized);
+    h.warp_to(deadline + CLOSE_GRACE_PERIOD).await;
+    h.close(&cranker, &expired).await.unwrap();
+    assert!(!h.exists(&expired).await);
+}
+
+#[tokio::test]
+async fn tokens_sent_to_the_vault_cannot_keep_it_open() {
+    let mut h = Harness::new().await;
+    let buyer = h.buyer.insecure_clone();
+    let seller = h.seller.insecure_clone();
+    let cranker = h.outsider.insecure_clone();
+
+    let negotiation = h.open(1).await;
+    h.offer(&buyer, &negotiation, 3_000_000).await.unwrap();
+    h.accept(&seller, &negotiation).await.unwrap();
+    let vault = h.vault_pda(&negotiation);
+    h.mint_to_account(&vault, 1).await;
+
+    let settled_at = h.negotiation(&negotiation).await.settled_at;
+    h.warp_to(settled_at + CLOSE_GRACE_PERIOD).await;
+    h.close(&cranker, &negotiation).await.unwrap();
+
+    assert!(!h.exists(&negotiation).await);
+    assert!(!h.exists(&vault).await);
+    assert_eq!(
+        h.token_balance(&buyer.pubkey()).await,
+        BUYER_FUNDS - 3_000_000 + 1
+    );
+    assert!(matches!(
+        h.events().as_slice(),
+        [HaggleEvent::Payout(p), HaggleEvent::NegotiationClosed(e)]
+            if p.reason == PayoutReason::Refund
+                && p.destination == h.ata(&buyer.pubkey())
+                && p.amount == 1
+                && e.closed_by == cranker.pubkey()
+    ));
+}
+
+#[tokio::test]
+async fn event_instructions_must_come_from_the_program() {
+    let mut h = Harness::new().await;
+    h.create(1).await;
+    assert!(matches!(
+        h.events().as_slice(),
+        [HaggleEvent::NegotiationCreated(_)]
+    ));
+    assert!(!h.logs.iter().any(|line| line.starts_with("Program data: ")));
+
+    // Replaying the self-CPI at the top level cannot sign for the event
+    // authority.
+    let (_, data) = h
+        .inner_instructions
+        .iter()
+        .find(|(program_id, _)| *program_id == haggle::ID)
+        .cloned()
+        .unwrap();
+    let ix = Instruction::new_with_bytes(
+        haggle::ID,
+        &data,
+        vec![AccountMeta::new_readonly(
+            find_event_authority_pda().0,
+            false,
+        )],
+    );
+    match h.send(&[ix], &[]).await {
+        Err(BanksClientError::TransactionError(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(code),
+        ))) => assert_eq!(code, ErrorCode::ConstraintSigner as u32),
+        other => panic!("expected ConstraintSigner, got {other:?}"),
+    }
+}
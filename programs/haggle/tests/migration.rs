@@ -0,0 +1,231 @@
+//! Accounts written in older layouts are decoded by the client and rewritten
+//! in place by `migrate_negotiation` and `migrate_config`.
+
+mod common;
+
+use anchor_lang::{AnchorDeserialize, AnchorSerialize, Discriminator};
+use common::*;
+use haggle::errors::HaggleError;
+use haggle::migration::{NegotiationStateV0, NegotiationStateV1, ProtocolConfigV0};
+use haggle::state::{NegotiationState, NegotiationStatus, ProtocolConfig, TerminationReason};
+use haggle_client::{decode_config, decode_negotiation, find_config_pda, instructions};
+use solana_sdk::account::Account;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signer::Signer;
+
+/// Replaces `address` with `data` in an older layout, funded for rent at that
+/// size as the old program would have left it.
+async fn write_legacy(h: &mut Harness, address: &Pubkey, data: Vec<u8>) {
+    let rent = h.ctx.banks_client.get_rent().await.unwrap();
+    let account = Account {
+        lamports: rent.minimum_balance(data.len()),
+        data,
+        owner: haggle::ID,
+        executable: false,
+        rent_epoch: 0,
+    };
+    h.ctx.set_account(address, &account.into());
+}
+
+async fn raw_data(h: &mut Harness, address: &Pubkey) -> Vec<u8> {
+    let account = h.ctx.banks_client.get_account(*address).await.unwrap();
+    account.unwrap().data
+}
+
+fn negotiation_v0(state: &NegotiationState) -> Vec<u8> {
+    let legacy = NegotiationStateV0 {
+        buyer: state.buyer,
+        seller: state.seller,
+        session_id: state.session_id,
+        status: state.status().unwrap(),
+        current_round: state.current_round,
+        current_offer_amount: state.current_offer_amount,
+        current_offer_by: state.current_offer_by,
+        offer_side: state.offer_side().unwrap(),
+        service_hash: state.service_hash,
+        escrow_amount: state.escrow_amount,
+        effective_escrow: state.effective_escrow,
+        token_mint: state.token_mint,
+        max_rounds: state.max_rounds,
+        decay_rate_bps: state.decay_rate_bps,
+        response_window: state.response_window,
+        global_deadline: state.global_deadline,
+        min_offer_bps: state.min_offer_bps,
+        protocol_fee_bps: state.protocol_fee_bps,
+        zopa_enabled: state.zopa_enabled(),
+        created_at: state.created_at,
+        last_offer_at: state.last_offer_at,
+        settled_at: state.settled_at,
+        settled_amount: state.settled_amount,
+        buyer_commitment: state.buyer_commitment,
+        seller_commitment: state.seller_commitment,
+        zopa_phase: state.zopa_phase().unwrap(),
+        metadata: state.metadata,
+        bump: state.bump,
+    };
+    let mut data = NegotiationState::DISCRIMINATOR.to_vec();
+    legacy.serialize(&mut data).unwrap();
+    assert_eq!(data.len(), NegotiationStateV0::LEN);
+    data
+}
+
+fn negotiation_v1(state: &NegotiationState) -> Vec<u8> {
+    let mut legacy = NegotiationStateV0::try_from_slice(&negotiation_v0(state)[8..])
+        .unwrap()
+        .upgrade();
+    legacy.mint_decimals = state.mint_decimals;
+    legacy.template = Pubkey::new_unique();
+    let mut data = NegotiationState::DISCRIMINATOR.to_vec();
+    legacy.serialize(&mut data).unwrap();
+    assert_eq!(data.len(), NegotiationStateV1::LEN);
+    data
+}
+
+fn legacy_config(config: &ProtocolConfig) -> Vec<u8> {
+    let legacy = ProtocolConfigV0 {
+        authority: config.authority,
+        treasury: config.treasury,
+        default_decay_rate_bps: config.default_decay_rate_bps,
+        default_response_window: config.default_response_window,
+        default_protocol_fee_bps: config.default_protocol_fee_bps,
+        default_max_rounds: config.default_max_rounds,
+        total_negotiations: config.total_negotiations,
+        total_settled_volume: config.total_settled_volume,
+        total_fees_collected: config.total_fees_collected,
+        is_paused: config.is_paused,
+        bump: config.bump,
+    };
+    let mut data = ProtocolConfig::DISCRIMINATOR.to_vec();
+    legacy.serialize(&mut data).unwrap();
+    assert_eq!(data.len(), ProtocolConfigV0::LEN);
+    data
+}
+
+/// Opens a negotiation with one offer, rewrites it as `legacy` and migrates
+/// it; returns the state before and after.
+async fn migrate(
+    h: &mut Harness,
+    legacy: fn(&NegotiationState) -> Vec<u8>,
+) -> (Pubkey, NegotiationState, NegotiationState) {
+    let negotiation = h.open(1).await;
+    let buyer = h.buyer.insecure_clone();
+    h.offer(&buyer, &negotiation, 3_000_000).await.unwrap();
+    let before = h.negotiation(&negotiation).await;
+    let data = legacy(&before);
+    let version = u8::from(data.len() != NegotiationStateV0::LEN);
+    write_legacy(h, &negotiation, data).await;
+
+    // The client reads the old layout; the program refuses it until migrated
+    let decoded = decode_negotiation(&raw_data(h, &negotiation).await).unwrap();
+    assert_eq!(decoded.version, version);
+    assert_eq!(decoded.buyer, before.buyer);
+    assert_eq!(decoded.status().unwrap(), before.status().unwrap());
+    assert_eq!(decoded.current_offer_amount, 3_000_000);
+    let seller = h.seller.insecure_clone();
+    assert!(h.accept(&seller, &negotiation).await.is_err());
+
+    // Anyone may migrate, paying the extra rent.
+    let outsider = h.outsider.insecure_clone();
+    let ix = instructions::migrate_negotiation(&outsider.pubkey(), &negotiation);
+    h.send(std::slice::from_ref(&ix), &[&outsider])
+        .await
+        .unwrap();
+
+    let data = raw_data(h, &negotiation).await;
+    assert_eq!(data.len(), NegotiationState::LEN);
+    let rent = h.ctx.banks_client.get_rent().await.unwrap();
+    assert!(h.lamports(&negotiation).await >= rent.minimum_balance(data.len()));
+    let after = h.negotiation(&negotiation).await;
+    assert_eq!(after.version, NegotiationState::VERSION);
+    assert_eq!(after.reserved, [0u8; 101]);
+    assert_eq!(after.crank_deposit, 0);
+    assert_eq!(after.invitation_deadline, 0);
+    assert_eq!(
+        after.termination_reason().unwrap(),
+        TerminationReason::Unspecified
+    );
+    assert!(!after.is_final_offer());
+    assert!(!after.is_admitted());
+    assert_eq!(after.buyer, before.buyer);
+    assert_eq!(after.session_id, before.session_id);
+    assert_eq!(after.status().unwrap(), before.status().unwrap());
+    assert_eq!(after.offer_side().unwrap(), before.offer_side().unwrap());
+    assert_eq!(after.current_offer_amount, before.current_offer_amount);
+    assert_eq!(after.effective_escrow, before.effective_escrow);
+    assert_eq!(after.global_deadline, before.global_deadline);
+    assert_eq!(after.bump, before.bump);
+
+    assert_error(
+        h.send(&[ix], &[&outsider]).await,
+        HaggleError::AlreadyMigrated,
+    );
+    (negotiation, before, after)
+}
+
+#[tokio::test]
+async fn legacy_negotiation_is_migrated_in_place_and_keeps_working() {
+    let mut h = Harness::new().await;
+    let (negotiation, _, after) = migrate(&mut h, negotiation_v0).await;
+    assert!(!after.is_quoted() && !after.is_cross_mint() && !after.is_pooled());
+
+    let seller = h.seller.insecure_clone();
+    h.accept(&seller, &negotiation).await.unwrap();
+    assert_eq!(
+        h.negotiation(&negotiation).await.status().unwrap(),
+        NegotiationStatus::Settled
+    );
+    assert_eq!(h.token_balance(&seller.pubkey()).await, 2_970_000);
+}
+
+#[tokio::test]
+async fn borsh_negotiation_is_migrated_to_zero_copy() {
+    let mut h = Harness::new().await;
+    let (negotiation, before, after) = migrate(&mut h, negotiation_v1).await;
+    assert_eq!(after.mint_decimals, before.mint_decimals);
+    assert_ne!(after.template, Pubkey::default());
+
+    let seller = h.seller.insecure_clone();
+    h.accept(&seller, &negotiation).await.unwrap();
+    assert_eq!(h.token_balance(&seller.pubkey()).await, 2_970_000);
+}
+
+#[tokio::test]
+async fn legacy_config_is_migrated_by_its_authority() {
+    let mut h = Harness::new().await;
+    h.create(1).await;
+    let config = find_config_pda().0;
+    let before = h.config().await;
+    write_legacy(&mut h, &config, legacy_config(&before)).await;
+
+    let decoded = decode_config(&raw_data(&mut h, &config).await).unwrap();
+    assert_eq!(decoded.version, 0);
+    assert_eq!(decoded.authority, before.authority);
+    assert_eq!(decoded.total_negotiations, 1);
+    assert!(h.create_with(2, params()).await.is_err());
+
+    let outsider = h.outsider.insecure_clone();
+    assert_error(
+        h.send(
+            &[instructions::migrate_config(&outsider.pubkey())],
+            &[&outsider],
+        )
+        .await,
+        HaggleError::Unauthorized,
+    );
+
+    let authority = h.ctx.payer.pubkey();
+    h.send(&[instructions::migrate_config(&authority)], &[])
+        .await
+        .unwrap();
+    let after = h.config().await;
+    assert_eq!(after.version, ProtocolConfig::VERSION);
+    assert_eq!(after.treasury, before.treasury);
+    assert_eq!(
+        after.default_protocol_fee_bps,
+        before.default_protocol_fee_bps
+    );
+    assert_eq!(after.total_negotiations, 1);
+
+    h.create_with(2, params()).await.unwrap();
+    assert_eq!(h.config().await.total_negotiations, 2);
+}
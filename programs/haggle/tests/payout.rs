@@ -0,0 +1,163 @@
+//! Cross-mint settlement: the seller's proceeds are swapped into the payout
+//! mint through the negotiated adapter, or paid in the escrow mint when the
+//! swap would slip too far or the seller waives the route.
+
+mod common;
+
+use anchor_spl::associated_token::get_associated_token_address;
+use common::*;
+use haggle::errors::HaggleError;
+use haggle::settlement::PayoutReason;
+use haggle::state::{NegotiationParams, PayoutTerms};
+use haggle_client::HaggleEvent;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signer::Signer;
+
+const OFFER: u64 = 3_000_000;
+/// `OFFER` less the 1% protocol fee.
+const SELLER_PAYMENT: u64 = 2_970_000;
+
+fn payout_params(mint: Pubkey) -> NegotiationParams {
+    NegotiationParams {
+        payout: Some(PayoutTerms {
+            mint,
+            swap_adapter: mock_amm::ID,
+            max_slippage_bps: 100,
+        }),
+        ..params()
+    }
+}
+
+/// Opens a cross-mint negotiation and has the buyer offer `OFFER`.
+async fn agree(h: &mut Harness, session_id: u64, payout_mint: Pubkey) -> Pubkey {
+    let negotiation = h
+        .create_with(session_id, payout_params(payout_mint))
+        .await
+        .unwrap();
+    let seller = h.seller.insecure_clone();
+    h.accept_invitation(&seller, &negotiation).await.unwrap();
+    let buyer = h.buyer.insecure_clone();
+    h.offer(&buyer, &negotiation, OFFER).await.unwrap();
+    negotiation
+}
+
+#[tokio::test]
+async fn seller_is_paid_in_the_payout_mint_through_the_swap() {
+    let mut h = Harness::new().await;
+    // Deep pool at two payout units per escrow unit.
+    let (reserve_in, reserve_out) = (1_000_000_000u64, 2_000_000_000u64);
+    let payout_mint = h.create_pool(reserve_in, reserve_out).await;
+    let negotiation = agree(&mut h, 1, payout_mint).await;
+
+    let seller = h.seller.insecure_clone();
+    h.accept(&seller, &negotiation).await.unwrap();
+
+    let expected = (reserve_out as u128 * SELLER_PAYMENT as u128
+        / (reserve_in + SELLER_PAYMENT) as u128) as u64;
+    let payout_account = get_associated_token_address(&seller.pubkey(), &payout_mint);
+    assert!(matches!(
+        h.events().as_slice(),
+        [_, HaggleEvent::Payout(p), _, HaggleEvent::NegotiationSettled(e)]
+            if p.reason == PayoutReason::Seller
+                && p.destination == payout_account
+                && p.mint == payout_mint
+                && (p.amount, p.received) == (SELLER_PAYMENT, expected)
+                && e.settled_amount == OFFER
+                && e.protocol_fee == OFFER - SELLER_PAYMENT
+                && e.payout_mint == payout_mint
+                && e.payout_amount == expected
+    ));
+    assert_eq!(h.balance(&payout_account).await, expected);
+    let seller_ata = h.ata(&seller.pubkey());
+    assert_eq!(h.balance(&seller_ata).await, 0);
+    let treasury_ata = h.ata(&h.treasury.pubkey());
+    assert_eq!(h.balance(&treasury_ata).await, OFFER - SELLER_PAYMENT);
+    let buyer_ata = h.ata(&h.buyer.pubkey());
+    assert_eq!(h.balance(&buyer_ata).await, BUYER_FUNDS - OFFER);
+}
+
+#[tokio::test]
+async fn settlement_falls_back_to_the_escrow_mint() {
+    let mut h = Harness::new().await;
+    // A shallow pool would slip ~23% on the seller's proceeds.
+    let payout_mint = h.create_pool(10_000_000, 10_000_000).await;
+    let seller = h.seller.insecure_clone();
+    let seller_ata = h.ata(&seller.pubkey());
+
+    let negotiation = agree(&mut h, 1, payout_mint).await;
+    h.accept(&seller, &negotiation).await.unwrap();
+    let mint = h.mint;
+    assert!(matches!(
+        h.events().as_slice(),
+        [.., HaggleEvent::NegotiationSettled(e)]
+            if e.payout_mint == mint && e.payout_amount == SELLER_PAYMENT
+    ));
+    assert_eq!(h.balance(&seller_ata).await, SELLER_PAYMENT);
+
+    // Only the negotiated adapter may be routed through.
+    let negotiation = agree(&mut h, 2, payout_mint).await;
+    h.swap_route.as_mut().unwrap().swap_adapter = anchor_spl::token::ID;
+    assert_error(
+        h.accept(&seller, &negotiation).await,
+        HaggleError::InvalidParams,
+    );
+
+    // Without a route the seller is paid in the escrow mint.
+    h.swap_route = None;
+    h.accept(&seller, &negotiation).await.unwrap();
+    assert_eq!(h.balance(&seller_ata).await, 2 * SELLER_PAYMENT);
+}
+
+#[tokio::test]
+async fn buyer_cannot_accept_without_the_swap_route() {
+    let mut h = Harness::new().await;
+    let payout_mint = h.create_pool(1_000_000_000, 2_000_000_000).await;
+    let buyer = h.buyer.insecure_clone();
+    let seller = h.seller.insecure_clone();
+    let negotiation = agree(&mut h, 1, payout_mint).await;
+    h.offer(&seller, &negotiation, OFFER).await.unwrap();
+
+    let route = h.swap_route.take();
+    assert_error(
+        h.accept(&buyer, &negotiation).await,
+        HaggleError::InvalidParams,
+    );
+
+    h.swap_route = route;
+    h.accept(&buyer, &negotiation).await.unwrap();
+    let payout_account = get_associated_token_address(&seller.pubkey(), &payout_mint);
+    assert!(matches!(
+        h.events().as_slice(),
+        [.., HaggleEvent::NegotiationSettled(e)] if e.payout_mint == payout_mint
+    ));
+    assert!(h.balance(&payout_account).await > 0);
+    let seller_ata = h.ata(&seller.pubkey());
+    assert_eq!(h.balance(&seller_ata).await, 0);
+}
+
+#[tokio::test]
+async fn adapter_that_breaks_its_quote_fails_the_swap() {
+    let mut h = Harness::new().await;
+    let payout_mint = h.create_pool(1_000_000_000, 2_000_000_000).await;
+    let seller = h.seller.insecure_clone();
+    let negotiation = agree(&mut h, 1, payout_mint).await;
+
+    // Paying less than quoted, or keeping a hold on the vault the
+    // negotiation signed for, aborts the settlement.
+    for mode in [
+        mock_amm::Mode::ShortChange,
+        mock_amm::Mode::Approve,
+        mock_amm::Mode::CloseAuthority,
+    ] {
+        h.set_pool_mode(mode).await;
+        assert_error(
+            h.accept(&seller, &negotiation).await,
+            HaggleError::SwapFailed,
+        );
+    }
+
+    h.set_pool_mode(mock_amm::Mode::Honest).await;
+    h.accept(&seller, &negotiation).await.unwrap();
+    let payout_account = get_associated_token_address(&seller.pubkey(), &payout_mint);
+    assert!(h.balance(&payout_account).await > 0);
+}
@@ -0,0 +1,161 @@
+//! Seller acceptance policies: who may open negotiations, on what terms, and
+//! how many at once.
+
+mod common;
+
+use common::*;
+use haggle::errors::HaggleError;
+use haggle::state::{BuyerFilter, NegotiationStatus};
+use haggle_client::HaggleEvent;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signer::Signer;
+
+#[tokio::test]
+async fn policy_filters_buyers_escrow_mints_and_templates() {
+    let mut h = Harness::new().await;
+    let buyer = h.buyer.pubkey();
+
+    let mut policy = policy_params();
+    policy.buyer_filter = BuyerFilter::Allow;
+    policy.buyers = vec![Pubkey::new_unique()];
+    h.set_policy(policy).await.unwrap();
+    assert!(matches!(
+        h.events().as_slice(),
+        [HaggleEvent::AcceptancePolicySet(e)]
+            if e.seller == h.seller.pubkey() && e.buyer_filter == BuyerFilter::Allow
+    ));
+    assert_error(
+        h.create_with(1, params()).await,
+        HaggleError::BuyerNotAllowed,
+    );
+
+    let mut policy = policy_params();
+    policy.buyer_filter = BuyerFilter::Deny;
+    policy.buyers = vec![buyer];
+    h.set_policy(policy).await.unwrap();
+    assert_error(
+        h.create_with(1, params()).await,
+        HaggleError::BuyerNotAllowed,
+    );
+
+    let mut policy = policy_params();
+    policy.buyer_filter = BuyerFilter::Allow;
+    policy.buyers = vec![buyer];
+    policy.min_escrow = ESCROW + 1;
+    h.set_policy(policy).await.unwrap();
+    assert_error(
+        h.create_with(1, params()).await,
+        HaggleError::PolicyViolation,
+    );
+
+    let mut policy = policy_params();
+    policy.accepted_mints = vec![Pubkey::new_unique()];
+    h.set_policy(policy).await.unwrap();
+    assert_error(
+        h.create_with(1, params()).await,
+        HaggleError::PolicyViolation,
+    );
+
+    // Requiring a template turns away plain negotiations and other templates.
+    let required = h.register_template(1, template_params()).await.unwrap();
+    let other = h.register_template(2, template_params()).await.unwrap();
+    let mut policy = policy_params();
+    policy.required_template = Some(required);
+    h.set_policy(policy).await.unwrap();
+    assert_error(
+        h.create_with(1, params()).await,
+        HaggleError::PolicyViolation,
+    );
+    assert_error(
+        h.create_from_template(1, &other, ESCROW).await,
+        HaggleError::PolicyViolation,
+    );
+    let negotiation = h.create_from_template(1, &required, ESCROW).await.unwrap();
+    assert_eq!(
+        h.negotiation(&negotiation).await.status().unwrap(),
+        NegotiationStatus::Created
+    );
+    assert_eq!(h.policy().await.open_negotiations, 1);
+
+    // Without a policy the seller takes anyone again.
+    h.close_policy().await.unwrap();
+    assert!(matches!(
+        h.events().as_slice(),
+        [HaggleEvent::AcceptancePolicyClosed(_)]
+    ));
+    h.create(2).await;
+}
+
+#[tokio::test]
+async fn open_limit_frees_slots_and_auto_accept_opens_the_first_round() {
+    let mut h = Harness::new().await;
+    let buyer = h.buyer.insecure_clone();
+    let seller = h.seller.insecure_clone();
+
+    let mut policy = policy_params();
+    policy.max_open_negotiations = 2;
+    policy.auto_accept = true;
+    h.set_policy(policy).await.unwrap();
+
+    let first = h.create(1).await;
+    let state = h.negotiation(&first).await;
+    assert_eq!(state.status().unwrap(), NegotiationStatus::Proposed);
+    assert_eq!(state.last_offer_at, state.created_at);
+    assert!(matches!(
+        h.events().as_slice(),
+        [HaggleEvent::NegotiationCreated(created), HaggleEvent::InvitationAccepted(e)]
+            if created.admitted && e.negotiation_id == first && e.seller == seller.pubkey()
+    ));
+    let second = h.create(2).await;
+    assert_eq!(h.policy().await.open_negotiations, 2);
+    assert_error(
+        h.create_with(3, params()).await,
+        HaggleError::TooManyOpenNegotiations,
+    );
+
+    // Rejecting and settling both give the slot back.
+    h.reject(&buyer, &first).await.unwrap();
+    assert_eq!(h.policy().await.open_negotiations, 1);
+    h.create(3).await;
+    h.offer(&buyer, &second, 2_000_000).await.unwrap();
+    h.accept(&seller, &second).await.unwrap();
+    assert_eq!(h.policy().await.open_negotiations, 1);
+
+    // Lowering the limit keeps open negotiations but blocks new ones.
+    let mut policy = policy_params();
+    policy.max_open_negotiations = 1;
+    h.set_policy(policy).await.unwrap();
+    assert_error(
+        h.create_with(4, params()).await,
+        HaggleError::TooManyOpenNegotiations,
+    );
+}
+
+#[tokio::test]
+async fn only_admitted_negotiations_give_a_slot_back() {
+    let mut h = Harness::new().await;
+    let buyer = h.buyer.insecure_clone();
+
+    // Opened before the seller had a policy, so it never took a slot.
+    let before = h.create(1).await;
+    assert!(!h.negotiation(&before).await.is_admitted());
+
+    let mut policy = policy_params();
+    policy.max_open_negotiations = 1;
+    h.set_policy(policy).await.unwrap();
+    let admitted = h.create(2).await;
+    assert!(h.negotiation(&admitted).await.is_admitted());
+    assert_eq!(h.policy().await.open_negotiations, 1);
+
+    // Ending the older negotiation leaves the admitted one's slot taken.
+    h.reject(&buyer, &before).await.unwrap();
+    assert_eq!(h.policy().await.open_negotiations, 1);
+    assert_error(
+        h.create_with(3, params()).await,
+        HaggleError::TooManyOpenNegotiations,
+    );
+
+    h.reject(&buyer, &admitted).await.unwrap();
+    assert!(!h.negotiation(&admitted).await.is_admitted());
+    assert_eq!(h.policy().await.open_negotiations, 0);
+}
@@ -0,0 +1,102 @@
+//! Quote-denominated negotiations: offers in a Pyth feed's quote currency,
+//! bounded by the escrow at the current price and converted at settlement.
+
+mod common;
+
+use common::*;
+use haggle::errors::HaggleError;
+use haggle::state::{NegotiationParams, NegotiationStatus};
+use haggle_client::HaggleEvent;
+use solana_sdk::signer::Signer;
+
+/// Two quote units (dollars on a USD feed) per whole token.
+const TWO_DOLLARS: i64 = 200_000_000;
+const EXPONENT: i32 = -8;
+const TIGHT_CONF: u64 = 100_000;
+
+fn quoted_params() -> NegotiationParams {
+    NegotiationParams {
+        price_feed: Some(FEED_ID),
+        ..params()
+    }
+}
+
+#[tokio::test]
+async fn quoted_offer_settles_at_the_price_on_acceptance() {
+    let mut h = Harness::new().await;
+    h.set_price(TWO_DOLLARS, TIGHT_CONF, EXPONENT).await;
+    let negotiation = h.create_with(1, quoted_params()).await.unwrap();
+    assert!(matches!(
+        h.events().as_slice(),
+        [HaggleEvent::NegotiationCreated(e)] if e.price_feed == FEED_ID && e.mint_decimals == 6
+    ));
+    let seller = h.seller.insecure_clone();
+    h.accept_invitation(&seller, &negotiation).await.unwrap();
+
+    // $4 is two tokens at the offer's price...
+    let buyer = h.buyer.insecure_clone();
+    h.offer(&buyer, &negotiation, 4_000_000).await.unwrap();
+    assert_eq!(
+        h.negotiation(&negotiation).await.current_offer_amount,
+        4_000_000
+    );
+
+    // ...but one token once the price doubles.
+    h.set_price(2 * TWO_DOLLARS, TIGHT_CONF, EXPONENT).await;
+    h.accept(&seller, &negotiation).await.unwrap();
+    assert!(matches!(
+        h.events().as_slice(),
+        [.., HaggleEvent::NegotiationSettled(e)] if e.settled_amount == 1_000_000
+    ));
+
+    let state = h.negotiation(&negotiation).await;
+    assert_eq!(state.status().unwrap(), NegotiationStatus::Settled);
+    assert_eq!(state.settled_amount, 1_000_000);
+    let seller_ata = h.ata(&seller.pubkey());
+    assert_eq!(h.balance(&seller_ata).await, 990_000);
+    let buyer_ata = h.ata(&buyer.pubkey());
+    assert_eq!(h.balance(&buyer_ata).await, BUYER_FUNDS - 1_000_000);
+}
+
+#[tokio::test]
+async fn quoted_offers_need_a_fresh_confident_price_within_escrow() {
+    let mut h = Harness::new().await;
+    let negotiation = h.create_with(1, quoted_params()).await.unwrap();
+    let seller = h.seller.insecure_clone();
+    h.accept_invitation(&seller, &negotiation).await.unwrap();
+    let buyer = h.buyer.insecure_clone();
+
+    assert_error(
+        h.offer(&buyer, &negotiation, 4_000_000).await,
+        HaggleError::InvalidPriceFeed,
+    );
+
+    // A 2.5% confidence interval is wider than the 2% allowed.
+    h.set_price(TWO_DOLLARS, 5_000_000, EXPONENT).await;
+    assert_error(
+        h.offer(&buyer, &negotiation, 4_000_000).await,
+        HaggleError::PriceUncertain,
+    );
+
+    h.set_price(TWO_DOLLARS, TIGHT_CONF, EXPONENT).await;
+    h.warp_by(61).await;
+    assert_error(
+        h.offer(&buyer, &negotiation, 4_000_000).await,
+        HaggleError::StalePrice,
+    );
+
+    // $20 is ten tokens, more than the escrow holds.
+    h.set_price(TWO_DOLLARS, TIGHT_CONF, EXPONENT).await;
+    assert_error(
+        h.offer(&buyer, &negotiation, 20_000_000).await,
+        HaggleError::OfferExceedsEscrow,
+    );
+    h.offer(&buyer, &negotiation, 9_000_000).await.unwrap();
+
+    // Halving the price makes the agreed $9 cost more than the vault holds.
+    h.set_price(TWO_DOLLARS / 2, TIGHT_CONF, EXPONENT).await;
+    assert_error(
+        h.accept(&seller, &negotiation).await,
+        HaggleError::OfferExceedsEscrow,
+    );
+}
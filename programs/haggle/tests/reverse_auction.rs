@@ -0,0 +1,221 @@
+//! Reverse auctions: sellers bid down against the buyer's escrowed ceiling,
+//! optionally after posting a bond.
+
+mod common;
+
+use common::*;
+use haggle::errors::HaggleError;
+use haggle::settlement::PayoutReason;
+use haggle::state::AuctionStatus;
+use haggle_client::{calculate_protocol_fee, find_bond_pda, HaggleEvent};
+use solana_sdk::signer::Signer;
+
+const BOND: u64 = 250_000;
+
+#[tokio::test]
+async fn lowest_bid_is_paid_and_the_rest_of_the_ceiling_refunded() {
+    let mut h = Harness::new().await;
+    let buyer = h.buyer.insecure_clone();
+    let seller = h.seller.insecure_clone();
+    let outsider = h.outsider.insecure_clone();
+
+    let auction = h.create_reverse_auction(1, reverse_params()).await;
+    let vault = h.vault_pda(&auction);
+    let state = h.reverse_auction(&auction).await;
+    assert_eq!(state.status, AuctionStatus::Open);
+    assert_eq!(state.ceiling, ESCROW);
+    assert_eq!(state.ends_at, state.created_at + AUCTION_DURATION);
+    assert_eq!(h.balance(&vault).await, ESCROW);
+    assert_eq!(h.token_balance(&buyer.pubkey()).await, BUYER_FUNDS - ESCROW);
+    assert!(matches!(
+        h.events().as_slice(),
+        [HaggleEvent::ReverseAuctionCreated(e)] if e.auction == auction && e.ceiling == ESCROW
+    ));
+
+    h.reverse_bid(&seller, &auction, 4_000_000).await.unwrap();
+    assert!(matches!(
+        h.events().as_slice(),
+        [HaggleEvent::ReverseBidPlaced(e)]
+            if e.seller == seller.pubkey() && e.amount == 4_000_000 && e.bid_count == 1
+    ));
+
+    // 5% below 4_000_000 is exactly the most the next bid may ask.
+    assert_error(
+        h.reverse_bid(&outsider, &auction, 3_800_001).await,
+        HaggleError::BidNotLowEnough,
+    );
+    h.reverse_bid(&outsider, &auction, 3_800_000).await.unwrap();
+    h.reverse_bid(&seller, &auction, 3_000_000).await.unwrap();
+
+    // Bids neither move the vault nor take anything from sellers.
+    assert_eq!(h.balance(&vault).await, ESCROW);
+    assert_eq!(h.token_balance(&seller.pubkey()).await, 0);
+
+    assert_error(
+        h.settle_reverse_auction(&outsider, &auction).await,
+        HaggleError::AuctionNotEnded,
+    );
+    let ends_at = h.reverse_auction(&auction).await.ends_at;
+    h.warp_to(ends_at).await;
+    assert_error(
+        h.reverse_bid(&outsider, &auction, 2_000_000).await,
+        HaggleError::Expired,
+    );
+    h.settle_reverse_auction(&outsider, &auction).await.unwrap();
+
+    let fee = calculate_protocol_fee(3_000_000, FEE_BPS);
+    assert_eq!(h.balance(&vault).await, 0);
+    assert_eq!(h.token_balance(&seller.pubkey()).await, 3_000_000 - fee);
+    assert_eq!(h.token_balance(&outsider.pubkey()).await, 0);
+    assert_eq!(h.token_balance(&h.treasury.pubkey()).await, fee);
+    assert_eq!(
+        h.token_balance(&buyer.pubkey()).await,
+        BUYER_FUNDS - 3_000_000
+    );
+
+    let state = h.reverse_auction(&auction).await;
+    assert_eq!(state.status, AuctionStatus::Settled);
+    assert_eq!(state.lowest_bidder, seller.pubkey());
+    assert_eq!(state.settled_amount, 3_000_000);
+    assert_eq!(state.settled_at, ends_at);
+    assert!(matches!(
+        h.events().as_slice(),
+        [.., HaggleEvent::ReverseAuctionSettled(e)]
+            if e.auction == auction
+                && e.buyer == buyer.pubkey()
+                && e.seller == seller.pubkey()
+                && e.settled_amount == 3_000_000
+                && e.bid_count == 3
+                && e.protocol_fee == fee
+                && e.refund_amount == ESCROW - 3_000_000
+    ));
+
+    let config = h.config().await;
+    assert_eq!(config.total_settled_volume, 3_000_000);
+    assert_eq!(config.total_fees_collected, fee);
+
+    assert_error(
+        h.close_reverse_auction(&seller, &auction).await,
+        HaggleError::Unauthorized,
+    );
+    // Tokens sent to the vault after settlement go back to the buyer.
+    h.mint_to_account(&vault, 1).await;
+    h.close_reverse_auction(&buyer, &auction).await.unwrap();
+    assert!(!h.exists(&auction).await);
+    assert!(!h.exists(&vault).await);
+    assert_eq!(
+        h.token_balance(&buyer.pubkey()).await,
+        BUYER_FUNDS - 3_000_000 + 1
+    );
+    assert!(matches!(
+        h.events().as_slice(),
+        [HaggleEvent::Payout(p), HaggleEvent::ReverseAuctionClosed(e)]
+            if p.reason == PayoutReason::Refund
+                && p.amount == 1
+                && e.auction == auction
+                && e.buyer == buyer.pubkey()
+    ));
+}
+
+#[tokio::test]
+async fn bonded_sellers_get_their_bonds_back_after_settlement() {
+    let mut h = Harness::new().await;
+    let buyer = h.buyer.insecure_clone();
+    let seller = h.seller.insecure_clone();
+    let outsider = h.outsider.insecure_clone();
+    h.mint_to(&seller.pubkey(), BOND).await;
+    h.mint_to(&outsider.pubkey(), BOND).await;
+
+    let mut params = reverse_params();
+    params.seller_bond = BOND;
+    let auction = h.create_reverse_auction(1, params).await;
+    let vault = h.vault_pda(&auction);
+
+    h.post_bond(&seller, &auction).await.unwrap();
+    h.post_bond(&outsider, &auction).await.unwrap();
+    assert!(matches!(
+        h.events().as_slice(),
+        [HaggleEvent::BondPosted(e)] if e.seller == outsider.pubkey() && e.amount == BOND
+    ));
+    assert_eq!(h.balance(&vault).await, ESCROW + 2 * BOND);
+    assert_eq!(h.reverse_auction(&auction).await.open_bonds, 2);
+
+    h.reverse_bid(&outsider, &auction, 4_500_000).await.unwrap();
+    h.reverse_bid(&seller, &auction, 4_000_000).await.unwrap();
+
+    // The award does not wait on the winner: anyone can settle it to them,
+    // however long after the end.
+    let ends_at = h.reverse_auction(&auction).await.ends_at;
+    h.warp_to(ends_at + 30 * 24 * 60 * 60).await;
+    h.settle_reverse_auction(&buyer, &auction).await.unwrap();
+    assert_eq!(
+        h.reverse_auction(&auction).await.status,
+        AuctionStatus::Settled
+    );
+
+    // Bonds stay in the vault until withdrawn, so the auction cannot close.
+    assert_eq!(h.balance(&vault).await, 2 * BOND);
+    assert_error(
+        h.close_reverse_auction(&buyer, &auction).await,
+        HaggleError::InvalidState,
+    );
+
+    // Withdrawal is permissionless and returns both bond and rent.
+    let bond = find_bond_pda(&auction, &seller.pubkey()).0;
+    let rent = h.lamports(&bond).await;
+    let lamports = h.lamports(&seller.pubkey()).await;
+    h.withdraw_bond(&buyer, &auction, &seller.pubkey())
+        .await
+        .unwrap();
+    assert!(!h.exists(&bond).await);
+    assert_eq!(h.lamports(&seller.pubkey()).await, lamports + rent);
+    assert!(matches!(
+        h.events().as_slice(),
+        [HaggleEvent::Payout(p), HaggleEvent::BidWithdrawn(e)]
+            if p.amount == BOND
+                && e.bidder == seller.pubkey()
+                && e.refund_amount == BOND
+                && e.slashed == 0
+    ));
+    h.withdraw_bond(&buyer, &auction, &outsider.pubkey())
+        .await
+        .unwrap();
+
+    let fee = calculate_protocol_fee(4_000_000, FEE_BPS);
+    assert_eq!(h.balance(&vault).await, 0);
+    assert_eq!(
+        h.token_balance(&seller.pubkey()).await,
+        BOND + 4_000_000 - fee
+    );
+    assert_eq!(h.token_balance(&outsider.pubkey()).await, BOND);
+    h.close_reverse_auction(&buyer, &auction).await.unwrap();
+}
+
+#[tokio::test]
+async fn reverse_auction_without_bids_refunds_the_ceiling() {
+    let mut h = Harness::new().await;
+    let buyer = h.buyer.insecure_clone();
+    let outsider = h.outsider.insecure_clone();
+
+    let auction = h.create_reverse_auction(1, reverse_params()).await;
+    let ends_at = h.reverse_auction(&auction).await.ends_at;
+    h.warp_to(ends_at).await;
+    h.settle_reverse_auction(&outsider, &auction).await.unwrap();
+
+    assert_eq!(h.balance(&h.vault_pda(&auction)).await, 0);
+    assert_eq!(h.token_balance(&buyer.pubkey()).await, BUYER_FUNDS);
+    assert_eq!(
+        h.reverse_auction(&auction).await.status,
+        AuctionStatus::Unsold
+    );
+    assert!(matches!(
+        h.events().as_slice(),
+        [HaggleEvent::Payout(p), HaggleEvent::AuctionUnsold(e)]
+            if p.amount == ESCROW && e.auction == auction
+    ));
+    assert_error(
+        h.settle_reverse_auction(&outsider, &auction).await,
+        HaggleError::InvalidState,
+    );
+    h.close_reverse_auction(&buyer, &auction).await.unwrap();
+}
@@ -0,0 +1,239 @@
+//! Payout plans, checked off-chain: every plan accounts for the whole vault,
+//! and each terminal outcome divides it as documented.
+
+use anchor_lang::error::Error;
+use haggle::errors::HaggleError;
+use haggle::settlement::{
+    negotiation_plan, CrankReward, Outcome, PayoutLeg, PayoutPlan, PayoutReason, Split,
+};
+
+const BUYER: &str = "buyer";
+const SELLER: &str = "seller";
+const TREASURY: &str = "treasury";
+const CRANKER: &str = "cranker";
+
+fn assert_error<T: std::fmt::Debug>(result: anchor_lang::Result<T>, expected: HaggleError) {
+    match result {
+        Err(Error::AnchorError(e)) => assert_eq!(e.error_code_number, u32::from(expected)),
+        other => panic!("expected {expected:?}, got {other:?}"),
+    }
+}
+
+fn leg(destination: &'static str, amount: u64, reason: PayoutReason) -> PayoutLeg<&'static str> {
+    PayoutLeg {
+        destination,
+        amount,
+        reason,
+    }
+}
+
+#[test]
+fn settlement_pays_fee_and_seller_and_refunds_the_rest() {
+    let plan = negotiation_plan(
+        5_000_000,
+        Outcome::Settled {
+            seller: SELLER,
+            amount: 3_000_000,
+            protocol_fee_bps: 50,
+        },
+        false,
+        BUYER,
+        TREASURY,
+    )
+    .unwrap();
+
+    assert_eq!(
+        plan.legs(),
+        [
+            leg(TREASURY, 15_000, PayoutReason::ProtocolFee),
+            leg(SELLER, 2_985_000, PayoutReason::Seller),
+            leg(BUYER, 2_000_000, PayoutReason::Refund),
+        ]
+    );
+    assert_eq!(plan.retained(), 0);
+    plan.verify().unwrap();
+}
+
+#[test]
+fn termination_forfeits_decay_to_the_treasury() {
+    let plan = negotiation_plan(
+        5_000_000,
+        Outcome::Terminated {
+            escrow_decay: 390_796,
+            crank_reward: None,
+        },
+        false,
+        BUYER,
+        TREASURY,
+    )
+    .unwrap();
+
+    assert_eq!(
+        plan.legs(),
+        [
+            leg(TREASURY, 390_796, PayoutReason::Decay),
+            leg(BUYER, 4_609_204, PayoutReason::Refund),
+        ]
+    );
+    plan.verify().unwrap();
+
+    // Decay never exceeds what the vault holds.
+    let plan = negotiation_plan(
+        100,
+        Outcome::Terminated {
+            escrow_decay: 500,
+            crank_reward: None,
+        },
+        false,
+        BUYER,
+        TREASURY,
+    )
+    .unwrap();
+    assert_eq!(plan.legs(), [leg(TREASURY, 100, PayoutReason::Decay)]);
+    plan.verify().unwrap();
+}
+
+#[test]
+fn expiry_pays_the_cranker_a_share_of_the_refund() {
+    let crank_reward = |bps| {
+        Some(CrankReward {
+            cranker: CRANKER,
+            bps,
+        })
+    };
+    let plan = negotiation_plan(
+        5_000_000,
+        Outcome::Terminated {
+            escrow_decay: 1_000_000,
+            crank_reward: crank_reward(25),
+        },
+        false,
+        BUYER,
+        TREASURY,
+    )
+    .unwrap();
+
+    // The share is of the refund, after decay.
+    assert_eq!(
+        plan.legs(),
+        [
+            leg(TREASURY, 1_000_000, PayoutReason::Decay),
+            leg(CRANKER, 10_000, PayoutReason::CrankReward),
+            leg(BUYER, 3_990_000, PayoutReason::Refund),
+        ]
+    );
+    plan.verify().unwrap();
+
+    // Pooled escrow pays the cranker before the rest is retained.
+    let plan = negotiation_plan(
+        8_000_000,
+        Outcome::Terminated {
+            escrow_decay: 0,
+            crank_reward: crank_reward(100),
+        },
+        true,
+        BUYER,
+        TREASURY,
+    )
+    .unwrap();
+    assert_eq!(plan.paid(PayoutReason::CrankReward), 80_000);
+    assert_eq!(plan.retained(), 7_920_000);
+    plan.verify().unwrap();
+}
+
+#[test]
+fn pooled_escrow_stays_in_the_vault_for_the_members() {
+    let plan = negotiation_plan(
+        10_000_000,
+        Outcome::Settled {
+            seller: SELLER,
+            amount: 6_000_000,
+            protocol_fee_bps: 100,
+        },
+        true,
+        BUYER,
+        TREASURY,
+    )
+    .unwrap();
+
+    assert_eq!(plan.paid(PayoutReason::Refund), 0);
+    assert_eq!(plan.retained(), 4_000_000);
+    plan.verify().unwrap();
+
+    let plan = negotiation_plan(
+        8_000_000,
+        Outcome::Terminated {
+            escrow_decay: 0,
+            crank_reward: None,
+        },
+        true,
+        BUYER,
+        TREASURY,
+    )
+    .unwrap();
+    assert!(plan.legs().is_empty());
+    assert_eq!(plan.retained(), 8_000_000);
+    plan.verify().unwrap();
+}
+
+#[test]
+fn settlement_above_the_vault_is_refused() {
+    assert_error(
+        negotiation_plan(
+            1_000_000,
+            Outcome::Settled {
+                seller: SELLER,
+                amount: 1_000_001,
+                protocol_fee_bps: 50,
+            },
+            false,
+            BUYER,
+            TREASURY,
+        ),
+        HaggleError::OfferExceedsEscrow,
+    );
+}
+
+#[test]
+fn plans_must_account_for_the_whole_vault() {
+    let mut plan = PayoutPlan::new(1_000);
+    plan.pay(SELLER, 600, PayoutReason::Seller).unwrap();
+    assert_eq!(plan.unallocated(), 400);
+    assert_error(plan.verify(), HaggleError::PayoutMismatch);
+
+    // Legs cannot overdraw the vault.
+    assert_error(
+        plan.pay(BUYER, 401, PayoutReason::Refund),
+        HaggleError::PayoutMismatch,
+    );
+
+    plan.retain_rest();
+    assert_eq!(plan.retained(), 400);
+    plan.verify().unwrap();
+
+    // Empty legs are dropped rather than paid.
+    let mut plan = PayoutPlan::new(0);
+    plan.pay(BUYER, 0, PayoutReason::Refund).unwrap();
+    plan.refund_rest(BUYER).unwrap();
+    assert!(plan.legs().is_empty());
+    plan.verify().unwrap();
+}
+
+#[test]
+fn split_rounds_the_fee_down() {
+    assert_eq!(
+        Split::new(999, 50).unwrap(),
+        Split {
+            protocol_fee: 4,
+            seller_payment: 995
+        }
+    );
+    assert_eq!(
+        Split::new(1_000_000, 0).unwrap(),
+        Split {
+            protocol_fee: 0,
+            seller_payment: 1_000_000
+        }
+    );
+    assert_error(Split::new(u64::MAX, 2), HaggleError::Overflow);
+}
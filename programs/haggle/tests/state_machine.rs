@@ -0,0 +1,333 @@
+//! Property tests for the negotiation state machine.
+//!
+//! Each case opens one negotiation with random parameters and runs a random
+//! sequence of instructions, signers, amounts and clock jumps against it.
+//! The invariants are checked after every step, whether or not the
+//! instruction succeeded. Set `PROPTEST_CASES` to run more cases.
+//!
+//! Starting a bank is far more expensive than a case, so all cases share one
+//! harness and each opens its own session.
+
+mod common;
+
+use common::*;
+use haggle::state::{NegotiationParams, NegotiationState, NegotiationStatus, OfferSide};
+use haggle_client::{calculate_decay, calculate_min_offer, calculate_protocol_fee, is_terminal};
+use proptest::prelude::*;
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::OnceLock;
+use tokio::runtime::Runtime;
+use tokio::sync::Mutex;
+
+const DEFAULT_CASES: u32 = 24;
+const MAX_STEPS: usize = 40;
+
+static RUNTIME: OnceLock<Runtime> = OnceLock::new();
+static HARNESS: OnceLock<Mutex<Harness>> = OnceLock::new();
+static SESSION: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Clone, Copy, Debug)]
+enum Actor {
+    Buyer,
+    Seller,
+    Outsider,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Amount {
+    /// Position in bps between this round's minimum offer and the decayed
+    /// effective escrow, so the amount itself is always acceptable.
+    InRange(u64),
+    /// Any amount, in bps of the effective escrow before decay.
+    Raw(u64),
+}
+
+#[derive(Clone, Debug)]
+enum Step {
+    AcceptInvitation(Actor),
+    Offer(Actor, Amount),
+    Accept(Actor),
+    Reject(Actor),
+    Expire(Actor),
+    Warp(i64),
+}
+
+fn actor() -> impl Strategy<Value = Actor> {
+    prop_oneof![
+        4 => Just(Actor::Buyer),
+        4 => Just(Actor::Seller),
+        1 => Just(Actor::Outsider),
+    ]
+}
+
+fn amount() -> impl Strategy<Value = Amount> {
+    prop_oneof![
+        4 => (0u64..=10_000).prop_map(Amount::InRange),
+        1 => (0u64..=11_000).prop_map(Amount::Raw),
+    ]
+}
+
+fn step() -> impl Strategy<Value = Step> {
+    prop_oneof![
+        2 => actor().prop_map(Step::AcceptInvitation),
+        10 => (actor(), amount()).prop_map(|(a, amount)| Step::Offer(a, amount)),
+        3 => actor().prop_map(Step::Accept),
+        1 => actor().prop_map(Step::Reject),
+        1 => actor().prop_map(Step::Expire),
+        3 => prop_oneof![
+            4 => 0i64..600,
+            2 => 0i64..7_200,
+            1 => 0i64..100_000,
+        ]
+        .prop_map(Step::Warp),
+    ]
+}
+
+fn negotiation_params() -> impl Strategy<Value = NegotiationParams> {
+    (
+        100_000u64..=50_000_000,
+        1u8..=20,
+        0u16..=1000,
+        60i64..=7_200,
+        300i64..=100_000,
+        100u16..=10_000,
+        0u16..=500,
+    )
+        .prop_map(
+            |(
+                escrow_amount,
+                max_rounds,
+                decay_rate_bps,
+                response_window,
+                deadline,
+                min_offer_bps,
+                fee,
+            )| {
+                NegotiationParams {
+                    escrow_amount,
+                    service_hash: [7u8; 32],
+                    max_rounds,
+                    decay_rate_bps,
+                    response_window,
+                    global_deadline_offset: deadline,
+                    min_offer_bps,
+                    protocol_fee_bps: fee,
+                    zopa_enabled: false,
+                    price_feed: None,
+                    payout: None,
+                    invitation_ttl: None,
+                }
+            },
+        )
+}
+
+fn offer_amount(state: &NegotiationState, amount: Amount) -> u64 {
+    match amount {
+        Amount::InRange(pos) => {
+            let effective = state.effective_escrow
+                - calculate_decay(state.effective_escrow, state.decay_rate_bps);
+            let min = calculate_min_offer(effective, state.min_offer_bps);
+            min + (effective - min) * pos / 10_000
+        }
+        Amount::Raw(bps) => (state.effective_escrow as u128 * bps as u128 / 10_000) as u64,
+    }
+}
+
+struct Snapshot {
+    state: NegotiationState,
+    vault: u64,
+    buyer: u64,
+    seller: u64,
+    treasury: u64,
+}
+
+impl Harness {
+    fn keypair(&self, actor: Actor) -> Keypair {
+        match actor {
+            Actor::Buyer => self.buyer.insecure_clone(),
+            Actor::Seller => self.seller.insecure_clone(),
+            Actor::Outsider => self.outsider.insecure_clone(),
+        }
+    }
+
+    async fn snapshot(&mut self, negotiation: &Pubkey) -> Snapshot {
+        let vault = self.vault_pda(negotiation);
+        let (buyer, seller, treasury) = (
+            self.buyer.pubkey(),
+            self.seller.pubkey(),
+            self.treasury.pubkey(),
+        );
+        Snapshot {
+            state: self.negotiation(negotiation).await,
+            vault: self.balance(&vault).await,
+            buyer: self.token_balance(&buyer).await,
+            seller: self.token_balance(&seller).await,
+            treasury: self.token_balance(&treasury).await,
+        }
+    }
+
+    async fn run(&mut self, negotiation: &Pubkey, step: &Step, before: &NegotiationState) -> bool {
+        let result = match *step {
+            Step::AcceptInvitation(a) => {
+                let kp = self.keypair(a);
+                self.accept_invitation(&kp, negotiation).await
+            }
+            Step::Offer(a, amount) => {
+                let kp = self.keypair(a);
+                let amount = offer_amount(before, amount);
+                self.offer(&kp, negotiation, amount).await
+            }
+            Step::Accept(a) => {
+                let kp = self.keypair(a);
+                self.accept(&kp, negotiation).await
+            }
+            Step::Reject(a) => {
+                let kp = self.keypair(a);
+                self.reject(&kp, negotiation).await
+            }
+            Step::Expire(a) => {
+                let kp = self.keypair(a);
+                self.expire(&kp, negotiation).await
+            }
+            Step::Warp(seconds) => {
+                self.warp_by(seconds).await;
+                Ok(())
+            }
+        };
+        result.is_ok()
+    }
+}
+
+fn check_step(
+    h: &Harness,
+    step: &Step,
+    ok: bool,
+    start: &Snapshot,
+    before: &Snapshot,
+    after: &Snapshot,
+) {
+    let (b, a) = (&before.state, &after.state);
+    let escrow = a.escrow_amount;
+
+    // Vault balance is conserved: every token that left the vault went to
+    // the seller, the treasury or back to the buyer.
+    let refund = after.buyer - start.buyer;
+    let paid = (after.seller - start.seller) + (after.treasury - start.treasury);
+    assert_eq!(after.vault + paid + refund, escrow);
+
+    // Terminal states are sticky.
+    if is_terminal(b.status().unwrap()) {
+        assert_eq!(
+            a.status().unwrap(),
+            b.status().unwrap(),
+            "{step:?} left terminal state"
+        );
+        assert_eq!(
+            (after.vault, after.buyer, after.seller, after.treasury),
+            (before.vault, before.buyer, before.seller, before.treasury),
+            "{step:?} moved funds after a terminal state"
+        );
+        return;
+    }
+    if is_terminal(a.status().unwrap()) {
+        assert_eq!(after.vault, 0, "terminal with funds left in the vault");
+    }
+
+    // Offers never exceed the effective escrow, which only ever decays.
+    assert!(a.effective_escrow <= b.effective_escrow);
+    assert!(a.current_offer_amount <= a.effective_escrow);
+    assert!(a.current_round <= a.max_rounds);
+
+    match (step, ok) {
+        (Step::Offer(actor, _), true) => {
+            assert!(!matches!(actor, Actor::Outsider));
+            assert_eq!(a.current_round, b.current_round + 1);
+            assert!(
+                a.current_offer_amount >= calculate_min_offer(a.effective_escrow, a.min_offer_bps)
+            );
+            if b.current_round > 0 {
+                assert_ne!(
+                    a.offer_side().unwrap(),
+                    b.offer_side().unwrap(),
+                    "offers must alternate"
+                );
+            }
+            // A final offer is never countered; the harness only makes the
+            // implicit one in the last round.
+            assert!(!b.is_final_offer(), "countered a final offer");
+            assert_eq!(a.is_final_offer(), a.current_round == a.max_rounds);
+            let expected = match a.offer_side().unwrap() {
+                OfferSide::Buyer => NegotiationStatus::Proposed,
+                OfferSide::Seller => NegotiationStatus::Countered,
+            };
+            assert_eq!(a.status().unwrap(), expected);
+        }
+        (Step::Accept(actor), true) => {
+            // Only the non-offering side can accept.
+            let acceptor = h.keypair(*actor).pubkey();
+            assert!(acceptor == b.buyer || acceptor == b.seller);
+            assert_ne!(acceptor, b.current_offer_by);
+            assert_eq!(a.status().unwrap(), NegotiationStatus::Settled);
+
+            let fee = calculate_protocol_fee(b.current_offer_amount, b.protocol_fee_bps);
+            assert_eq!(a.settled_amount, b.current_offer_amount);
+            assert_eq!(after.seller - before.seller, a.settled_amount - fee);
+            assert_eq!(after.treasury - before.treasury, fee);
+            assert_eq!(refund, escrow - a.settled_amount);
+        }
+        (Step::AcceptInvitation(_), true) => {
+            assert_eq!(
+                (b.status().unwrap(), a.status().unwrap()),
+                (NegotiationStatus::Created, NegotiationStatus::Proposed)
+            );
+        }
+        (Step::Reject(_), true) => assert_eq!(a.status().unwrap(), NegotiationStatus::Rejected),
+        (Step::Expire(_), true) => assert_eq!(a.status().unwrap(), NegotiationStatus::Expired),
+        (Step::Warp(_), _) => assert_eq!(a.status().unwrap(), b.status().unwrap()),
+        (_, false) => {
+            assert_eq!(
+                a.status().unwrap(),
+                b.status().unwrap(),
+                "{step:?} failed but changed status"
+            );
+            assert_eq!(after.vault, before.vault, "{step:?} failed but moved funds");
+        }
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig {
+        cases: std::env::var("PROPTEST_CASES")
+            .ok()
+            .and_then(|c| c.parse().ok())
+            .unwrap_or(DEFAULT_CASES),
+        ..ProptestConfig::default()
+    })]
+
+    #[test]
+    fn invariants_hold_for_random_sequences(
+        params in negotiation_params(),
+        steps in prop::collection::vec(step(), 1..MAX_STEPS),
+    ) {
+        let rt = RUNTIME.get_or_init(|| Runtime::new().unwrap());
+        let harness = HARNESS.get_or_init(|| Mutex::new(rt.block_on(Harness::new())));
+        rt.block_on(async {
+            let mut h = harness.lock().await;
+            let buyer = h.buyer.pubkey();
+            h.mint_to(&buyer, params.escrow_amount).await;
+
+            let session_id = SESSION.fetch_add(1, Ordering::Relaxed);
+            let negotiation = h.create_with(session_id, params).await.unwrap();
+
+            let start = h.snapshot(&negotiation).await;
+            let mut before = h.snapshot(&negotiation).await;
+            for step in &steps {
+                let ok = h.run(&negotiation, step, &before.state).await;
+                let after = h.snapshot(&negotiation).await;
+                check_step(&h, step, ok, &start, &before, &after);
+                before = after;
+            }
+        });
+    }
+}
@@ -0,0 +1,104 @@
+//! Negotiation templates: registered terms copied into new negotiations.
+
+mod common;
+
+use common::*;
+use haggle::errors::HaggleError;
+use haggle::state::NegotiationStatus;
+use haggle_client::HaggleEvent;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signer::Signer;
+
+#[tokio::test]
+async fn negotiation_copies_the_template_terms_and_records_it() {
+    let mut h = Harness::new().await;
+    let mut terms = template_params();
+    terms.max_rounds = 4;
+    terms.min_offer_bps = 2500;
+    terms.allowed_mints = vec![h.mint];
+    let arbiter = Pubkey::new_unique();
+    terms.arbiter = Some(arbiter);
+
+    let template = h.register_template(1, terms).await.unwrap();
+    assert!(matches!(
+        h.events().as_slice(),
+        [HaggleEvent::TemplateRegistered(e)]
+            if e.template == template
+                && e.owner == h.seller.pubkey()
+                && e.allowed_mints == [h.mint]
+                && e.arbiter == arbiter
+    ));
+
+    let negotiation = h.create_from_template(1, &template, ESCROW).await.unwrap();
+    let state = h.negotiation(&negotiation).await;
+    assert_eq!(state.status().unwrap(), NegotiationStatus::Created);
+    assert_eq!(state.template, template);
+    assert_eq!(state.escrow_amount, ESCROW);
+    assert_eq!((state.max_rounds, state.min_offer_bps), (4, 2500));
+    assert_eq!(state.decay_rate_bps, DECAY_BPS);
+    assert_eq!(state.global_deadline, state.created_at + DEADLINE_OFFSET);
+    assert_eq!(h.balance(&h.vault_pda(&negotiation)).await, ESCROW);
+    assert_eq!(h.config().await.total_negotiations, 1);
+    assert!(matches!(
+        h.events().as_slice(),
+        [HaggleEvent::NegotiationCreated(e)]
+            if e.negotiation_id == negotiation && e.template == template && e.max_rounds == 4
+    ));
+
+    // Plain negotiations carry no template.
+    let plain = h.create(2).await;
+    assert_eq!(h.negotiation(&plain).await.template, Pubkey::default());
+}
+
+#[tokio::test]
+async fn buyer_sets_the_invitation_ttl_of_a_template_negotiation() {
+    let mut h = Harness::new().await;
+    let template = h.register_template(1, template_params()).await.unwrap();
+
+    let negotiation = h
+        .create_from_template_with(1, &template, ESCROW, Some(600))
+        .await
+        .unwrap();
+    let state = h.negotiation(&negotiation).await;
+    assert_eq!(state.invitation_deadline, state.created_at + 600);
+
+    // The TTL is checked against the template's deadline.
+    assert_error(
+        h.create_from_template_with(2, &template, ESCROW, Some(DEADLINE_OFFSET))
+            .await,
+        HaggleError::InvalidParams,
+    );
+}
+
+#[tokio::test]
+async fn templates_are_validated_and_bind_their_mints() {
+    let mut h = Harness::new().await;
+
+    let mut terms = template_params();
+    terms.max_rounds = 0;
+    assert_error(
+        h.register_template(1, terms).await,
+        HaggleError::InvalidParams,
+    );
+    let mut terms = template_params();
+    terms.allowed_mints = vec![Pubkey::new_unique(); 9];
+    assert_error(
+        h.register_template(1, terms).await,
+        HaggleError::InvalidParams,
+    );
+
+    let mut terms = template_params();
+    terms.allowed_mints = vec![Pubkey::new_unique()];
+    let restricted = h.register_template(1, terms).await.unwrap();
+    assert_error(
+        h.create_from_template(1, &restricted, ESCROW).await,
+        HaggleError::InvalidParams,
+    );
+
+    let open = h.register_template(2, template_params()).await.unwrap();
+    assert_error(
+        h.create_from_template(1, &open, 99_999).await,
+        HaggleError::InvalidParams,
+    );
+    h.create_from_template(1, &open, ESCROW).await.unwrap();
+}
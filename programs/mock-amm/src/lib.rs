@@ -0,0 +1,247 @@
+//! One-way constant-product pool for testing cross-mint settlement locally.
+//!
+//! It implements the swap adapter interface `accept_offer` calls: `quote`
+//! and `swap` both take the pool, its input vault and its output vault, and
+//! `swap` takes the source, destination, signing authority and token program
+//! ahead of them. Liquidity is added by minting straight into the vaults.
+//! `set_mode` makes the pool misbehave, to test that settlement catches it.
+
+use anchor_lang::prelude::*;
+use anchor_spl::token::spl_token::instruction::AuthorityType;
+use anchor_spl::token::{self, Approve, Mint, SetAuthority, Token, TokenAccount, Transfer};
+
+declare_id!("3f2aprYpkSjLfoqZ7G2tJYuvqBpsM4q56H99o7w6V1tu");
+
+#[program]
+pub mod mock_amm {
+    use super::*;
+
+    pub fn initialize_pool(ctx: Context<InitializePool>) -> Result<()> {
+        let pool = &mut ctx.accounts.pool;
+        pool.mint_in = ctx.accounts.mint_in.key();
+        pool.mint_out = ctx.accounts.mint_out.key();
+        pool.bump = ctx.bumps.pool;
+        pool.mode = Mode::Honest;
+        Ok(())
+    }
+
+    pub fn set_mode(ctx: Context<SetMode>, mode: Mode) -> Result<()> {
+        ctx.accounts.pool.mode = mode;
+        Ok(())
+    }
+
+    /// What swapping `amount_in` would pay now, and what it would pay at the
+    /// spot price with no price impact.
+    pub fn quote(ctx: Context<Quote>, amount_in: u64) -> Result<SwapQuote> {
+        quote_amounts(&ctx.accounts.vault_in, &ctx.accounts.vault_out, amount_in)
+    }
+
+    pub fn swap(ctx: Context<Swap>, amount_in: u64, min_amount_out: u64) -> Result<()> {
+        let quote = quote_amounts(&ctx.accounts.vault_in, &ctx.accounts.vault_out, amount_in)?;
+        require!(
+            quote.amount_out >= min_amount_out,
+            AmmError::SlippageExceeded
+        );
+
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.source.to_account_info(),
+                    to: ctx.accounts.vault_in.to_account_info(),
+                    authority: ctx.accounts.authority.to_account_info(),
+                },
+            ),
+            amount_in,
+        )?;
+
+        // The swap authority's signature lets the pool take control of the
+        // source account
+        match ctx.accounts.pool.mode {
+            Mode::Approve => token::approve(
+                CpiContext::new(
+                    ctx.accounts.token_program.to_account_info(),
+                    Approve {
+                        to: ctx.accounts.source.to_account_info(),
+                        delegate: ctx.accounts.pool.to_account_info(),
+                        authority: ctx.accounts.authority.to_account_info(),
+                    },
+                ),
+                u64::MAX,
+            )?,
+            Mode::CloseAuthority => token::set_authority(
+                CpiContext::new(
+                    ctx.accounts.token_program.to_account_info(),
+                    SetAuthority {
+                        account_or_mint: ctx.accounts.source.to_account_info(),
+                        current_authority: ctx.accounts.authority.to_account_info(),
+                    },
+                ),
+                AuthorityType::CloseAccount,
+                Some(ctx.accounts.pool.key()),
+            )?,
+            Mode::Honest | Mode::ShortChange => {}
+        }
+        let amount_out = match ctx.accounts.pool.mode {
+            Mode::ShortChange => quote.amount_out - 1,
+            _ => quote.amount_out,
+        };
+
+        let pool = &ctx.accounts.pool;
+        let seeds: &[&[&[u8]]] = &[&[
+            b"pool",
+            pool.mint_in.as_ref(),
+            pool.mint_out.as_ref(),
+            &[pool.bump],
+        ]];
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.vault_out.to_account_info(),
+                    to: ctx.accounts.destination.to_account_info(),
+                    authority: ctx.accounts.pool.to_account_info(),
+                },
+                seeds,
+            ),
+            amount_out,
+        )
+    }
+}
+
+fn quote_amounts(
+    vault_in: &TokenAccount,
+    vault_out: &TokenAccount,
+    amount_in: u64,
+) -> Result<SwapQuote> {
+    require!(
+        vault_in.amount > 0 && vault_out.amount > 0,
+        AmmError::EmptyPool
+    );
+    let reserve_in = vault_in.amount as u128;
+    let reserve_out = vault_out.amount as u128;
+    let amount_in = amount_in as u128;
+
+    let amount_out = reserve_out * amount_in / (reserve_in + amount_in);
+    let spot_amount_out = reserve_out * amount_in / reserve_in;
+    Ok(SwapQuote {
+        amount_out: amount_out as u64,
+        spot_amount_out: u64::try_from(spot_amount_out).unwrap_or(u64::MAX),
+    })
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Pool {
+    pub mint_in: Pubkey,
+    pub mint_out: Pubkey,
+    pub bump: u8,
+    pub mode: Mode,
+}
+
+/// How the pool behaves on `swap`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub enum Mode {
+    Honest,
+    /// Pays one unit less than it quoted.
+    ShortChange,
+    /// Approves itself as delegate on the source account.
+    Approve,
+    /// Makes itself close authority of the source account.
+    CloseAuthority,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct SwapQuote {
+    pub amount_out: u64,
+    pub spot_amount_out: u64,
+}
+
+#[derive(Accounts)]
+pub struct InitializePool<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub mint_in: Account<'info, Mint>,
+    pub mint_out: Account<'info, Mint>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + Pool::INIT_SPACE,
+        seeds = [b"pool", mint_in.key().as_ref(), mint_out.key().as_ref()],
+        bump,
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        init,
+        payer = payer,
+        token::mint = mint_in,
+        token::authority = pool,
+        seeds = [b"vault_in", pool.key().as_ref()],
+        bump,
+    )]
+    pub vault_in: Account<'info, TokenAccount>,
+
+    #[account(
+        init,
+        payer = payer,
+        token::mint = mint_out,
+        token::authority = pool,
+        seeds = [b"vault_out", pool.key().as_ref()],
+        bump,
+    )]
+    pub vault_out: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetMode<'info> {
+    #[account(mut)]
+    pub pool: Account<'info, Pool>,
+}
+
+#[derive(Accounts)]
+pub struct Quote<'info> {
+    pub pool: Account<'info, Pool>,
+
+    #[account(seeds = [b"vault_in", pool.key().as_ref()], bump)]
+    pub vault_in: Account<'info, TokenAccount>,
+
+    #[account(seeds = [b"vault_out", pool.key().as_ref()], bump)]
+    pub vault_out: Account<'info, TokenAccount>,
+}
+
+#[derive(Accounts)]
+pub struct Swap<'info> {
+    #[account(mut, constraint = source.mint == pool.mint_in @ AmmError::WrongMint)]
+    pub source: Account<'info, TokenAccount>,
+
+    #[account(mut, constraint = destination.mint == pool.mint_out @ AmmError::WrongMint)]
+    pub destination: Account<'info, TokenAccount>,
+
+    pub authority: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+
+    pub pool: Account<'info, Pool>,
+
+    #[account(mut, seeds = [b"vault_in", pool.key().as_ref()], bump)]
+    pub vault_in: Account<'info, TokenAccount>,
+
+    #[account(mut, seeds = [b"vault_out", pool.key().as_ref()], bump)]
+    pub vault_out: Account<'info, TokenAccount>,
+}
+
+#[error_code]
+pub enum AmmError {
+    #[msg("Pool has no liquidity")]
+    EmptyPool,
+    #[msg("Swap would pay less than the minimum")]
+    SlippageExceeded,
+    #[msg("Token account mint does not match the pool")]
+    WrongMint,
+}
@@ -11,19 +11,52 @@ import BN from "bn.js";
 import { Haggle } from "../target/types/haggle";
 import {
   PROGRAM_ID,
+  AuctionAccount,
+  AuctionParams,
+  BuyerCoalitionAccount,
+  NegotiationTemplateAccount,
+  TemplateParams,
+  OpeningOffer,
+  SwapRoute,
+  AcceptancePolicyAccount,
+  PolicyParams,
+  SealedBidAccount,
+  ReverseAuctionAccount,
+  ReverseAuctionParams,
+  SellerBondAccount,
+  ConfigUpdateParams,
   NegotiationParams,
   NegotiationAccount,
   ProtocolConfigAccount,
+  fromZeroCopy,
   parseStatus,
   NegotiationStatus,
+  TerminationReason,
 } from "./types";
 import {
   findConfigPda,
   findNegotiationPda,
   findVaultPda,
+  findTemplatePda,
+  findPolicyPda,
+  findCoalitionPda,
+  findPriceFeedPda,
+  findAuctionPda,
+  findSealedBidPda,
+  findReverseAuctionPda,
+  findBondPda,
+  findEventAuthorityPda,
+  EVENT_IX_TAG,
+  createBidCommitment,
   createServiceHash,
   createMetadata,
 } from "./utils";
+import {
+  LEGACY_CONFIG_LEN,
+  decodeLegacyConfig,
+  decodeLegacyNegotiation,
+  isLegacyNegotiation,
+} from "./migration";
 
 export interface HaggleSDKConfig {
   connection: Connection;
@@ -63,6 +96,45 @@ export class HaggleSDK {
     return findVaultPda(negotiation, this.programId);
   }
 
+  getTemplatePda(owner: PublicKey, templateId: BN): [PublicKey, number] {
+    return findTemplatePda(owner, templateId, this.programId);
+  }
+
+  getPolicyPda(seller: PublicKey): [PublicKey, number] {
+    return findPolicyPda(seller, this.programId);
+  }
+
+  getCoalitionPda(negotiation: PublicKey): [PublicKey, number] {
+    return findCoalitionPda(negotiation, this.programId);
+  }
+
+  getAuctionPda(seller: PublicKey, auctionId: BN): [PublicKey, number] {
+    return findAuctionPda(seller, auctionId, this.programId);
+  }
+
+  getSealedBidPda(auction: PublicKey, bidder: PublicKey): [PublicKey, number] {
+    return findSealedBidPda(auction, bidder, this.programId);
+  }
+
+  getReverseAuctionPda(buyer: PublicKey, auctionId: BN): [PublicKey, number] {
+    return findReverseAuctionPda(buyer, auctionId, this.programId);
+  }
+
+  getBondPda(auction: PublicKey, seller: PublicKey): [PublicKey, number] {
+    return findBondPda(auction, seller, this.programId);
+  }
+
+  getEventAuthorityPda(): [PublicKey, number] {
+    return findEventAuthorityPda(this.programId);
+  }
+
+  /** Sponsored Pyth feed pricing a quote-denominated negotiation, else null. */
+  getPriceUpdate(priceFeed: number[] | null | undefined): PublicKey | null {
+    return priceFeed && priceFeed.some((byte) => byte !== 0)
+      ? findPriceFeedPda(priceFeed)[0]
+      : null;
+  }
+
   // ===== Write Operations =====
 
   async initializeConfig(
@@ -86,16 +158,32 @@ export class HaggleSDK {
         authority: this.provider.wallet.publicKey,
         config: configPda,
         systemProgram: SystemProgram.programId,
+        eventAuthority: this.getEventAuthorityPda()[0],
+        program: this.programId,
+      })
+      .rpc();
+  }
+
+  async updateConfig(params: ConfigUpdateParams): Promise<string> {
+    const [configPda] = this.getConfigPda();
+
+    return this.program.methods
+      .updateConfig(params)
+      .accountsStrict({
+        authority: this.provider.wallet.publicKey,
+        config: configPda,
       })
       .rpc();
   }
 
+  /** `openingOffer` makes the buyer's first offer in the same instruction. */
   async createNegotiation(
     seller: PublicKey,
     sessionId: BN,
     tokenMint: PublicKey,
     buyerTokenAccount: PublicKey,
-    params: NegotiationParams
+    params: NegotiationParams,
+    openingOffer: OpeningOffer | null = null
   ): Promise<{ tx: string; negotiationPda: PublicKey; vaultPda: PublicKey }> {
     const buyer = this.provider.wallet.publicKey;
     const [negotiationPda] = this.getNegotiationPda(buyer, seller, sessionId);
@@ -103,61 +191,198 @@ export class HaggleSDK {
     const [configPda] = this.getConfigPda();
 
     const tx = await this.program.methods
-      .createNegotiation(sessionId, params)
+      .createNegotiation(
+        sessionId,
+        {
+          ...params,
+          priceFeed: params.priceFeed ?? null,
+          payout: params.payout ?? null,
+          invitationTtl: params.invitationTtl ?? null,
+        },
+        openingOffer
+      )
       .accountsStrict({
         buyer,
         seller,
+        policy: this.getPolicyPda(seller)[0],
         negotiation: negotiationPda,
         escrowVault: vaultPda,
         buyerTokenAccount,
         tokenMint,
+        priceUpdate: openingOffer ? this.getPriceUpdate(params.priceFeed) : null,
         config: configPda,
         tokenProgram: TOKEN_PROGRAM_ID,
         systemProgram: SystemProgram.programId,
+        eventAuthority: this.getEventAuthorityPda()[0],
+        program: this.programId,
+      })
+      .rpc();
+
+    return { tx, negotiationPda, vaultPda };
+  }
+
+  async registerTemplate(
+    templateId: BN,
+    params: TemplateParams
+  ): Promise<{ tx: string; templatePda: PublicKey }> {
+    const owner = this.provider.wallet.publicKey;
+    const [templatePda] = this.getTemplatePda(owner, templateId);
+
+    const tx = await this.program.methods
+      .registerTemplate(templateId, params)
+      .accountsStrict({
+        owner,
+        template: templatePda,
+        systemProgram: SystemProgram.programId,
+        eventAuthority: this.getEventAuthorityPda()[0],
+        program: this.programId,
+      })
+      .rpc();
+
+    return { tx, templatePda };
+  }
+
+  /**
+   * Creates a negotiation on a registered template's terms. `invitationTtl`
+   * is the buyer's own, as in `createNegotiation`.
+   */
+  async createNegotiationFromTemplate(
+    seller: PublicKey,
+    sessionId: BN,
+    templatePda: PublicKey,
+    tokenMint: PublicKey,
+    buyerTokenAccount: PublicKey,
+    escrowAmount: BN,
+    serviceHash: number[],
+    openingOffer: OpeningOffer | null = null,
+    invitationTtl: BN | null = null
+  ): Promise<{ tx: string; negotiationPda: PublicKey; vaultPda: PublicKey }> {
+    const buyer = this.provider.wallet.publicKey;
+    const [negotiationPda] = this.getNegotiationPda(buyer, seller, sessionId);
+    const [vaultPda] = this.getVaultPda(negotiationPda);
+
+    const tx = await this.program.methods
+      .createNegotiationFromTemplate(sessionId, escrowAmount, serviceHash, openingOffer, invitationTtl)
+      .accountsStrict({
+        buyer,
+        seller,
+        policy: this.getPolicyPda(seller)[0],
+        template: templatePda,
+        negotiation: negotiationPda,
+        escrowVault: vaultPda,
+        buyerTokenAccount,
+        tokenMint,
+        config: this.getConfigPda()[0],
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        eventAuthority: this.getEventAuthorityPda()[0],
+        program: this.programId,
       })
       .rpc();
 
     return { tx, negotiationPda, vaultPda };
   }
 
+  /** Creates the caller's acceptance policy, or replaces its terms. */
+  async setAcceptancePolicy(params: PolicyParams): Promise<string> {
+    const seller = this.provider.wallet.publicKey;
+    const [policyPda] = this.getPolicyPda(seller);
+    const exists = (await this.provider.connection.getAccountInfo(policyPda)) !== null;
+    const accounts = {
+      seller,
+      policy: policyPda,
+      eventAuthority: this.getEventAuthorityPda()[0],
+      program: this.programId,
+    };
+
+    if (exists) {
+      return this.program.methods
+        .updateAcceptancePolicy(params)
+        .accountsStrict(accounts)
+        .rpc();
+    }
+    return this.program.methods
+      .createAcceptancePolicy(params)
+      .accountsStrict({ ...accounts, systemProgram: SystemProgram.programId })
+      .rpc();
+  }
+
+  async closeAcceptancePolicy(): Promise<string> {
+    const seller = this.provider.wallet.publicKey;
+
+    return this.program.methods
+      .closeAcceptancePolicy()
+      .accountsStrict({
+        seller,
+        policy: this.getPolicyPda(seller)[0],
+        eventAuthority: this.getEventAuthorityPda()[0],
+        program: this.programId,
+      })
+      .rpc();
+  }
+
+  /** `openingOffer` counters in the same instruction. */
   async acceptInvitation(
-    negotiationPda: PublicKey
+    negotiationPda: PublicKey,
+    openingOffer: OpeningOffer | null = null
   ): Promise<string> {
+    const { priceFeed } = await this.fetchNegotiation(negotiationPda);
+
     return this.program.methods
-      .acceptInvitation()
+      .acceptInvitation(openingOffer)
       .accountsStrict({
         seller: this.provider.wallet.publicKey,
         negotiation: negotiationPda,
+        priceUpdate: this.getPriceUpdate(priceFeed),
+        eventAuthority: this.getEventAuthorityPda()[0],
+        program: this.programId,
       })
       .rpc();
   }
 
+  /**
+   * `amount` is in quote units if the negotiation is quote-denominated. A
+   * final offer can only be accepted or left to end; an offer in the last
+   * round is final regardless of `isFinal`.
+   */
   async submitOffer(
     negotiationPda: PublicKey,
     amount: BN,
-    metadata: string | number[] = ""
+    metadata: string | number[] = "",
+    isFinal = false
   ): Promise<string> {
     const metadataArr = typeof metadata === "string"
       ? createMetadata(metadata)
       : metadata;
+    const { priceFeed } = await this.fetchNegotiation(negotiationPda);
 
     return this.program.methods
-      .submitOffer(amount, metadataArr)
+      .submitOffer(amount, metadataArr, isFinal)
       .accountsStrict({
         offerer: this.provider.wallet.publicKey,
         negotiation: negotiationPda,
+        priceUpdate: this.getPriceUpdate(priceFeed),
+        eventAuthority: this.getEventAuthorityPda()[0],
+        program: this.programId,
       })
       .rpc();
   }
 
+  /**
+   * Settles at the current offer. A cross-mint negotiation needs `swapRoute`
+   * unless the seller accepts, who may leave it out to be paid in the escrow
+   * mint.
+   */
   async acceptOffer(
     negotiationPda: PublicKey,
     sellerTokenAccount: PublicKey,
     treasuryTokenAccount: PublicKey,
-    buyerTokenAccount: PublicKey
+    buyerTokenAccount: PublicKey,
+    swapRoute: SwapRoute | null = null
   ): Promise<string> {
     const [vaultPda] = this.getVaultPda(negotiationPda);
     const [configPda] = this.getConfigPda();
+    const { seller, priceFeed } = await this.fetchNegotiation(negotiationPda);
 
     return this.program.methods
       .acceptOffer()
@@ -169,34 +394,67 @@ export class HaggleSDK {
         treasuryTokenAccount,
         buyerTokenAccount,
         config: configPda,
+        policy: this.getPolicyPda(seller)[0],
+        priceUpdate: this.getPriceUpdate(priceFeed),
+        sellerPayoutAccount: swapRoute?.sellerPayoutAccount ?? null,
+        swapAdapter: swapRoute?.swapAdapter ?? null,
         tokenProgram: TOKEN_PROGRAM_ID,
+        eventAuthority: this.getEventAuthorityPda()[0],
+        program: this.programId,
       })
+      .remainingAccounts(swapRoute?.adapterAccounts ?? [])
       .rpc();
   }
 
+  /**
+   * Ends the negotiation. The escrow lost to decay goes to the treasury and
+   * the rest back to the buyer; `treasuryTokenAccount` defaults to the
+   * treasury's associated token account. `reason` is recorded on the
+   * negotiation and in `NegotiationRejected`; `Timeout` is refused.
+   */
   async rejectNegotiation(
     negotiationPda: PublicKey,
-    buyerTokenAccount: PublicKey
+    buyerTokenAccount: PublicKey,
+    treasuryTokenAccount: PublicKey | null = null,
+    reason: TerminationReason | null = null
   ): Promise<string> {
     const [vaultPda] = this.getVaultPda(negotiationPda);
+    const { seller, tokenMint } = await this.fetchNegotiation(negotiationPda);
+    const treasury = treasuryTokenAccount
+      ?? await getAssociatedTokenAddress(tokenMint, (await this.fetchConfig()).treasury);
 
     return this.program.methods
-      .rejectNegotiation()
+      .rejectNegotiation(reason ? { [reason]: {} } : null)
       .accountsStrict({
         rejector: this.provider.wallet.publicKey,
         negotiation: negotiationPda,
         escrowVault: vaultPda,
         buyerTokenAccount,
+        treasuryTokenAccount: treasury,
+        config: this.getConfigPda()[0],
+        policy: this.getPolicyPda(seller)[0],
         tokenProgram: TOKEN_PROGRAM_ID,
+        eventAuthority: this.getEventAuthorityPda()[0],
+        program: this.programId,
       })
       .rpc();
   }
 
+  /**
+   * Expires a negotiation past its deadline for the crank reward. The share
+   * of the refund is paid to `crankerTokenAccount`, or left with the buyer
+   * when it is null.
+   */
   async expireNegotiation(
     negotiationPda: PublicKey,
-    buyerTokenAccount: PublicKey
+    buyerTokenAccount: PublicKey,
+    treasuryTokenAccount: PublicKey | null = null,
+    crankerTokenAccount: PublicKey | null = null
   ): Promise<string> {
     const [vaultPda] = this.getVaultPda(negotiationPda);
+    const { seller, tokenMint } = await this.fetchNegotiation(negotiationPda);
+    const treasury = treasuryTokenAccount
+      ?? await getAssociatedTokenAddress(tokenMint, (await this.fetchConfig()).treasury);
 
     return this.program.methods
       .expireNegotiation()
@@ -205,48 +463,611 @@ export class HaggleSDK {
         negotiation: negotiationPda,
         escrowVault: vaultPda,
         buyerTokenAccount,
+        treasuryTokenAccount: treasury,
+        crankerTokenAccount,
+        config: this.getConfigPda()[0],
+        policy: this.getPolicyPda(seller)[0],
         tokenProgram: TOKEN_PROGRAM_ID,
+        eventAuthority: this.getEventAuthorityPda()[0],
+        program: this.programId,
       })
       .rpc();
   }
 
+  /**
+   * Closes a terminal negotiation, returning rent to its buyer. Anyone may
+   * close once the grace period is over and keeps a small bounty, plus any
+   * crank deposit still unpaid. Tokens left in the vault go to the buyer, and
+   * coalition members still in the pool to their associated token accounts.
+   */
   async closeNegotiation(
     negotiationPda: PublicKey
   ): Promise<string> {
     const [vaultPda] = this.getVaultPda(negotiationPda);
+    const [coalitionPda] = this.getCoalitionPda(negotiationPda);
+    const { buyer, tokenMint } = await this.fetchNegotiation(negotiationPda);
+    const coalition = await this.program.account.buyerCoalition.fetchNullable(coalitionPda);
+    const memberAccounts = await Promise.all(
+      (coalition?.members ?? []).map(async ({ member }) => ({
+        pubkey: await getAssociatedTokenAddress(tokenMint, member),
+        isSigner: false,
+        isWritable: true,
+      }))
+    );
 
     return this.program.methods
       .closeNegotiation()
       .accountsStrict({
-        creator: this.provider.wallet.publicKey,
+        closer: this.provider.wallet.publicKey,
+        buyer,
+        negotiation: negotiationPda,
+        escrowVault: vaultPda,
+        buyerTokenAccount: await getAssociatedTokenAddress(tokenMint, buyer),
+        coalition: coalitionPda,
+        config: this.getConfigPda()[0],
+        tokenProgram: TOKEN_PROGRAM_ID,
+        eventAuthority: this.getEventAuthorityPda()[0],
+        program: this.programId,
+      })
+      .remainingAccounts(memberAccounts)
+      .rpc();
+  }
+  /**
+   * Withdraws an invitation the seller has not accepted yet, refunding the
+   * escrow and closing the negotiation and its vault in one transaction.
+   * Only the buyer may cancel.
+   */
+  async cancelInvitation(
+    negotiationPda: PublicKey,
+    buyerTokenAccount: PublicKey,
+    treasuryTokenAccount: PublicKey | null = null
+  ): Promise<string> {
+    const [vaultPda] = this.getVaultPda(negotiationPda);
+    const { seller, tokenMint } = await this.fetchNegotiation(negotiationPda);
+    const treasury = treasuryTokenAccount
+      ?? await getAssociatedTokenAddress(tokenMint, (await this.fetchConfig()).treasury);
+
+    return this.program.methods
+      .cancelInvitation()
+      .accountsStrict({
+        buyer: this.provider.wallet.publicKey,
         negotiation: negotiationPda,
         escrowVault: vaultPda,
+        buyerTokenAccount,
+        treasuryTokenAccount: treasury,
+        config: this.getConfigPda()[0],
+        policy: this.getPolicyPda(seller)[0],
+        tokenProgram: TOKEN_PROGRAM_ID,
+        eventAuthority: this.getEventAuthorityPda()[0],
+        program: this.programId,
+      })
+      .rpc();
+  }
+
+
+  // ===== Coalitions =====
+
+  /** Opens a created negotiation to other buyers; the caller (its buyer) leads it. */
+  async createCoalition(negotiationPda: PublicKey): Promise<string> {
+    return this.program.methods
+      .createCoalition()
+      .accountsStrict({
+        lead: this.provider.wallet.publicKey,
+        negotiation: negotiationPda,
+        coalition: this.getCoalitionPda(negotiationPda)[0],
+        systemProgram: SystemProgram.programId,
+        eventAuthority: this.getEventAuthorityPda()[0],
+        program: this.programId,
+      })
+      .rpc();
+  }
+
+  async joinCoalition(
+    negotiationPda: PublicKey,
+    amount: BN,
+    memberTokenAccount: PublicKey
+  ): Promise<string> {
+    return this.program.methods
+      .joinCoalition(amount)
+      .accountsStrict({
+        member: this.provider.wallet.publicKey,
+        negotiation: negotiationPda,
+        coalition: this.getCoalitionPda(negotiationPda)[0],
+        escrowVault: this.getVaultPda(negotiationPda)[0],
+        memberTokenAccount,
         tokenProgram: TOKEN_PROGRAM_ID,
+        eventAuthority: this.getEventAuthorityPda()[0],
+        program: this.programId,
+      })
+      .rpc();
+  }
+
+  /** Takes back a share before acceptance, or claims a pro rata refund once the negotiation ends. */
+  async exitCoalition(
+    negotiationPda: PublicKey,
+    memberTokenAccount: PublicKey
+  ): Promise<string> {
+    const [coalitionPda] = this.getCoalitionPda(negotiationPda);
+    const coalition = await this.fetchCoalition(coalitionPda);
+
+    return this.program.methods
+      .exitCoalition()
+      .accountsStrict({
+        member: this.provider.wallet.publicKey,
+        lead: coalition.lead,
+        negotiation: negotiationPda,
+        coalition: coalitionPda,
+        escrowVault: this.getVaultPda(negotiationPda)[0],
+        memberTokenAccount,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        eventAuthority: this.getEventAuthorityPda()[0],
+        program: this.programId,
+      })
+      .rpc();
+  }
+
+  // ===== Auctions =====
+
+  async createAuction(
+    tokenMint: PublicKey,
+    auctionId: BN,
+    params: AuctionParams
+  ): Promise<{ tx: string; auctionPda: PublicKey }> {
+    const seller = this.provider.wallet.publicKey;
+    const [auctionPda] = this.getAuctionPda(seller, auctionId);
+    const [vaultPda] = this.getVaultPda(auctionPda);
+    const [configPda] = this.getConfigPda();
+
+    const tx = await this.program.methods
+      .createAuction(auctionId, params)
+      .accountsStrict({
+        seller,
+        auction: auctionPda,
+        escrowVault: vaultPda,
+        tokenMint,
+        config: configPda,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        eventAuthority: this.getEventAuthorityPda()[0],
+        program: this.programId,
+      })
+      .rpc();
+
+    return { tx, auctionPda };
+  }
+
+  /** Bids on an English auction; the current highest bidder is refunded to their ATA. */
+  async placeBid(auctionPda: PublicKey, amount: BN): Promise<string> {
+    const bidder = this.provider.wallet.publicKey;
+    const auction = await this.fetchAuction(auctionPda);
+    const [vaultPda] = this.getVaultPda(auctionPda);
+    const outbidTokenAccount = auction.bidCount > 0
+      ? await getAssociatedTokenAddress(auction.tokenMint, auction.highestBidder)
+      : null;
+
+    return this.program.methods
+      .placeBid(amount)
+      .accountsStrict({
+        bidder,
+        auction: auctionPda,
+        escrowVault: vaultPda,
+        bidderTokenAccount: await getAssociatedTokenAddress(auction.tokenMint, bidder),
+        outbidTokenAccount,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        eventAuthority: this.getEventAuthorityPda()[0],
+        program: this.programId,
+      })
+      .rpc();
+  }
+
+  /** Buys a Dutch auction at its current price, failing if that exceeds `maxPrice`. */
+  async buyAuction(
+    auctionPda: PublicKey,
+    maxPrice: BN,
+    buyerTokenAccount: PublicKey,
+    sellerTokenAccount: PublicKey,
+    treasuryTokenAccount: PublicKey
+  ): Promise<string> {
+    const [vaultPda] = this.getVaultPda(auctionPda);
+    const [configPda] = this.getConfigPda();
+
+    return this.program.methods
+      .buyAuction(maxPrice)
+      .accountsStrict({
+        buyer: this.provider.wallet.publicKey,
+        auction: auctionPda,
+        escrowVault: vaultPda,
+        buyerTokenAccount,
+        sellerTokenAccount,
+        treasuryTokenAccount,
+        config: configPda,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        eventAuthority: this.getEventAuthorityPda()[0],
+        program: this.programId,
+      })
+      .rpc();
+  }
+
+  async settleAuction(
+    auctionPda: PublicKey,
+    sellerTokenAccount: PublicKey,
+    treasuryTokenAccount: PublicKey
+  ): Promise<string> {
+    const [vaultPda] = this.getVaultPda(auctionPda);
+    const [configPda] = this.getConfigPda();
+
+    return this.program.methods
+      .settleAuction()
+      .accountsStrict({
+        cranker: this.provider.wallet.publicKey,
+        auction: auctionPda,
+        escrowVault: vaultPda,
+        sellerTokenAccount,
+        treasuryTokenAccount,
+        config: configPda,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        eventAuthority: this.getEventAuthorityPda()[0],
+        program: this.programId,
+      })
+      .rpc();
+  }
+
+  /** Closes a settled or unsold auction; tokens left in the vault go to the seller. */
+  async closeAuction(auctionPda: PublicKey): Promise<string> {
+    const seller = this.provider.wallet.publicKey;
+    const [vaultPda] = this.getVaultPda(auctionPda);
+    const { tokenMint } = await this.fetchAuction(auctionPda);
+
+    return this.program.methods
+      .closeAuction()
+      .accountsStrict({
+        seller,
+        auction: auctionPda,
+        escrowVault: vaultPda,
+        sellerTokenAccount: await getAssociatedTokenAddress(tokenMint, seller),
+        tokenProgram: TOKEN_PROGRAM_ID,
+        eventAuthority: this.getEventAuthorityPda()[0],
+        program: this.programId,
+      })
+      .rpc();
+  }
+
+  /** Commits a sealed Vickrey bid; keep `salt` (32 bytes) to reveal with. */
+  async commitBid(
+    auctionPda: PublicKey,
+    amount: BN,
+    salt: number[],
+    deposit: BN,
+    bidderTokenAccount: PublicKey
+  ): Promise<string> {
+    const bidder = this.provider.wallet.publicKey;
+    const [vaultPda] = this.getVaultPda(auctionPda);
+
+    return this.program.methods
+      .commitBid(createBidCommitment(amount, salt), deposit)
+      .accountsStrict({
+        bidder,
+        auction: auctionPda,
+        sealedBid: this.getSealedBidPda(auctionPda, bidder)[0],
+        escrowVault: vaultPda,
+        bidderTokenAccount,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        eventAuthority: this.getEventAuthorityPda()[0],
+        program: this.programId,
+      })
+      .rpc();
+  }
+
+  async revealBid(auctionPda: PublicKey, amount: BN, salt: number[]): Promise<string> {
+    const bidder = this.provider.wallet.publicKey;
+
+    return this.program.methods
+      .revealBid(amount, salt)
+      .accountsStrict({
+        bidder,
+        auction: auctionPda,
+        sealedBid: this.getSealedBidPda(auctionPda, bidder)[0],
+        eventAuthority: this.getEventAuthorityPda()[0],
+        program: this.programId,
+      })
+      .rpc();
+  }
+
+  /** Returns a sealed bid's deposit after the auction ends; anyone may call it. */
+  async withdrawBid(
+    auctionPda: PublicKey,
+    bidder: PublicKey,
+    bidderTokenAccount: PublicKey,
+    sellerTokenAccount: PublicKey
+  ): Promise<string> {
+    const [vaultPda] = this.getVaultPda(auctionPda);
+
+    return this.program.methods
+      .withdrawBid()
+      .accountsStrict({
+        cranker: this.provider.wallet.publicKey,
+        bidder,
+        auction: auctionPda,
+        sealedBid: this.getSealedBidPda(auctionPda, bidder)[0],
+        escrowVault: vaultPda,
+        bidderTokenAccount,
+        sellerTokenAccount,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        eventAuthority: this.getEventAuthorityPda()[0],
+        program: this.programId,
+      })
+      .rpc();
+  }
+
+  // ===== Reverse Auctions =====
+
+  /** Opens a reverse auction, escrowing `params.ceiling` from the buyer's token account. */
+  async createReverseAuction(
+    tokenMint: PublicKey,
+    auctionId: BN,
+    params: ReverseAuctionParams,
+    buyerTokenAccount: PublicKey
+  ): Promise<{ tx: string; auctionPda: PublicKey }> {
+    const buyer = this.provider.wallet.publicKey;
+    const [auctionPda] = this.getReverseAuctionPda(buyer, auctionId);
+    const [vaultPda] = this.getVaultPda(auctionPda);
+    const [configPda] = this.getConfigPda();
+
+    const tx = await this.program.methods
+      .createReverseAuction(auctionId, params)
+      .accountsStrict({
+        buyer,
+        auction: auctionPda,
+        escrowVault: vaultPda,
+        buyerTokenAccount,
+        tokenMint,
+        config: configPda,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        eventAuthority: this.getEventAuthorityPda()[0],
+        program: this.programId,
+      })
+      .rpc();
+
+    return { tx, auctionPda };
+  }
+
+  async postBond(auctionPda: PublicKey, sellerTokenAccount: PublicKey): Promise<string> {
+    const seller = this.provider.wallet.publicKey;
+
+    return this.program.methods
+      .postBond()
+      .accountsStrict({
+        seller,
+        auction: auctionPda,
+        bond: this.getBondPda(auctionPda, seller)[0],
+        escrowVault: this.getVaultPda(auctionPda)[0],
+        sellerTokenAccount,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        eventAuthority: this.getEventAuthorityPda()[0],
+        program: this.programId,
+      })
+      .rpc();
+  }
+
+  /** Undercuts the lowest bid; passes the seller's bond when the auction requires one. */
+  async placeReverseBid(auctionPda: PublicKey, amount: BN): Promise<string> {
+    const seller = this.provider.wallet.publicKey;
+    const auction = await this.fetchReverseAuction(auctionPda);
+    const bond = auction.sellerBond.isZero()
+      ? null
+      : this.getBondPda(auctionPda, seller)[0];
+
+    return this.program.methods
+      .placeReverseBid(amount)
+      .accountsStrict({
+        seller,
+        auction: auctionPda,
+        bond,
+        eventAuthority: this.getEventAuthorityPda()[0],
+        program: this.programId,
+      })
+      .rpc();
+  }
+
+  /** Pays the lowest bidder's ATA and refunds the rest of the ceiling to the buyer's. */
+  async settleReverseAuction(
+    auctionPda: PublicKey,
+    treasuryTokenAccount: PublicKey
+  ): Promise<string> {
+    const auction = await this.fetchReverseAuction(auctionPda);
+    const sellerTokenAccount = auction.bidCount > 0
+      ? await getAssociatedTokenAddress(auction.tokenMint, auction.lowestBidder)
+      : null;
+
+    return this.program.methods
+      .settleReverseAuction()
+      .accountsStrict({
+        cranker: this.provider.wallet.publicKey,
+        auction: auctionPda,
+        escrowVault: this.getVaultPda(auctionPda)[0],
+        buyerTokenAccount: await getAssociatedTokenAddress(auction.tokenMint, auction.buyer),
+        sellerTokenAccount,
+        treasuryTokenAccount,
+        config: this.getConfigPda()[0],
+        tokenProgram: TOKEN_PROGRAM_ID,
+        eventAuthority: this.getEventAuthorityPda()[0],
+        program: this.programId,
+      })
+      .rpc();
+  }
+
+  /** Returns a seller's bond once the reverse auction has ended; anyone may call it. */
+  async withdrawBond(
+    auctionPda: PublicKey,
+    seller: PublicKey,
+    sellerTokenAccount: PublicKey
+  ): Promise<string> {
+    return this.program.methods
+      .withdrawBond()
+      .accountsStrict({
+        cranker: this.provider.wallet.publicKey,
+        seller,
+        auction: auctionPda,
+        bond: this.getBondPda(auctionPda, seller)[0],
+        escrowVault: this.getVaultPda(auctionPda)[0],
+        sellerTokenAccount,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        eventAuthority: this.getEventAuthorityPda()[0],
+        program: this.programId,
+      })
+      .rpc();
+  }
+
+  /** Closes a settled or unsold reverse auction; tokens left in the vault go to the buyer. */
+  async closeReverseAuction(auctionPda: PublicKey): Promise<string> {
+    const buyer = this.provider.wallet.publicKey;
+    const { tokenMint } = await this.fetchReverseAuction(auctionPda);
+
+    return this.program.methods
+      .closeReverseAuction()
+      .accountsStrict({
+        buyer,
+        auction: auctionPda,
+        escrowVault: this.getVaultPda(auctionPda)[0],
+        buyerTokenAccount: await getAssociatedTokenAddress(tokenMint, buyer),
+        tokenProgram: TOKEN_PROGRAM_ID,
+        eventAuthority: this.getEventAuthorityPda()[0],
+        program: this.programId,
+      })
+      .rpc();
+  }
+
+  /** Rewrites a negotiation from an older account layout; anyone can pay. */
+  async migrateNegotiation(negotiationPda: PublicKey): Promise<string> {
+    return this.program.methods
+      .migrateNegotiation()
+      .accountsStrict({
+        payer: this.provider.wallet.publicKey,
+        negotiation: negotiationPda,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+  }
+
+  async migrateConfig(): Promise<string> {
+    return this.program.methods
+      .migrateConfig()
+      .accountsStrict({
+        authority: this.provider.wallet.publicKey,
+        config: this.getConfigPda()[0],
+        systemProgram: SystemProgram.programId,
       })
       .rpc();
   }
 
   // ===== Read Operations =====
 
+  /** Decodes a negotiation in any layout version. */
+  decodeNegotiation(data: Buffer): NegotiationAccount {
+    if (isLegacyNegotiation(data)) return decodeLegacyNegotiation(data);
+    return fromZeroCopy(this.program.coder.accounts.decode("NegotiationState", data));
+  }
+
   async fetchNegotiation(pda: PublicKey): Promise<NegotiationAccount> {
-    return this.program.account.negotiationState.fetch(pda) as Promise<NegotiationAccount>;
+    return this.decodeNegotiation(await this.fetchData(pda));
+  }
+
+  async fetchTemplate(pda: PublicKey): Promise<NegotiationTemplateAccount> {
+    return this.program.account.negotiationTemplate.fetch(pda) as Promise<NegotiationTemplateAccount>;
+  }
+
+  async fetchPolicy(pda: PublicKey): Promise<AcceptancePolicyAccount> {
+    return this.program.account.acceptancePolicy.fetch(pda) as Promise<AcceptancePolicyAccount>;
+  }
+
+  async fetchCoalition(pda: PublicKey): Promise<BuyerCoalitionAccount> {
+    return this.program.account.buyerCoalition.fetch(pda) as Promise<BuyerCoalitionAccount>;
+  }
+
+  async fetchAuction(pda: PublicKey): Promise<AuctionAccount> {
+    return this.program.account.auction.fetch(pda) as Promise<AuctionAccount>;
+  }
+
+  async fetchSealedBid(pda: PublicKey): Promise<SealedBidAccount> {
+    return this.program.account.sealedBid.fetch(pda) as Promise<SealedBidAccount>;
+  }
+
+  async fetchReverseAuction(pda: PublicKey): Promise<ReverseAuctionAccount> {
+    return this.program.account.reverseAuction.fetch(pda) as Promise<ReverseAuctionAccount>;
+  }
+
+  async fetchSellerBond(pda: PublicKey): Promise<SellerBondAccount> {
+    return this.program.account.sellerBond.fetch(pda) as Promise<SellerBondAccount>;
   }
 
   async fetchConfig(): Promise<ProtocolConfigAccount> {
-    const [configPda] = this.getConfigPda();
-    return this.program.account.protocolConfig.fetch(configPda) as Promise<ProtocolConfigAccount>;
+    const data = await this.fetchData(this.getConfigPda()[0]);
+    if (data.length === LEGACY_CONFIG_LEN) return decodeLegacyConfig(data);
+    return this.program.coder.accounts.decode("ProtocolConfig", data);
   }
 
   async findNegotiationsByBuyer(buyer: PublicKey): Promise<{ publicKey: PublicKey; account: NegotiationAccount }[]> {
-    return this.program.account.negotiationState.all([
-      { memcmp: { offset: 8, bytes: buyer.toBase58() } },
-    ]) as any;
+    return this.findNegotiations(8, buyer);
   }
 
   async findNegotiationsBySeller(seller: PublicKey): Promise<{ publicKey: PublicKey; account: NegotiationAccount }[]> {
-    return this.program.account.negotiationState.all([
-      { memcmp: { offset: 8 + 32, bytes: seller.toBase58() } },
-    ]) as any;
+    return this.findNegotiations(8 + 32, seller);
+  }
+
+  /** Negotiations of every layout version with `key` at `offset`. */
+  private async findNegotiations(
+    offset: number,
+    key: PublicKey
+  ): Promise<{ publicKey: PublicKey; account: NegotiationAccount }[]> {
+    const accounts = await this.provider.connection.getProgramAccounts(this.programId, {
+      filters: [
+        { memcmp: this.program.coder.accounts.memcmp("NegotiationState") },
+        { memcmp: { offset, bytes: key.toBase58() } },
+      ],
+    });
+    return accounts.map(({ pubkey, account }) => ({
+      publicKey: pubkey,
+      account: this.decodeNegotiation(account.data),
+    }));
+  }
+
+  private async fetchData(pda: PublicKey): Promise<Buffer> {
+    const info = await this.provider.connection.getAccountInfo(pda);
+    if (!info) throw new Error(`Account does not exist: ${pda.toBase58()}`);
+    return info.data;
+  }
+
+  /**
+   * Events emitted by a transaction. The program emits them through
+   * self-CPIs, so they are read from the inner instructions rather than the
+   * logs, which can be truncated.
+   */
+  async fetchEvents(signature: string): Promise<{ name: string; data: any }[]> {
+    const tx = await this.provider.connection.getTransaction(signature, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+    if (!tx?.meta?.innerInstructions) return [];
+
+    const keys = tx.transaction.message.getAccountKeys({
+      accountKeysFromLookups: tx.meta.loadedAddresses,
+    });
+    const events: { name: string; data: any }[] = [];
+    for (const inner of tx.meta.innerInstructions) {
+      for (const ix of inner.instructions) {
+        if (!keys.get(ix.programIdIndex)?.equals(this.programId)) continue;
+        const data = Buffer.from(anchor.utils.bytes.bs58.decode(ix.data));
+        if (!data.subarray(0, 8).equals(EVENT_IX_TAG)) continue;
+        const event = this.program.coder.events.decode(
+          data.subarray(8).toString("base64")
+        );
+        if (event) events.push(event);
+      }
+    }
+    return events;
   }
 
   // ===== Helpers =====
@@ -2,21 +2,62 @@ export { HaggleSDK } from "./haggle";
 export type { HaggleSDKConfig } from "./haggle";
 export {
   PROGRAM_ID,
+  PYTH_PUSH_ORACLE_ID,
   NegotiationStatus,
   OfferSide,
   ZopaPhase,
+  TerminationReason,
+  AuctionKind,
+  AuctionStatus,
+  BuyerFilter,
   parseStatus,
   parseOfferSide,
+  parseTerminationReason,
+  fromZeroCopy,
 } from "./types";
 export type {
+  AuctionParams,
+  AuctionAccount,
+  BuyerCoalitionAccount,
+  TemplateParams,
+  NegotiationTemplateAccount,
+  PolicyParams,
+  AcceptancePolicyAccount,
+  SealedBidAccount,
+  ReverseAuctionParams,
+  ReverseAuctionAccount,
+  SellerBondAccount,
+  ConfigUpdateParams,
   NegotiationParams,
+  PayoutTerms,
+  SwapRoute,
+  OpeningOffer,
   NegotiationAccount,
   ProtocolConfigAccount,
 } from "./types";
+export {
+  LEGACY_NEGOTIATION_LEN,
+  LEGACY_NEGOTIATION_V1_LEN,
+  LEGACY_CONFIG_LEN,
+  isLegacyNegotiation,
+  decodeLegacyNegotiation,
+  decodeLegacyConfig,
+} from "./migration";
 export {
   findConfigPda,
   findNegotiationPda,
   findVaultPda,
+  findTemplatePda,
+  findPolicyPda,
+  findCoalitionPda,
+  findPriceFeedPda,
+  findAuctionPda,
+  findSealedBidPda,
+  findReverseAuctionPda,
+  findBondPda,
+  findEventAuthorityPda,
+  EVENT_IX_TAG,
+  createBidCommitment,
   createServiceHash,
   createMetadata,
   decodeMetadata,
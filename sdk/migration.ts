@@ -0,0 +1,171 @@
+import { PublicKey } from "@solana/web3.js";
+import BN from "bn.js";
+import {
+  NegotiationAccount,
+  NegotiationStatus,
+  OfferSide,
+  ProtocolConfigAccount,
+  TerminationReason,
+  ZopaPhase,
+} from "./types";
+
+// Older account layouts. They share the current discriminators and are told
+// apart by their exact length; decoding one fills the fields added since with
+// the values `migrate_negotiation` and `migrate_config` would write, but
+// keeps the account's own `version`.
+
+/** Size of a version 0 `NegotiationState` account, discriminator included. */
+export const LEGACY_NEGOTIATION_LEN = 389;
+
+/**
+ * Size of a version 1 `NegotiationState` account, the Borsh layout before
+ * zero-copy, discriminator included.
+ */
+export const LEGACY_NEGOTIATION_V1_LEN = 653;
+
+/** Size of a version 0 `ProtocolConfig` account, discriminator included. */
+export const LEGACY_CONFIG_LEN = 111;
+
+class Reader {
+  private offset = 8;
+
+  constructor(private readonly data: Buffer) {}
+
+  u8(): number {
+    return this.data.readUInt8(this.offset++);
+  }
+
+  u16(): number {
+    const value = this.data.readUInt16LE(this.offset);
+    this.offset += 2;
+    return value;
+  }
+
+  u64(): BN {
+    return new BN(this.bytes(8), "le");
+  }
+
+  i64(): BN {
+    return new BN(this.bytes(8), "le").fromTwos(64);
+  }
+
+  bool(): boolean {
+    return this.u8() !== 0;
+  }
+
+  pubkey(): PublicKey {
+    return new PublicKey(this.bytes(32));
+  }
+
+  bytes(len: number): number[] {
+    const bytes = [...this.data.subarray(this.offset, this.offset + len)];
+    this.offset += len;
+    return bytes;
+  }
+
+  variant(names: string[]): Record<string, object> {
+    return { [names[this.u8()]]: {} };
+  }
+}
+
+export function isLegacyNegotiation(data: Buffer): boolean {
+  return data.length === LEGACY_NEGOTIATION_LEN || data.length === LEGACY_NEGOTIATION_V1_LEN;
+}
+
+export function decodeLegacyNegotiation(data: Buffer): NegotiationAccount {
+  const r = new Reader(data);
+  const base = {
+    buyer: r.pubkey(),
+    seller: r.pubkey(),
+    sessionId: r.u64(),
+    status: r.variant(Object.values(NegotiationStatus)),
+    currentRound: r.u8(),
+    currentOfferAmount: r.u64(),
+    currentOfferBy: r.pubkey(),
+    offerSide: r.variant(Object.values(OfferSide)),
+    serviceHash: r.bytes(32),
+    escrowAmount: r.u64(),
+    effectiveEscrow: r.u64(),
+    tokenMint: r.pubkey(),
+    maxRounds: r.u8(),
+    decayRateBps: r.u16(),
+    responseWindow: r.i64(),
+    globalDeadline: r.i64(),
+    minOfferBps: r.u16(),
+    protocolFeeBps: r.u16(),
+    zopaEnabled: r.bool(),
+    createdAt: r.i64(),
+    lastOfferAt: r.i64(),
+    settledAt: r.i64(),
+    settledAmount: r.u64(),
+    buyerCommitment: r.bytes(32),
+    sellerCommitment: r.bytes(32),
+    zopaPhase: r.variant(Object.values(ZopaPhase)),
+  };
+  if (data.length === LEGACY_NEGOTIATION_V1_LEN) {
+    return {
+      ...base,
+      priceFeed: r.bytes(32),
+      mintDecimals: r.u8(),
+      payoutMint: r.pubkey(),
+      swapAdapter: r.pubkey(),
+      maxSlippageBps: r.u16(),
+      template: r.pubkey(),
+      coalition: r.pubkey(),
+      metadata: r.bytes(64),
+      bump: r.u8(),
+      version: r.u8(),
+      crankDeposit: new BN(0),
+      crankRewardBps: 0,
+      invitationDeadline: new BN(0),
+      terminationReason: { [TerminationReason.Unspecified]: {} },
+      finalOffer: false,
+      admitted: false,
+      reserved: new Array(101).fill(0),
+    };
+  }
+  return {
+    ...base,
+    priceFeed: new Array(32).fill(0),
+    mintDecimals: 0,
+    payoutMint: PublicKey.default,
+    swapAdapter: PublicKey.default,
+    maxSlippageBps: 0,
+    template: PublicKey.default,
+    coalition: PublicKey.default,
+    metadata: r.bytes(64),
+    bump: r.u8(),
+    version: 0,
+    crankDeposit: new BN(0),
+    crankRewardBps: 0,
+    invitationDeadline: new BN(0),
+    terminationReason: { [TerminationReason.Unspecified]: {} },
+    finalOffer: false,
+    admitted: false,
+    reserved: new Array(101).fill(0),
+  };
+}
+
+export function decodeLegacyConfig(data: Buffer): ProtocolConfigAccount {
+  const r = new Reader(data);
+  return {
+    authority: r.pubkey(),
+    treasury: r.pubkey(),
+    defaultDecayRateBps: r.u16(),
+    defaultResponseWindow: r.i64(),
+    defaultProtocolFeeBps: r.u16(),
+    defaultMaxRounds: r.u8(),
+    totalNegotiations: r.u64(),
+    totalSettledVolume: r.u64(),
+    totalFeesCollected: r.u64(),
+    isPaused: r.bool(),
+    bump: r.u8(),
+    version: 0,
+    crankRewardLamports: new BN(0),
+    crankRewardBps: 0,
+    totalCranks: new BN(0),
+    totalCrankRewardsLamports: new BN(0),
+    totalCrankRewardsTokens: new BN(0),
+    reserved: new Array(30).fill(0),
+  };
+}
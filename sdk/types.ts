@@ -1,10 +1,15 @@
-import { PublicKey } from "@solana/web3.js";
+import { AccountMeta, PublicKey } from "@solana/web3.js";
 import BN from "bn.js";
 
 export const PROGRAM_ID = new PublicKey(
   "DRXGcVHj1GZSc7wD4LTnrM8RJ1shWH93s1zKCXtJtGbq"
 );
 
+/** Pyth push oracle, whose PDAs hold the sponsored price feeds. */
+export const PYTH_PUSH_ORACLE_ID = new PublicKey(
+  "pythWSnswVUd12oZpeFP8e9CVaEqJg25g1Vtc2biRsT"
+);
+
 export enum NegotiationStatus {
   Created = "created",
   Proposed = "proposed",
@@ -28,6 +33,35 @@ export enum ZopaPhase {
   Skipped = "skipped",
 }
 
+/** Why a negotiation ended without settling. */
+export enum TerminationReason {
+  Unspecified = "unspecified",
+  PriceGap = "priceGap",
+  Timeout = "timeout",
+  ZopaFailed = "zopaFailed",
+  CounterpartyUnresponsive = "counterpartyUnresponsive",
+  PolicyViolation = "policyViolation",
+  Cancelled = "cancelled",
+}
+
+export enum AuctionKind {
+  English = "english",
+  Dutch = "dutch",
+  Vickrey = "vickrey",
+}
+
+export enum AuctionStatus {
+  Open = "open",
+  Settled = "settled",
+  Unsold = "unsold",
+}
+
+export enum BuyerFilter {
+  Any = "any",
+  Allow = "allow",
+  Deny = "deny",
+}
+
 export interface NegotiationParams {
   escrowAmount: BN;
   serviceHash: number[];
@@ -38,6 +72,94 @@ export interface NegotiationParams {
   minOfferBps: number;
   protocolFeeBps: number;
   zopaEnabled: boolean;
+  /** Pyth feed id to denominate offers in its quote currency, in millionths. */
+  priceFeed?: number[] | null;
+  /** Pay the seller in another mint through a swap at settlement. */
+  payout?: PayoutTerms | null;
+  /** Seconds the seller has to accept before the negotiation can be expired. */
+  invitationTtl?: BN | null;
+}
+
+export interface PayoutTerms {
+  mint: PublicKey;
+  /** Program implementing the swap adapter interface. */
+  swapAdapter: PublicKey;
+  maxSlippageBps: number;
+}
+
+/** Accounts to pay a cross-mint negotiation's seller through its adapter. */
+export interface SwapRoute {
+  swapAdapter: PublicKey;
+  sellerPayoutAccount: PublicKey;
+  /** The adapter's own accounts, in the order its `quote` and `swap` take them. */
+  adapterAccounts: AccountMeta[];
+}
+
+/** An offer made by the instruction that creates or joins a negotiation. */
+export interface OpeningOffer {
+  amount: BN;
+  metadata: number[];
+}
+
+/** Standard terms; `allowedMints` empty allows any mint. */
+export interface TemplateParams {
+  maxRounds: number;
+  decayRateBps: number;
+  responseWindow: BN;
+  globalDeadlineOffset: BN;
+  minOfferBps: number;
+  protocolFeeBps: number;
+  zopaEnabled: boolean;
+  allowedMints: PublicKey[];
+  arbiter: PublicKey | null;
+}
+
+/** `acceptedMints` empty accepts any mint; `maxOpenNegotiations` zero is no limit. */
+export interface PolicyParams {
+  buyerFilter: { any: {} } | { allow: {} } | { deny: {} };
+  buyers: PublicKey[];
+  minEscrow: BN;
+  acceptedMints: PublicKey[];
+  maxOpenNegotiations: number;
+  autoAccept: boolean;
+  requiredTemplate: PublicKey | null;
+}
+
+/** Fields that do not apply to `kind` are ignored and stored as zero. */
+export interface AuctionParams {
+  kind: { english: {} } | { dutch: {} } | { vickrey: {} };
+  serviceHash: number[];
+  startPrice: BN;
+  floorPrice: BN;
+  minIncrementBps: number;
+  decayRateBps: number;
+  decayInterval: BN;
+  duration: BN;
+  protocolFeeBps: number;
+  commitDuration: BN;
+  slashBps: number;
+}
+
+export interface ReverseAuctionParams {
+  serviceHash: number[];
+  ceiling: BN;
+  minDecrementBps: number;
+  sellerBond: BN;
+  duration: BN;
+  protocolFeeBps: number;
+}
+
+/** Fields left null keep their current value. */
+export interface ConfigUpdateParams {
+  authority: PublicKey | null;
+  treasury: PublicKey | null;
+  defaultDecayRateBps: number | null;
+  defaultResponseWindow: BN | null;
+  defaultProtocolFeeBps: number | null;
+  defaultMaxRounds: number | null;
+  isPaused: boolean | null;
+  crankRewardLamports: BN | null;
+  crankRewardBps: number | null;
 }
 
 export interface NegotiationAccount {
@@ -67,8 +189,133 @@ export interface NegotiationAccount {
   buyerCommitment: number[];
   sellerCommitment: number[];
   zopaPhase: Record<string, object>;
+  priceFeed: number[];
+  mintDecimals: number;
+  payoutMint: PublicKey;
+  swapAdapter: PublicKey;
+  maxSlippageBps: number;
+  template: PublicKey;
+  coalition: PublicKey;
   metadata: number[];
   bump: number;
+  /** Layout version; below 2 for accounts that still need `migrateNegotiation`. */
+  version: number;
+  /** Lamports prepaid for whoever cranks the cleanup; zero once paid. */
+  crankDeposit: BN;
+  crankRewardBps: number;
+  /** When an unaccepted invitation lapses, or zero for `globalDeadline`. */
+  invitationDeadline: BN;
+  /** Set when the negotiation is rejected or expired. */
+  terminationReason: Record<string, object>;
+  /** The current offer can only be accepted; set explicitly or in the last round. */
+  finalOffer: boolean;
+  /** Holds one of the seller's acceptance policy slots. */
+  admitted: boolean;
+  reserved: number[];
+}
+
+export interface NegotiationTemplateAccount {
+  owner: PublicKey;
+  templateId: BN;
+  maxRounds: number;
+  decayRateBps: number;
+  responseWindow: BN;
+  globalDeadlineOffset: BN;
+  minOfferBps: number;
+  protocolFeeBps: number;
+  zopaEnabled: boolean;
+  allowedMints: PublicKey[];
+  arbiter: PublicKey;
+  createdAt: BN;
+  bump: number;
+}
+
+export interface AcceptancePolicyAccount {
+  seller: PublicKey;
+  buyerFilter: Record<string, object>;
+  buyers: PublicKey[];
+  minEscrow: BN;
+  acceptedMints: PublicKey[];
+  maxOpenNegotiations: number;
+  openNegotiations: number;
+  autoAccept: boolean;
+  requiredTemplate: PublicKey;
+  bump: number;
+}
+
+export interface BuyerCoalitionAccount {
+  negotiation: PublicKey;
+  lead: PublicKey;
+  totalContributed: BN;
+  /** Protocol fee attributed to members who exited after the negotiation ended. */
+  feeClaimed: BN;
+  members: { member: PublicKey; amount: BN }[];
+  bump: number;
+}
+
+export interface AuctionAccount {
+  seller: PublicKey;
+  auctionId: BN;
+  tokenMint: PublicKey;
+  kind: Record<string, object>;
+  status: Record<string, object>;
+  serviceHash: number[];
+  startPrice: BN;
+  floorPrice: BN;
+  minIncrementBps: number;
+  decayRateBps: number;
+  decayInterval: BN;
+  protocolFeeBps: number;
+  slashBps: number;
+  highestBid: BN;
+  highestBidder: PublicKey;
+  secondBid: BN;
+  bidCount: number;
+  openBids: number;
+  createdAt: BN;
+  commitEndsAt: BN;
+  endsAt: BN;
+  settledAt: BN;
+  settledAmount: BN;
+  bump: number;
+}
+
+export interface SealedBidAccount {
+  auction: PublicKey;
+  bidder: PublicKey;
+  commitment: number[];
+  deposit: BN;
+  amount: BN;
+  revealed: boolean;
+  bump: number;
+}
+
+export interface ReverseAuctionAccount {
+  buyer: PublicKey;
+  auctionId: BN;
+  tokenMint: PublicKey;
+  status: Record<string, object>;
+  serviceHash: number[];
+  ceiling: BN;
+  minDecrementBps: number;
+  sellerBond: BN;
+  protocolFeeBps: number;
+  openBonds: number;
+  lowestBid: BN;
+  lowestBidder: PublicKey;
+  bidCount: number;
+  createdAt: BN;
+  endsAt: BN;
+  settledAt: BN;
+  settledAmount: BN;
+  bump: number;
+}
+
+export interface SellerBondAccount {
+  auction: PublicKey;
+  seller: PublicKey;
+  amount: BN;
+  bump: number;
 }
 
 export interface ProtocolConfigAccount {
@@ -83,6 +330,37 @@ export interface ProtocolConfigAccount {
   totalFeesCollected: BN;
   isPaused: boolean;
   bump: number;
+  /** Layout version; 0 until `migrateConfig` has run. */
+  version: number;
+  crankRewardLamports: BN;
+  crankRewardBps: number;
+  totalCranks: BN;
+  totalCrankRewardsLamports: BN;
+  totalCrankRewardsTokens: BN;
+  reserved: number[];
+}
+
+function variant(names: string[], index: number): Record<string, object> {
+  return { [names[index]]: {} };
+}
+
+/**
+ * `NegotiationState` is zero-copy, so the program stores its enums,
+ * `zopaEnabled`, `finalOffer` and `admitted` as bytes. Converts an account as Anchor's coder decodes it
+ * to the shape the SDK returns.
+ */
+export function fromZeroCopy(raw: any): NegotiationAccount {
+  const { padding, crankPadding, ...fields } = raw;
+  return {
+    ...fields,
+    status: variant(Object.values(NegotiationStatus), raw.status),
+    offerSide: variant(Object.values(OfferSide), raw.offerSide),
+    zopaPhase: variant(Object.values(ZopaPhase), raw.zopaPhase),
+    zopaEnabled: raw.zopaEnabled !== 0,
+    terminationReason: variant(Object.values(TerminationReason), raw.terminationReason),
+    finalOffer: raw.finalOffer !== 0,
+    admitted: raw.admitted !== 0,
+  };
 }
 
 export function parseStatus(status: Record<string, object>): NegotiationStatus {
@@ -94,3 +372,8 @@ export function parseOfferSide(side: Record<string, object>): OfferSide {
   const key = Object.keys(side)[0];
   return key as OfferSide;
 }
+
+export function parseTerminationReason(reason: Record<string, object>): TerminationReason {
+  const key = Object.keys(reason)[0];
+  return key as TerminationReason;
+}
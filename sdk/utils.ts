@@ -1,6 +1,7 @@
+import { createHash } from "crypto";
 import { PublicKey } from "@solana/web3.js";
 import BN from "bn.js";
-import { PROGRAM_ID } from "./types";
+import { PROGRAM_ID, PYTH_PUSH_ORACLE_ID } from "./types";
 
 export function findConfigPda(programId: PublicKey = PROGRAM_ID): [PublicKey, number] {
   return PublicKey.findProgramAddressSync(
@@ -26,6 +27,107 @@ export function findNegotiationPda(
   );
 }
 
+export function findTemplatePda(
+  owner: PublicKey,
+  templateId: BN,
+  programId: PublicKey = PROGRAM_ID
+): [PublicKey, number] {
+  return PublicKey.findProgramAddressSync(
+    [
+      Buffer.from("template"),
+      owner.toBuffer(),
+      templateId.toArrayLike(Buffer, "le", 8),
+    ],
+    programId
+  );
+}
+
+export function findPolicyPda(
+  seller: PublicKey,
+  programId: PublicKey = PROGRAM_ID
+): [PublicKey, number] {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from("policy"), seller.toBuffer()],
+    programId
+  );
+}
+
+export function findCoalitionPda(
+  negotiation: PublicKey,
+  programId: PublicKey = PROGRAM_ID
+): [PublicKey, number] {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from("coalition"), negotiation.toBuffer()],
+    programId
+  );
+}
+
+/** Pyth sponsored price feed account for `feedId` on `shardId`. */
+export function findPriceFeedPda(
+  feedId: number[],
+  shardId: number = 0
+): [PublicKey, number] {
+  const shard = Buffer.alloc(2);
+  shard.writeUInt16LE(shardId);
+  return PublicKey.findProgramAddressSync(
+    [shard, Buffer.from(feedId)],
+    PYTH_PUSH_ORACLE_ID
+  );
+}
+
+export function findAuctionPda(
+  seller: PublicKey,
+  auctionId: BN,
+  programId: PublicKey = PROGRAM_ID
+): [PublicKey, number] {
+  return PublicKey.findProgramAddressSync(
+    [
+      Buffer.from("auction"),
+      seller.toBuffer(),
+      auctionId.toArrayLike(Buffer, "le", 8),
+    ],
+    programId
+  );
+}
+
+export function findSealedBidPda(
+  auction: PublicKey,
+  bidder: PublicKey,
+  programId: PublicKey = PROGRAM_ID
+): [PublicKey, number] {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from("sealed_bid"), auction.toBuffer(), bidder.toBuffer()],
+    programId
+  );
+}
+
+export function findReverseAuctionPda(
+  buyer: PublicKey,
+  auctionId: BN,
+  programId: PublicKey = PROGRAM_ID
+): [PublicKey, number] {
+  return PublicKey.findProgramAddressSync(
+    [
+      Buffer.from("reverse_auction"),
+      buyer.toBuffer(),
+      auctionId.toArrayLike(Buffer, "le", 8),
+    ],
+    programId
+  );
+}
+
+export function findBondPda(
+  auction: PublicKey,
+  seller: PublicKey,
+  programId: PublicKey = PROGRAM_ID
+): [PublicKey, number] {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from("bond"), auction.toBuffer(), seller.toBuffer()],
+    programId
+  );
+}
+
+/** Escrow vault of a negotiation or an auction. */
 export function findVaultPda(
   negotiation: PublicKey,
   programId: PublicKey = PROGRAM_ID
@@ -36,6 +138,28 @@ export function findVaultPda(
   );
 }
 
+export function findEventAuthorityPda(
+  programId: PublicKey = PROGRAM_ID
+): [PublicKey, number] {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from("__event_authority")],
+    programId
+  );
+}
+
+// Prefix of the program's event self-CPIs (Anchor's EVENT_IX_TAG, little-endian).
+export const EVENT_IX_TAG = Buffer.from("e445a52e51cb9a1d", "hex");
+
+/** SHA-256 of the bid (u64 little-endian) followed by the 32-byte salt. */
+export function createBidCommitment(amount: BN, salt: number[]): number[] {
+  return Array.from(
+    createHash("sha256")
+      .update(amount.toArrayLike(Buffer, "le", 8))
+      .update(Buffer.from(salt))
+      .digest()
+  );
+}
+
 export function createServiceHash(service: string): number[] {
   const buf = Buffer.alloc(32);
   Buffer.from(service).copy(buf);
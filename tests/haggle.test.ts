@@ -17,6 +17,7 @@ import {
   sendAndConfirmTransaction,
 } from "@solana/web3.js";
 import BN from "bn.js";
+import { fromZeroCopy } from "../sdk/types";
 
 async function fundAccount(
   provider: anchor.AnchorProvider,
@@ -185,7 +186,7 @@ describe("haggle-protocol", () => {
       .signers([buyer])
       .rpc();
 
-    const neg = await program.account.negotiationState.fetch(negotiationPda);
+    const neg = fromZeroCopy(await program.account.negotiationState.fetch(negotiationPda));
     assert.equal(neg.buyer.toBase58(), buyer.publicKey.toBase58());
     assert.equal(neg.seller.toBase58(), seller.publicKey.toBase58());
     assert.equal(neg.sessionId.toNumber(), 1);
@@ -214,7 +215,7 @@ describe("haggle-protocol", () => {
       .signers([seller])
       .rpc();
 
-    const neg = await program.account.negotiationState.fetch(negotiationPda);
+    const neg = fromZeroCopy(await program.account.negotiationState.fetch(negotiationPda));
     assert.deepEqual(neg.status, { proposed: {} });
   });
 
@@ -223,7 +224,7 @@ describe("haggle-protocol", () => {
     Buffer.from("initial-offer").copy(metadata);
 
     await program.methods
-      .submitOffer(new BN(2_000_000), Array.from(metadata))
+      .submitOffer(new BN(2_000_000), Array.from(metadata), false)
       .accounts({
         offerer: buyer.publicKey,
         negotiation: negotiationPda,
@@ -231,7 +232,7 @@ describe("haggle-protocol", () => {
       .signers([buyer])
       .rpc();
 
-    const neg = await program.account.negotiationState.fetch(negotiationPda);
+    const neg = fromZeroCopy(await program.account.negotiationState.fetch(negotiationPda));
     assert.equal(neg.currentOfferAmount.toNumber(), 2_000_000);
     assert.equal(neg.currentRound, 1);
     assert.deepEqual(neg.offerSide, { buyer: {} });
@@ -244,7 +245,7 @@ describe("haggle-protocol", () => {
     const metadata = Buffer.alloc(64);
 
     await program.methods
-      .submitOffer(new BN(4_200_000), Array.from(metadata))
+      .submitOffer(new BN(4_200_000), Array.from(metadata), false)
       .accounts({
         offerer: seller.publicKey,
         negotiation: negotiationPda,
@@ -252,7 +253,7 @@ describe("haggle-protocol", () => {
       .signers([seller])
       .rpc();
 
-    const neg = await program.account.negotiationState.fetch(negotiationPda);
+    const neg = fromZeroCopy(await program.account.negotiationState.fetch(negotiationPda));
     assert.equal(neg.currentOfferAmount.toNumber(), 4_200_000);
     assert.equal(neg.currentRound, 2);
     assert.deepEqual(neg.offerSide, { seller: {} });
@@ -265,7 +266,7 @@ describe("haggle-protocol", () => {
     const metadata = Buffer.alloc(64);
 
     await program.methods
-      .submitOffer(new BN(2_500_000), Array.from(metadata))
+      .submitOffer(new BN(2_500_000), Array.from(metadata), false)
       .accounts({
         offerer: buyer.publicKey,
         negotiation: negotiationPda,
@@ -273,7 +274,7 @@ describe("haggle-protocol", () => {
       .signers([buyer])
       .rpc();
 
-    const neg = await program.account.negotiationState.fetch(negotiationPda);
+    const neg = fromZeroCopy(await program.account.negotiationState.fetch(negotiationPda));
     assert.equal(neg.currentOfferAmount.toNumber(), 2_500_000);
     assert.equal(neg.currentRound, 3);
     assert.deepEqual(neg.status, { proposed: {} });
@@ -283,7 +284,7 @@ describe("haggle-protocol", () => {
     const metadata = Buffer.alloc(64);
 
     await program.methods
-      .submitOffer(new BN(3_500_000), Array.from(metadata))
+      .submitOffer(new BN(3_500_000), Array.from(metadata), false)
       .accounts({
         offerer: seller.publicKey,
         negotiation: negotiationPda,
@@ -291,17 +292,17 @@ describe("haggle-protocol", () => {
       .signers([seller])
       .rpc();
 
-    const neg = await program.account.negotiationState.fetch(negotiationPda);
+    const neg = fromZeroCopy(await program.account.negotiationState.fetch(negotiationPda));
     assert.equal(neg.currentOfferAmount.toNumber(), 3_500_000);
     assert.equal(neg.currentRound, 4);
     assert.deepEqual(neg.status, { countered: {} });
   });
 
-  it("Buyer counters (3.0 USDC)", async () => {
+  it("Buyer makes a final offer (3.0 USDC)", async () => {
     const metadata = Buffer.alloc(64);
 
     await program.methods
-      .submitOffer(new BN(3_000_000), Array.from(metadata))
+      .submitOffer(new BN(3_000_000), Array.from(metadata), true)
       .accounts({
         offerer: buyer.publicKey,
         negotiation: negotiationPda,
@@ -309,10 +310,11 @@ describe("haggle-protocol", () => {
       .signers([buyer])
       .rpc();
 
-    const neg = await program.account.negotiationState.fetch(negotiationPda);
+    const neg = fromZeroCopy(await program.account.negotiationState.fetch(negotiationPda));
     assert.equal(neg.currentOfferAmount.toNumber(), 3_000_000);
     assert.equal(neg.currentRound, 5);
     assert.deepEqual(neg.status, { proposed: {} });
+    assert.isTrue(neg.finalOffer);
   });
 
   it("Seller accepts offer at 3.0 USDC (settlement)", async () => {
@@ -336,7 +338,7 @@ describe("haggle-protocol", () => {
       .signers([seller])
       .rpc();
 
-    const neg = await program.account.negotiationState.fetch(negotiationPda);
+    const neg = fromZeroCopy(await program.account.negotiationState.fetch(negotiationPda));
     assert.deepEqual(neg.status, { settled: {} });
     assert.equal(neg.settledAmount.toNumber(), 3_000_000);
     assert.ok(neg.settledAt.toNumber() > 0);
@@ -377,9 +379,11 @@ describe("haggle-protocol", () => {
     await program.methods
       .closeNegotiation()
       .accounts({
-        creator: buyer.publicKey,
+        closer: buyer.publicKey,
+        buyer: buyer.publicKey,
         negotiation: negotiationPda,
         escrowVault: vaultPda,
+        buyerTokenAccount,
         tokenProgram: TOKEN_PROGRAM_ID,
       })
       .signers([buyer])
@@ -463,19 +467,21 @@ describe("haggle-protocol", () => {
 
       // Seller rejects
       await program.methods
-        .rejectNegotiation()
+        .rejectNegotiation({ priceGap: {} })
         .accounts({
           rejector: seller.publicKey,
           negotiation: negPda2,
           escrowVault: vaultPda2,
           buyerTokenAccount: buyerTokenAccount,
+          treasuryTokenAccount: treasuryTokenAccount,
           tokenProgram: TOKEN_PROGRAM_ID,
         })
         .signers([seller])
         .rpc();
 
-      const neg = await program.account.negotiationState.fetch(negPda2);
+      const neg = fromZeroCopy(await program.account.negotiationState.fetch(negPda2));
       assert.deepEqual(neg.status, { rejected: {} });
+      assert.deepEqual(neg.terminationReason, { priceGap: {} });
 
       // Verify buyer got full refund
       const buyerBalanceAfter = await getAccount(